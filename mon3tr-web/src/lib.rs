@@ -0,0 +1,143 @@
+//! Shows a model pack in a `<canvas>`, drawn with wgpu on WebGL 2.
+//!
+//! winit and wgpu only reach the browser through wasm-bindgen, which doesn't work with
+//! Emscripten, so this is built for `wasm32-unknown-unknown` with spine-c compiled by the `cc`
+//! crate against wasi-libc. Point `WASI_SYSROOT` at a wasi-sdk sysroot and build with
+//! `wasm-pack build mon3tr-web --target web`, then call `show` with a canvas and the bytes of a
+//! pack.
+
+mod pack;
+mod renderer;
+
+use anyhow::{anyhow, Result};
+use spine::{AnimationState, AnimationStateData, Atlas, Bounds, Skeleton, SkeletonData};
+
+pub use pack::PackLoader;
+pub use renderer::Renderer;
+
+/// Every atlas comes with a [`PackLoader`], spine-c never asks for files on its own.
+struct NoFiles;
+impl spine::SpineCallbacks for NoFiles {
+    type Texture = ();
+
+    type LoadTextureError = anyhow::Error;
+    type LoadFileError = anyhow::Error;
+
+    fn load_texture(path: &str, _: &spine::AtlasPage) -> Result<((), u32, u32)> {
+        Err(anyhow!("No file system to load {} from", path))
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>> {
+        Err(anyhow!("No file system to load {} from", path))
+    }
+}
+spine::spine_init!(NoFiles);
+
+/// A model playing one animation in a loop.
+pub struct Model {
+    pub skel: Skeleton,
+    pub anim: AnimationState,
+    /// Area the model is fitted into, taken from its first frame.
+    pub bounds: Bounds,
+}
+
+impl Model {
+    /// Load a model pack from memory and play `animation`, or `Idle`.
+    pub fn load(pack: Vec<u8>, animation: Option<&str>) -> Result<Self> {
+        let atlas = Atlas::with_loader(pack::ATLAS_PATH, PackLoader::new(pack)?)?;
+        let skel_data = SkeletonData::try_new_binary(&atlas, pack::SKELETON_PATH, 1.0)?;
+        let anim_data = AnimationStateData::new(&skel_data, 0.0)?;
+
+        let mut skel = Skeleton::new(&skel_data)?;
+        let mut anim = AnimationState::new(&anim_data)?;
+        let animation = animation.unwrap_or("Idle");
+        if anim.set_animation_by_name(0, animation, true).is_none() {
+            log::warn!("The model has no animation named {}", animation);
+        }
+
+        anim.update(0.0);
+        skel.apply_animation(&anim);
+        skel.update_world_transform();
+        let bounds = skel
+            .bounds()
+            .ok_or_else(|| anyhow!("Nothing is attached to the model"))?;
+
+        Ok(Self { skel, anim, bounds })
+    }
+
+    pub fn step(&mut self, delta: f32) {
+        self.anim.update(delta);
+        self.skel.apply_animation(&self.anim);
+        self.skel.update_world_transform();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+    use web_sys::HtmlCanvasElement;
+    use winit::{
+        dpi::PhysicalSize,
+        event::{Event, WindowEvent},
+        event_loop::{ControlFlow, EventLoop},
+        platform::web::WindowBuilderExtWebSys,
+        window::WindowBuilder,
+    };
+
+    use super::*;
+
+    /// Play `animation`, or `Idle`, of the model pack in `pack` on `canvas` until the page is
+    /// closed.
+    #[wasm_bindgen]
+    pub fn show(canvas: HtmlCanvasElement, pack: Vec<u8>, animation: Option<String>) {
+        console_error_panic_hook::set_once();
+        // Only fails when called again
+        let _ = console_log::init_with_level(log::Level::Info);
+
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = run(canvas, pack, animation).await {
+                log::error!("Failed to show the model: {:?}", e);
+            }
+        });
+    }
+
+    async fn run(
+        canvas: HtmlCanvasElement,
+        pack: Vec<u8>,
+        animation: Option<String>,
+    ) -> Result<()> {
+        let mut model = Model::load(pack, animation.as_deref())?;
+
+        let size = PhysicalSize::new(canvas.width(), canvas.height());
+        let event_loop = EventLoop::new();
+        let window = WindowBuilder::new()
+            .with_canvas(Some(canvas))
+            .with_inner_size(size)
+            .build(&event_loop)?;
+        let mut renderer = Renderer::new(&window).await?;
+
+        let mut last_frame = instant::Instant::now();
+        // Never returns, winit throws to hand control back to the browser
+        event_loop.run(move |event, _, control_flow| {
+            *control_flow = ControlFlow::Wait;
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::Resized(size),
+                    ..
+                } => renderer.resize(size),
+                Event::RedrawRequested(_) => {
+                    let now = instant::Instant::now();
+                    model.step((now - last_frame).as_secs_f32());
+                    last_frame = now;
+
+                    if let Err(e) = renderer.render(&model.skel, &model.bounds) {
+                        log::error!("Failed to render: {:?}", e);
+                        *control_flow = ControlFlow::Exit;
+                    }
+                }
+                Event::MainEventsCleared => window.request_redraw(),
+                _ => {}
+            }
+        });
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::{Cursor, Read};
+
+use anyhow::Result;
+use image::RgbaImage;
+use spine::{AtlasLoader, AtlasPage};
+use zip::ZipArchive;
+
+/// Where the widget expects the atlas and skeleton inside a pack.
+pub const ATLAS_PATH: &str = "char.atlas";
+pub const SKELETON_PATH: &str = "char.skel";
+
+/// Reads the atlas, skeleton and textures of a model pack, the zip file the widget loads from
+/// its `data` folder, held in memory. Textures are decoded here and uploaded by the renderer
+/// when first drawn.
+pub struct PackLoader {
+    archive: ZipArchive<Cursor<Vec<u8>>>,
+}
+
+impl PackLoader {
+    pub fn new(pack: Vec<u8>) -> Result<Self> {
+        Ok(Self {
+            archive: ZipArchive::new(Cursor::new(pack))?,
+        })
+    }
+
+    fn read(&mut self, path: &str) -> Result<Vec<u8>> {
+        let mut file = self.archive.by_name(path)?;
+        let mut buf = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl AtlasLoader for PackLoader {
+    type Texture = RgbaImage;
+
+    fn load_texture(&mut self, path: &str, _: &AtlasPage) -> Result<(RgbaImage, u32, u32)> {
+        let image = image::load_from_memory(&self.read(path)?)?.into_rgba8();
+        let (width, height) = image.dimensions();
+        Ok((image, width, height))
+    }
+
+    fn load_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        self.read(path)
+    }
+}
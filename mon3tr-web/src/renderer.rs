@@ -0,0 +1,558 @@
+use std::{collections::HashMap, mem, ops::Range};
+
+use anyhow::{anyhow, Result};
+use image::RgbaImage;
+use spine::{
+    atlas::{AtlasFilter, AtlasWrap},
+    AtlasPage, BlendMode, Bounds, RenderCommands, RenderVertex, Skeleton,
+};
+use wgpu::util::DeviceExt;
+use winit::{dpi::PhysicalSize, window::Window};
+
+/// Room left around the model, as a fraction of its size.
+const MARGIN: f32 = 0.05;
+
+/// Blend modes in the order of [`Renderer::pipelines`].
+const BLEND_MODES: [BlendMode; 4] = [
+    BlendMode::Normal,
+    BlendMode::Additive,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Default)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+impl From<&RenderVertex> for Vertex {
+    fn from(vertex: &RenderVertex) -> Self {
+        Self {
+            position: vertex.position,
+            uv: vertex.uv,
+            color: vertex.color,
+        }
+    }
+}
+
+impl Vertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+            wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Maps skeleton space to NDC.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct Transform {
+    scale: [f32; 2],
+    offset: [f32; 2],
+}
+
+impl Transform {
+    /// Center `bounds` in a canvas of `width` by `height` pixels, as large as it fits without
+    /// stretching.
+    fn fit(bounds: &Bounds, width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let model_width = bounds.width().max(1.0) * (1.0 + 2.0 * MARGIN);
+        let model_height = bounds.height().max(1.0) * (1.0 + 2.0 * MARGIN);
+        // Pixels per unit of skeleton space
+        let pixels = (width / model_width).min(height / model_height);
+
+        let scale = [2.0 * pixels / width, 2.0 * pixels / height];
+        let center = [
+            (bounds.min_x + bounds.max_x) / 2.0,
+            (bounds.min_y + bounds.max_y) / 2.0,
+        ];
+        Self {
+            scale,
+            offset: [-center[0] * scale[0], -center[1] * scale[1]],
+        }
+    }
+}
+
+/// Part of the vertex and index buffers drawn with one page and blend mode.
+struct Draw {
+    page: usize,
+    blend_mode: BlendMode,
+    first_vertex: usize,
+    indices: Range<usize>,
+}
+
+/// Draws a skeleton into the canvas of a window with WebGL 2, through wgpu.
+pub struct Renderer {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    /// One for each of [`BLEND_MODES`].
+    pipelines: Vec<wgpu::RenderPipeline>,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    /// Bind groups of the atlas pages drawn so far, by the address of the page.
+    pages: HashMap<usize, wgpu::BindGroup>,
+    texture_format: wgpu::TextureFormat,
+    transform_buffer: wgpu::Buffer,
+    transform_bind_group: wgpu::BindGroup,
+    /// Both with their size in bytes.
+    vertex_buffer: (wgpu::Buffer, usize),
+    index_buffer: (wgpu::Buffer, usize),
+
+    commands: RenderCommands,
+    world_vertices: Vec<[f32; 2]>,
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    draws: Vec<Draw>,
+}
+
+impl Renderer {
+    pub async fn new(window: &Window) -> Result<Self> {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let surface = unsafe { instance.create_surface(window) };
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                force_fallback_adapter: false,
+                compatible_surface: Some(&surface),
+            })
+            .await
+            .ok_or_else(|| anyhow!("No WebGL 2 adapter available"))?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: None,
+                    features: wgpu::Features::empty(),
+                    limits: wgpu::Limits::downlevel_webgl2_defaults()
+                        .using_resolution(adapter.limits()),
+                },
+                None,
+            )
+            .await?;
+
+        let format = surface
+            .get_preferred_format(&adapter)
+            .ok_or_else(|| anyhow!("The canvas has no usable format"))?;
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        surface.configure(&device, &config);
+        // Sample pages the way the canvas is encoded, so colors come out as they're stored
+        let texture_format = if format.describe().srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("texture_bind_group_layout"),
+            });
+
+        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Transform Buffer"),
+            contents: bytemuck::cast_slice(&[Transform {
+                scale: [1.0; 2],
+                offset: [0.0; 2],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let transform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("transform_bind_group_layout"),
+            });
+        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &transform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            }],
+            label: Some("transform_bind_group"),
+        });
+
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Pipeline Layout"),
+            bind_group_layouts: &[&texture_bind_group_layout, &transform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipelines = BLEND_MODES
+            .iter()
+            .map(|&mode| {
+                device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Render Pipeline"),
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader,
+                        entry_point: "main_v",
+                        buffers: &[Vertex::desc()],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader,
+                        entry_point: "main_f",
+                        targets: &[wgpu::ColorTargetState {
+                            format,
+                            blend: Some(blend_state(mode)),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                })
+            })
+            .collect();
+
+        let vertex_buffer = create_buffer(&device, "Vertex Buffer", wgpu::BufferUsages::VERTEX, 0);
+        let index_buffer = create_buffer(&device, "Index Buffer", wgpu::BufferUsages::INDEX, 0);
+
+        Ok(Self {
+            surface,
+            device,
+            queue,
+            config,
+            pipelines,
+            texture_bind_group_layout,
+            pages: HashMap::new(),
+            texture_format,
+            transform_buffer,
+            transform_bind_group,
+            vertex_buffer,
+            index_buffer,
+
+            commands: RenderCommands::new(),
+            world_vertices: Vec::new(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            draws: Vec::new(),
+        })
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    /// Draw `skel`, whose world transform is up to date, fitting `bounds` into the canvas.
+    pub fn render(&mut self, skel: &Skeleton, bounds: &Bounds) -> Result<()> {
+        self.vertices.clear();
+        self.indices.clear();
+        self.draws.clear();
+
+        for command in self.commands.extract(skel, &mut self.world_vertices) {
+            let page = command.page as *const AtlasPage as usize;
+            if !self.pages.contains_key(&page) {
+                let image = match unsafe { command.page.render_object::<RgbaImage>() } {
+                    Some(image) => image,
+                    None => continue,
+                };
+                let bind_group = upload_page(
+                    &self.device,
+                    &self.queue,
+                    &self.texture_bind_group_layout,
+                    self.texture_format,
+                    command.page,
+                    image,
+                );
+                self.pages.insert(page, bind_group);
+            }
+
+            let first_vertex = self.vertices.len();
+            self.vertices
+                .extend(command.vertices.iter().map(Vertex::from));
+            let first_index = self.indices.len();
+            self.indices.extend_from_slice(command.indices);
+            self.draws.push(Draw {
+                page,
+                blend_mode: command.blend_mode,
+                first_vertex,
+                indices: first_index..self.indices.len(),
+            });
+            // Index buffer slices start at a multiple of 4 bytes
+            if self.indices.len() % 2 != 0 {
+                self.indices.push(0);
+            }
+        }
+
+        let transform = Transform::fit(bounds, self.config.width, self.config.height);
+        self.queue.write_buffer(
+            &self.transform_buffer,
+            0,
+            bytemuck::cast_slice(&[transform]),
+        );
+        self.upload_geometry();
+
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            // Happens when the canvas changes, try again next frame
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_bind_group(1, &self.transform_bind_group, &[]);
+
+            // WebGL 2 has no base vertex, so each draw gets the vertex buffer from its start
+            for draw in &self.draws {
+                let blend = BLEND_MODES
+                    .iter()
+                    .position(|&mode| mode == draw.blend_mode)
+                    .unwrap();
+                let vertex_offset = (draw.first_vertex * mem::size_of::<Vertex>()) as u64;
+                let index_offset = (draw.indices.start * mem::size_of::<u16>()) as u64;
+
+                render_pass.set_pipeline(&self.pipelines[blend]);
+                render_pass.set_bind_group(0, &self.pages[&draw.page], &[]);
+                render_pass.set_vertex_buffer(0, self.vertex_buffer.0.slice(vertex_offset..));
+                render_pass.set_index_buffer(
+                    self.index_buffer.0.slice(index_offset..),
+                    wgpu::IndexFormat::Uint16,
+                );
+                render_pass.draw_indexed(0..draw.indices.len() as u32, 0, 0..1);
+            }
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    /// Copy this frame's vertices and indices to the GPU, growing the buffers if needed.
+    fn upload_geometry(&mut self) {
+        let vertices: &[u8] = bytemuck::cast_slice(&self.vertices);
+        let indices: &[u8] = bytemuck::cast_slice(&self.indices);
+
+        if vertices.len() > self.vertex_buffer.1 {
+            self.vertex_buffer = create_buffer(
+                &self.device,
+                "Vertex Buffer",
+                wgpu::BufferUsages::VERTEX,
+                vertices.len(),
+            );
+        }
+        if indices.len() > self.index_buffer.1 {
+            self.index_buffer = create_buffer(
+                &self.device,
+                "Index Buffer",
+                wgpu::BufferUsages::INDEX,
+                indices.len(),
+            );
+        }
+
+        self.queue.write_buffer(&self.vertex_buffer.0, 0, vertices);
+        self.queue.write_buffer(&self.index_buffer.0, 0, indices);
+    }
+}
+
+/// Buffer of at least `size` bytes, rounded up to a power of two so it doesn't have to grow
+/// every frame. Returned with its actual size.
+fn create_buffer(
+    device: &wgpu::Device,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    size: usize,
+) -> (wgpu::Buffer, usize) {
+    let size = size.max(1024).next_power_of_two();
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        usage: usage | wgpu::BufferUsages::COPY_DST,
+        size: size as u64,
+        mapped_at_creation: false,
+    });
+    (buffer, size)
+}
+
+fn upload_page(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    layout: &wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    page: &AtlasPage,
+    image: &RgbaImage,
+) -> wgpu::BindGroup {
+    let texture = device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: Some("Page Texture"),
+            size: wgpu::Extent3d {
+                width: image.width(),
+                height: image.height(),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        },
+        image.as_raw(),
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let filter = |filter| match filter {
+        AtlasFilter::Nearest | AtlasFilter::MipmapNearestNearest => wgpu::FilterMode::Nearest,
+        _ => wgpu::FilterMode::Linear,
+    };
+    let address_mode = |wrap| match wrap {
+        AtlasWrap::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        AtlasWrap::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        AtlasWrap::Repeat => wgpu::AddressMode::Repeat,
+    };
+    // No mipmaps are generated, a preview is rarely drawn much smaller than the pages
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: address_mode(page.u_wrap()),
+        address_mode_v: address_mode(page.v_wrap()),
+        mag_filter: filter(page.mag_filter()),
+        min_filter: filter(page.min_filter()),
+        ..Default::default()
+    });
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+        label: Some("page_bind_group"),
+    })
+}
+
+/// Spine's blend modes for straight alpha textures.
+fn blend_state(mode: BlendMode) -> wgpu::BlendState {
+    let color = |src_factor, dst_factor| wgpu::BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: wgpu::BlendOperation::Add,
+    };
+    use wgpu::BlendFactor::*;
+    wgpu::BlendState {
+        color: match mode {
+            BlendMode::Normal => color(SrcAlpha, OneMinusSrcAlpha),
+            BlendMode::Additive => color(SrcAlpha, One),
+            BlendMode::Multiply => color(Dst, OneMinusSrcAlpha),
+            BlendMode::Screen => color(One, OneMinusSrc),
+        },
+        alpha: wgpu::BlendComponent::OVER,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(transform: &Transform, [x, y]: [f32; 2]) -> [f32; 2] {
+        [
+            x * transform.scale[0] + transform.offset[0],
+            y * transform.scale[1] + transform.offset[1],
+        ]
+    }
+
+    #[test]
+    fn fits_bounds_into_the_canvas() {
+        let bounds = Bounds {
+            min_x: -50.0,
+            min_y: 0.0,
+            max_x: 50.0,
+            max_y: 400.0,
+        };
+        let transform = Transform::fit(&bounds, 800, 800);
+
+        // Centered, and the taller side touches the margin
+        assert_eq!(apply(&transform, [0.0, 200.0]), [0.0, 0.0]);
+        let top = apply(&transform, [0.0, 400.0])[1];
+        assert!((top - 1.0 / (1.0 + 2.0 * MARGIN)).abs() < 1e-5);
+        // Not stretched on a square canvas
+        assert_eq!(transform.scale[0], transform.scale[1]);
+    }
+
+    #[test]
+    fn keeps_aspect_ratio_on_wide_canvases() {
+        let bounds = Bounds {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 100.0,
+            max_y: 100.0,
+        };
+        let transform = Transform::fit(&bounds, 400, 200);
+
+        // Twice as wide in pixels, so half as much NDC per unit horizontally
+        assert!((transform.scale[0] * 2.0 - transform.scale[1]).abs() < 1e-6);
+    }
+}
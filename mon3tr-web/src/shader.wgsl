@@ -0,0 +1,43 @@
+// Vertex shader
+
+struct Transform {
+    scale: vec2<f32>;
+    offset: vec2<f32>;
+};
+
+[[group(1), binding(0)]]
+var<uniform> transform: Transform;
+
+struct VertexInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn main_v(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = model.uv;
+    out.color = model.color;
+    // From skeleton space straight to NDC, both have y up
+    out.clip_position = vec4<f32>(model.position * transform.scale + transform.offset, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+[[group(0), binding(0)]]
+var t_page: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_page: sampler;
+
+[[stage(fragment)]]
+fn main_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(t_page, s_page, in.uv) * in.color;
+}
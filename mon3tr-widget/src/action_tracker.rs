@@ -0,0 +1,98 @@
+use std::collections::{HashMap, VecDeque};
+
+use spine::{AnimationEvent, AnimationState, EventType};
+
+use crate::config::{Action, Retrigger};
+
+/// Most actions waiting for the running one to finish, further triggers are dropped.
+const MAX_QUEUED: usize = 8;
+
+/// Knows which action is playing until its animations complete, so triggering it again can
+/// queue it or be ignored instead of cutting it off.
+pub struct ActionTracker {
+    running: Option<Running>,
+    queued: VecDeque<Action>,
+}
+
+struct Running {
+    action: Action,
+    /// Last animation of each track the action plays on, removed as they complete.
+    pending: HashMap<usize, String>,
+}
+
+impl ActionTracker {
+    pub fn new() -> Self {
+        Self {
+            running: None,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Whether to play `action` now, following its [`Retrigger`] if it's already playing.
+    pub fn should_play(&mut self, action: &Action) -> bool {
+        let running = match &self.running {
+            Some(running) if running.action == *action => running,
+            _ => return true,
+        };
+
+        match action.on_retrigger {
+            Retrigger::Restart => true,
+            Retrigger::Ignore => false,
+            Retrigger::Queue => {
+                if self.queued.len() < MAX_QUEUED {
+                    self.queued.push_back(running.action.clone());
+                }
+                false
+            }
+        }
+    }
+
+    /// Call when `action` starts playing with `pending` as the last animation of each track.
+    pub fn started(&mut self, action: &Action, pending: HashMap<usize, String>) {
+        if self.queued.front().map_or(false, |queued| queued != action) {
+            // Whatever was queued belonged to the action that just got cut off
+            self.queued.clear();
+        }
+
+        self.running = (!pending.is_empty()).then(|| Running {
+            action: action.clone(),
+            pending,
+        });
+    }
+
+    /// Forget the running action and drop whatever was queued.
+    pub fn clear(&mut self) {
+        self.running = None;
+        self.queued.clear();
+    }
+
+    /// Returns the next queued action once the running one is done.
+    pub fn on_event(&mut self, event: &AnimationEvent, anim: &AnimationState) -> Option<Action> {
+        if !matches!(event.kind, EventType::Complete | EventType::End) {
+            return None;
+        }
+        let running = self.running.as_mut()?;
+        if running.pending.get(&event.track_index) != Some(&event.animation_name) {
+            return None;
+        }
+        // Loops complete every time around, and a restarted action ends the entries it cut off
+        if is_playing(anim, event.track_index, &event.animation_name) {
+            return None;
+        }
+
+        running.pending.remove(&event.track_index);
+        if !running.pending.is_empty() {
+            return None;
+        }
+        self.running = None;
+        self.queued.pop_front()
+    }
+}
+
+/// Whether `name` is still the current animation of `track` and hasn't reached its end.
+fn is_playing(anim: &AnimationState, track: usize, name: &str) -> bool {
+    anim.current(track).map_or(false, |entry| {
+        let animation = entry.animation();
+        animation.name() == name && (entry.is_loop() || entry.track_time() < animation.duration())
+    })
+}
@@ -0,0 +1,72 @@
+//! Merging of separate alpha masks into atlas pages, as extracted from games that store
+//! color and alpha in different textures.
+
+use std::cell::RefCell;
+
+use anyhow::Result;
+use image::{imageops, DynamicImage, RgbaImage};
+
+use crate::{config::MaskPattern, utils::load_file_packed};
+
+thread_local! {
+    /// Patterns tried in order when loading a page, set from the config before loading models.
+    static PATTERNS: RefCell<Vec<MaskPattern>> = RefCell::new(Vec::new());
+}
+
+pub fn set_patterns(patterns: &[MaskPattern]) {
+    PATTERNS.with(|p| *p.borrow_mut() = patterns.to_vec());
+}
+
+/// Load the image at `path` as RGBA, taking its alpha from the first mask found next to it.
+pub fn load_page(path: &str) -> Result<DynamicImage> {
+    let mut base = image::load_from_memory(&load_file_packed(path)?)?.to_rgba8();
+
+    let mask = PATTERNS.with(|patterns| {
+        patterns
+            .borrow()
+            .iter()
+            .filter_map(|pattern| mask_path(pattern, path))
+            .find_map(|mask_path| Some((load_file_packed(&mask_path).ok()?, mask_path)))
+    });
+    if let Some((mask_buf, mask_path)) = mask {
+        log::debug!("Using alpha mask {}", mask_path);
+        merge(&mut base, &image::load_from_memory(&mask_buf)?);
+    }
+
+    Ok(DynamicImage::ImageRgba8(base))
+}
+
+/// Path of the mask for the page at `path`, which may be inside a pack.
+fn mask_path(pattern: &MaskPattern, path: &str) -> Option<String> {
+    let (dir, file) = match path.rfind(&['/', '\\'][..]) {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
+    };
+
+    match pattern {
+        MaskPattern::Suffix(suffix) => {
+            let (stem, ext) = file.rsplit_once('.')?;
+            Some(format!("{}{}{}.{}", dir, stem, suffix, ext))
+        }
+        MaskPattern::Subfolder(folder) => Some(format!("{}{}/{}", dir, folder, file)),
+    }
+}
+
+/// Replace the alpha of `base` with `mask`. Masks with an alpha channel in use provide it
+/// directly, otherwise the brightness of an RGB or grayscale mask is used.
+fn merge(base: &mut RgbaImage, mask: &DynamicImage) {
+    let mut mask = mask.to_luma_alpha8();
+    if mask.dimensions() != base.dimensions() {
+        mask = imageops::resize(
+            &mask,
+            base.width(),
+            base.height(),
+            imageops::FilterType::Triangle,
+        );
+    }
+
+    let use_alpha = mask.pixels().any(|m| m[1] != 255);
+    for (b, m) in base.pixels_mut().zip(mask.pixels()) {
+        b[3] = if use_alpha { m[1] } else { m[0] };
+    }
+}
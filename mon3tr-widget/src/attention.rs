@@ -0,0 +1,127 @@
+//! Notices other applications asking for attention, e.g. a chat program flashing its taskbar
+//! button when a message arrives, so the model can react to it.
+
+#[cfg(not(windows))]
+use std::{collections::HashSet, thread, time::Duration};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::{
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            DeregisterShellHookWindow, RegisterShellHookWindow, RegisterWindowMessageW,
+        },
+    },
+};
+#[cfg(windows)]
+use winit::platform::windows::WindowExtWindows;
+use winit::{event_loop::EventLoopProxy, window::Window};
+
+use crate::{window_ext, UserEvent};
+
+#[cfg(windows)]
+const SUBCLASS_ID: usize = 0x4d34;
+/// `HSHELL_REDRAW | HSHELL_HIGHBIT`, sent when a window flashes.
+#[cfg(windows)]
+const HSHELL_FLASH: usize = 0x8006;
+
+/// Listens to shell hook messages on a window for flashing windows.
+#[cfg(windows)]
+pub struct AttentionWatcher {
+    hwnd: HWND,
+    data: *mut SubclassData,
+}
+
+#[cfg(windows)]
+struct SubclassData {
+    proxy: EventLoopProxy<UserEvent>,
+    shell_hook_message: u32,
+}
+
+#[cfg(windows)]
+impl AttentionWatcher {
+    /// `window` must outlive the watcher, the hidden owner window is a good fit.
+    pub fn new(window: &Window, proxy: EventLoopProxy<UserEvent>) -> Self {
+        let hwnd = HWND(window.hwnd() as isize);
+        let data = Box::into_raw(Box::new(SubclassData {
+            proxy,
+            shell_hook_message: unsafe { RegisterWindowMessageW("SHELLHOOK") },
+        }));
+
+        unsafe {
+            SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, data as usize);
+            if !RegisterShellHookWindow(hwnd).as_bool() {
+                log::warn!("Failed to register for shell hook messages");
+            }
+        }
+
+        Self { hwnd, data }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for AttentionWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            DeregisterShellHookWindow(self.hwnd);
+            RemoveWindowSubclass(self.hwnd, Some(subclass_proc), SUBCLASS_ID);
+            drop(Box::from_raw(self.data));
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    w_param: WPARAM,
+    l_param: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    let data = &*(ref_data as *const SubclassData);
+    if msg == data.shell_hook_message && w_param.0 == HSHELL_FLASH {
+        let window = window_ext::foreign_window(HWND(l_param.0));
+        let _ = data.proxy.send_event(UserEvent::Attention(window));
+    }
+
+    DefSubclassProc(hwnd, msg, w_param, l_param)
+}
+
+/// How often windows are checked for the demands attention state.
+#[cfg(not(windows))]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Polls the window list on a thread of its own, nothing is found on Wayland.
+#[cfg(not(windows))]
+pub struct AttentionWatcher;
+
+#[cfg(not(windows))]
+impl AttentionWatcher {
+    pub fn new(_window: &Window, proxy: EventLoopProxy<UserEvent>) -> Self {
+        thread::spawn(move || {
+            // Only report windows when they start asking
+            let mut asking = HashSet::new();
+            loop {
+                thread::sleep(POLL_INTERVAL);
+
+                let mut still_asking = HashSet::new();
+                for window in window_ext::list_windows() {
+                    if !window_ext::demands_attention(window.id) {
+                        continue;
+                    }
+                    still_asking.insert(window.id);
+                    if !asking.contains(&window.id)
+                        && proxy.send_event(UserEvent::Attention(window)).is_err()
+                    {
+                        // The event loop is gone
+                        return;
+                    }
+                }
+                asking = still_asking;
+            }
+        });
+        Self
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+use crate::utils::load_file_packed;
+
+/// Plays sound effects through the default output device.
+pub struct Audio {
+    /// `None` if there is no usable output device, sounds are silently skipped then.
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    /// Master volume from 0 to 100.
+    volume: u8,
+    muted: bool,
+}
+
+impl Audio {
+    pub fn new(volume: u8, muted: bool) -> Self {
+        let output = match OutputStream::try_default() {
+            Ok(output) => Some(output),
+            Err(e) => {
+                log::warn!("No audio output available: {}", e);
+                None
+            }
+        };
+
+        Self {
+            output,
+            volume: volume.min(100),
+            muted,
+        }
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume.min(100);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Play an `.ogg`, `.wav`, `.flac` or `.mp3` file, which may be inside a model pack.
+    /// Returns immediately, the sound keeps playing in the background.
+    pub fn play(&self, path: &str) -> Result<()> {
+        let handle = match &self.output {
+            Some((_, handle)) if !self.muted && self.volume > 0 => handle,
+            _ => return Ok(()),
+        };
+
+        let source = Decoder::new(Cursor::new(load_file_packed(path)?))?;
+        let sink = Sink::try_new(handle)?;
+        sink.set_volume(self.volume as f32 / 100.0);
+        sink.append(source);
+        sink.detach();
+
+        Ok(())
+    }
+}
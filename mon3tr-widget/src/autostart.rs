@@ -0,0 +1,133 @@
+//! Starting the widget on login, with the config file it is running with now.
+
+use std::path::PathBuf;
+
+#[cfg(not(windows))]
+use anyhow::anyhow;
+#[cfg(windows)]
+use anyhow::bail;
+use anyhow::Result;
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS},
+    System::Registry::{
+        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
+        HKEY_CURRENT_USER, KEY_QUERY_VALUE, KEY_SET_VALUE, REG_SAM_FLAGS, REG_SZ,
+    },
+};
+
+#[cfg(windows)]
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg(windows)]
+const VALUE_NAME: &str = "Mon3tr-Widget";
+
+/// Whether the widget is registered to start on login.
+#[cfg(windows)]
+pub fn is_enabled() -> bool {
+    let key = match open_run_key(KEY_QUERY_VALUE) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    unsafe {
+        let result = RegQueryValueExW(
+            key,
+            VALUE_NAME,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        RegCloseKey(key);
+        result == ERROR_SUCCESS
+    }
+}
+
+/// Register or unregister the widget in `HKCU\...\Run`.
+#[cfg(windows)]
+pub fn set_enabled(enabled: bool, config_path: &str) -> Result<()> {
+    let key = open_run_key(KEY_SET_VALUE)?;
+    let result = unsafe {
+        let result = if enabled {
+            let command: Vec<u16> = command_line(config_path)?
+                .encode_utf16()
+                .chain(Some(0))
+                .collect();
+            RegSetValueExW(
+                key,
+                VALUE_NAME,
+                0,
+                REG_SZ,
+                command.as_ptr() as *const u8,
+                (command.len() * 2) as u32,
+            )
+        } else {
+            match RegDeleteValueW(key, VALUE_NAME) {
+                ERROR_FILE_NOT_FOUND => ERROR_SUCCESS,
+                result => result,
+            }
+        };
+        RegCloseKey(key);
+        result
+    };
+    if result != ERROR_SUCCESS {
+        bail!("Failed to update the Run key, error {}", result);
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn open_run_key(access: REG_SAM_FLAGS) -> Result<HKEY> {
+    let mut key = HKEY::default();
+    let result = unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, RUN_KEY, 0, access, &mut key) };
+    if result != ERROR_SUCCESS {
+        bail!("Failed to open the Run key, error {}", result);
+    }
+
+    Ok(key)
+}
+
+/// Whether the widget has an XDG autostart entry.
+#[cfg(not(windows))]
+pub fn is_enabled() -> bool {
+    desktop_entry_path().map_or(false, |path| path.exists())
+}
+
+/// Create or remove the XDG autostart entry.
+#[cfg(not(windows))]
+pub fn set_enabled(enabled: bool, config_path: &str) -> Result<()> {
+    let path = desktop_entry_path()?;
+    if enabled {
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(
+            &path,
+            format!(
+                "[Desktop Entry]\nType=Application\nName=Mon3tr-Widget\nExec={}\n",
+                command_line(config_path)?
+            ),
+        )?;
+    } else if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn desktop_entry_path() -> Result<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok_or_else(|| anyhow!("No config directory to put the autostart entry in"))?;
+
+    Ok(config_dir.join("autostart").join("mon3tr-widget.desktop"))
+}
+
+/// The executable and the absolute config path, quoted. Autostarted programs don't run in
+/// the directory the widget was started from.
+fn command_line(config_path: &str) -> Result<String> {
+    let exe = std::env::current_exe()?;
+    let config: PathBuf = std::env::current_dir()?.join(config_path);
+
+    Ok(format!("\"{}\" \"{}\"", exe.display(), config.display()))
+}
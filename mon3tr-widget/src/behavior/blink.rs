@@ -0,0 +1,31 @@
+use std::time::Instant;
+
+use crate::{config::BlinkConfig, spine_state::SpineState, utils::random_duration};
+
+/// Plays the blink animation at random intervals on its own track.
+pub struct Blink {
+    next_blink: Instant,
+}
+
+impl Blink {
+    pub fn new(config: &BlinkConfig) -> Self {
+        Self {
+            next_blink: Instant::now() + random_duration(config.interval),
+        }
+    }
+
+    pub fn update(&mut self, config: &BlinkConfig, spine: &mut SpineState) {
+        let now = Instant::now();
+        if now < self.next_blink {
+            return;
+        }
+
+        spine
+            .anim
+            .set_animation_by_name(config.track, &config.animation, false);
+        // Fade the track out again, so it only layers over the base track while blinking
+        spine.anim.add_empty_animation(config.track, 0.0, 0.0);
+
+        self.next_blink = now + random_duration(config.interval);
+    }
+}
@@ -0,0 +1,25 @@
+use std::{f32::consts::TAU, time::Instant};
+
+use crate::config::BreathingConfig;
+
+/// Slowly grows and lifts the whole model and back, so it doesn't look frozen when its idle
+/// animation hardly moves.
+pub struct Breathing {
+    started: Instant,
+}
+
+impl Breathing {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+
+    /// Scale multiplier and offset in logical pixels for the current point of the breath.
+    pub fn motion(&self, config: &BreathingConfig) -> (f32, f32) {
+        let phase = self.started.elapsed().as_secs_f32() / config.period.max(0.1) * TAU;
+        // 0 at rest, 1 at the peak, easing in and out of both
+        let breath = (1.0 - phase.cos()) / 2.0;
+        (1.0 + config.scale * breath, config.offset * breath)
+    }
+}
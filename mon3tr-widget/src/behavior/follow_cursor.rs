@@ -0,0 +1,37 @@
+use spine::Skeleton;
+
+use crate::config::FollowCursor;
+
+/// Move the configured bone (or IK target) towards `cursor`, given in model space.
+///
+/// This has to be called after the animation is applied and before the world transform
+/// is updated. The parent's world transform is the one from the previous frame, which
+/// is close enough for this purpose.
+pub fn apply(skel: &mut Skeleton, follow: &FollowCursor, cursor: [f32; 2]) {
+    let bone = match (&follow.ik_constraint, &follow.bone) {
+        (Some(ik), _) => skel.find_ik_constraint(ik).map(|ik| ik.target_mut()),
+        (None, Some(bone)) => skel.find_bone(bone),
+        (None, None) => None,
+    };
+    let bone = if let Some(bone) = bone {
+        bone
+    } else {
+        return;
+    };
+
+    let (target_x, target_y) = match bone.parent() {
+        Some(parent) => parent.world_to_local(cursor[0], cursor[1]),
+        None => (cursor[0], cursor[1]),
+    };
+
+    // Stay within `max_offset` of the animated position
+    let (dx, dy) = (target_x - bone.x(), target_y - bone.y());
+    let distance = (dx * dx + dy * dy).sqrt();
+    let factor = if distance > follow.max_offset {
+        follow.max_offset / distance
+    } else {
+        1.0
+    };
+
+    bone.set_position(bone.x() + dx * factor, bone.y() + dy * factor);
+}
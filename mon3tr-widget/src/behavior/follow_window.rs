@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+use winit::{dpi::PhysicalPosition, window::Window};
+
+use crate::{
+    config::{Corner, FollowWindowConfig},
+    window_ext,
+};
+
+/// How often the followed window is checked for moves and resizes.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How often to look for a matching window while there is none.
+const SEARCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Keeps the window docked to a corner of another application's window.
+pub struct WindowFollower {
+    target: Option<u64>,
+    last_update: Option<Instant>,
+    last_search: Option<Instant>,
+}
+
+impl WindowFollower {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            last_update: None,
+            last_search: None,
+        }
+    }
+
+    /// Follow this window right away instead of searching for one.
+    pub fn set_target(&mut self, id: u64) {
+        self.target = Some(id);
+        self.last_update = None;
+    }
+
+    pub fn target(&self) -> Option<u64> {
+        self.target
+    }
+
+    pub fn update(&mut self, config: &FollowWindowConfig, window: &Window) {
+        let now = Instant::now();
+        if let Some(last_update) = self.last_update {
+            if now - last_update < POLL_INTERVAL {
+                return;
+            }
+        }
+        self.last_update = Some(now);
+
+        let rect = match self.target.and_then(window_ext::window_rect) {
+            Some(rect) => rect,
+            None => {
+                // Closed or minimized, stay where we are until a matching window shows up
+                self.target = None;
+                if self
+                    .last_search
+                    .map_or(true, |last_search| now - last_search >= SEARCH_INTERVAL)
+                {
+                    self.last_search = Some(now);
+                    self.target = window_ext::list_windows()
+                        .into_iter()
+                        .find(|candidate| {
+                            candidate.matches(config.title.as_deref(), config.class.as_deref())
+                        })
+                        .map(|candidate| candidate.id);
+                }
+                return;
+            }
+        };
+
+        let size = window.outer_size();
+        let (width, height) = (size.width as i32, size.height as i32);
+        let scale_factor = window.scale_factor();
+        // Top corners perch on the window, bottom corners sit inside it
+        let (x, y) = match config.corner {
+            Corner::TopLeft => (rect.left, rect.top - height),
+            Corner::TopRight => (rect.right - width, rect.top - height),
+            Corner::BottomLeft => (rect.left, rect.bottom - height),
+            Corner::BottomRight => (rect.right - width, rect.bottom - height),
+        };
+        let target = PhysicalPosition::new(
+            x + (config.offset.0 * scale_factor) as i32,
+            y + (config.offset.1 * scale_factor) as i32,
+        );
+
+        if window.outer_position().ok() != Some(target) {
+            window.set_outer_position(target);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Instant;
+
+use spine::Skeleton;
+use winit::{dpi::PhysicalPosition, window::Window};
+
+use crate::{config::LeanConfig, window_ext::SpineWidgetWindowExt};
+
+/// Seconds it takes to get most of the way to a new angle.
+const SMOOTHING: f32 = 0.15;
+
+/// Tilts a bone as the window moves sideways, like the model is dragged along, and towards a
+/// screen edge it's close to, like it's leaning on it.
+pub struct Lean {
+    last_position: Option<(PhysicalPosition<i32>, Instant)>,
+    /// Degrees added to the bone's rotation, positive is counterclockwise.
+    angle: f32,
+}
+
+impl Lean {
+    pub fn new() -> Self {
+        Self {
+            last_position: None,
+            angle: 0.0,
+        }
+    }
+
+    /// Follow the window's movement, call every frame.
+    pub fn update(&mut self, config: &LeanConfig, window: &Window) {
+        let position = match window.outer_position() {
+            Ok(position) => position,
+            Err(_) => return,
+        };
+        let now = Instant::now();
+        let scale_factor = window.scale_factor();
+
+        let (velocity, dt) = match self.last_position {
+            Some((last, at)) if now > at => {
+                let dt = (now - at).as_secs_f64();
+                ((position.x - last.x) as f64 / scale_factor / dt, dt as f32)
+            }
+            _ => (0.0, 0.0),
+        };
+        self.last_position = Some((position, now));
+
+        // The top lags behind when moving, so moving right tilts it left
+        let mut target = velocity as f32 / 1000.0 * config.drag;
+
+        if let Some(work_area) = window.work_area() {
+            let width = window.outer_size().width as i32;
+            let left = (position.x - work_area.left) as f64 / scale_factor;
+            let right = (work_area.right - position.x - width) as f64 / scale_factor;
+            if config.edge_distance > 0.0 && left.min(right) < config.edge_distance {
+                let closeness = 1.0 - (left.min(right).max(0.0) / config.edge_distance) as f32;
+                let direction = if left < right { 1.0 } else { -1.0 };
+                target += direction * closeness * config.edge;
+            }
+        }
+
+        let target = target.clamp(-config.max_angle, config.max_angle);
+        self.angle += (target - self.angle) * (1.0 - (-dt / SMOOTHING).exp());
+    }
+
+    /// Rotate the bone, after the animation is applied and before the world transform is
+    /// updated.
+    pub fn apply(&self, config: &LeanConfig, skel: &mut Skeleton) {
+        if let Some(bone) = skel.find_bone(&config.bone) {
+            bone.set_rotation(bone.rotation() + self.angle);
+        }
+    }
+}
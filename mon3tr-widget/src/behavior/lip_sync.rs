@@ -0,0 +1,127 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Device, Sample, SampleFormat, Stream, StreamConfig,
+};
+use spine::Skeleton;
+
+use crate::{config::LipSyncConfig, spine_state::SpineState};
+
+/// How fast the mouth closes again, in fractions of fully open per second.
+const CLOSE_SPEED: f32 = 6.0;
+
+/// Opens the mouth of the model according to the microphone loudness.
+pub struct LipSync {
+    _stream: Stream,
+    /// Peak RMS level since the last update, as `f32` bits.
+    level: Arc<AtomicU32>,
+    /// How far the mouth is open, from 0 to 1.
+    openness: f32,
+    last_update: Instant,
+}
+
+impl LipSync {
+    /// Start listening on the default input device.
+    pub fn new() -> Result<Self> {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No microphone available"))?;
+        let supported = device.default_input_config()?;
+        let level = Arc::new(AtomicU32::new(0));
+
+        let config = supported.config();
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, level.clone())?,
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, level.clone())?,
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, level.clone())?,
+        };
+        stream.play()?;
+
+        Ok(Self {
+            _stream: stream,
+            level,
+            openness: 0.0,
+            last_update: Instant::now(),
+        })
+    }
+
+    /// Follow the loudness and drive the talk animation, should be called every frame.
+    pub fn update(&mut self, config: &LipSyncConfig, spine: &mut SpineState) {
+        let now = Instant::now();
+        let dt = (now - self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        let level = f32::from_bits(self.level.swap(0, Ordering::Relaxed));
+        let target = if level < config.threshold {
+            0.0
+        } else {
+            (level / config.max_level).min(1.0)
+        };
+        // Open instantly, close smoothly so the mouth does not flicker between syllables
+        self.openness = target.max(self.openness - CLOSE_SPEED * dt);
+
+        if let Some(animation) = &config.animation {
+            let playing = matches!(
+                spine.anim.current(config.track),
+                Some(entry) if entry.animation().name() == animation
+            );
+            if !playing {
+                spine
+                    .anim
+                    .set_animation_by_name(config.track, animation, true);
+            }
+            if let Some(entry) = spine.anim.current_mut(config.track) {
+                entry.set_alpha(self.openness);
+            }
+        }
+    }
+
+    /// Switch the mouth attachment, call after the animation has been applied.
+    pub fn apply(&self, config: &LipSyncConfig, skel: &mut Skeleton) {
+        let slot = if let Some(slot) = &config.slot {
+            slot
+        } else {
+            return;
+        };
+        if config.attachments.is_empty() {
+            return;
+        }
+
+        let max_index = config.attachments.len() - 1;
+        let index = ((self.openness * max_index as f32).round() as usize).min(max_index);
+        skel.set_attachment(slot, &config.attachments[index]);
+    }
+}
+
+fn build_stream<T: Sample>(
+    device: &Device,
+    config: &StreamConfig,
+    level: Arc<AtomicU32>,
+) -> Result<Stream> {
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            if data.is_empty() {
+                return;
+            }
+            let sum: f32 = data
+                .iter()
+                .map(|sample| sample.to_f32() * sample.to_f32())
+                .sum();
+            let rms = (sum / data.len() as f32).sqrt();
+            // The bits of non-negative floats order the same way as the floats
+            level.fetch_max(rms.to_bits(), Ordering::Relaxed);
+        },
+        |e| log::error!("Microphone error: {}", e),
+    )?;
+
+    Ok(stream)
+}
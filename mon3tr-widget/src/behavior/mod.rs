@@ -0,0 +1,11 @@
+//! Optional behaviors layered on top of the configured animations.
+
+pub mod blink;
+pub mod breathing;
+pub mod follow_cursor;
+pub mod follow_window;
+pub mod lean;
+pub mod lip_sync;
+pub mod peek;
+pub mod taskbar;
+pub mod wander;
@@ -0,0 +1,167 @@
+use std::time::{Duration, Instant};
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
+};
+
+use crate::{config::PeekConfig, spine_state::SpineState, window_ext::SpineWidgetWindowExt};
+
+enum Phase {
+    Shown {
+        last_activity: Instant,
+    },
+    /// Sliding towards `target`, off screen when hiding or back to where it was when peeking.
+    Sliding {
+        /// Sub-pixel window position
+        x: f64,
+        target: i32,
+        /// Where the window was before hiding
+        home: i32,
+        hiding: bool,
+        last_update: Instant,
+    },
+    Hidden {
+        home: i32,
+    },
+}
+
+/// Slides the window mostly off the side of the screen when left alone and back out when the
+/// cursor comes close.
+pub struct Peek {
+    phase: Phase,
+}
+
+impl Peek {
+    pub fn new() -> Self {
+        Self {
+            phase: Phase::Shown {
+                last_activity: Instant::now(),
+            },
+        }
+    }
+
+    /// Whether the window is hidden or on its way, the window position then shouldn't be
+    /// remembered or corrected.
+    pub fn is_away(&self) -> bool {
+        !matches!(self.phase, Phase::Shown { .. })
+    }
+
+    pub fn update(&mut self, config: &PeekConfig, window: &Window, spine: &mut SpineState) {
+        let now = Instant::now();
+        let (position, work_area) = match (window.outer_position(), window.work_area()) {
+            (Ok(position), Some(work_area)) => (position, work_area),
+            _ => return,
+        };
+        let size = window.outer_size();
+        let scale_factor = window.scale_factor();
+        let cursor_near = window.cursor_position().map_or(false, |cursor| {
+            is_near(cursor, window.inner_size(), config.distance * scale_factor)
+        });
+
+        match &mut self.phase {
+            Phase::Shown { last_activity } => {
+                if cursor_near {
+                    *last_activity = now;
+                    return;
+                }
+                if now - *last_activity < Duration::from_secs_f32(config.idle_time) {
+                    return;
+                }
+
+                // Hide behind the nearer side of the screen
+                let visible = (config.visible * scale_factor) as i32;
+                let center = position.x + size.width as i32 / 2;
+                let target = if center - work_area.left < work_area.right - center {
+                    work_area.left - size.width as i32 + visible
+                } else {
+                    work_area.right - visible
+                };
+
+                if let Some(animation) = &config.hide_animation {
+                    spine.anim.set_animation_by_name(0, animation, false);
+                }
+                self.phase = Phase::Sliding {
+                    x: position.x as f64,
+                    target,
+                    home: position.x,
+                    hiding: true,
+                    last_update: now,
+                };
+            }
+            Phase::Sliding {
+                x,
+                target,
+                home,
+                hiding,
+                last_update,
+            } => {
+                if *hiding && cursor_near {
+                    peek_out(config, spine);
+                    *target = *home;
+                    *hiding = false;
+                }
+
+                let dt = (now - *last_update).as_secs_f64();
+                *last_update = now;
+                let step = config.speed * scale_factor * dt;
+                let remaining = *target as f64 - *x;
+                *x += remaining.signum() * step.min(remaining.abs());
+                window.set_outer_position(PhysicalPosition::new(*x as i32, position.y));
+
+                if (*x - *target as f64).abs() < 0.5 {
+                    self.phase = if *hiding {
+                        Phase::Hidden { home: *home }
+                    } else {
+                        Phase::Shown { last_activity: now }
+                    };
+                }
+            }
+            Phase::Hidden { home } => {
+                if cursor_near {
+                    peek_out(config, spine);
+                    self.phase = Phase::Sliding {
+                        x: position.x as f64,
+                        target: *home,
+                        home: *home,
+                        hiding: false,
+                        last_update: now,
+                    };
+                }
+            }
+        }
+    }
+
+    /// Stay where the window is now, e.g. when it is being dragged.
+    pub fn stop(&mut self) {
+        self.phase = Phase::Shown {
+            last_activity: Instant::now(),
+        };
+    }
+
+    /// Put the window back where it was right away, e.g. before saving its position.
+    pub fn show(&mut self, window: &Window) {
+        if let Phase::Sliding { home, .. } | Phase::Hidden { home } = self.phase {
+            if let Ok(position) = window.outer_position() {
+                window.set_outer_position(PhysicalPosition::new(home, position.y));
+            }
+        }
+        self.phase = Phase::Shown {
+            last_activity: Instant::now(),
+        };
+    }
+}
+
+fn peek_out(config: &PeekConfig, spine: &mut SpineState) {
+    if let Some(animation) = &config.peek_animation {
+        spine.anim.set_animation_by_name(0, animation, false);
+    }
+}
+
+/// Whether `cursor`, relative to the client area, is within `distance` pixels of it.
+fn is_near(cursor: PhysicalPosition<f64>, size: PhysicalSize<u32>, distance: f64) -> bool {
+    cursor.x >= -distance
+        && cursor.y >= -distance
+        && cursor.x <= size.width as f64 + distance
+        && cursor.y <= size.height as f64 + distance
+}
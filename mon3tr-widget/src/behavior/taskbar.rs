@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+use winit::{dpi::PhysicalPosition, window::Window};
+
+use crate::window_ext::SpineWidgetWindowExt;
+
+/// How often the work area is polled for resolution, DPI or taskbar changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Keeps the bottom of the window aligned to the top of the taskbar.
+pub struct TaskbarSnap {
+    last_check: Option<Instant>,
+}
+
+impl TaskbarSnap {
+    pub fn new() -> Self {
+        Self { last_check: None }
+    }
+
+    /// Re-apply the snapping, at most once per [`POLL_INTERVAL`] unless `force` is set.
+    pub fn update(&mut self, window: &Window, force: bool) {
+        let now = Instant::now();
+        if let (false, Some(last_check)) = (force, self.last_check) {
+            if now - last_check < POLL_INTERVAL {
+                return;
+            }
+        }
+        self.last_check = Some(now);
+
+        let (work_area, position) = match (window.work_area(), window.outer_position()) {
+            (Some(work_area), Ok(position)) => (work_area, position),
+            _ => return,
+        };
+        let size = window.outer_size();
+
+        let max_x = (work_area.right - size.width as i32).max(work_area.left);
+        let target = PhysicalPosition::new(
+            position.x.max(work_area.left).min(max_x),
+            work_area.bottom - size.height as i32,
+        );
+
+        if target != position {
+            window.set_outer_position(target);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Instant;
+
+use rand::Rng;
+use winit::{dpi::PhysicalPosition, window::Window};
+
+use crate::{
+    config::WanderConfig, spine_state::SpineState, utils::random_duration,
+    window_ext::SpineWidgetWindowExt,
+};
+
+enum Phase {
+    Resting {
+        until: Instant,
+    },
+    Walking {
+        until: Instant,
+        /// -1.0 for left, 1.0 for right
+        direction: f64,
+        /// Sub-pixel window position
+        x: f64,
+        last_update: Instant,
+    },
+}
+
+/// Periodically walks the window along the bottom of the screen.
+pub struct Wander {
+    phase: Phase,
+}
+
+impl Wander {
+    pub fn new(config: &WanderConfig) -> Self {
+        Self {
+            phase: Phase::Resting {
+                until: Instant::now() + random_duration(config.rest_interval),
+            },
+        }
+    }
+
+    /// Returns `true` when a walk just ended, the caller should go back to idle.
+    pub fn update(
+        &mut self,
+        config: &WanderConfig,
+        window: &Window,
+        spine: &mut SpineState,
+    ) -> bool {
+        let now = Instant::now();
+        let position = match window.outer_position() {
+            Ok(position) => position,
+            Err(_) => return false,
+        };
+        let width = window.outer_size().width as i32;
+        let (min_x, max_x) = match (config.area, window.work_area()) {
+            (Some(area), _) => area,
+            (None, Some(work_area)) => (work_area.left, work_area.right),
+            (None, None) => return false,
+        };
+        let max_x = (max_x - width).max(min_x);
+
+        match &mut self.phase {
+            Phase::Resting { until } => {
+                if now < *until {
+                    return false;
+                }
+
+                // Walk away from the nearer edge, or randomly in the middle
+                let direction = if position.x <= min_x {
+                    1.0
+                } else if position.x >= max_x {
+                    -1.0
+                } else if rand::thread_rng().gen_bool(0.5) {
+                    1.0
+                } else {
+                    -1.0
+                };
+
+                spine
+                    .anim
+                    .set_animation_by_name(0, &config.walk_animation, true);
+                spine
+                    .skel
+                    .set_flip_x((direction > 0.0) != config.faces_right);
+
+                self.phase = Phase::Walking {
+                    until: now + random_duration(config.walk_duration),
+                    direction,
+                    x: position.x as f64,
+                    last_update: now,
+                };
+                false
+            }
+            Phase::Walking {
+                until,
+                direction,
+                x,
+                last_update,
+            } => {
+                // Pick up external moves (e.g. dragging) instead of snapping back
+                if (*x - position.x as f64).abs() > 1.0 {
+                    *x = position.x as f64;
+                }
+
+                let dt = (now - *last_update).as_secs_f64();
+                *last_update = now;
+                *x += *direction * config.speed * window.scale_factor() * dt;
+
+                let reached_edge = *x <= min_x as f64 || *x >= max_x as f64;
+                *x = x.max(min_x as f64).min(max_x as f64);
+                window.set_outer_position(PhysicalPosition::new(*x as i32, position.y));
+
+                if now >= *until || reached_edge {
+                    self.rest(config)
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Stop walking, returns `true` if it was walking.
+    pub fn rest(&mut self, config: &WanderConfig) -> bool {
+        let was_walking = matches!(self.phase, Phase::Walking { .. });
+
+        self.phase = Phase::Resting {
+            until: Instant::now() + random_duration(config.rest_interval),
+        };
+
+        was_walking
+    }
+}
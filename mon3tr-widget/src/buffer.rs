@@ -1,9 +1,29 @@
-use crate::{renderer::texture::TextureID, vertex::Vertex};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use spine::{AttachmentType, RenderCommands, Skeleton};
+
+use crate::{
+    renderer::{texture::TextureID, Renderer, Texture},
+    vertex::{LineVertex, Vertex},
+};
+
+const BONE_COLOR: [f32; 4] = [1.0, 0.25, 0.25, 1.0];
+const SLOT_COLOR: [f32; 4] = [0.25, 1.0, 0.25, 1.0];
+const WIREFRAME_COLOR: [f32; 4] = [0.25, 0.75, 1.0, 0.5];
+const BOUNDS_COLOR: [f32; 4] = [1.0, 1.0, 0.25, 1.0];
+/// Half the size of the cross marking a slot origin, in model units.
+const SLOT_MARKER_SIZE: f32 = 3.0;
 
 pub struct ScratchBuffers {
     index: usize,
     vertex_buffers: Vec<(TextureID, Vec<Vertex>)>,
     index_buffers: Vec<(TextureID, Vec<u16>)>,
+    /// Pairs of vertices of the debug overlay, drawn on top of everything.
+    debug_lines: Vec<LineVertex>,
+    commands: RenderCommands,
 }
 
 impl ScratchBuffers {
@@ -12,12 +32,15 @@ impl ScratchBuffers {
             index: 0,
             vertex_buffers: Vec::new(),
             index_buffers: Vec::new(),
+            debug_lines: Vec::new(),
+            commands: RenderCommands::new(),
         }
     }
 
     pub fn clear(&mut self) {
         self.vertex_buffers.iter_mut().for_each(|(_, v)| v.clear());
         self.index_buffers.iter_mut().for_each(|(_, v)| v.clear());
+        self.debug_lines.clear();
         self.index = 0;
     }
 
@@ -65,6 +88,136 @@ impl ScratchBuffers {
         )
     }
 
+    /// Append the attachments of the skeleton in draw order, registering their textures with
+    /// the renderer. `opacity` (0 to 1) multiplies the alpha of every vertex.
+    pub fn fill(
+        &mut self,
+        skel: &Skeleton,
+        renderer: &mut dyn Renderer,
+        world_vertices: &mut Vec<[f32; 2]>,
+        opacity: f32,
+    ) {
+        // Taken out while its commands are borrowed, put back for the next frame
+        let mut commands = std::mem::take(&mut self.commands);
+        for command in commands.extract(skel, world_vertices) {
+            let tex = if let Some(tex) = unsafe { command.page.render_object::<Texture>() } {
+                tex
+            } else {
+                continue;
+            };
+            let tex_id = tex.id();
+            renderer.register_texture(tex);
+
+            let (scratch_vb, scratch_ib) = self.get_buffers_mut(tex_id);
+
+            let offset = scratch_vb.len() as u16;
+            let new_vertices = command.vertices.iter().map(|vertex| {
+                let [r, g, b, a] = vertex.color;
+                Vertex {
+                    position: vertex.position,
+                    tex_coords: vertex.uv,
+                    tint: [r, g, b, a * opacity],
+                }
+            });
+            scratch_vb.extend(new_vertices);
+
+            let new_indices = command.indices.iter().map(|i| i + offset);
+            scratch_ib.extend(new_indices);
+        }
+        self.commands = commands;
+    }
+
+    /// Append the debug overlay of the skeleton: bones, slot origins, attachment wireframes and
+    /// the bounding box of all attachments.
+    pub fn fill_debug(&mut self, skel: &Skeleton, world_vertices: &mut Vec<[f32; 2]>) {
+        let mut min = [f32::INFINITY; 2];
+        let mut max = [f32::NEG_INFINITY; 2];
+
+        for slot in skel.iter_slots() {
+            let attachment = if let Some(a) = slot.attachment() {
+                a
+            } else {
+                continue;
+            };
+
+            let mut triangles = Vec::new();
+            match attachment.as_inner() {
+                AttachmentType::Region(region) => {
+                    region.compute_world_vertices(world_vertices);
+                    triangles.extend_from_slice(&[0, 1, 2, 2, 3, 0]);
+                }
+                AttachmentType::Mesh(mesh) => {
+                    mesh.compute_world_vertices(world_vertices);
+                    triangles.extend_from_slice(mesh.indices());
+                }
+                _ => continue,
+            }
+
+            for triangle in triangles.chunks_exact(3) {
+                for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                    self.line(
+                        world_vertices[triangle[a] as usize],
+                        world_vertices[triangle[b] as usize],
+                        WIREFRAME_COLOR,
+                    );
+                }
+            }
+
+            for p in world_vertices.iter() {
+                min = [min[0].min(p[0]), min[1].min(p[1])];
+                max = [max[0].max(p[0]), max[1].max(p[1])];
+            }
+        }
+
+        for bone in skel.bones() {
+            let origin = [bone.world_x(), bone.world_y()];
+            let (tip_x, tip_y) = bone.local_to_world(bone.length(), 0.0);
+            self.line(origin, [tip_x, tip_y], BONE_COLOR);
+        }
+
+        for slot in skel.iter_slots() {
+            let bone = slot.bone();
+            let (x, y) = (bone.world_x(), bone.world_y());
+            let d = SLOT_MARKER_SIZE;
+            self.line([x - d, y], [x + d, y], SLOT_COLOR);
+            self.line([x, y - d], [x, y + d], SLOT_COLOR);
+        }
+
+        if min[0] <= max[0] {
+            let corners = [min, [max[0], min[1]], max, [min[0], max[1]]];
+            for i in 0..4 {
+                self.line(corners[i], corners[(i + 1) % 4], BOUNDS_COLOR);
+            }
+        }
+    }
+
+    fn line(&mut self, from: [f32; 2], to: [f32; 2], color: [f32; 4]) {
+        self.debug_lines.push(LineVertex {
+            position: from,
+            color,
+        });
+        self.debug_lines.push(LineVertex {
+            position: to,
+            color,
+        });
+    }
+
+    /// Hash of everything to be drawn, to tell whether a frame differs from the last one.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for ((tex_id, vb), (_, ib)) in self.vertex_buffers.iter().zip(&self.index_buffers) {
+            tex_id.hash(&mut hasher);
+            hasher.write(bytemuck::cast_slice(vb));
+            hasher.write(bytemuck::cast_slice(ib));
+        }
+        hasher.write(bytemuck::cast_slice(&self.debug_lines));
+        hasher.finish()
+    }
+
+    pub fn debug_lines(&self) -> &[LineVertex] {
+        &self.debug_lines
+    }
+
     pub fn iter_mut(
         &mut self,
     ) -> impl Iterator<Item = (TextureID, &mut Vec<Vertex>, &mut Vec<u16>)> {
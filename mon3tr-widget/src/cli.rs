@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::{config::Command, export::ExportOptions};
+
+const DEFAULT_CONFIG_PATH: &str = "config.yml";
+
+/// What the process was started to do.
+pub enum Invocation {
+    /// Run the widget with the given config file.
+    Run { config_path: String },
+    /// Forward a command to the running widget, e.g. `mon3tr-widget trigger Attack`.
+    Send(Command),
+    /// Render an animation to files without opening a window,
+    /// e.g. `mon3tr-widget --export Attack --fps 30 --out frames/` or `--out attack.gif`.
+    Export {
+        config_path: String,
+        options: ExportOptions,
+    },
+}
+
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Invocation> {
+    let first = match args.next() {
+        Some(first) => first,
+        None => {
+            return Ok(Invocation::Run {
+                config_path: DEFAULT_CONFIG_PATH.to_string(),
+            })
+        }
+    };
+
+    if first == "--export" {
+        let animation = args
+            .next()
+            .ok_or_else(|| anyhow!("Missing <animation> for --export"))?;
+        return parse_export(animation, args);
+    }
+
+    let mut arg = |name: &str| {
+        args.next()
+            .ok_or_else(|| anyhow!("Missing <{}> for {:?}", name, first))
+    };
+
+    let command = match first.as_str() {
+        "trigger" => Command::Action(arg("action")?),
+        "animation" => Command::Animation(arg("animation")?),
+        "set-model" => Command::SetModel(arg("index")?.parse()?),
+        "next-model" => Command::NextModel,
+        "opacity" => Command::SetOpacity(arg("opacity")?.parse()?),
+        "speed" => Command::SetPlaybackSpeed(arg("speed")?.parse()?),
+        "toggle-windowed" => Command::ToggleWindowed,
+        "toggle-click-passthrough" => Command::ToggleClickPassthrough,
+        "toggle-recording" => Command::ToggleRecording,
+        "toggle-replay" => Command::ToggleReplay,
+        _ if first.starts_with('-') => bail!("Unknown option {:?}", first),
+        // Anything else is the path to the config file
+        _ => return Ok(Invocation::Run { config_path: first }),
+    };
+
+    Ok(Invocation::Send(command))
+}
+
+fn parse_export(animation: String, mut args: impl Iterator<Item = String>) -> Result<Invocation> {
+    let mut config_path = DEFAULT_CONFIG_PATH.to_string();
+    let mut options = ExportOptions::new(animation);
+
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow!("Missing value for {:?}", flag))?;
+
+        match flag.as_str() {
+            "--fps" => options.fps = value.parse()?,
+            "--out" => options.out = PathBuf::from(value),
+            "--model" => options.model = Some(value),
+            "--size" => {
+                let (width, height) = value
+                    .split_once('x')
+                    .ok_or_else(|| anyhow!("Size should look like 800x600"))?;
+                options.size = Some((width.parse()?, height.parse()?));
+            }
+            "--config" => config_path = value,
+            _ => bail!("Unknown option {:?}", flag),
+        }
+    }
+
+    if options.fps == 0 {
+        bail!("--fps must be positive");
+    }
+
+    Ok(Invocation::Export {
+        config_path,
+        options,
+    })
+}
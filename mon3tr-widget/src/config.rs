@@ -1,20 +1,55 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use spine::Pose;
 use winit::event::VirtualKeyCode;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+use crate::{hotkey::Hotkey, i18n::Language, utils::pick_weighted};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AnimationItem {
-    pub name: String,
+    /// Animation to play, leave out to mix the track out with an empty animation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     #[serde(rename = "loop", default, skip_serializing_if = "is_false")]
     pub loop_: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub length: Option<f32>,
+    /// Track to play on, items on the same track are queued after each other
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub track: usize,
+    /// Seconds to mix from the previous animation on the track
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mix_duration: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_scale: Option<f32>,
+}
+
+/// How an animation plays when triggered by name, from the tray menu, hotkeys or remote
+/// control, see [`Command::Animation`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct AnimationDefaults {
+    #[serde(rename = "loop", default, skip_serializing_if = "is_false")]
+    pub loop_: bool,
+    /// Track to play on, the animation mixes out again on tracks other than 0 unless it loops
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub track: usize,
+    /// Seconds to mix from the animation playing on the track before
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mix_duration: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_scale: Option<f32>,
 }
 
 fn is_false(loop_: &bool) -> bool {
     !loop_
 }
 
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
 fn is_true(loop_: &bool) -> bool {
     *loop_
 }
@@ -23,12 +58,671 @@ fn default_return_to_idle() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+fn default_true() -> bool {
+    true
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ActionVariant {
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    pub sequence: Vec<AnimationItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Action {
-    pub trigger: VirtualKeyCode,
+    /// Name used to refer to this action from [`Command::Action`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Key that triggers this action while the widget is focused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<VirtualKeyCode>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub sequence: Vec<AnimationItem>,
+    /// Alternative sequences, one of them is picked at random instead of `sequence`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ActionVariant>,
+    /// Sound file in the model pack, played when the action starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    /// Message shown in a speech bubble when the action starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub say: Option<String>,
     #[serde(default = "default_return_to_idle", skip_serializing_if = "is_true")]
     pub return_to_idle: bool,
+    /// What to do when triggered again before its animations completed
+    #[serde(default = "default_retrigger", skip_serializing_if = "is_restart")]
+    pub on_retrigger: Retrigger,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Retrigger {
+    /// Start over right away
+    Restart,
+    /// Play again once done
+    Queue,
+    Ignore,
+}
+
+fn default_retrigger() -> Retrigger {
+    Retrigger::Restart
+}
+
+fn is_restart(retrigger: &Retrigger) -> bool {
+    *retrigger == Retrigger::Restart
+}
+
+/// Something the widget can be told to do from outside of its own window.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    /// Run the action with the given name
+    Action(String),
+    /// Play a single animation, then return to idle, as set in `Config::animation_defaults`
+    Animation(String),
+    ToggleClickPassthrough,
+    ToggleWindowed,
+    /// Switch to the model at the given index of the data directory
+    SetModel(usize),
+    NextModel,
+    /// Set the opacity of the model, from 0 to 100
+    SetOpacity(u8),
+    /// Play all models at this speed, from 0.25 to 2
+    SetPlaybackSpeed(f32),
+    /// Multiply the color of the whole model with RGBA from 0 to 1, `[1, 1, 1, 1]` to reset
+    SetTint([f32; 4]),
+    /// Show bones, slots, wireframes and bounds on top of the model
+    ToggleDebugOverlay,
+    /// Show frame time, draw calls and memory usage in the corner of the window
+    ToggleStatsOverlay,
+    /// Hold the model in its current pose until toggled again
+    ToggleFreezePose,
+    /// Start or stop writing triggered actions to `recording_path`
+    ToggleRecording,
+    /// Start or stop playing back the actions in `recording_path`
+    ToggleReplay,
+    /// Show a message in a speech bubble, for `duration` seconds or the configured default
+    Say {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        duration: Option<f32>,
+    },
+    /// Send what the model plays to remote clients, as a `snapshot` event
+    Snapshot,
+    /// Mirror the model of another widget, from its `snapshot` event
+    ApplySnapshot(spine::Snapshot),
+}
+
+/// Let other programs control the widget over HTTP, only reachable from this computer.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RemoteConfig {
+    #[serde(default = "default_remote_port")]
+    pub port: u16,
+    /// Also push state changes to WebSocket clients on this port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub websocket_port: Option<u16>,
+    /// Speak a subset of the VTube Studio API on this port, which is 8001 in VTube Studio
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vtube_studio_port: Option<u16>,
+    /// Web pages that may send commands besides those served from this computer, like
+    /// `https://example.com`. Browsers let any page reach localhost, so others are turned away
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_origins: Vec<String>,
+}
+
+/// Let viewers trigger reactions from Twitch chat.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TwitchConfig {
+    /// Channel to join
+    pub channel: String,
+    /// Login name, chat is joined anonymously if this or `token` is not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// OAuth token, starting with `oauth:`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Chat commands like `!attack`, matched against the first word of messages
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, Command>,
+    /// Channel point rewards by ID, unknown IDs are logged when redeemed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rewards: HashMap<String, Command>,
+    /// Seconds before the same command or reward can trigger again
+    #[serde(default = "default_twitch_cooldown")]
+    pub cooldown: f32,
+}
+
+/// React to messages from an MQTT broker, e.g. home automation events.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MqttConfig {
+    pub host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub port: u16,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub triggers: Vec<MqttTrigger>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MqttTrigger {
+    /// Topic filter, may contain `+` and `#` wildcards
+    pub topic: String,
+    /// Only trigger if the payload equals this
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    pub command: Command,
+}
+
+/// Reaction to a user-defined event keyed in a Spine animation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventHook {
+    Command(Command),
+    /// Run a command line through `cmd /C` on Windows or `sh -c` elsewhere
+    Shell(String),
+    /// POST a JSON description of the event to the given URL
+    Webhook(String),
+    /// Play a sound file in the model pack
+    Sound(String),
+}
+
+/// Effect applied to the rendered model as a whole. Sizes and offsets are in pixels, with y
+/// pointing down.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostEffect {
+    Outline {
+        #[serde(default = "default_effect_color")]
+        color: [f32; 4],
+        #[serde(default = "default_outline_width")]
+        width: f32,
+    },
+    DropShadow {
+        #[serde(default = "default_shadow_color")]
+        color: [f32; 4],
+        #[serde(default = "default_shadow_offset")]
+        offset: (f32, f32),
+        #[serde(default = "default_shadow_blur")]
+        blur: f32,
+    },
+    Glow {
+        #[serde(default = "default_effect_color")]
+        color: [f32; 4],
+        #[serde(default = "default_glow_radius")]
+        radius: f32,
+    },
+    /// Path to a WGSL file with a `main_f` fragment entry point, see `post.wgsl` for what it
+    /// can use
+    Custom(String),
+}
+
+/// Extra model shown in the same window as the main one.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModelInstance {
+    /// File name of the model pack in the `data` folder
+    pub pack: String,
+    /// Position relative to the main model in model units, with y pointing up
+    #[serde(default)]
+    pub offset: (f32, f32),
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Looping idle animation, `Idle` if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle: Option<String>,
+    /// Draw behind the main model instead of in front of it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub behind: bool,
+}
+
+/// Model shown in a window of its own, next to the main one.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WindowInstance {
+    /// File name of the model pack in the `data` folder
+    pub pack: String,
+    #[serde(default = "default_initial_size")]
+    pub size: (f64, f64),
+    #[serde(default)]
+    pub position: (f64, f64),
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Looping idle animation, `Idle` if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle: Option<String>,
+}
+
+/// Where the separate alpha mask of an atlas page such as `dir/page.png` is looked for.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskPattern {
+    /// Suffix added to the file name before the extension, `[alpha]` gives
+    /// `dir/page[alpha].png`
+    Suffix(String),
+    /// Folder holding a file of the same name, `a` gives `dir/a/page.png`
+    Subfolder(String),
+}
+
+/// Color space blending and tinting happen in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    /// Work on sRGB-encoded values directly, like the Spine editor does
+    Legacy,
+    /// Decode textures and tints to linear values and encode the output back to sRGB
+    Linear,
+}
+
+/// How opacity below 100% is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpacityMode {
+    /// Fade every part of the model, overlapping parts show through each other
+    Vertex,
+    /// Fade the window as a whole, looks uniform but needs a compositor on Linux
+    Window,
+}
+
+/// Look of the speech bubbles shown by actions and the `say` command.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BubbleStyle {
+    /// Font file, a system font is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    /// In logical pixels
+    #[serde(default = "default_bubble_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_bubble_text_color")]
+    pub text_color: [f32; 4],
+    #[serde(default = "default_bubble_background")]
+    pub background: [f32; 4],
+    /// Longer messages are wrapped, in logical pixels
+    #[serde(default = "default_bubble_max_width")]
+    pub max_width: f32,
+    /// Moves the bubble from the top center of the window, in logical pixels
+    #[serde(default)]
+    pub offset: (f32, f32),
+    /// Seconds a message is shown for
+    #[serde(default = "default_bubble_duration")]
+    pub duration: f32,
+}
+
+impl Default for BubbleStyle {
+    fn default() -> Self {
+        Self {
+            font: None,
+            font_size: default_bubble_font_size(),
+            text_color: default_bubble_text_color(),
+            background: default_bubble_background(),
+            max_width: default_bubble_max_width(),
+            offset: (0.0, 0.0),
+            duration: default_bubble_duration(),
+        }
+    }
+}
+
+/// Which GPU and graphics API to draw with, left to wgpu when unset.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GpuConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<GraphicsBackend>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_preference: Option<PowerPreference>,
+    /// Use the first adapter whose name contains this, ignoring case, e.g. `intel`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapter: Option<String>,
+    #[serde(default)]
+    pub presentation: Presentation,
+}
+
+/// How drawn frames get onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Presentation {
+    /// Through the swapchain of a transparent window
+    Surface,
+    /// Through a premultiplied flip model swapchain on a DirectComposition visual, which tears
+    /// less than a transparent window but reads every frame back from the GPU. Windows 10 and
+    /// later, ignored elsewhere
+    DirectComposition,
+}
+
+impl Default for Presentation {
+    fn default() -> Self {
+        Presentation::Surface
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphicsBackend {
+    Vulkan,
+    Dx12,
+    Dx11,
+    Gl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPreference {
+    /// Usually the integrated GPU of a laptop
+    LowPower,
+    /// Usually the discrete GPU
+    HighPerformance,
+}
+
+/// Where log messages go besides the console.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LogConfig {
+    /// `info` if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+    /// Also write to `logs/mon3tr-widget.log` next to the executable
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub file: bool,
+    /// Start a new file after this many megabytes, 10 if not set. A new day always starts one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+    /// Older files to keep around, 5 if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// Animation played whenever a key is pressed anywhere in the system.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TypingReaction {
+    pub animation: String,
+    /// Track to play the animation on, so it layers over the idle animation
+    #[serde(default = "default_typing_track")]
+    pub track: usize,
+    /// Minimum interval between two typing animations in seconds
+    #[serde(default = "default_typing_interval")]
+    pub min_interval: f32,
+}
+
+/// Make a bone track the mouse cursor.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowCursor {
+    /// Bone moved towards the cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bone: Option<String>,
+    /// IK constraint whose target bone is moved towards the cursor, takes precedence over `bone`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ik_constraint: Option<String>,
+    /// Maximum distance the bone may move away from its animated position
+    #[serde(default = "default_follow_max_offset")]
+    pub max_offset: f32,
+}
+
+/// Size the window to fit the model instead of using `window_size` and `bottom_offset`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AutoSize {
+    /// Space left around the model in logical pixels, also covers animations reaching
+    /// further out than the idle pose the window is fitted to
+    #[serde(default = "default_auto_size_padding")]
+    pub padding: f32,
+}
+
+/// Tilt a bone as the window is dragged sideways and when it's close to a screen edge.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LeanConfig {
+    pub bone: String,
+    /// Degrees per 1000 logical pixels per second of sideways movement
+    #[serde(default = "default_lean_drag")]
+    pub drag: f32,
+    /// Degrees towards a screen edge the window touches
+    #[serde(default = "default_lean_edge")]
+    pub edge: f32,
+    /// Leaning towards an edge starts this close to it, in logical pixels
+    #[serde(default = "default_lean_edge_distance")]
+    pub edge_distance: f64,
+    #[serde(default = "default_lean_max_angle")]
+    pub max_angle: f32,
+}
+
+/// Blink at random intervals, layered over the base animation.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlinkConfig {
+    pub animation: String,
+    #[serde(default = "default_blink_track")]
+    pub track: usize,
+    /// Range of seconds between two blinks
+    #[serde(default = "default_blink_interval")]
+    pub interval: (f32, f32),
+}
+
+/// Slow rise and fall of the whole model, for models whose idle animation hardly moves.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BreathingConfig {
+    /// Seconds per breath
+    #[serde(default = "default_breathing_period")]
+    pub period: f32,
+    /// How much bigger the model gets, `0.01` is 1%
+    #[serde(default = "default_breathing_scale")]
+    pub scale: f32,
+    /// Logical pixels the model moves up
+    #[serde(default)]
+    pub offset: f32,
+}
+
+/// Move the mouth along with the microphone loudness.
+/// Either switches the attachment of a mouth slot, or fades a talk animation in and out.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LipSyncConfig {
+    /// Mouth slot whose attachment is switched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<String>,
+    /// Attachments of `slot`, from closed to fully open
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+    /// Looping animation whose alpha follows the loudness
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<String>,
+    #[serde(default = "default_lip_sync_track")]
+    pub track: usize,
+    /// Loudness (RMS, 0 to 1) below which the mouth stays closed
+    #[serde(default = "default_lip_sync_threshold")]
+    pub threshold: f32,
+    /// Loudness at which the mouth is fully open
+    #[serde(default = "default_lip_sync_max_level")]
+    pub max_level: f32,
+}
+
+/// Walk the window around the screen every now and then.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WanderConfig {
+    pub walk_animation: String,
+    /// Whether the walk animation faces right when not flipped
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub faces_right: bool,
+    /// Walking speed in logical pixels per second
+    #[serde(default = "default_wander_speed")]
+    pub speed: f64,
+    /// Range of seconds to rest between walks
+    #[serde(default = "default_wander_rest_interval")]
+    pub rest_interval: (f32, f32),
+    /// Range of seconds a single walk lasts
+    #[serde(default = "default_wander_walk_duration")]
+    pub walk_duration: (f32, f32),
+    /// Horizontal range in screen pixels to wander in, defaults to the work area of the monitor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub area: Option<(i32, i32)>,
+}
+
+/// Slide mostly off the side of the screen when left alone and peek back out when the cursor
+/// comes close, like classic desktop mascots.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PeekConfig {
+    /// Seconds without the cursor coming close before hiding
+    #[serde(default = "default_peek_idle_time")]
+    pub idle_time: f32,
+    /// Logical pixels of the window left on screen while hidden
+    #[serde(default = "default_peek_visible")]
+    pub visible: f64,
+    /// How close in logical pixels the cursor has to come to the window to bring it out
+    #[serde(default = "default_peek_distance")]
+    pub distance: f64,
+    /// Sliding speed in logical pixels per second
+    #[serde(default = "default_peek_speed")]
+    pub speed: f64,
+    /// Played once when starting to hide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hide_animation: Option<String>,
+    /// Played once when peeking back out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peek_animation: Option<String>,
+}
+
+/// Keep the window docked to a corner of another application's window, e.g. perched on top of
+/// an editor. The first window matching both `title` and `class` is followed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowWindowConfig {
+    /// Part of the window title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Window class, like `Notepad` on Windows or the `WM_CLASS` class name on X11
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(default = "default_follow_corner")]
+    pub corner: Corner,
+    /// Moves the window away from the corner, in logical pixels
+    #[serde(default)]
+    pub offset: (f64, f64),
+}
+
+/// Corner of a followed window. Top corners put the widget on top of the window, bottom
+/// corners inside of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Command run when another application asks for attention, e.g. a chat program flashing its
+/// taskbar button for a new message. Only windows matching both `title` and `class` count.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationReaction {
+    /// Part of the window title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Window class, like `Notepad` on Windows or the `WM_CLASS` class name on X11
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    pub command: Command,
+    /// Seconds before this reaction can trigger again, windows tend to flash repeatedly
+    #[serde(default = "default_notification_cooldown")]
+    pub cooldown: f32,
+}
+
+/// Run commands while the computer is busy, e.g. look exhausted while the CPU is above 90%.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SystemLoadConfig {
+    /// Seconds between two samples, CPU usage is averaged over this time
+    #[serde(default = "default_system_load_interval")]
+    pub interval: f32,
+    pub triggers: Vec<LoadTrigger>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoadTrigger {
+    pub resource: Resource,
+    /// Usage in percent at which `enter` runs
+    pub above: f32,
+    /// Usage in percent to fall below before `exit` runs, 5 less than `above` if not set, so
+    /// usage hovering around the threshold doesn't trigger over and over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enter: Vec<Command>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exit: Vec<Command>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    Cpu,
+    Memory,
+}
+
+/// Work and break timer started from the tray. A speech bubble announces each phase unless
+/// its action says something else.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PomodoroConfig {
+    #[serde(default = "default_work_minutes")]
+    pub work_minutes: f32,
+    #[serde(default = "default_break_minutes")]
+    pub break_minutes: f32,
+    /// Start working again after a break instead of stopping
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub repeat: bool,
+    /// Played when a work period starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub work_start: Option<Action>,
+    /// Played when a work period is over and the break starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub break_start: Option<Action>,
+    /// Played when the break is over without `repeat`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished: Option<Action>,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: default_work_minutes(),
+            break_minutes: default_break_minutes(),
+            repeat: true,
+            work_start: None,
+            break_start: None,
+            finished: None,
+        }
+    }
+}
+
+/// One of the idle animations to pick from at random.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IdleAnimation {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Keep looping this animation if it gets picked again
+    #[serde(rename = "loop", default, skip_serializing_if = "is_false")]
+    pub loop_: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -37,14 +731,207 @@ pub struct Config {
     pub actions: Vec<Action>,
     /// Animation to play on idle
     pub idle_animation: Option<String>,
+    /// Weighted idle animations, a new one is picked whenever the current one completes.
+    /// Takes precedence over `idle_animation`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub idles: Vec<IdleAnimation>,
     #[serde(default = "default_initial_size")]
     pub window_size: (f64, f64),
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_size: Option<AutoSize>,
     #[serde(default)]
     pub window_position: (f64, f64),
+    /// Window position on each monitor it has been on, relative to the monitor in logical
+    /// pixels, by monitor name. Takes precedence over `window_position`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub monitor_positions: HashMap<String, (f64, f64)>,
+    /// Monitor the window was on when last closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+    /// Size of the main model, changed with Ctrl + mouse wheel or Ctrl + `=`/`-`
     #[serde(default = "default_scale")]
     pub scale: f32,
     #[serde(default = "default_bottom_offset")]
     pub bottom_offset: f32,
+    /// Language of the tray menu, follows the system language if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    /// How animations play when triggered by name, by animation name. Others play once on
+    /// track 0
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub animation_defaults: HashMap<String, AnimationDefaults>,
+    /// Commands triggered by system-wide hotkeys, even when the widget is not focused
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub global_hotkeys: HashMap<Hotkey, Command>,
+    /// React to global key presses (bongo-cat style)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typing: Option<TypingReaction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_cursor: Option<FollowCursor>,
+    /// Attachment shown in a slot whatever the animations set, by slot name, `~` to show
+    /// nothing. Also chosen from the tray menu
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attachment_overrides: HashMap<String, Option<String>>,
+    /// Pose frozen from the tray menu, shown instead of the animations until unfrozen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frozen_pose: Option<Pose>,
+    /// Only react to clicks on opaque parts of the model
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hit_test: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_click: Option<Command>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_double_click: Option<Command>,
+    /// Played once the model is loaded at startup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_startup: Option<Action>,
+    /// Played before exiting, the window closes once it's done or after `exit_timeout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_exit: Option<Action>,
+    /// Longest time to wait for `on_exit` in seconds, looping animations never finish
+    #[serde(default = "default_exit_timeout")]
+    pub exit_timeout: f32,
+    /// Let the window glide for a bit after being dragged and released
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub drag_inertia: bool,
+    /// Keep the window behind other windows, like a desktop widget, instead of on top
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub always_on_bottom: bool,
+    /// Keep the window standing on the taskbar
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub snap_to_taskbar: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wander: Option<WanderConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peek: Option<PeekConfig>,
+    /// Also chosen from the tray menu
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_window: Option<FollowWindowConfig>,
+    /// Reactions to other applications asking for attention, the first matching one is used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifications: Vec<NotificationReaction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_load: Option<SystemLoadConfig>,
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blink: Option<BlinkConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breathing: Option<BreathingConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lean: Option<LeanConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lip_sync: Option<LipSyncConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_effect: Option<PostEffect>,
+    /// Draw on `background_color` instead of a transparent window, so capture software that
+    /// cannot handle transparent windows can key it out
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub chroma_key: bool,
+    #[serde(default = "default_background_color")]
+    pub background_color: [f32; 3],
+    #[serde(default = "default_opacity_mode")]
+    pub opacity_mode: OpacityMode,
+    /// Publish frames through Spout under this sender name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spout_sender: Option<String>,
+    /// Hooks run when an animation fires the Spine event with the given name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub spine_events: HashMap<String, Vec<EventHook>>,
+    /// Master volume of sounds, from 0 to 100
+    #[serde(default = "default_volume")]
+    pub volume: u8,
+    /// Opacity of the model from 0 to 100, changed with Shift + mouse wheel
+    #[serde(default = "default_opacity")]
+    pub opacity: u8,
+    /// Speed all animations play at, from 0.25 to 2, changed with Ctrl + `[` and `]`
+    #[serde(default = "default_playback_speed")]
+    pub playback_speed: f32,
+    /// File triggered actions are recorded to and replayed from, one JSON object per line
+    #[serde(default = "default_recording_path")]
+    pub recording_path: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub muted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitch: Option<TwitchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mqtt: Option<MqttConfig>,
+    #[serde(default)]
+    pub bubble: BubbleStyle,
+    /// Show the frame statistics overlay on startup
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stats_overlay: bool,
+    /// Log rolling averages of the frame statistics every 30 seconds
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub log_frame_stats: bool,
+    /// Draw at most this many frames per second, otherwise as fast as the display refreshes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps_limit: Option<u32>,
+    /// Drop to a few frames per second while the window is hidden or the model stands still
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub power_saving: bool,
+    /// Hide the widget while Windows is in battery saver mode
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pause_on_battery_saver: bool,
+    /// Hide the widget while a fullscreen game or presentation is in the foreground
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pause_on_fullscreen: bool,
+    /// Samples per pixel for anti-aliasing, one of 1, 2, 4 or 8. 1 disables it
+    #[serde(default = "default_msaa_samples")]
+    pub msaa_samples: u32,
+    /// Generate mip chains for textures whose atlas asks for a `MipMap*` filter
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub generate_mipmaps: bool,
+    #[serde(default = "default_color_space")]
+    pub color_space: ColorSpace,
+    #[serde(default)]
+    pub gpu: GpuConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    /// Downscale atlas pages by this factor when loading them, to save video memory
+    #[serde(default = "default_texture_scale")]
+    pub texture_scale: f32,
+    /// Tried in order for each atlas page, the first mask found replaces the page's alpha
+    #[serde(default = "default_alpha_masks")]
+    pub alpha_masks: Vec<MaskPattern>,
+    /// Other models sharing the window, drawn in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub companions: Vec<ModelInstance>,
+    /// Number of previously shown models kept loaded for fast switching
+    #[serde(default = "default_model_cache_size")]
+    pub model_cache_size: usize,
+    /// Model packs downloaded into the `data` folder on startup, unless already there
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_urls: Vec<String>,
+    /// Models opened in windows of their own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_windows: Vec<WindowInstance>,
+}
+
+impl Action {
+    /// The sequence to play, picked from `variants` if there are any.
+    pub fn pick_sequence(&self) -> &[AnimationItem] {
+        pick_weighted(&self.variants, |variant| variant.weight)
+            .map_or(&self.sequence, |variant| &variant.sequence)
+    }
+}
+
+impl Config {
+    /// Pick the next idle animation, returns its name and whether it loops.
+    pub fn pick_idle(&self) -> Option<(&str, bool)> {
+        if let Some(idle) = pick_weighted(&self.idles, |idle| idle.weight) {
+            return Some((&idle.name, idle.loop_));
+        }
+
+        self.idle_animation.as_deref().map(|name| (name, true))
+    }
+
+    pub fn is_idle(&self, animation_name: &str) -> bool {
+        self.idles.iter().any(|idle| idle.name == animation_name)
+            || self.idle_animation.as_deref() == Some(animation_name)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -54,6 +941,94 @@ fn default_initial_size() -> (f64, f64) {
     (300.0, 400.0)
 }
 
+fn default_remote_port() -> u16 {
+    8372
+}
+
+fn default_twitch_cooldown() -> f32 {
+    5.0
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_mqtt_client_id() -> String {
+    "mon3tr-widget".to_string()
+}
+
+fn default_background_color() -> [f32; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_effect_color() -> [f32; 4] {
+    [1.0, 1.0, 1.0, 1.0]
+}
+
+fn default_outline_width() -> f32 {
+    2.0
+}
+
+fn default_shadow_color() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 0.5]
+}
+
+fn default_shadow_offset() -> (f32, f32) {
+    (4.0, 4.0)
+}
+
+fn default_shadow_blur() -> f32 {
+    4.0
+}
+
+fn default_glow_radius() -> f32 {
+    6.0
+}
+
+fn default_model_cache_size() -> usize {
+    2
+}
+
+fn default_alpha_masks() -> Vec<MaskPattern> {
+    vec![
+        MaskPattern::Suffix("[alpha]".to_string()),
+        MaskPattern::Suffix("_alpha".to_string()),
+        MaskPattern::Subfolder("a".to_string()),
+    ]
+}
+
+fn default_texture_scale() -> f32 {
+    1.0
+}
+
+fn default_color_space() -> ColorSpace {
+    ColorSpace::Legacy
+}
+
+fn default_opacity_mode() -> OpacityMode {
+    OpacityMode::Vertex
+}
+
+fn default_msaa_samples() -> u32 {
+    1
+}
+
+fn default_volume() -> u8 {
+    100
+}
+
+fn default_opacity() -> u8 {
+    100
+}
+
+fn default_playback_speed() -> f32 {
+    1.0
+}
+
+fn default_recording_path() -> String {
+    "recording.jsonl".to_owned()
+}
+
 fn default_scale() -> f32 {
     1.0
 }
@@ -62,6 +1037,138 @@ fn default_bottom_offset() -> f32 {
     5.0
 }
 
+fn default_auto_size_padding() -> f32 {
+    10.0
+}
+
+fn default_typing_track() -> usize {
+    1
+}
+
+fn default_typing_interval() -> f32 {
+    0.15
+}
+
+fn default_follow_max_offset() -> f32 {
+    30.0
+}
+
+fn default_blink_track() -> usize {
+    1
+}
+
+fn default_blink_interval() -> (f32, f32) {
+    (2.0, 6.0)
+}
+
+fn default_breathing_period() -> f32 {
+    4.0
+}
+
+fn default_breathing_scale() -> f32 {
+    0.01
+}
+
+fn default_lip_sync_track() -> usize {
+    3
+}
+
+fn default_lip_sync_threshold() -> f32 {
+    0.01
+}
+
+fn default_lip_sync_max_level() -> f32 {
+    0.1
+}
+
+fn default_bubble_font_size() -> f32 {
+    16.0
+}
+
+fn default_bubble_text_color() -> [f32; 4] {
+    [0.1, 0.1, 0.1, 1.0]
+}
+
+fn default_bubble_background() -> [f32; 4] {
+    [1.0, 1.0, 1.0, 0.9]
+}
+
+fn default_bubble_max_width() -> f32 {
+    240.0
+}
+
+fn default_bubble_duration() -> f32 {
+    4.0
+}
+
+fn default_lean_drag() -> f32 {
+    10.0
+}
+
+fn default_lean_edge() -> f32 {
+    5.0
+}
+
+fn default_lean_edge_distance() -> f64 {
+    50.0
+}
+
+fn default_lean_max_angle() -> f32 {
+    15.0
+}
+
+fn default_exit_timeout() -> f32 {
+    3.0
+}
+
+fn default_work_minutes() -> f32 {
+    25.0
+}
+
+fn default_break_minutes() -> f32 {
+    5.0
+}
+
+fn default_system_load_interval() -> f32 {
+    2.0
+}
+
+fn default_notification_cooldown() -> f32 {
+    10.0
+}
+
+fn default_follow_corner() -> Corner {
+    Corner::TopRight
+}
+
+fn default_peek_idle_time() -> f32 {
+    60.0
+}
+
+fn default_peek_visible() -> f64 {
+    40.0
+}
+
+fn default_peek_distance() -> f64 {
+    60.0
+}
+
+fn default_peek_speed() -> f64 {
+    600.0
+}
+
+fn default_wander_speed() -> f64 {
+    60.0
+}
+
+fn default_wander_rest_interval() -> (f32, f32) {
+    (20.0, 60.0)
+}
+
+fn default_wander_walk_duration() -> (f32, f32) {
+    (2.0, 6.0)
+}
+
 pub fn load(path: &str) -> Result<Config> {
     let file = std::fs::File::open(path)?;
     let config: Config = serde_yaml::from_reader(file)?;
@@ -73,3 +1180,39 @@ pub fn save(config: &Config, path: &str) -> Result<()> {
     serde_yaml::to_writer(file, config)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> Config {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn pick_idle_falls_back_to_idle_animation() {
+        let config = config("actions: []\nidle_animation: Relax\n");
+        assert_eq!(config.pick_idle(), Some(("Relax", true)));
+
+        let config = self::config("actions: []\n");
+        assert_eq!(config.pick_idle(), None);
+    }
+
+    #[test]
+    fn pick_idle_prefers_weighted_idles() {
+        let config = config(
+            "actions: []
+idle_animation: Relax
+idles:
+  - name: Idle
+    loop: true
+  - name: Sit
+    weight: 0
+",
+        );
+        assert_eq!(config.pick_idle(), Some(("Idle", true)));
+        assert!(config.is_idle("Sit"));
+        assert!(config.is_idle("Relax"));
+        assert!(!config.is_idle("Attack"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Writing down why the widget crashed, since there is usually no console to read it from.
+
+use std::{backtrace::Backtrace, fmt::Write as _, panic::PanicInfo, path::PathBuf, sync::Mutex};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{MessageBoxW, MB_ICONERROR, MB_OK},
+};
+
+use crate::utils::exe_dir_path;
+
+/// What else is worth knowing about the process when it crashes.
+struct Context {
+    config_path: Option<String>,
+    adapters: Vec<String>,
+}
+
+static CONTEXT: Mutex<Context> = Mutex::new(Context {
+    config_path: None,
+    adapters: Vec::new(),
+});
+
+/// Write a crash log next to the executable and tell the user about it on panics.
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let report = report(info);
+        let path = log_path();
+        let message = match std::fs::write(&path, &report) {
+            Ok(()) => format!(
+                "Mon3tr-Widget crashed.\n\n{}\n\nDetails were saved to {}",
+                panic_message(info),
+                path.display()
+            ),
+            Err(e) => format!(
+                "Mon3tr-Widget crashed.\n\n{}\n\nFailed to save details to {}: {}",
+                panic_message(info),
+                path.display(),
+                e
+            ),
+        };
+        show_message(&message);
+    }));
+}
+
+pub fn set_config_path(path: &str) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.config_path = Some(path.to_owned());
+    }
+}
+
+/// Note an adapter a device was created on, e.g. `"NVIDIA GeForce RTX 3060 (Vulkan)"`.
+pub fn add_adapter(adapter: String) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        if !context.adapters.contains(&adapter) {
+            context.adapters.push(adapter);
+        }
+    }
+}
+
+fn report(info: &PanicInfo) -> String {
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "Mon3tr-Widget {} ({})",
+        env!("VERGEN_GIT_SEMVER"),
+        env!("VERGEN_CARGO_TARGET_TRIPLE")
+    );
+    let _ = writeln!(report, "Time: {}", chrono::Local::now().to_rfc3339());
+    let _ = writeln!(
+        report,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+
+    // Don't deadlock or panic again if the panic happened while holding the lock
+    if let Ok(context) = CONTEXT.try_lock() {
+        let _ = writeln!(
+            report,
+            "Config: {}",
+            context.config_path.as_deref().unwrap_or("<not loaded>")
+        );
+        for adapter in &context.adapters {
+            let _ = writeln!(report, "Adapter: {}", adapter);
+        }
+    }
+
+    let _ = writeln!(report, "\n{}", panic_message(info));
+    if let Some(location) = info.location() {
+        let _ = writeln!(report, "at {}", location);
+    }
+    let _ = writeln!(report, "\n{}", Backtrace::force_capture());
+
+    report
+}
+
+fn panic_message(info: &PanicInfo) -> String {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_owned())
+}
+
+fn log_path() -> PathBuf {
+    exe_dir_path().join(format!(
+        "crash-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+#[cfg(windows)]
+fn show_message(message: &str) {
+    unsafe {
+        MessageBoxW(
+            HWND::default(),
+            message,
+            "Mon3tr-Widget",
+            MB_OK | MB_ICONERROR,
+        );
+    }
+}
+
+#[cfg(not(windows))]
+fn show_message(message: &str) {
+    let _ = std::process::Command::new("zenity")
+        .args(["--error", "--title=Mon3tr-Widget", "--text", message])
+        .status();
+}
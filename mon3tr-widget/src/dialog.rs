@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+#[cfg(windows)]
+use std::{ffi::OsString, os::windows::ffi::OsStringExt};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HWND, PWSTR},
+    UI::Controls::Dialogs::{
+        GetSaveFileNameW, OFN_NOCHANGEDIR, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+    },
+};
+#[cfg(windows)]
+use winit::platform::windows::WindowExtWindows;
+use winit::window::Window;
+
+#[cfg(windows)]
+const MAX_PATH_LEN: usize = 1024;
+
+/// Ask the user where to save a file, returns `None` if cancelled.
+///
+/// `filters` are pairs of description and pattern, e.g. `("GIF", "*.gif")`; the extension of
+/// the selected filter is appended when the user does not type one.
+#[cfg(windows)]
+pub fn save_file(owner: &Window, default_name: &str, filters: &[(&str, &str)]) -> Option<PathBuf> {
+    let mut filter: Vec<u16> = filters
+        .iter()
+        .flat_map(|(name, pattern)| [*name, *pattern])
+        .flat_map(|s| s.encode_utf16().chain(Some(0)))
+        .collect();
+    filter.push(0);
+
+    let mut file: Vec<u16> = default_name.encode_utf16().collect();
+    file.resize(MAX_PATH_LEN, 0);
+
+    // Only used to enable appending the extension of the selected filter
+    let mut default_ext: Vec<u16> = "\0".encode_utf16().collect();
+
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: HWND(owner.hwnd() as isize),
+        lpstrFilter: PWSTR(filter.as_mut_ptr()),
+        nFilterIndex: 1,
+        lpstrFile: PWSTR(file.as_mut_ptr()),
+        nMaxFile: file.len() as u32,
+        lpstrDefExt: PWSTR(default_ext.as_mut_ptr()),
+        Flags: OFN_OVERWRITEPROMPT | OFN_PATHMUSTEXIST | OFN_NOCHANGEDIR,
+        ..Default::default()
+    };
+
+    if !unsafe { GetSaveFileNameW(&mut ofn) }.as_bool() {
+        return None;
+    }
+
+    let len = file.iter().position(|&c| c == 0).unwrap_or(file.len());
+    Some(OsString::from_wide(&file[..len]).into())
+}
+
+/// Ask the user where to save a file through `zenity`, returns `None` if cancelled.
+#[cfg(not(windows))]
+pub fn save_file(_owner: &Window, default_name: &str, filters: &[(&str, &str)]) -> Option<PathBuf> {
+    let mut command = std::process::Command::new("zenity");
+    command.args(["--file-selection", "--save", "--confirm-overwrite"]);
+    command.arg(format!("--filename={}", default_name));
+    for (name, pattern) in filters {
+        command.arg(format!("--file-filter={} | {}", name, pattern));
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("Failed to show save dialog: {}", e);
+            return None;
+        }
+    };
+    if !output.status.success() {
+        return None;
+    }
+
+    let path = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_owned();
+    Some(path.into())
+}
@@ -0,0 +1,78 @@
+//! Downloading of model packs from URLs into the `data` folder.
+
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Result};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{utils::exe_dir_path, UserEvent};
+
+/// File name a pack downloaded from `url` is saved as in the `data` folder.
+pub fn pack_name(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let path = rest.split(&['?', '#'][..]).next()?;
+    let (_, name) = path.rsplit_once('/')?;
+    if name.is_empty() {
+        return None;
+    }
+
+    if Path::new(name).extension().is_some() {
+        Some(name.to_string())
+    } else {
+        Some(format!("{}.zip", name))
+    }
+}
+
+pub fn data_path(name: &str) -> PathBuf {
+    let mut path = exe_dir_path();
+    path.push("data");
+    path.push(name);
+    path
+}
+
+/// Download the pack in the background, then send [`UserEvent::PackDownloaded`] so it can be
+/// checked and installed on the main thread. `select` switches to the model once installed.
+pub fn start(url: String, select: bool, proxy: EventLoopProxy<UserEvent>) {
+    std::thread::spawn(move || match download(&url) {
+        Ok((name, temp_path)) => {
+            log::info!("Downloaded {} from {}", name, url);
+            let _ = proxy.send_event(UserEvent::PackDownloaded {
+                name,
+                temp_path,
+                select,
+            });
+        }
+        Err(e) => log::error!("Failed to download {}: {:#}", url, e),
+    });
+}
+
+fn download(url: &str) -> Result<(String, PathBuf)> {
+    let name = match pack_name(url) {
+        Some(name) => name,
+        None => bail!("Not a URL to a model pack"),
+    };
+
+    let response = ureq::get(url).call()?;
+    let temp_path = std::env::temp_dir().join(format!("mon3tr-{}", name));
+    let mut file = BufWriter::new(File::create(&temp_path)?);
+    std::io::copy(&mut response.into_reader(), &mut file)?;
+
+    Ok((name, temp_path))
+}
+
+/// Move a downloaded pack that loaded fine into the `data` folder.
+pub fn install(name: &str, temp_path: &Path) -> Result<PathBuf> {
+    let path = data_path(name);
+    // The temporary folder may be on another drive, where renaming fails
+    if std::fs::rename(temp_path, &path).is_err() {
+        std::fs::copy(temp_path, &path)?;
+        let _ = std::fs::remove_file(temp_path);
+    }
+    Ok(path)
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use winit::{dpi::PhysicalPosition, window::Window};
+
+use crate::window_ext::{is_left_button_down, ScreenRect, SpineWidgetWindowExt};
+
+/// Fraction of the velocity kept after one second of gliding.
+const FRICTION: f64 = 0.02;
+/// Gliding stops below this speed, in pixels per second.
+const MIN_SPEED: f64 = 30.0;
+const MAX_SPEED: f64 = 4000.0;
+/// Only movement within this window before release counts towards the throw velocity.
+const SAMPLE_WINDOW: Duration = Duration::from_millis(100);
+
+/// Tracks window dragging, lets the window glide after release and keeps it on screen.
+pub struct DragState {
+    dragging: bool,
+    samples: VecDeque<(Instant, PhysicalPosition<f64>)>,
+    glide: Option<Glide>,
+}
+
+struct Glide {
+    position: PhysicalPosition<f64>,
+    velocity: (f64, f64),
+    last_update: Instant,
+}
+
+impl DragState {
+    pub fn new() -> Self {
+        Self {
+            dragging: false,
+            samples: VecDeque::new(),
+            glide: None,
+        }
+    }
+
+    /// Whether the window is being dragged or still gliding after a drag.
+    pub fn is_active(&self) -> bool {
+        self.dragging || self.glide.is_some()
+    }
+
+    /// Call right before handing the window over to the system for dragging.
+    pub fn start(&mut self, window: &Window) {
+        self.dragging = true;
+        self.glide = None;
+        self.samples.clear();
+        if let Ok(pos) = window.outer_position() {
+            self.samples.push_back((Instant::now(), pos.cast()));
+        }
+    }
+
+    /// Call on every `WindowEvent::Moved`.
+    pub fn moved(&mut self, position: PhysicalPosition<i32>) {
+        if !self.dragging {
+            return;
+        }
+
+        let now = Instant::now();
+        self.samples.push_back((now, position.cast()));
+        while let Some((time, _)) = self.samples.front() {
+            if now - *time > SAMPLE_WINDOW && self.samples.len() > 2 {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Advance the glide, should be called every frame.
+    pub fn update(&mut self, window: &Window, inertia: bool) {
+        if self.dragging {
+            if is_left_button_down() {
+                return;
+            }
+            self.release(window, inertia);
+        }
+
+        let glide = if let Some(glide) = self.glide.as_mut() {
+            glide
+        } else {
+            return;
+        };
+
+        let now = Instant::now();
+        let dt = (now - glide.last_update).as_secs_f64();
+        glide.last_update = now;
+
+        let decay = FRICTION.powf(dt);
+        glide.velocity.0 *= decay;
+        glide.velocity.1 *= decay;
+        glide.position.x += glide.velocity.0 * dt;
+        glide.position.y += glide.velocity.1 * dt;
+
+        // Stop at screen edges
+        let size = window.outer_size();
+        if let Some(work_area) = window.work_area() {
+            let clamped = clamp_to_area(glide.position, size.width, size.height, work_area);
+            if clamped.x != glide.position.x {
+                glide.velocity.0 = 0.0;
+            }
+            if clamped.y != glide.position.y {
+                glide.velocity.1 = 0.0;
+            }
+            glide.position = clamped;
+        }
+
+        window.set_outer_position(glide.position);
+
+        let speed = glide.velocity.0.hypot(glide.velocity.1);
+        if speed < MIN_SPEED {
+            self.glide = None;
+        }
+    }
+
+    fn release(&mut self, window: &Window, inertia: bool) {
+        self.dragging = false;
+
+        let position = if let Ok(pos) = window.outer_position() {
+            pos.cast()
+        } else {
+            return;
+        };
+
+        let velocity = match (self.samples.front(), self.samples.back()) {
+            (Some((t0, p0)), Some((t1, p1))) if inertia && *t1 > *t0 => {
+                let dt = (*t1 - *t0).as_secs_f64();
+                let (vx, vy) = ((p1.x - p0.x) / dt, (p1.y - p0.y) / dt);
+                let speed = vx.hypot(vy);
+                if speed > MAX_SPEED {
+                    (vx * MAX_SPEED / speed, vy * MAX_SPEED / speed)
+                } else {
+                    (vx, vy)
+                }
+            }
+            _ => (0.0, 0.0),
+        };
+        self.samples.clear();
+
+        // A zero-velocity glide still clamps the window back on screen once.
+        self.glide = Some(Glide {
+            position,
+            velocity,
+            last_update: Instant::now(),
+        });
+    }
+}
+
+/// Clamp the top-left `position` of a window of the given size so it lies within `area`.
+pub fn clamp_to_area(
+    position: PhysicalPosition<f64>,
+    width: u32,
+    height: u32,
+    area: ScreenRect,
+) -> PhysicalPosition<f64> {
+    let max_x = (area.right - width as i32).max(area.left) as f64;
+    let max_y = (area.bottom - height as i32).max(area.top) as f64;
+
+    PhysicalPosition::new(
+        position.x.max(area.left as f64).min(max_x),
+        position.y.max(area.top as f64).min(max_y),
+    )
+}
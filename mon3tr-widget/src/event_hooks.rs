@@ -0,0 +1,64 @@
+use std::{process, thread};
+
+use spine::AnimationEvent;
+
+use crate::config::EventHook;
+
+/// Run a hook that leaves the widget, on a separate thread so the event loop never blocks.
+///
+/// [`EventHook::Command`] and [`EventHook::Sound`] are handled by the caller since they need
+/// the widget state.
+pub fn spawn(hook: &EventHook, event: &AnimationEvent) {
+    let spine_event = if let Some(e) = &event.event {
+        e
+    } else {
+        return;
+    };
+
+    match hook {
+        EventHook::Command(_) | EventHook::Sound(_) => {}
+        EventHook::Shell(command_line) => {
+            let command_line = command_line.clone();
+            thread::spawn(move || {
+                let result = shell_command(&command_line).status();
+                match result {
+                    Ok(status) if !status.success() => {
+                        log::warn!("Event hook {:?} exited with {}", command_line, status)
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to run event hook {:?}: {}", command_line, e),
+                }
+            });
+        }
+        EventHook::Webhook(url) => {
+            let url = url.clone();
+            let body = serde_json::json!({
+                "event": spine_event.name,
+                "animation": event.animation_name,
+                "track": event.track_index,
+                "int": spine_event.int_value,
+                "float": spine_event.float_value,
+                "string": spine_event.string_value,
+            });
+            thread::spawn(move || {
+                if let Err(e) = ureq::post(&url).send_json(body) {
+                    log::error!("Failed to send webhook to {}: {}", url, e);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell_command(command_line: &str) -> process::Command {
+    let mut command = process::Command::new("cmd");
+    command.args(["/C", command_line]);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell_command(command_line: &str) -> process::Command {
+    let mut command = process::Command::new("sh");
+    command.args(["-c", command_line]);
+    command
+}
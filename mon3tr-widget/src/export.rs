@@ -0,0 +1,235 @@
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+};
+
+use anyhow::{anyhow, bail, Result};
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, RgbaImage,
+};
+use winit::dpi::PhysicalSize;
+
+use crate::{
+    alpha_mask,
+    buffer::ScratchBuffers,
+    config::Config,
+    renderer::{backend::hardware::HardwareRenderer, texture, Renderer},
+    spine_state::SpineState,
+    utils::exe_dir_path,
+};
+
+/// File types offered when exporting from the tray, see [`crate::dialog::save_file`].
+pub const FILE_FILTERS: &[(&str, &str)] = &[
+    ("GIF", "*.gif"),
+    ("Animated PNG", "*.png"),
+    ("WebM", "*.webm"),
+];
+
+/// Which animation to render and where, see [`crate::cli::Invocation::Export`].
+pub struct ExportOptions {
+    pub animation: String,
+    pub fps: u32,
+    /// A directory for a PNG sequence, or a `.gif`, `.png`/`.apng` or `.webm` file
+    pub out: PathBuf,
+    /// Model pack in the data directory, the first one if not set
+    pub model: Option<String>,
+    /// Size of the frames in pixels, the configured window size if not set
+    pub size: Option<(u32, u32)>,
+}
+
+impl ExportOptions {
+    pub fn new(animation: String) -> Self {
+        Self {
+            animation,
+            fps: 30,
+            out: PathBuf::from("export"),
+            model: None,
+            size: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    PngSequence,
+    Gif,
+    Apng,
+    Webm,
+}
+
+impl ExportFormat {
+    /// Guess from the extension of the output path, anything else is a directory.
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("gif") => ExportFormat::Gif,
+            Some("png" | "apng") => ExportFormat::Apng,
+            Some("webm") => ExportFormat::Webm,
+            _ => ExportFormat::PngSequence,
+        }
+    }
+}
+
+/// Render one pass of the animation offscreen and encode it with transparency.
+pub fn export(config: &Config, options: &ExportOptions) -> Result<()> {
+    // Created first so textures can be loaded in the formats the GPU supports
+    let (width, height) = options.size.unwrap_or((
+        config.window_size.0.round() as u32,
+        config.window_size.1.round() as u32,
+    ));
+    let mut renderer = pollster::block_on(HardwareRenderer::headless(
+        PhysicalSize::new(width, height),
+        config,
+    ))?;
+
+    let pack = find_pack(options.model.as_deref())?;
+    texture::set_texture_scale(config.texture_scale);
+    alpha_mask::set_patterns(&config.alpha_masks);
+    let mut spine = SpineState::new(&pack.to_string_lossy(), None)?;
+
+    let duration = spine
+        .skel_data
+        .animations()
+        .iter()
+        .find(|anim| anim.name() == options.animation)
+        .map(|anim| anim.duration())
+        .ok_or_else(|| anyhow!("No animation named {:?}", options.animation))?;
+    spine
+        .anim
+        .set_animation_by_name(0, &options.animation, false);
+
+    let format = ExportFormat::from_path(&options.out);
+    let mut sink = FrameSink::open(format, options, width, height)?;
+
+    let frame_time = 1.0 / options.fps as f32;
+    let frame_count = ((duration / frame_time).ceil() as usize).max(1);
+    let mut buffers = ScratchBuffers::new();
+    let mut world_vertices = Vec::new();
+
+    for i in 0..frame_count {
+        spine.step(if i == 0 { 0.0 } else { frame_time }, |_| {});
+        buffers.fill(&spine.skel, &mut renderer, &mut world_vertices, 1.0);
+        let frame = renderer.capture(&mut buffers)?;
+        buffers.clear();
+
+        sink.push(i, frame)?;
+    }
+    sink.finish()?;
+
+    log::info!(
+        "Exported {} frames of {:?} to {}",
+        frame_count,
+        options.animation,
+        options.out.display()
+    );
+
+    Ok(())
+}
+
+/// Where rendered frames go.
+enum FrameSink<'a> {
+    PngSequence(&'a ExportOptions),
+    Gif(GifEncoder<BufWriter<File>>, Delay),
+    /// APNG and WebM are encoded by piping raw frames into `ffmpeg`.
+    Ffmpeg(Child),
+}
+
+impl<'a> FrameSink<'a> {
+    fn open(
+        format: ExportFormat,
+        options: &'a ExportOptions,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let sink = match format {
+            ExportFormat::PngSequence => {
+                std::fs::create_dir_all(&options.out)?;
+                FrameSink::PngSequence(options)
+            }
+            ExportFormat::Gif => {
+                let mut encoder = GifEncoder::new(BufWriter::new(File::create(&options.out)?));
+                encoder.set_repeat(Repeat::Infinite)?;
+                FrameSink::Gif(encoder, Delay::from_numer_denom_ms(1000, options.fps))
+            }
+            ExportFormat::Apng | ExportFormat::Webm => {
+                let mut command = Command::new("ffmpeg");
+                command
+                    .args(["-y", "-loglevel", "error"])
+                    .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+                    .args(["-s", &format!("{}x{}", width, height)])
+                    .args(["-r", &options.fps.to_string()])
+                    .args(["-i", "-"]);
+                if format == ExportFormat::Apng {
+                    command.args(["-f", "apng", "-plays", "0"]);
+                } else {
+                    // VP9 is the codec browsers and OBS read alpha from
+                    command.args(["-c:v", "libvpx-vp9", "-pix_fmt", "yuva420p"]);
+                }
+
+                let child = command
+                    .arg(&options.out)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| anyhow!("Failed to run ffmpeg, is it in PATH? {}", e))?;
+                FrameSink::Ffmpeg(child)
+            }
+        };
+
+        Ok(sink)
+    }
+
+    fn push(&mut self, index: usize, frame: RgbaImage) -> Result<()> {
+        match self {
+            FrameSink::PngSequence(options) => frame.save(
+                options
+                    .out
+                    .join(format!("{}_{:04}.png", options.animation, index)),
+            )?,
+            FrameSink::Gif(encoder, delay) => {
+                encoder.encode_frame(Frame::from_parts(frame, 0, 0, *delay))?
+            }
+            FrameSink::Ffmpeg(child) => child.stdin.as_mut().unwrap().write_all(&frame)?,
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        if let FrameSink::Ffmpeg(mut child) = self {
+            // Closing stdin ends the input
+            drop(child.stdin.take());
+            let status = child.wait()?;
+            if !status.success() {
+                bail!("ffmpeg exited with {}", status);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Path of a model pack in the data directory.
+fn find_pack(model: Option<&str>) -> Result<PathBuf> {
+    let mut data_dir = exe_dir_path();
+    data_dir.push("data");
+
+    let mut files: Vec<OsString> = std::fs::read_dir(&data_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect::<Result<_, _>>()?;
+    files.sort();
+
+    let file = match model {
+        Some(model) => files.into_iter().find(|f| f.to_string_lossy() == model),
+        None => files.into_iter().next(),
+    }
+    .ok_or_else(|| anyhow!("Model not found in {}", data_dir.display()))?;
+
+    Ok(data_dir.join(file))
+}
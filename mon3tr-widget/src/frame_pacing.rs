@@ -0,0 +1,69 @@
+//! Deciding when to draw the next frame, instead of redrawing as fast as possible.
+
+use std::time::{Duration, Instant};
+
+/// Frame rate while throttled, high enough to notice the model moving again quickly.
+const THROTTLED_FPS: u32 = 10;
+/// Identical frames in a row after which the model counts as standing still.
+const STATIC_AFTER_FRAMES: u32 = 30;
+
+pub struct FramePacer {
+    next_frame: Instant,
+    last_fingerprint: Option<u64>,
+    unchanged_frames: u32,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self {
+            next_frame: Instant::now(),
+            last_fingerprint: None,
+            unchanged_frames: 0,
+        }
+    }
+
+    /// Whether the last frames all looked the same.
+    pub fn is_static(&self) -> bool {
+        self.unchanged_frames >= STATIC_AFTER_FRAMES
+    }
+
+    /// When the next frame is due, `None` if as soon as possible.
+    pub fn next_frame(&self, fps_limit: Option<u32>, throttled: bool) -> Option<Instant> {
+        if throttled || fps_limit.is_some() {
+            Some(self.next_frame)
+        } else {
+            None
+        }
+    }
+
+    /// Schedule the next frame after drawing one whose contents hash to `fingerprint`.
+    pub fn frame_drawn(&mut self, fingerprint: u64, fps_limit: Option<u32>, throttled: bool) {
+        if self.last_fingerprint == Some(fingerprint) {
+            self.unchanged_frames = self.unchanged_frames.saturating_add(1);
+        } else {
+            self.unchanged_frames = 0;
+        }
+        self.last_fingerprint = Some(fingerprint);
+
+        let fps = match (fps_limit, throttled) {
+            (Some(fps), true) => fps.min(THROTTLED_FPS),
+            (None, true) => THROTTLED_FPS,
+            (Some(fps), false) => fps,
+            (None, false) => return,
+        };
+        let interval = Duration::from_secs_f64(1.0 / fps.max(1) as f64);
+
+        // Keep a steady rhythm, unless we fell behind by more than a frame
+        let now = Instant::now();
+        self.next_frame += interval;
+        if self.next_frame + interval < now || self.next_frame > now + interval {
+            self.next_frame = now + interval;
+        }
+    }
+
+    /// Draw the next frame right away, e.g. after the user did something.
+    pub fn wake(&mut self) {
+        self.unchanged_frames = 0;
+        self.next_frame = Instant::now();
+    }
+}
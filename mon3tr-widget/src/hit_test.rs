@@ -0,0 +1,104 @@
+use image::GenericImageView;
+use spine::{AttachmentType, Skeleton};
+
+use crate::renderer::Texture;
+
+/// Alpha (0-255) above which a pixel counts as opaque.
+const ALPHA_THRESHOLD: f32 = 16.0;
+
+/// Test whether the rendered skeleton has an opaque pixel at `point` (in model space).
+///
+/// Attachments are walked front to back, the texture is sampled at the interpolated UV of the
+/// triangle under the point, so this matches what is on screen without reading back from GPU.
+pub fn hit_test(skel: &Skeleton, point: [f32; 2], world_vertices: &mut Vec<[f32; 2]>) -> bool {
+    let skel_alpha = skel.tint_color()[3];
+
+    for slot in skel.iter_slots().rev() {
+        let attachment = if let Some(a) = slot.attachment() {
+            a
+        } else {
+            continue;
+        };
+        let alpha = skel_alpha * slot.tint_color()[3];
+
+        let hit = match attachment.as_inner() {
+            AttachmentType::Region(region) => {
+                let tex = unsafe { region.atlas_region().page().render_object::<Texture>() };
+                let tex = if let Some(tex) = tex { tex } else { continue };
+
+                region.compute_world_vertices(world_vertices);
+                hit_triangles(world_vertices, &[0, 1, 2, 2, 3, 0], point, |i| region.uv(i))
+                    .map(|uv| sample_alpha(tex, uv))
+            }
+            AttachmentType::Mesh(mesh) => {
+                let tex = unsafe { mesh.atlas_region().page().render_object::<Texture>() };
+                let tex = if let Some(tex) = tex { tex } else { continue };
+
+                mesh.compute_world_vertices(world_vertices);
+                hit_triangles(world_vertices, mesh.indices(), point, |i| mesh.uv(i))
+                    .map(|uv| sample_alpha(tex, uv))
+            }
+            _ => None,
+        };
+
+        if let Some(sampled) = hit {
+            if sampled * alpha > ALPHA_THRESHOLD {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Find the triangle containing `point` and return the UV at that point.
+fn hit_triangles(
+    vertices: &[[f32; 2]],
+    indices: &[u16],
+    point: [f32; 2],
+    uv: impl Fn(usize) -> (f32, f32),
+) -> Option<(f32, f32)> {
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+        if let Some([wa, wb, wc]) = barycentric(vertices[a], vertices[b], vertices[c], point) {
+            let (ua, va) = uv(a);
+            let (ub, vb) = uv(b);
+            let (uc, vc) = uv(c);
+            return Some((ua * wa + ub * wb + uc * wc, va * wa + vb * wb + vc * wc));
+        }
+    }
+
+    None
+}
+
+/// Barycentric weights of `p` in triangle `abc`, `None` if `p` is outside.
+fn barycentric(a: [f32; 2], b: [f32; 2], c: [f32; 2], p: [f32; 2]) -> Option<[f32; 3]> {
+    let det = (b[1] - c[1]) * (a[0] - c[0]) + (c[0] - b[0]) * (a[1] - c[1]);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let wa = ((b[1] - c[1]) * (p[0] - c[0]) + (c[0] - b[0]) * (p[1] - c[1])) / det;
+    let wb = ((c[1] - a[1]) * (p[0] - c[0]) + (a[0] - c[0]) * (p[1] - c[1])) / det;
+    let wc = 1.0 - wa - wb;
+
+    if wa >= 0.0 && wb >= 0.0 && wc >= 0.0 {
+        Some([wa, wb, wc])
+    } else {
+        None
+    }
+}
+
+fn sample_alpha(tex: &Texture, (u, v): (f32, f32)) -> f32 {
+    let image = match tex.image() {
+        Some(image) => image,
+        // Compressed textures are not kept decoded, treat them as opaque
+        None => return 255.0,
+    };
+    let (width, height) = image.dimensions();
+
+    let x = ((u * width as f32) as u32).min(width.saturating_sub(1));
+    let y = ((v * height as f32) as u32).min(height.saturating_sub(1));
+
+    image.get_pixel(x, y)[3] as f32
+}
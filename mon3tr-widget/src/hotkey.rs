@@ -0,0 +1,210 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use winit::event::ModifiersState;
+
+/// A key combination as reported by the global keyboard hook, written as e.g. `Ctrl+Alt+1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Hotkey {
+    pub modifiers: ModifiersState,
+    pub vk_code: u32,
+}
+
+/// Named virtual-key codes, besides letters, digits and function keys.
+const KEY_NAMES: &[(&str, u32)] = &[
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0D),
+    ("Pause", 0x13),
+    ("Escape", 0x1B),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("PrintScreen", 0x2C),
+    ("Insert", 0x2D),
+    ("Delete", 0x2E),
+    ("Multiply", 0x6A),
+    ("Add", 0x6B),
+    ("Subtract", 0x6D),
+    ("Decimal", 0x6E),
+    ("Divide", 0x6F),
+    ("Semicolon", 0xBA),
+    ("Equals", 0xBB),
+    ("Comma", 0xBC),
+    ("Minus", 0xBD),
+    ("Period", 0xBE),
+    ("Slash", 0xBF),
+    ("Grave", 0xC0),
+    ("LBracket", 0xDB),
+    ("Backslash", 0xDC),
+    ("RBracket", 0xDD),
+    ("Apostrophe", 0xDE),
+];
+
+fn parse_key(name: &str) -> Option<u32> {
+    let upper = name.to_ascii_uppercase();
+
+    // Letters and digits map to their ASCII values
+    if upper.len() == 1 {
+        let c = upper.as_bytes()[0];
+        if c.is_ascii_uppercase() || c.is_ascii_digit() {
+            return Some(c as u32);
+        }
+    }
+
+    if let Some(n) = upper
+        .strip_prefix("NUMPAD")
+        .and_then(|n| n.parse::<u32>().ok())
+    {
+        if n <= 9 {
+            return Some(0x60 + n);
+        }
+    }
+
+    if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(0x70 + n - 1);
+        }
+    }
+
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, vk)| *vk)
+}
+
+fn key_name(vk_code: u32) -> String {
+    match vk_code {
+        0x30..=0x39 | 0x41..=0x5A => char::from(vk_code as u8).to_string(),
+        0x60..=0x69 => format!("Numpad{}", vk_code - 0x60),
+        0x70..=0x87 => format!("F{}", vk_code - 0x70 + 1),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(_, vk)| *vk == vk_code)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| format!("0x{:02X}", vk_code)),
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = ModifiersState::empty();
+        let mut vk_code = None;
+
+        for part in s.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= ModifiersState::CTRL,
+                "alt" => modifiers |= ModifiersState::ALT,
+                "shift" => modifiers |= ModifiersState::SHIFT,
+                "win" | "super" | "logo" => modifiers |= ModifiersState::LOGO,
+                _ => {
+                    if vk_code.is_some() {
+                        bail!("Hotkey {:?} has more than one non-modifier key", s);
+                    }
+                    vk_code = Some(
+                        parse_key(part)
+                            .ok_or_else(|| anyhow!("Unknown key {:?} in hotkey", part))?,
+                    );
+                }
+            }
+        }
+
+        Ok(Hotkey {
+            modifiers,
+            vk_code: vk_code.ok_or_else(|| anyhow!("Hotkey {:?} has no key", s))?,
+        })
+    }
+}
+
+impl TryFrom<String> for Hotkey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (mask, name) in [
+            (ModifiersState::CTRL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::LOGO, "Win"),
+        ] {
+            if self.modifiers.contains(mask) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", key_name(self.vk_code))
+    }
+}
+
+impl From<Hotkey> for String {
+    fn from(hotkey: Hotkey) -> Self {
+        hotkey.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkey(s: &str) -> Hotkey {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        let parsed = hotkey("Ctrl+Alt+1");
+        assert_eq!(parsed.modifiers, ModifiersState::CTRL | ModifiersState::ALT);
+        assert_eq!(parsed.vk_code, 0x31);
+    }
+
+    #[test]
+    fn ignores_case_and_spaces() {
+        assert_eq!(hotkey(" control + shift + a "), hotkey("Ctrl+Shift+A"));
+        assert_eq!(hotkey("super+pageup").modifiers, ModifiersState::LOGO);
+        assert_eq!(hotkey("super+pageup").vk_code, 0x21);
+    }
+
+    #[test]
+    fn parses_numpad_and_function_keys() {
+        assert_eq!(hotkey("Numpad0").vk_code, 0x60);
+        assert_eq!(hotkey("Numpad9").vk_code, 0x69);
+        assert_eq!(hotkey("F1").vk_code, 0x70);
+        assert_eq!(hotkey("F24").vk_code, 0x87);
+    }
+
+    #[test]
+    fn rejects_invalid_hotkeys() {
+        for invalid in ["", "Ctrl+Alt", "Ctrl+A+B", "Ctrl+Nope", "F25", "Numpad10"] {
+            assert!(invalid.parse::<Hotkey>().is_err(), "{:?} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_display() {
+        for s in ["Ctrl+Alt+1", "Shift+Win+F12", "Numpad5", "Ctrl+LBracket"] {
+            assert_eq!(hotkey(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn displays_unnamed_keys_as_hex() {
+        let unnamed = Hotkey {
+            modifiers: ModifiersState::ALT,
+            vk_code: 0xFF,
+        };
+        assert_eq!(unnamed.to_string(), "Alt+0xFF");
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
+use windows::Win32::Globalization::GetUserDefaultUILanguage;
+
+/// Primary language ID of Chinese in a Windows `LANGID`.
+#[cfg(windows)]
+const LANG_CHINESE: u16 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    English,
+    Chinese,
+}
+
+/// Strings shown in the tray menu and notifications.
+pub struct Strings {
+    pub windowed: &'static str,
+    pub settings: &'static str,
+    pub animation_preview: &'static str,
+    pub click_passthrough: &'static str,
+    pub debug_overlay: &'static str,
+    pub stats_overlay: &'static str,
+    pub freeze_pose: &'static str,
+    pub record_actions: &'static str,
+    pub replay_recording: &'static str,
+    pub chroma_key: &'static str,
+    pub always_on_bottom: &'static str,
+    pub follow_window: &'static str,
+    pub stop_following: &'static str,
+    pub refresh_window_list: &'static str,
+    pub pomodoro: &'static str,
+    pub start_work: &'static str,
+    pub start_break: &'static str,
+    pub stop_timer: &'static str,
+    /// Shown in the pomodoro submenu, followed by the minutes left.
+    pub working: &'static str,
+    pub on_break: &'static str,
+    pub minutes_left: &'static str,
+    /// Said in a speech bubble when a phase starts or the timer finishes.
+    pub work_started: &'static str,
+    pub break_started: &'static str,
+    pub timer_finished: &'static str,
+    pub autostart: &'static str,
+    pub open_log_folder: &'static str,
+    pub switch_model: &'static str,
+    pub reload_model_list: &'static str,
+    pub add_model_from_url: &'static str,
+    pub opacity: &'static str,
+    pub playback_speed: &'static str,
+    pub volume: &'static str,
+    pub mute: &'static str,
+    pub animation_list: &'static str,
+    pub animation_queue: &'static str,
+    /// Followed by the track index in the animation queue submenu.
+    pub track: &'static str,
+    pub looping: &'static str,
+    pub clear_queue: &'static str,
+    pub attachments: &'static str,
+    /// Items of each slot in the attachments submenu, to leave it to the animations or hide it.
+    pub default_attachment: &'static str,
+    pub no_attachment: &'static str,
+    /// Unit suffix appended to animation durations.
+    pub seconds: &'static str,
+    pub export_animation: &'static str,
+    pub save_screenshot: &'static str,
+    pub copy_screenshot: &'static str,
+    /// Items of the submenu of each extra window.
+    pub show: &'static str,
+    pub close: &'static str,
+    pub exit: &'static str,
+    /// Title of the notification when a dropped or downloaded model can't be added.
+    pub add_model_failed: &'static str,
+    /// Title of the notification when textures of a model are missing.
+    pub textures_failed: &'static str,
+}
+
+static ENGLISH: Strings = Strings {
+    windowed: "Windowed / Resize",
+    settings: "Settings…",
+    animation_preview: "Animation Preview…",
+    click_passthrough: "Click Passthrough",
+    debug_overlay: "Debug Overlay",
+    stats_overlay: "Frame Statistics",
+    freeze_pose: "Freeze Pose",
+    record_actions: "Record Actions",
+    replay_recording: "Replay Recording",
+    chroma_key: "Chroma Key Background",
+    always_on_bottom: "Stay Behind Windows",
+    follow_window: "Follow Window",
+    stop_following: "Don't Follow",
+    refresh_window_list: "Refresh Window List",
+    pomodoro: "Pomodoro Timer",
+    start_work: "Start Working",
+    start_break: "Take a Break",
+    stop_timer: "Stop",
+    working: "Working",
+    on_break: "On break",
+    minutes_left: "min left",
+    work_started: "Time to focus!",
+    break_started: "Time for a break~",
+    timer_finished: "Break's over!",
+    autostart: "Start on Login",
+    open_log_folder: "Open Log Folder",
+    switch_model: "Switch Model",
+    reload_model_list: "Reload Model List",
+    add_model_from_url: "Add Model from URL…",
+    opacity: "Opacity",
+    playback_speed: "Playback Speed",
+    volume: "Volume",
+    mute: "Mute",
+    animation_list: "Animations",
+    animation_queue: "Animation Queue",
+    track: "Track",
+    looping: "looping",
+    clear_queue: "Clear Queue / Back to Idle",
+    attachments: "Attachments",
+    default_attachment: "Default",
+    no_attachment: "None",
+    seconds: "s",
+    export_animation: "Export Current Animation…",
+    save_screenshot: "Save Screenshot…",
+    copy_screenshot: "Copy Screenshot",
+    show: "Show",
+    close: "Close",
+    exit: "Exit",
+    add_model_failed: "Failed to add model",
+    textures_failed: "Some textures failed to load",
+};
+
+static CHINESE: Strings = Strings {
+    windowed: "窗口化/调整大小",
+    settings: "设置…",
+    animation_preview: "动画预览…",
+    click_passthrough: "鼠标点击穿透",
+    debug_overlay: "调试显示",
+    stats_overlay: "帧率统计",
+    freeze_pose: "冻结姿势",
+    record_actions: "录制动作",
+    replay_recording: "回放录制",
+    chroma_key: "纯色背景（抠像）",
+    always_on_bottom: "置于其他窗口之下",
+    follow_window: "跟随窗口",
+    stop_following: "不跟随",
+    refresh_window_list: "刷新窗口列表",
+    pomodoro: "番茄钟",
+    start_work: "开始工作",
+    start_break: "休息一下",
+    stop_timer: "停止",
+    working: "工作中",
+    on_break: "休息中",
+    minutes_left: "分钟后结束",
+    work_started: "该专心工作了！",
+    break_started: "休息一下吧~",
+    timer_finished: "休息结束啦！",
+    autostart: "开机自动启动",
+    open_log_folder: "打开日志文件夹",
+    switch_model: "切换模型",
+    reload_model_list: "重新加载模型列表",
+    add_model_from_url: "从网址添加模型…",
+    opacity: "不透明度",
+    playback_speed: "播放速度",
+    volume: "音量",
+    mute: "静音",
+    animation_list: "动画列表",
+    animation_queue: "动画队列",
+    track: "轨道",
+    looping: "循环",
+    clear_queue: "清空队列 / 回到待机",
+    attachments: "附件",
+    default_attachment: "默认",
+    no_attachment: "无",
+    seconds: "秒",
+    export_animation: "导出当前动画…",
+    save_screenshot: "保存截图…",
+    copy_screenshot: "复制截图",
+    show: "显示",
+    close: "关闭",
+    exit: "退出",
+    add_model_failed: "添加模型失败",
+    textures_failed: "部分贴图加载失败",
+};
+
+impl Language {
+    /// Guess the language from the user's UI language, falling back to English.
+    #[cfg(windows)]
+    pub fn system() -> Self {
+        let lang_id = unsafe { GetUserDefaultUILanguage() };
+
+        match lang_id & 0x3ff {
+            LANG_CHINESE => Language::Chinese,
+            _ => Language::English,
+        }
+    }
+
+    /// Guess the language from the locale, such as `zh_CN.UTF-8`, falling back to English.
+    #[cfg(not(windows))]
+    pub fn system() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+
+        if locale.starts_with("zh") {
+            Language::Chinese
+        } else {
+            Language::English
+        }
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Chinese => &CHINESE,
+        }
+    }
+}
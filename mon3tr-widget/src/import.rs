@@ -0,0 +1,64 @@
+//! Importing of model packs dropped onto the widget, either as a `.zip` or an extracted folder.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use zip::{write::FileOptions, ZipWriter};
+
+/// Put the dropped `path` into a pack in the temporary folder, leaving the original alone.
+/// Returns the name to install it as and the temporary path, like a finished download.
+pub fn prepare(path: &Path) -> Result<(String, PathBuf)> {
+    let stem = match path.file_stem() {
+        Some(stem) => stem.to_string_lossy(),
+        None => bail!("Not a model pack: {}", path.display()),
+    };
+    let name = format!("{}.zip", stem);
+    let temp_path = std::env::temp_dir().join(format!("mon3tr-{}", name));
+
+    if path.is_dir() {
+        for required in ["char.atlas", "char.skel"] {
+            if !path.join(required).is_file() {
+                bail!("{} is missing from {}", required, path.display());
+            }
+        }
+        pack_folder(path, &temp_path)
+            .with_context(|| format!("Failed to pack {}", path.display()))?;
+    } else if is_zip(path) {
+        std::fs::copy(path, &temp_path)?;
+    } else {
+        bail!("Only .zip files and folders can be dropped");
+    }
+
+    Ok((name, temp_path))
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+fn pack_folder(dir: &Path, dest: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(dest)?));
+    add_dir(&mut zip, dir, "")?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Add everything in `dir` to `zip` under `prefix`, names always use `/` as separator.
+fn add_dir<W: Write + Seek>(zip: &mut ZipWriter<W>, dir: &Path, prefix: &str) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if entry.file_type()?.is_dir() {
+            add_dir(zip, &entry.path(), &format!("{}/", name))?;
+        } else {
+            zip.start_file(name, FileOptions::default())?;
+            std::io::copy(&mut File::open(entry.path())?, zip)?;
+        }
+    }
+    Ok(())
+}
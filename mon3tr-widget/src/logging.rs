@@ -0,0 +1,196 @@
+//! Logging to the console and, if enabled, to rotating files next to the executable.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use chrono::NaiveDate;
+use fern::colors::ColoredLevelConfig;
+use log::LevelFilter;
+
+use crate::{config::LogConfig, utils::exe_dir_path};
+
+const FILE_NAME: &str = "mon3tr-widget.log";
+const DEFAULT_MAX_SIZE_MB: u64 = 10;
+const DEFAULT_MAX_FILES: usize = 5;
+
+/// Set once the config is loaded, messages before that only go to the console.
+static FILE: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+pub fn init() {
+    let colors = ColoredLevelConfig::new();
+    fern::Dispatch::new()
+        .chain(
+            fern::Dispatch::new()
+                .format(move |out, message, record| {
+                    out.finish(format_args!(
+                        "{}[{}][{}] {}",
+                        chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                        record.target(),
+                        colors.color(record.level()),
+                        message
+                    ))
+                })
+                .chain(std::io::stdout()),
+        )
+        .chain(
+            fern::Dispatch::new()
+                .format(|out, message, record| {
+                    out.finish(format_args!(
+                        "{}[{}][{}] {}",
+                        chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                        record.target(),
+                        record.level(),
+                        message
+                    ))
+                })
+                .chain(Box::new(FileOutput) as Box<dyn Write + Send>),
+        )
+        .apply()
+        .unwrap();
+
+    // Changed by `configure`, the dispatch itself lets everything through
+    log::set_max_level(LevelFilter::Info);
+}
+
+/// Apply the log level and start or stop writing to files.
+pub fn configure(config: &LogConfig) {
+    log::set_max_level(config.level.map_or(LevelFilter::Info, Into::into));
+
+    let file = if config.file {
+        let max_size = config.max_size_mb.unwrap_or(DEFAULT_MAX_SIZE_MB) * 1024 * 1024;
+        let max_files = config.max_files.unwrap_or(DEFAULT_MAX_FILES);
+        match RotatingFile::open(log_dir(), max_size, max_files) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                log::error!("Failed to open log file: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    *FILE.lock().unwrap() = file;
+}
+
+pub fn log_dir() -> PathBuf {
+    exe_dir_path().join("logs")
+}
+
+/// Forwards to the current [`RotatingFile`], if any.
+struct FileOutput;
+
+impl Write for FileOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match FILE.lock() {
+            Ok(mut file) => match file.as_mut() {
+                Some(file) => file.write(buf),
+                None => Ok(buf.len()),
+            },
+            Err(_) => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match FILE.lock() {
+            Ok(mut file) => file.as_mut().map_or(Ok(()), |file| file.flush()),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Log file that is moved aside once it gets too big or a new day starts, keeping
+/// `mon3tr-widget.log.1` (the newest) up to `.{max_files}` around.
+struct RotatingFile {
+    dir: PathBuf,
+    /// Only `None` while rotating, Windows can't rename open files.
+    file: Option<File>,
+    size: u64,
+    date: NaiveDate,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(dir: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(FILE_NAME))?;
+
+        let metadata = file.metadata()?;
+        // A file left from an earlier day is rotated on the first write
+        let date = match metadata.modified() {
+            Ok(modified) => chrono::DateTime::<chrono::Local>::from(modified)
+                .naive_local()
+                .date(),
+            Err(_) => today(),
+        };
+
+        Ok(Self {
+            dir,
+            file: Some(file),
+            size: metadata.len(),
+            date,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(FILE_NAME),
+            _ => self.dir.join(format!("{}.{}", FILE_NAME, index)),
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        drop(self.file.take());
+
+        if self.max_files == 0 {
+            std::fs::remove_file(self.path(0))?;
+        } else {
+            let _ = std::fs::remove_file(self.path(self.max_files));
+            for index in (0..self.max_files).rev() {
+                let _ = std::fs::rename(self.path(index), self.path(index + 1));
+            }
+        }
+
+        self.file = Some(File::create(self.path(0))?);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = today();
+        if self.size > 0 && (self.size + buf.len() as u64 > self.max_size || today != self.date) {
+            // Logging here would deadlock
+            if let Err(e) = self.rotate() {
+                eprintln!("Failed to rotate log file: {}", e);
+            }
+        }
+        self.date = today;
+
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return Ok(buf.len()),
+        };
+        let written = file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |file| file.flush())
+    }
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().naive_local().date()
+}
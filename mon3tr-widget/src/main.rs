@@ -3,39 +3,132 @@
 //     windows_subsystem = "windows"
 // )]
 
-use std::{collections::HashSet, ffi::OsString, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use image::GenericImageView;
 
-use spine::{atlas::AtlasPage, spine_init, AttachmentType, SpineCallbacks};
+use spine::{atlas::AtlasPage, spine_init, AnimationEvent, EventType, Pose, SpineCallbacks};
 
+#[cfg(windows)]
 use trayicon::{MenuBuilder, MenuItem, TrayIcon, TrayIconBuilder};
-use window_ext::SpineWidgetWindowExt;
+use window_ext::{ForeignWindow, SpineWidgetWindowExt};
+#[cfg(target_os = "linux")]
+use winit::platform::unix::{WindowBuilderExtUnix, XWindowType};
+#[cfg(windows)]
+use winit::platform::windows::{WindowBuilderExtWindows, WindowExtWindows};
 use winit::{
     dpi::{LogicalSize, PhysicalPosition},
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    platform::windows::{WindowBuilderExtWindows, WindowExtWindows},
-    window::{Window, WindowBuilder},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowBuilder, WindowId},
 };
 
+mod action_tracker;
+mod alpha_mask;
+mod attention;
+mod audio;
+mod autostart;
+mod behavior;
 mod buffer;
+mod cli;
 mod config;
+mod crash;
+mod dialog;
+mod download;
+mod drag;
+mod event_hooks;
+mod export;
+mod frame_pacing;
+mod hit_test;
+#[cfg(windows)]
 mod hook;
+mod hotkey;
+mod i18n;
+mod import;
+mod logging;
+mod model_cache;
+mod notify;
+mod pause;
+mod placement;
+mod pomodoro;
+mod recorder;
+mod remote;
 mod renderer;
+mod screenshot;
+mod session;
+mod single_instance;
 mod spine_state;
+#[cfg(windows)]
+mod spout;
+mod system_stats;
+mod touch;
+mod ui;
 mod utils;
 mod vertex;
+mod widget_window;
 mod window_ext;
 
+#[cfg(windows)]
 use crate::hook::KeyboardHook;
+use action_tracker::ActionTracker;
+use attention::AttentionWatcher;
+use audio::Audio;
+use behavior::{
+    blink::Blink, breathing::Breathing, follow_window::WindowFollower, lean::Lean,
+    lip_sync::LipSync, peek::Peek, taskbar::TaskbarSnap, wander::Wander,
+};
 use buffer::ScratchBuffers;
-use config::Config;
-use renderer::{texture::TextureConfig, Renderer, Texture};
-use spine_state::SpineState;
+use cli::Invocation;
+use config::{
+    Action, AutoSize, Command, Config, Corner, EventHook, FollowWindowConfig, OpacityMode,
+    Presentation, TypingReaction,
+};
+use drag::DragState;
+use export::ExportOptions;
+use frame_pacing::FramePacer;
+use hotkey::Hotkey;
+use i18n::Strings;
+use model_cache::ModelCache;
+use notify::Notifier;
+use pause::AutoPause;
+use placement::Placement;
+use pomodoro::{Phase, Pomodoro, PomodoroEvent};
+use recorder::{Recorder, Replay};
+use remote::{Broadcaster, RemoteEvent, SharedStatus, Status};
+use renderer::{
+    texture::{self, CompressedImage, TextureConfig},
+    Renderer, Texture,
+};
+use screenshot::ScreenshotTarget;
+use session::{SessionEvent, SessionWatcher};
+use single_instance::InstanceGuard;
+use spine_state::{Companion, SpineState, PLAYBACK_SPEEDS};
+#[cfg(windows)]
+use spout::SpoutSender;
+use system_stats::SystemMonitor;
+use touch::{Gesture, TouchGestures};
+use ui::UiWindows;
 use utils::*;
-use vertex::Vertex;
+use widget_window::WidgetWindow;
+
+/// Model scale changed per `Ctrl` + `=`/`-` press or wheel step.
+const MODEL_SCALE_STEP: f32 = 0.1;
+const MIN_MODEL_SCALE: f32 = 0.1;
+const MAX_MODEL_SCALE: f32 = 5.0;
+/// Opacity changed per `Shift` + wheel step.
+const OPACITY_STEP: f32 = 5.0;
+/// Scrolling stops here, so the model can't be faded out of sight by accident.
+const MIN_WHEEL_OPACITY: f32 = 10.0;
+/// Touchpads scroll in pixels, this many make one wheel step.
+const PIXELS_PER_WHEEL_STEP: f64 = 50.0;
+/// Seconds to blend from interrupted animations back to idle.
+const BACK_TO_IDLE_MIX_DURATION: f32 = 0.2;
 
 struct SpineCb;
 impl SpineCallbacks for SpineCb {
@@ -48,33 +141,46 @@ impl SpineCallbacks for SpineCb {
         path: &str,
         atlas: &AtlasPage,
     ) -> Result<(Texture, u32, u32), Self::LoadTextureError> {
-        let mut img = image::load_from_memory(&load_file_packed(path)?)?;
-
-        let mask_path = PathBuf::from(path.replace(".png", "[alpha].png").as_str());
-        if let Ok(mask_buf) = load_file_packed(mask_path.to_str().unwrap()) {
-            let mask_img = image::load_from_memory(&mask_buf)?;
-
-            let base = img.as_mut_rgba8().unwrap();
-            let mask = mask_img.as_rgba8().unwrap();
-
-            for (b, m) in base.pixels_mut().zip(mask.pixels()) {
-                b[3] = m[0];
+        // Prefer a KTX2 file next to the page when the GPU can sample its format
+        if let Some(ktx2_path) = path
+            .strip_suffix(".png")
+            .map(|stem| format!("{}.ktx2", stem))
+        {
+            if let Ok(buf) = load_file_packed(&ktx2_path) {
+                match CompressedImage::from_ktx2(&buf) {
+                    Ok(mut image) if image.format.is_supported() => {
+                        let (width, height) = (image.width, image.height);
+                        image.downscale(texture::texture_scale());
+                        let texture = Texture::compressed(image, TextureConfig::from_atlas(atlas));
+                        return Ok((texture, width, height));
+                    }
+                    Ok(image) => log::info!(
+                        "{:?} textures are not supported by the GPU, loading {}",
+                        image.format,
+                        path
+                    ),
+                    Err(e) => log::warn!("Failed to load {}: {}", ktx2_path, e),
+                }
             }
         }
 
+        let mut img = alpha_mask::load_page(path)?;
+
+        // Report the original size, so the UVs spine-c derives from it still match
         let width = img.width();
         let height = img.height();
 
+        let scale = texture::texture_scale();
+        if scale < 1.0 {
+            img = img.resize_exact(
+                ((width as f32 * scale).round() as u32).max(1),
+                ((height as f32 * scale).round() as u32).max(1),
+                image::imageops::FilterType::Triangle,
+            );
+        }
+
         Ok((
-            Texture::new(
-                img,
-                TextureConfig {
-                    mag_filter: atlas.mag_filter(),
-                    min_filter: atlas.min_filter(),
-                    u_wrap: atlas.u_wrap(),
-                    v_wrap: atlas.v_wrap(),
-                },
-            ),
+            Texture::new(img, TextureConfig::from_atlas(atlas)),
             width,
             height,
         ))
@@ -86,22 +192,68 @@ impl SpineCallbacks for SpineCb {
 }
 spine_init!(SpineCb);
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum UserEvent {
     GlobalKey {
         state: ElementState,
         vk_code: u32,
         modifiers: ModifiersState,
     },
+    /// Command received from a remote control client.
+    Command(Command),
+    /// A model pack finished downloading to `temp_path`, see [`download::start`].
+    PackDownloaded {
+        name: String,
+        temp_path: PathBuf,
+        select: bool,
+    },
+    /// The screen was locked, turned off or similar, see [`session::SessionWatcher`].
+    Session(SessionEvent),
+    /// Another application asks for attention, see [`attention::AttentionWatcher`].
+    Attention(ForeignWindow),
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum TrayEvent {
+    OpenSettings,
+    OpenAnimationPreview,
     ToggleWindowed,
     ToggleClickPassthrough,
+    ToggleDebugOverlay,
+    ToggleStatsOverlay,
+    ToggleFreezePose,
+    ToggleRecording,
+    ToggleReplay,
+    ToggleChromaKey,
+    ToggleAlwaysOnBottom,
+    /// Follow the window at the given index of the windows listed in the tray menu.
+    FollowWindow(usize),
+    StopFollowing,
+    RefreshWindowList,
+    StartWork,
+    StartBreak,
+    StopTimer,
+    ToggleAutostart,
+    OpenLogFolder,
     SetOpacity(u8),
+    /// Index into [`PLAYBACK_SPEEDS`].
+    SetPlaybackSpeed(usize),
+    SetVolume(u8),
+    ToggleMute,
     SetModel(usize),
+    ReloadModelList,
+    AddModelFromUrl,
+    /// Show or hide the extra window at the given index.
+    ToggleExtraWindow(usize),
+    CloseExtraWindow(usize),
     TriggerAnimation(String),
+    ClearAnimationQueue,
+    /// Show an attachment in the slot, or nothing if `None`.
+    SetAttachment(String, Option<String>),
+    ResetAttachment(String),
+    ExportAnimation,
+    SaveScreenshot,
+    CopyScreenshot,
     About,
     Exit,
 }
@@ -115,19 +267,81 @@ struct State {
 
     /// Opacity value from 0 to 100.
     opacity: u8,
+    opacity_mode: OpacityMode,
+    model_scale: f32,
+    auto_size: Option<AutoSize>,
 
     spine: Option<SpineState>,
+    /// Models shown before, see [`ModelCache`].
+    model_cache: ModelCache,
+    companions: Vec<Companion>,
+    extra_windows: Vec<WidgetWindow>,
     world_vertices: Vec<[f32; 2]>,
     scratch_buffers: ScratchBuffers,
 
     pressed_keys: HashSet<VirtualKeyCode>,
     modifiers_state: ModifiersState,
+    /// Keys held down according to the global keyboard hook.
+    pressed_global_keys: HashSet<u32>,
+    last_typing_animation: Option<Instant>,
+    last_click: Option<Instant>,
+    action_tracker: ActionTracker,
+    touch: TouchGestures,
+    /// When the speech bubble shown now should disappear.
+    bubble_until: Option<Instant>,
+    drag: DragState,
+    placement: Placement,
+    frame_pacer: FramePacer,
+    auto_pause: AutoPause,
+    taskbar_snap: TaskbarSnap,
+    wander: Option<Wander>,
+    peek: Option<Peek>,
+    follow_window: Option<FollowWindowConfig>,
+    window_follower: WindowFollower,
+    /// Windows that can be followed, as listed in the tray menu. Only listed again when asked
+    /// to, so the menu items keep pointing at the same windows.
+    followable_windows: Vec<ForeignWindow>,
+    /// When each of `Config::notifications` last triggered, by index.
+    notification_triggered: HashMap<usize, Instant>,
+    attachment_overrides: HashMap<String, Option<String>>,
+    /// Shown instead of whatever the animations are doing, see [`Self::toggle_freeze_pose`].
+    frozen_pose: Option<Pose>,
+    pomodoro: Pomodoro,
+    /// Minutes left shown in the tray menu, refreshed when it changes.
+    pomodoro_minutes_shown: Option<u64>,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    blink: Option<Blink>,
+    breathing: Option<Breathing>,
+    lean: Option<Lean>,
+    lip_sync: Option<LipSync>,
+    system_monitor: Option<SystemMonitor>,
+    #[cfg(windows)]
+    spout: Option<SpoutSender>,
+    /// Where to save the next rendered frame.
+    screenshot: Option<ScreenshotTarget>,
+    audio: Audio,
 
     windowed: bool,
     click_passthrough: bool,
-
+    /// Draw bones, slots and wireframes on top of the model.
+    debug_overlay: bool,
+    stats_overlay: bool,
+    /// Draw on a solid background color instead of transparency.
+    chroma_key: bool,
+    /// Behind other windows instead of on top of them.
+    always_on_bottom: bool,
+
+    #[cfg(windows)]
     tray: TrayIcon<TrayEvent>,
+    notifier: Notifier,
+    strings: &'static Strings,
+    /// State published to remote control clients.
+    remote_status: SharedStatus,
+    remote_events: Broadcaster,
     data_files: Vec<OsString>,
+    /// Index into `data_files` of the currently loaded model.
+    data_file_index: Option<usize>,
 }
 
 impl State {
@@ -135,118 +349,472 @@ impl State {
     async fn new(
         window: Window,
         config: &config::Config,
+        remote_status: SharedStatus,
+        remote_events: Broadcaster,
     ) -> (Self, std::sync::mpsc::Receiver<TrayEvent>) {
         let size = window.inner_size();
 
         let (tray_sender, tray_receiver) = std::sync::mpsc::channel();
 
+        #[cfg(windows)]
         let tray = TrayIconBuilder::new()
             .icon_from_buffer(include_bytes!("tray.ico"))
             .sender(tray_sender)
             .build()
             .unwrap();
+        // No tray icon elsewhere yet, the menu is reachable through remote control only
+        #[cfg(not(windows))]
+        drop(tray_sender);
 
         let scale_factor = window.scale_factor();
+        let notifier = Notifier::new(&window);
 
         let mut r = Self {
-            renderer: Box::new(
-                renderer::backend::hardware::HardwareRenderer::new(&window, config)
-                    .await
-                    .unwrap(),
-            ),
+            renderer: renderer::backend::for_window(&window, config)
+                .await
+                .unwrap(),
             window,
 
             size,
             scale_factor,
 
             opacity: 100,
+            opacity_mode: config.opacity_mode,
+            model_scale: config.scale,
+            auto_size: config.auto_size.clone(),
 
             spine: None,
+            model_cache: ModelCache::new(config.model_cache_size),
+            companions: Vec::new(),
+            extra_windows: Vec::new(),
             world_vertices: Vec::new(),
             scratch_buffers: ScratchBuffers::new(),
 
             pressed_keys: HashSet::new(),
             modifiers_state: Default::default(),
+            pressed_global_keys: HashSet::new(),
+            last_typing_animation: None,
+            last_click: None,
+            action_tracker: ActionTracker::new(),
+            touch: TouchGestures::new(),
+            bubble_until: None,
+            drag: DragState::new(),
+            placement: Placement::new(config),
+            frame_pacer: FramePacer::new(),
+            auto_pause: AutoPause::new(),
+            taskbar_snap: TaskbarSnap::new(),
+            wander: config.wander.as_ref().map(Wander::new),
+            peek: config.peek.as_ref().map(|_| Peek::new()),
+            follow_window: config.follow_window.clone(),
+            window_follower: WindowFollower::new(),
+            followable_windows: window_ext::list_windows(),
+            notification_triggered: HashMap::new(),
+            attachment_overrides: config.attachment_overrides.clone(),
+            frozen_pose: config.frozen_pose.clone(),
+            pomodoro: Pomodoro::new(),
+            pomodoro_minutes_shown: None,
+            recorder: None,
+            replay: None,
+            blink: config.blink.as_ref().map(Blink::new),
+            breathing: config.breathing.as_ref().map(|_| Breathing::new()),
+            lean: config.lean.as_ref().map(|_| Lean::new()),
+            system_monitor: config.system_load.as_ref().map(SystemMonitor::new),
+            lip_sync: config.lip_sync.as_ref().and_then(|_| match LipSync::new() {
+                Ok(lip_sync) => Some(lip_sync),
+                Err(e) => {
+                    log::error!("Failed to open microphone for lip sync: {}", e);
+                    None
+                }
+            }),
+            #[cfg(windows)]
+            spout: config
+                .spout_sender
+                .as_ref()
+                .and_then(|name| match SpoutSender::new(name) {
+                    Ok(spout) => Some(spout),
+                    Err(e) => {
+                        log::error!("Failed to set up Spout output: {}", e);
+                        None
+                    }
+                }),
+            screenshot: None,
+            audio: Audio::new(config.volume, config.muted),
 
             windowed: false,
             click_passthrough: true,
+            debug_overlay: false,
+            stats_overlay: config.stats_overlay,
+            chroma_key: false,
+            always_on_bottom: false,
 
+            #[cfg(windows)]
             tray,
+            notifier,
+            strings: config
+                .language
+                .unwrap_or_else(i18n::Language::system)
+                .strings(),
+            remote_status,
+            remote_events,
             data_files: vec![],
+            data_file_index: None,
         };
 
+        r.placement.restore(&r.window);
         r.set_windowed(false);
         r.set_click_passthrough(true);
+        r.set_chroma_key(config.chroma_key, config);
+        r.set_always_on_bottom(config.always_on_bottom);
+        r.set_opacity(config.opacity.min(100));
+        spine_state::set_playback_speed(config.playback_speed);
 
-        r.scan_data_files().unwrap();
-        r.load_data_file_index(0).unwrap();
-        r.update_tray();
+        if let Err(e) = r.scan_data_files() {
+            log::error!("Failed to scan data directory: {}", e);
+        }
+        // The widget stays up without a model, one can be picked or added from the tray
+        if r.data_files.is_empty() {
+            log::warn!("No models in the data directory");
+        } else if let Err(e) = r.load_data_file_index(0, config) {
+            log::error!("Failed to load model: {}", e);
+        }
+        r.load_companions(config);
 
         (r, tray_receiver)
     }
 
     fn update_tray(&mut self) {
+        // Whatever changes the tray menu is also interesting to remote clients
+        self.publish_status();
+        #[cfg(windows)]
+        self.set_tray_menu();
+    }
+
+    #[cfg(windows)]
+    fn set_tray_menu(&mut self) {
         let tray = &mut self.tray;
+        let strings = self.strings;
+
+        let mut menu = MenuBuilder::new()
+            .item(strings.settings, TrayEvent::OpenSettings)
+            .item(strings.animation_preview, TrayEvent::OpenAnimationPreview)
+            .separator()
+            .checkable(strings.windowed, self.windowed, TrayEvent::ToggleWindowed)
+            .checkable(
+                strings.click_passthrough,
+                self.click_passthrough,
+                TrayEvent::ToggleClickPassthrough,
+            )
+            .checkable(
+                strings.debug_overlay,
+                self.debug_overlay,
+                TrayEvent::ToggleDebugOverlay,
+            )
+            .checkable(
+                strings.stats_overlay,
+                self.stats_overlay,
+                TrayEvent::ToggleStatsOverlay,
+            )
+            .checkable(
+                strings.freeze_pose,
+                self.frozen_pose.is_some(),
+                TrayEvent::ToggleFreezePose,
+            )
+            .checkable(
+                strings.record_actions,
+                self.recorder.is_some(),
+                TrayEvent::ToggleRecording,
+            )
+            .checkable(
+                strings.replay_recording,
+                self.replay.is_some(),
+                TrayEvent::ToggleReplay,
+            )
+            .checkable(
+                strings.chroma_key,
+                self.chroma_key,
+                TrayEvent::ToggleChromaKey,
+            )
+            .checkable(
+                strings.always_on_bottom,
+                self.always_on_bottom,
+                TrayEvent::ToggleAlwaysOnBottom,
+            )
+            .submenu(strings.follow_window, {
+                let mut submenu = MenuBuilder::new().checkable(
+                    strings.stop_following,
+                    self.follow_window.is_none(),
+                    TrayEvent::StopFollowing,
+                );
+
+                if !self.followable_windows.is_empty() {
+                    submenu = submenu.separator();
+                }
+                for (i, window) in self.followable_windows.iter().enumerate() {
+                    let title: String = window.title.chars().take(60).collect();
+                    submenu = submenu.checkable(
+                        &title,
+                        self.follow_window.is_some()
+                            && self.window_follower.target() == Some(window.id),
+                        TrayEvent::FollowWindow(i),
+                    );
+                }
 
-        let _ = tray.set_menu(
-            &MenuBuilder::new()
-                .checkable("窗口化/调整大小", self.windowed, TrayEvent::ToggleWindowed)
-                .checkable(
-                    "鼠标点击穿透",
-                    self.click_passthrough,
-                    TrayEvent::ToggleClickPassthrough,
-                )
-                .submenu("切换模型", {
-                    let mut submenu = MenuBuilder::new();
+                submenu
+                    .separator()
+                    .item(strings.refresh_window_list, TrayEvent::RefreshWindowList)
+            })
+            .submenu(strings.pomodoro, {
+                let mut submenu = MenuBuilder::new();
+
+                let status = match self.pomodoro.phase() {
+                    Some(Phase::Work) => Some(strings.working),
+                    Some(Phase::Break) => Some(strings.on_break),
+                    None => None,
+                };
+                if let (Some(status), Some(minutes)) = (status, self.pomodoro_minutes_left()) {
+                    submenu = submenu
+                        .with(MenuItem::Item {
+                            name: format!("{}, {} {}", status, minutes, strings.minutes_left),
+                            id: TrayEvent::About,
+                            disabled: true,
+                            icon: None,
+                        })
+                        .separator();
+                }
 
-                    for (i, model) in self.data_files.iter().enumerate() {
-                        let model = model.to_string_lossy();
-                        submenu = submenu.checkable(&model, false, TrayEvent::SetModel(i));
-                    }
+                submenu
+                    .checkable(
+                        strings.start_work,
+                        self.pomodoro.phase() == Some(Phase::Work),
+                        TrayEvent::StartWork,
+                    )
+                    .checkable(
+                        strings.start_break,
+                        self.pomodoro.phase() == Some(Phase::Break),
+                        TrayEvent::StartBreak,
+                    )
+                    .checkable(
+                        strings.stop_timer,
+                        self.pomodoro.phase().is_none(),
+                        TrayEvent::StopTimer,
+                    )
+            })
+            .submenu(strings.switch_model, {
+                let mut submenu = MenuBuilder::new();
+
+                for (i, model) in self.data_files.iter().enumerate() {
+                    let model = model.to_string_lossy();
+                    submenu = submenu.checkable(
+                        &model,
+                        self.data_file_index == Some(i),
+                        TrayEvent::SetModel(i),
+                    );
+                }
 
-                    submenu
-                })
-                .submenu("不透明度", {
-                    let mut submenu = MenuBuilder::new();
-
-                    for i in (10..=100).step_by(10) {
-                        submenu = submenu.checkable(
-                            &format!("{}%", i),
-                            self.opacity == i,
-                            TrayEvent::SetOpacity(i as u8),
+                submenu
+                    .separator()
+                    .item(strings.reload_model_list, TrayEvent::ReloadModelList)
+                    .item(strings.add_model_from_url, TrayEvent::AddModelFromUrl)
+            })
+            .submenu(strings.opacity, {
+                let mut submenu = MenuBuilder::new();
+
+                for i in (10..=100).step_by(10) {
+                    submenu = submenu.checkable(
+                        &format!("{}%", i),
+                        self.opacity == i,
+                        TrayEvent::SetOpacity(i as u8),
+                    );
+                }
+
+                submenu
+            })
+            .submenu(strings.playback_speed, {
+                let mut submenu = MenuBuilder::new();
+
+                for (i, &speed) in PLAYBACK_SPEEDS.iter().enumerate() {
+                    submenu = submenu.checkable(
+                        &format!("{}×", speed),
+                        spine_state::playback_speed() == speed,
+                        TrayEvent::SetPlaybackSpeed(i),
+                    );
+                }
+
+                submenu
+            })
+            .submenu(strings.volume, {
+                let mut submenu = MenuBuilder::new()
+                    .checkable(strings.mute, self.audio.is_muted(), TrayEvent::ToggleMute)
+                    .separator();
+
+                for i in (0..=100).step_by(20) {
+                    submenu = submenu.checkable(
+                        &format!("{}%", i),
+                        self.audio.volume() == i,
+                        TrayEvent::SetVolume(i),
+                    );
+                }
+
+                submenu
+            })
+            .submenu(strings.animation_list, {
+                let mut submenu = MenuBuilder::new();
+
+                if let Some(spine) = self.spine.as_ref() {
+                    for anim in spine.skel_data.animations() {
+                        let name = anim.name();
+                        submenu = submenu.item(
+                            &format!("{} ({:.2}{})", name, anim.duration(), strings.seconds),
+                            TrayEvent::TriggerAnimation(name.into()),
                         );
                     }
+                }
 
-                    submenu
-                })
-                .submenu("动画列表", {
-                    let mut submenu = MenuBuilder::new();
-
-                    if let Some(spine) = self.spine.as_ref() {
-                        for anim in spine.skel_data.animations() {
-                            let name = anim.name();
+                submenu
+            })
+            .submenu(strings.animation_queue, {
+                let mut submenu = MenuBuilder::new();
+
+                if let Some(spine) = self.spine.as_ref() {
+                    for track in 0..spine.anim.track_count() {
+                        let mut entry = spine.anim.current(track);
+                        let mut first = true;
+                        while let Some(current) = entry {
+                            let animation = current.animation();
+                            let time = if first && current.is_loop() {
+                                strings.looping.to_owned()
+                            } else if first {
+                                let remaining = (animation.duration() - current.track_time())
+                                    / current.time_scale();
+                                format!("{:.1}{}", remaining.max(0.0), strings.seconds)
+                            } else {
+                                format!("{:.1}{}", animation.duration(), strings.seconds)
+                            };
+                            let name = if first {
+                                format!("{} {}: {}", strings.track, track, animation.name())
+                            } else {
+                                format!("    → {}", animation.name())
+                            };
                             submenu = submenu.with(MenuItem::Item {
-                                name: format!("{} ({:.2}秒)", name, anim.duration()),
-                                id: TrayEvent::TriggerAnimation(name.into()),
+                                name: format!("{} ({})", name, time),
+                                id: TrayEvent::About,
                                 disabled: true,
                                 icon: None,
                             });
+
+                            first = false;
+                            entry = current.next();
                         }
                     }
+                }
 
-                    submenu
-                })
+                submenu
+                    .separator()
+                    .item(strings.clear_queue, TrayEvent::ClearAnimationQueue)
+            })
+            .submenu(strings.attachments, {
+                let mut submenu = MenuBuilder::new();
+
+                if let Some(spine) = self.spine.as_ref() {
+                    let attachments = spine.skel_data.skin_attachments();
+                    // Only slots with something to choose from
+                    for (slot_index, slot) in spine.skel_data.iter_slots().enumerate() {
+                        let names: Vec<&str> = attachments
+                            .iter()
+                            .filter(|(index, _)| *index == slot_index)
+                            .map(|(_, name)| name.as_str())
+                            .collect();
+                        if names.len() < 2 {
+                            continue;
+                        }
+
+                        let slot = slot.name();
+                        let current = self.attachment_overrides.get(slot);
+                        let mut slot_menu = MenuBuilder::new()
+                            .checkable(
+                                strings.default_attachment,
+                                current.is_none(),
+                                TrayEvent::ResetAttachment(slot.to_owned()),
+                            )
+                            .checkable(
+                                strings.no_attachment,
+                                current == Some(&None),
+                                TrayEvent::SetAttachment(slot.to_owned(), None),
+                            )
+                            .separator();
+                        for name in names {
+                            slot_menu = slot_menu.checkable(
+                                name,
+                                current.and_then(Option::as_deref) == Some(name),
+                                TrayEvent::SetAttachment(slot.to_owned(), Some(name.to_owned())),
+                            );
+                        }
+                        submenu = submenu.submenu(slot, slot_menu);
+                    }
+                }
+
+                submenu
+            });
+
+        for (i, extra) in self.extra_windows.iter().enumerate() {
+            menu = menu.submenu(
+                &extra.name,
+                MenuBuilder::new()
+                    .checkable(strings.show, extra.visible, TrayEvent::ToggleExtraWindow(i))
+                    .item(strings.close, TrayEvent::CloseExtraWindow(i)),
+            );
+        }
+
+        let _ = tray.set_menu(
+            &menu
+                .item(strings.export_animation, TrayEvent::ExportAnimation)
+                .item(strings.save_screenshot, TrayEvent::SaveScreenshot)
+                .item(strings.copy_screenshot, TrayEvent::CopyScreenshot)
                 .separator()
+                .checkable(
+                    strings.autostart,
+                    autostart::is_enabled(),
+                    TrayEvent::ToggleAutostart,
+                )
+                .item(strings.open_log_folder, TrayEvent::OpenLogFolder)
                 .with(MenuItem::Item {
                     id: TrayEvent::About,
                     name: format!("Mon3tr-Widget {}", env!("VERGEN_GIT_SEMVER")),
                     disabled: true,
                     icon: None,
                 })
-                .item("退出", TrayEvent::Exit),
+                .item(strings.exit, TrayEvent::Exit),
         );
     }
 
+    fn publish_status(&self) {
+        let status = Status {
+            models: self
+                .data_files
+                .iter()
+                .map(|f| f.to_string_lossy().into_owned())
+                .collect(),
+            model: self.data_file_index,
+            animations: self.spine.as_ref().map_or_else(Vec::new, |spine| {
+                spine
+                    .skel_data
+                    .animations()
+                    .iter()
+                    .map(|anim| anim.name().to_owned())
+                    .collect()
+            }),
+            opacity: self.opacity,
+            windowed: self.windowed,
+            click_passthrough: self.click_passthrough,
+        };
+
+        let mut published = self.remote_status.lock().unwrap();
+        if *published != status {
+            *published = status.clone();
+            self.remote_events.send(&RemoteEvent::Status(status));
+        }
+    }
+
     fn set_windowed(&mut self, windowed: bool) {
         self.window.set_decorations(windowed); // Hide window borders.
 
@@ -259,7 +827,9 @@ impl State {
 
     fn set_click_passthrough(&mut self, click_passthrough: bool) {
         self.window.set_click_passthrough(click_passthrough);
-        self.window.set_enable(!click_passthrough); // Also hides window from task switcher if disabled.
+        for extra in &self.extra_windows {
+            extra.set_click_passthrough(click_passthrough);
+        }
 
         self.click_passthrough = click_passthrough;
         self.update_tray();
@@ -268,12 +838,396 @@ impl State {
         self.set_click_passthrough(!self.click_passthrough);
     }
 
+    /// Show or hide bones, slots, wireframes and bounds on top of the model.
+    fn toggle_debug_overlay(&mut self) {
+        self.debug_overlay = !self.debug_overlay;
+        self.update_tray();
+        self.request_redraw();
+    }
+
+    fn toggle_stats_overlay(&mut self) {
+        self.stats_overlay = !self.stats_overlay;
+        self.renderer.set_stats_overlay(self.stats_overlay);
+        self.update_tray();
+        self.request_redraw();
+    }
+
+    /// Hold the model in its current pose, or let the animations move it again. Animations keep
+    /// playing underneath, so unfreezing picks up wherever they are by then.
+    fn toggle_freeze_pose(&mut self) {
+        self.frozen_pose = match (&self.frozen_pose, &self.spine) {
+            (None, Some(spine)) => Some(spine.skel.capture_pose()),
+            _ => None,
+        };
+        self.update_tray();
+        self.request_redraw();
+    }
+
+    fn toggle_recording(&mut self, config: &Config) {
+        self.recorder = match self.recorder.take() {
+            Some(_) => None,
+            None => match Recorder::start(&config.recording_path) {
+                Ok(recorder) => Some(recorder),
+                Err(e) => {
+                    log::error!("Failed to start recording: {}", e);
+                    None
+                }
+            },
+        };
+        self.update_tray();
+    }
+
+    fn toggle_replay(&mut self, config: &Config) {
+        self.replay = match self.replay.take() {
+            Some(_) => None,
+            None => match Replay::start(&config.recording_path) {
+                Ok(replay) => Some(replay),
+                Err(e) => {
+                    log::error!("Failed to replay recording: {}", e);
+                    None
+                }
+            },
+        };
+        self.update_tray();
+    }
+
+    /// Show `text` in a speech bubble for `duration` seconds, or the configured default.
+    fn say(&mut self, text: &str, duration: Option<f32>, config: &Config) {
+        let duration = duration.unwrap_or(config.bubble.duration).max(0.0);
+        self.renderer.set_bubble(Some(text.to_owned()));
+        self.bubble_until = Some(Instant::now() + Duration::from_secs_f32(duration));
+        self.frame_pacer.wake();
+        self.request_redraw();
+    }
+
+    fn set_chroma_key(&mut self, chroma_key: bool, config: &Config) {
+        self.chroma_key = chroma_key;
+        self.renderer
+            .set_background(chroma_key.then(|| config.background_color));
+        self.update_tray();
+    }
+
+    /// Dock to `window` from now on, see [`WindowFollower`].
+    fn follow(&mut self, window: ForeignWindow) {
+        // Titles often change with the open document, so only fall back to them
+        let (title, class) = if window.class.is_empty() {
+            (Some(window.title), None)
+        } else {
+            (None, Some(window.class))
+        };
+        let previous = self.follow_window.take();
+        self.follow_window = Some(FollowWindowConfig {
+            title,
+            class,
+            corner: previous
+                .as_ref()
+                .map_or(Corner::TopRight, |previous| previous.corner),
+            offset: previous.map_or((0.0, 0.0), |previous| previous.offset),
+        });
+        self.window_follower.set_target(window.id);
+        self.update_tray();
+    }
+
+    /// Run the first notification reaction matching `window`, unless it's cooling down.
+    fn on_attention(&mut self, window: &ForeignWindow, config: &Config) {
+        let (index, reaction) =
+            match config
+                .notifications
+                .iter()
+                .enumerate()
+                .find(|(_, reaction)| {
+                    window.matches(reaction.title.as_deref(), reaction.class.as_deref())
+                }) {
+                Some(found) => found,
+                None => return,
+            };
+
+        let now = Instant::now();
+        if let Some(last) = self.notification_triggered.get(&index) {
+            if now - *last < Duration::from_secs_f32(reaction.cooldown) {
+                return;
+            }
+        }
+        self.notification_triggered.insert(index, now);
+
+        log::info!("{:?} asks for attention", window.title);
+        self.execute(&reaction.command, config);
+    }
+
+    fn start_pomodoro(&mut self, phase: Phase, config: &Config) {
+        self.pomodoro.start(phase, &config.pomodoro);
+        self.on_pomodoro_event(PomodoroEvent::Started(phase), config);
+    }
+
+    fn stop_pomodoro(&mut self) {
+        self.pomodoro.stop();
+        self.update_tray();
+    }
+
+    /// Minutes left in the current pomodoro phase, rounded up.
+    fn pomodoro_minutes_left(&self) -> Option<u64> {
+        self.pomodoro
+            .remaining()
+            .map(|remaining| (remaining.as_secs() + 59) / 60)
+    }
+
+    /// Play the action for `event`, announcing it in a speech bubble if the action doesn't.
+    fn on_pomodoro_event(&mut self, event: PomodoroEvent, config: &Config) {
+        let (action, text) = match event {
+            PomodoroEvent::Started(Phase::Work) => {
+                (&config.pomodoro.work_start, self.strings.work_started)
+            }
+            PomodoroEvent::Started(Phase::Break) => {
+                (&config.pomodoro.break_start, self.strings.break_started)
+            }
+            PomodoroEvent::Finished => (&config.pomodoro.finished, self.strings.timer_finished),
+        };
+
+        if let Some(action) = action {
+            self.play_action(action, config);
+        }
+        if action.as_ref().map_or(true, |action| action.say.is_none()) {
+            self.say(text, None, config);
+        }
+        self.update_tray();
+    }
+
+    /// Play an animation the way `Config::animation_defaults` says, then go back to idling
+    /// unless it loops.
+    fn play_animation(&mut self, name: &str, config: &Config) {
+        let spine = match self.spine.as_mut() {
+            Some(spine) => spine,
+            None => return,
+        };
+        let defaults = config
+            .animation_defaults
+            .get(name)
+            .cloned()
+            .unwrap_or_default();
+
+        let entry = spine
+            .anim
+            .set_animation_by_name(defaults.track, name, defaults.loop_);
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                log::warn!("No animation named {:?}", name);
+                return;
+            }
+        };
+        if let Some(mix_duration) = defaults.mix_duration {
+            entry.set_mix_duration(mix_duration);
+        }
+        if let Some(time_scale) = defaults.time_scale {
+            entry.set_time_scale(time_scale);
+        }
+
+        if defaults.loop_ {
+            return;
+        }
+        if defaults.track != 0 {
+            let mix_duration = defaults.mix_duration.unwrap_or(0.0);
+            spine
+                .anim
+                .add_empty_animation(defaults.track, mix_duration, 0.0);
+        } else if let Some((idle_name, idle_loop)) = config.pick_idle() {
+            spine
+                .anim
+                .add_animation_by_name(0, idle_name, idle_loop, 0.0);
+        }
+    }
+
+    /// Drop everything playing or queued on all tracks and go back to idling.
+    fn back_to_idle(&mut self, config: &Config) {
+        self.action_tracker.clear();
+        let spine = match self.spine.as_mut() {
+            Some(spine) => spine,
+            None => return,
+        };
+
+        // Blend out of whatever was interrupted, the layered tracks are cleared once mixed out
+        for track in 1..spine.anim.track_count() {
+            spine
+                .anim
+                .fade_out_track(track, BACK_TO_IDLE_MIX_DURATION, move |anim| {
+                    anim.clear_track(track)
+                });
+        }
+        match config.pick_idle() {
+            Some((idle_name, idle_loop)) => {
+                if let Some(entry) = spine.anim.set_animation_by_name(0, idle_name, idle_loop) {
+                    entry.set_mix_duration(BACK_TO_IDLE_MIX_DURATION);
+                }
+            }
+            None => {
+                spine
+                    .anim
+                    .fade_out_track(0, BACK_TO_IDLE_MIX_DURATION, |_| {});
+            }
+        }
+        self.update_tray();
+    }
+
+    /// Leave `slot` to the animations again, starting from its setup pose attachment.
+    fn reset_attachment(&mut self, slot: &str) {
+        self.attachment_overrides.remove(slot);
+        if let Some(spine) = self.spine.as_mut() {
+            let setup = spine
+                .skel_data
+                .iter_slots()
+                .find(|data| data.name() == slot)
+                .and_then(|data| data.attachment_name());
+            match setup {
+                Some(attachment) => spine.skel.set_attachment(slot, attachment),
+                None => spine.skel.clear_attachment(slot),
+            }
+        }
+        self.update_tray();
+    }
+
+    fn set_always_on_bottom(&mut self, on_bottom: bool) {
+        self.window.set_always_on_bottom(on_bottom);
+        for extra in &mut self.extra_windows {
+            extra.set_always_on_bottom(on_bottom);
+        }
+
+        self.always_on_bottom = on_bottom;
+        self.update_tray();
+    }
+
     /// Set opacity of the model, from 0 to 100.
     fn set_opacity(&mut self, opacity: u8) {
         self.opacity = opacity;
+        if self.opacity_mode == OpacityMode::Window {
+            let opacity = opacity as f32 / 100.0;
+            self.window.set_window_opacity(opacity);
+            for extra in &self.extra_windows {
+                extra.window.set_window_opacity(opacity);
+            }
+        }
+        self.update_tray();
+    }
+
+    fn set_model_scale(&mut self, scale: f32) {
+        self.model_scale = scale.clamp(MIN_MODEL_SCALE, MAX_MODEL_SCALE);
+        self.renderer.set_model_scale(self.model_scale);
+        self.fit_window();
+    }
+
+    /// Resize the window around the model as it is posed now, if enabled in the config.
+    fn fit_window(&mut self) {
+        let (auto_size, spine) = match (&self.auto_size, self.spine.as_mut()) {
+            (Some(auto_size), Some(spine)) => (auto_size, spine),
+            _ => return,
+        };
+        spine.step(0.0, |_| {});
+        let bounds = match spine.skel.bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        // Same transform as the vertex shader, which keeps the model origin horizontally
+        // centered, so the window has to reach the farther side on both sides
+        let scale = self.model_scale;
+        let half_width = (bounds.min_x + 0.5).abs().max((bounds.max_x + 0.5).abs()) * scale;
+        let size = LogicalSize::new(
+            (2.0 * (half_width + auto_size.padding)) as f64,
+            (bounds.height() * scale + 2.0 * auto_size.padding) as f64,
+        );
+        let bottom_offset = auto_size.padding - (bounds.min_y + 0.5) * scale - 0.5;
+
+        self.renderer.set_bottom_offset(bottom_offset);
+        self.window.set_inner_size(size);
+    }
+
+    /// Opacity models are drawn with, from 0 to 1.
+    fn vertex_opacity(&self) -> f32 {
+        match self.opacity_mode {
+            OpacityMode::Vertex => self.opacity as f32 / 100.0,
+            OpacityMode::Window => 1.0,
+        }
+    }
+
+    /// Set the speed all models play at, see [`spine_state::set_playback_speed`].
+    fn set_playback_speed(&mut self, speed: f32) {
+        spine_state::set_playback_speed(speed);
+        self.update_tray();
+    }
+
+    /// Go to the next slower or faster of the [`PLAYBACK_SPEEDS`].
+    fn step_playback_speed(&mut self, faster: bool) {
+        let current = spine_state::playback_speed();
+        let next = if faster {
+            PLAYBACK_SPEEDS.iter().find(|&&speed| speed > current)
+        } else {
+            PLAYBACK_SPEEDS.iter().rev().find(|&&speed| speed < current)
+        };
+        if let Some(&speed) = next {
+            self.set_playback_speed(speed);
+        }
+    }
+
+    /// Set the master volume of sounds, from 0 to 100.
+    fn set_volume(&mut self, volume: u8) {
+        self.audio.set_volume(volume);
+        self.update_tray();
+    }
+    fn toggle_mute(&mut self) {
+        self.audio.set_muted(!self.audio.is_muted());
         self.update_tray();
     }
 
+    /// Play a sound file from the current model pack.
+    fn play_sound(&self, name: &str) {
+        if let Some(spine) = self.spine.as_ref() {
+            if let Err(e) = self.audio.play(&spine.pack_file(name)) {
+                log::error!("Failed to play sound {:?}: {}", name, e);
+            }
+        }
+    }
+
+    /// Ask where to save the animation playing on track 0 and export it in the background.
+    fn export_current_animation(&self, config: &Config) {
+        let (model, animation) = match (
+            self.data_file_index,
+            self.spine.as_ref().and_then(|spine| spine.anim.current(0)),
+        ) {
+            (Some(index), Some(entry)) => (
+                self.data_files[index].to_string_lossy().into_owned(),
+                entry.animation().name().to_string(),
+            ),
+            _ => return,
+        };
+
+        let out = match dialog::save_file(
+            &self.window,
+            &format!("{}.gif", animation),
+            export::FILE_FILTERS,
+        ) {
+            Some(out) => out,
+            None => return,
+        };
+
+        let mut options = ExportOptions::new(animation);
+        options.out = out;
+        options.model = Some(model);
+        options.size = Some((self.size.width, self.size.height));
+
+        // The export has its own skeleton and renderer, so the widget keeps running meanwhile
+        let config = config.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = export::export(&config, &options) {
+                log::error!("Export failed: {:#}", e);
+            }
+        });
+    }
+
+    /// Save the next frame, with transparency, once it is rendered.
+    fn take_screenshot(&mut self, target: ScreenshotTarget) {
+        self.screenshot = Some(target);
+        self.request_redraw();
+    }
+
     fn scan_data_files(&mut self) -> std::io::Result<()> {
         let mut path = exe_dir_path();
         path.push("data");
@@ -282,9 +1236,11 @@ impl State {
             std::fs::create_dir_all(&path)?;
         }
 
-        let dir = std::fs::read_dir(path)?
+        let mut dir: Vec<OsString> = std::fs::read_dir(path)?
             .map(|entry| entry.map(|entry| entry.file_name()))
             .collect::<Result<_, _>>()?;
+        // Keep the menu order stable across rescans.
+        dir.sort();
 
         self.data_files = dir;
 
@@ -293,18 +1249,197 @@ impl State {
         Ok(())
     }
 
-    fn load_data_file_index(&mut self, index: usize) -> Result<()> {
+    /// Re-scan the data directory, keeping the currently loaded model selected.
+    fn rescan_data_files(&mut self) -> std::io::Result<()> {
+        // Pick up models that changed on disk
+        self.model_cache.clear();
+        spine::cache::clear();
+
+        let old_files = std::mem::take(&mut self.data_files);
+        let current = self.data_file_index.map(|i| old_files[i].clone());
+
+        self.scan_data_files()?;
+
+        for added in self.data_files.iter().filter(|f| !old_files.contains(f)) {
+            log::info!("Model added: {:?}", added);
+        }
+        for removed in old_files.iter().filter(|f| !self.data_files.contains(f)) {
+            log::info!("Model removed: {:?}", removed);
+        }
+
+        self.data_file_index =
+            current.and_then(|current| self.data_files.iter().position(|f| *f == current));
+        self.update_tray();
+
+        Ok(())
+    }
+
+    fn load_data_file_index(&mut self, index: usize, config: &Config) -> Result<()> {
         let mut path = exe_dir_path();
         path.push("data");
         path.push(self.data_files[index].clone());
 
-        let spine = SpineState::new(&path.to_string_lossy())?;
+        texture::set_texture_scale(config.texture_scale);
+        alpha_mask::set_patterns(&config.alpha_masks);
+        let pack = path.to_string_lossy();
+        let spine = match self.model_cache.take(&pack) {
+            Some(mut spine) => {
+                if let Some((idle_name, idle_loop)) = config.pick_idle() {
+                    spine.anim.set_animation_by_name(0, idle_name, idle_loop);
+                }
+                spine
+            }
+            None => {
+                let spine = SpineState::new(&pack, config.pick_idle())?;
+                self.report_texture_errors(&spine);
+                spine
+            }
+        };
+
+        if let Some(previous) = self.spine.replace(spine) {
+            self.model_cache.put(previous);
+        }
+        self.data_file_index = Some(index);
+        self.fit_window();
+        self.update_tray();
+        self.remote_events.send(&RemoteEvent::ModelSwitched {
+            index,
+            name: self.data_files[index].to_string_lossy().into_owned(),
+        });
+
+        Ok(())
+    }
+
+    /// Load the models configured to share the window, skipping those that fail to load.
+    fn load_companions(&mut self, config: &Config) {
+        let mut data_dir = exe_dir_path();
+        data_dir.push("data");
+
+        self.companions = config
+            .companions
+            .iter()
+            .filter_map(|instance| {
+                let path = data_dir.join(&instance.pack);
+                let idle = instance.idle.as_deref().map(|name| (name, true));
+                match SpineState::new(&path.to_string_lossy(), idle) {
+                    Ok(mut spine) => {
+                        spine.place(instance.offset, instance.scale);
+                        Some(Companion {
+                            spine,
+                            behind: instance.behind,
+                        })
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load companion {}: {}", instance.pack, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+    }
+
+    /// Open a window for each of the extra models in the config.
+    fn open_extra_windows<T>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        owner: &Window,
+        config: &Config,
+    ) {
+        for (index, instance) in config.extra_windows.iter().enumerate() {
+            let window = create_window(target, owner, instance.size, instance.position, config);
+            match WidgetWindow::new(window, index, instance, config) {
+                Ok(mut extra) => {
+                    extra.set_click_passthrough(self.click_passthrough);
+                    if self.always_on_bottom {
+                        extra.set_always_on_bottom(true);
+                    }
+                    if self.opacity_mode == OpacityMode::Window {
+                        extra.window.set_window_opacity(self.opacity as f32 / 100.0);
+                    }
+                    self.extra_windows.push(extra);
+                }
+                Err(e) => log::error!("Failed to open window for {}: {}", instance.pack, e),
+            }
+        }
+        self.update_tray();
+    }
+
+    fn extra_window_mut(&mut self, id: WindowId) -> Option<&mut WidgetWindow> {
+        self.extra_windows.iter_mut().find(|extra| extra.id() == id)
+    }
+
+    fn toggle_extra_window(&mut self, index: usize) {
+        if let Some(extra) = self.extra_windows.get_mut(index) {
+            extra.set_visible(!extra.visible);
+            self.update_tray();
+        }
+    }
 
-        self.spine = Some(spine);
+    fn close_extra_window(&mut self, index: usize) {
+        if index < self.extra_windows.len() {
+            self.extra_windows.remove(index);
+            self.update_tray();
+        }
+    }
 
+    /// Check that the downloaded or dropped pack at `temp_path` loads, then move it into `data`
+    /// as `name` and list it, switching to it if `select` is set.
+    fn install_pack(
+        &mut self,
+        name: &str,
+        temp_path: &Path,
+        select: bool,
+        config: &Config,
+    ) -> Result<()> {
+        if let Err(e) = SpineState::new(&temp_path.to_string_lossy(), None) {
+            let _ = std::fs::remove_file(temp_path);
+            return Err(e.context(format!("Model {} failed to load", name)));
+        }
+        download::install(name, temp_path)
+            .map_err(|e| e.context(format!("Failed to install model {}", name)))?;
+        self.rescan_data_files()?;
+
+        let index = self
+            .data_files
+            .iter()
+            .position(|f| f.to_str() == Some(name));
+        if let (true, Some(index)) = (select, index) {
+            self.load_data_file_index(index, config)?;
+        }
         Ok(())
     }
 
+    /// Add a `.zip` or folder dropped onto the widget and switch to it.
+    fn drop_pack(&mut self, path: &Path, config: &Config) {
+        let result = import::prepare(path)
+            .and_then(|(name, temp_path)| self.install_pack(&name, &temp_path, true, config));
+        if let Err(e) = result {
+            self.report_add_model_error(&e);
+        }
+    }
+
+    /// Tell the user about atlas pages that failed to load, the model shows up with parts
+    /// missing otherwise. The errors are logged already.
+    fn report_texture_errors(&mut self, spine: &SpineState) {
+        let errors = spine.atlas.load_errors();
+        if errors.is_empty() {
+            return;
+        }
+
+        let message = errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.notifier.show(self.strings.textures_failed, &message);
+    }
+
+    fn report_add_model_error(&mut self, e: &anyhow::Error) {
+        log::error!("{:#}", e);
+        self.notifier
+            .show(self.strings.add_model_failed, &format!("{:#}", e));
+    }
+
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
@@ -318,9 +1453,6 @@ impl State {
     }
 
     fn input(&mut self, event: &WindowEvent, config: &Config) -> bool {
-        let window = &self.window;
-        let spine = self.spine.as_mut().unwrap();
-
         match event {
             WindowEvent::KeyboardInput {
                 input:
@@ -339,44 +1471,28 @@ impl State {
                 match (self.modifiers_state, keycode) {
                     (ModifiersState::CTRL, VirtualKeyCode::Equals) => {
                         // "=+" on main keyboard
-                        // *self.scaling_state.model_scaling_mut() += 0.1;
+                        self.set_model_scale(self.model_scale + MODEL_SCALE_STEP);
                         return true;
                     }
                     (ModifiersState::CTRL, VirtualKeyCode::Minus) => {
                         // "-_" on main keyboard
-                        // *self.scaling_state.model_scaling_mut() -= 0.1;
+                        self.set_model_scale(self.model_scale - MODEL_SCALE_STEP);
+                        return true;
+                    }
+                    (ModifiersState::CTRL, VirtualKeyCode::LBracket) => {
+                        self.step_playback_speed(false);
+                        return true;
+                    }
+                    (ModifiersState::CTRL, VirtualKeyCode::RBracket) => {
+                        self.step_playback_speed(true);
                         return true;
                     }
                     _ => {}
                 }
 
                 for action in &config.actions {
-                    if action.trigger == *keycode {
-                        let mut last_length = 0.0;
-                        let mut is_first = true;
-                        for item in &action.sequence {
-                            if is_first {
-                                is_first = false;
-                                spine.anim.set_animation_by_name(0, &item.name, item.loop_);
-                            } else {
-                                spine.anim.add_animation_by_name(
-                                    0,
-                                    &item.name,
-                                    item.loop_,
-                                    last_length,
-                                );
-                            }
-                            last_length = item.length.unwrap_or(0.0);
-                        }
-
-                        // Return to idle
-                        if let (true, Some(idle_name)) =
-                            (action.return_to_idle, &config.idle_animation)
-                        {
-                            spine
-                                .anim
-                                .add_animation_by_name(0, idle_name, true, last_length);
-                        }
+                    if action.trigger == Some(*keycode) {
+                        self.play_action(action, config);
                     }
                 }
                 true
@@ -394,139 +1510,695 @@ impl State {
                 self.modifiers_state = *mod_state;
                 true
             }
-            WindowEvent::MouseInput {
+            WindowEvent::MouseWheel { delta, .. } => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.y / PIXELS_PER_WHEEL_STEP) as f32
+                    }
+                };
+                match self.modifiers_state {
+                    ModifiersState::CTRL => {
+                        self.set_model_scale(self.model_scale + steps * MODEL_SCALE_STEP);
+                        true
+                    }
+                    ModifiersState::SHIFT => {
+                        let opacity = self.opacity as f32 + steps * OPACITY_STEP;
+                        self.set_opacity(opacity.round().clamp(MIN_WHEEL_OPACITY, 100.0) as u8);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            WindowEvent::MouseInput {
                 button: MouseButton::Left,
                 state: ElementState::Pressed,
                 ..
             } => {
-                let _ = window.drag_window();
+                if config.hit_test && !self.cursor_over_model() {
+                    return true;
+                }
+
+                self.click(config);
+                self.drag.start(&self.window);
+                let _ = self.window.drag_window();
+                true
+            }
+            WindowEvent::Touch(touch) => {
+                if let Some(gesture) = self.touch.touch(touch, self.scale_factor) {
+                    self.gesture(gesture, config);
+                }
+                true
+            }
+            WindowEvent::Moved(position) => {
+                self.drag.moved(*position);
+                true
+            }
+            WindowEvent::Focused(true) if self.always_on_bottom => {
+                self.window.set_always_on_bottom(true);
+                true
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.drop_pack(path, config);
                 true
             }
             _ => false,
         }
     }
 
-    fn update(&mut self) {
-        self.renderer.update();
+    fn cursor_over_model(&mut self) -> bool {
+        match self.window.cursor_position() {
+            Some(pos) => self.over_model(pos),
+            None => false,
+        }
     }
 
-    fn render(&mut self) -> Result<()> {
-        let spine = self.spine.as_mut().unwrap();
-        spine.prepare_render();
+    /// Whether `position` in the client area is on the model.
+    fn over_model(&mut self, position: PhysicalPosition<f64>) -> bool {
+        let position = self.renderer.window_to_model(position);
+        match self.spine.as_ref() {
+            Some(spine) => hit_test::hit_test(&spine.skel, position, &mut self.world_vertices),
+            None => false,
+        }
+    }
 
-        let opacity = self.opacity as f32 / 100.0;
+    /// Tap to click, pinch to scale and long press to toggle windowed mode.
+    /// Play `on_exit`, returns how long to wait for it before actually exiting.
+    fn play_exit_action(&mut self, config: &Config) -> Duration {
+        let action = match &config.on_exit {
+            Some(action)
+                if self.spine.is_some()
+                    && self.auto_pause.reason().is_none()
+                    && !self.window.is_occluded() =>
+            {
+                action
+            }
+            _ => return Duration::ZERO,
+        };
 
-        let skel_tint = spine.skel.tint_color();
-        for slot in spine.skel.slots() {
-            let attachment = if let Some(a) = slot.attachment() {
-                a
-            } else {
-                continue;
-            };
+        let length = self.play_action(action, config);
+        Duration::from_secs_f32(length.min(config.exit_timeout).max(0.0))
+    }
+
+    fn gesture(&mut self, gesture: Gesture, config: &Config) {
+        match gesture {
+            Gesture::Tap(position) => {
+                if !config.hit_test || self.over_model(position) {
+                    self.click(config);
+                }
+            }
+            Gesture::LongPress => self.toggle_windowed(),
+            Gesture::Drag(offset) => {
+                if let Some(peek) = self.peek.as_mut() {
+                    peek.stop();
+                }
+                if let Ok(position) = self.window.outer_position() {
+                    self.window.set_outer_position(PhysicalPosition::new(
+                        position.x + offset.x.round() as i32,
+                        position.y + offset.y.round() as i32,
+                    ));
+                }
+            }
+            Gesture::Pinch(factor) => self.set_model_scale(self.model_scale * factor as f32),
+        }
+    }
+
+    fn click(&mut self, config: &Config) {
+        let now = Instant::now();
+        let double_click_time = window_ext::double_click_time();
+
+        let command = match self.last_click {
+            Some(last) if now - last <= double_click_time => {
+                self.last_click = None;
+                config.on_double_click.as_ref()
+            }
+            _ => {
+                self.last_click = Some(now);
+                config.on_click.as_ref()
+            }
+        };
+
+        if let Some(command) = command {
+            self.execute(command, config);
+        }
+    }
+
+    /// Handle a key event from the global keyboard hook.
+    fn global_key(
+        &mut self,
+        state: ElementState,
+        vk_code: u32,
+        modifiers: ModifiersState,
+        config: &Config,
+    ) {
+        match state {
+            ElementState::Pressed => {
+                // Ignore auto-repeat
+                if !self.pressed_global_keys.insert(vk_code) {
+                    return;
+                }
+
+                if let Some(command) = config.global_hotkeys.get(&Hotkey { modifiers, vk_code }) {
+                    self.execute(command, config);
+                }
+
+                if let Some(typing) = &config.typing {
+                    self.play_typing_animation(typing);
+                }
+            }
+            ElementState::Released => {
+                self.pressed_global_keys.remove(&vk_code);
+            }
+        }
+    }
+
+    fn execute(&mut self, command: &Command, config: &Config) {
+        match command {
+            Command::Action(name) => {
+                match config
+                    .actions
+                    .iter()
+                    .find(|action| action.name.as_ref() == Some(name))
+                {
+                    Some(action) => {
+                        self.play_action(action, config);
+                    }
+                    None => log::warn!("No action named {:?}", name),
+                }
+            }
+            Command::Animation(name) => self.play_animation(name, config),
+            Command::ToggleClickPassthrough => self.toggle_click_passthrough(),
+            Command::ToggleWindowed => self.toggle_windowed(),
+            Command::SetModel(index) => {
+                if *index >= self.data_files.len() {
+                    log::warn!("No model at index {}", index);
+                } else if let Err(e) = self.load_data_file_index(*index, config) {
+                    log::error!("Failed to load model: {}", e);
+                }
+            }
+            Command::NextModel => {
+                if self.data_files.is_empty() {
+                    return;
+                }
+                let index = self
+                    .data_file_index
+                    .map_or(0, |i| (i + 1) % self.data_files.len());
+                if let Err(e) = self.load_data_file_index(index, config) {
+                    log::error!("Failed to load model: {}", e);
+                }
+            }
+            Command::SetOpacity(opacity) => self.set_opacity((*opacity).min(100)),
+            Command::SetPlaybackSpeed(speed) => self.set_playback_speed(*speed),
+            Command::SetTint(tint) => self.renderer.set_global_tint(*tint),
+            Command::ToggleDebugOverlay => self.toggle_debug_overlay(),
+            Command::ToggleStatsOverlay => self.toggle_stats_overlay(),
+            Command::ToggleFreezePose => self.toggle_freeze_pose(),
+            Command::ToggleRecording => self.toggle_recording(config),
+            Command::ToggleReplay => self.toggle_replay(config),
+            Command::Say { text, duration } => self.say(text, *duration, config),
+            Command::Snapshot => {
+                if let Some(spine) = self.spine.as_ref() {
+                    let snapshot = spine.skel.snapshot(&spine.anim);
+                    self.remote_events.send(&RemoteEvent::Snapshot(snapshot));
+                }
+            }
+            Command::ApplySnapshot(snapshot) => {
+                if let Some(spine) = self.spine.as_mut() {
+                    spine.skel.apply_snapshot(&mut spine.anim, snapshot);
+                }
+            }
+        }
+    }
+
+    fn play_typing_animation(&mut self, typing: &TypingReaction) {
+        let now = Instant::now();
+        if let Some(last) = self.last_typing_animation {
+            if now - last < Duration::from_secs_f32(typing.min_interval) {
+                return;
+            }
+        }
+
+        if let Some(spine) = self.spine.as_mut() {
+            spine
+                .anim
+                .set_animation_by_name(typing.track, &typing.animation, false);
+            // Fade the track out after the animation, revealing the base track again
+            spine.anim.add_empty_animation(typing.track, 0.1, 0.0);
+            self.last_typing_animation = Some(now);
+        }
+    }
+
+    /// Returns how long the sequence takes in seconds, not counting the idle animation after it.
+    fn play_action(&mut self, action: &Action, config: &Config) -> f32 {
+        if self.spine.is_some() && !self.action_tracker.should_play(action) {
+            return 0.0;
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(action);
+        }
+        if let Some(sound) = &action.sound {
+            self.play_sound(sound);
+        }
+        if let Some(text) = &action.say {
+            self.say(text, None, config);
+        }
+
+        let spine = if let Some(spine) = self.spine.as_mut() {
+            spine
+        } else {
+            return 0.0;
+        };
 
-            let slot_tint = slot.tint_color();
-            let tint = [
-                skel_tint[0] * slot_tint[0],
-                skel_tint[1] * slot_tint[1],
-                skel_tint[2] * slot_tint[2],
-                skel_tint[3] * slot_tint[3] * opacity,
-            ];
-
-            let to_vertex = |(uv, pos): ([f32; 2], [f32; 2])| Vertex {
-                position: pos,
-                tex_coords: uv,
-                tint,
+        // Delay for the next item of each track used so far
+        let mut last_lengths: HashMap<usize, f32> = HashMap::new();
+        let mut track_lengths: HashMap<usize, f32> = HashMap::new();
+        // Last animation of each track, the action is done once they all completed
+        let mut last_names: HashMap<usize, Option<String>> = HashMap::new();
+        for item in action.pick_sequence() {
+            let track = item.track;
+            let mix_duration = item.mix_duration.unwrap_or(0.0);
+
+            let entry = match (&item.name, last_lengths.get(&track)) {
+                (Some(name), None) => spine.anim.set_animation_by_name(track, name, item.loop_),
+                (Some(name), Some(delay)) => spine
+                    .anim
+                    .add_animation_by_name(track, name, item.loop_, *delay),
+                (None, None) => spine.anim.set_empty_animation(track, mix_duration),
+                (None, Some(delay)) => spine.anim.add_empty_animation(track, mix_duration, *delay),
             };
 
-            match attachment.as_inner() {
-                AttachmentType::Region(region) => {
-                    let tex = if let Some(tex) =
-                        unsafe { region.atlas_region().page().render_object::<Texture>() }
-                    {
-                        tex
-                    } else {
-                        continue;
-                    };
-                    let tex_id = tex.id();
-                    self.renderer.register_texture(tex);
-
-                    let (scratch_vb, scratch_ib) = self.scratch_buffers.get_buffers_mut(tex_id);
-
-                    let offset = scratch_vb.len() as u16;
-                    region.compute_world_vertices(&mut self.world_vertices);
-                    let new_vertices = self
-                        .world_vertices
-                        .iter()
-                        .enumerate()
-                        .map(|(i, p)| {
-                            let (u, v) = region.uv(i);
-                            ([u, v], *p)
-                        })
-                        .map(to_vertex);
-                    scratch_vb.extend(new_vertices);
+            let length = item.length.or_else(|| {
+                let time_scale = item.time_scale.unwrap_or(1.0);
+                entry
+                    .as_ref()
+                    .map(|entry| entry.animation().duration() / time_scale)
+            });
+            *track_lengths.entry(track).or_default() += length.unwrap_or(0.0);
+            last_names.insert(track, entry.is_some().then(|| item.name.clone()).flatten());
+
+            if let Some(entry) = entry {
+                if let Some(mix_duration) = item.mix_duration {
+                    entry.set_mix_duration(mix_duration);
+                }
+                if let Some(time_scale) = item.time_scale {
+                    entry.set_time_scale(time_scale);
+                }
+            }
+
+            last_lengths.insert(track, item.length.unwrap_or(0.0));
+        }
+
+        let pending = last_names
+            .into_iter()
+            .filter_map(|(track, name)| Some((track, name?)))
+            .collect();
+        self.action_tracker.started(action, pending);
 
-                    let new_indices = [0, 1, 2, 2, 3, 0].iter().map(|i| i + offset);
-                    scratch_ib.extend(new_indices);
+        let length = track_lengths.values().copied().fold(0.0, f32::max);
+        if !action.return_to_idle {
+            return length;
+        }
+
+        // Return to idle on the base track, clear the layered ones
+        for (&track, &last_length) in &last_lengths {
+            if track == 0 {
+                if let Some((idle_name, idle_loop)) = config.pick_idle() {
+                    spine
+                        .anim
+                        .add_animation_by_name(0, idle_name, idle_loop, last_length);
                 }
-                AttachmentType::Mesh(mesh) => {
-                    let tex = if let Some(tex) =
-                        unsafe { mesh.atlas_region().page().render_object::<Texture>() }
-                    {
-                        tex
-                    } else {
-                        continue;
-                    };
-                    let tex_id = tex.id();
-                    self.renderer.register_texture(tex);
-
-                    let (scratch_vb, scratch_ib) = self.scratch_buffers.get_buffers_mut(tex_id);
-
-                    let offset = scratch_vb.len() as u16;
-                    mesh.compute_world_vertices(&mut self.world_vertices);
-                    let new_vertices = self
-                        .world_vertices
-                        .iter()
-                        .enumerate()
-                        .map(|(i, p)| {
-                            let (u, v) = mesh.uv(i);
-                            ([u, v], *p)
-                        })
-                        .map(to_vertex);
-                    scratch_vb.extend(new_vertices);
+            } else {
+                spine.anim.add_empty_animation(track, 0.0, last_length);
+            }
+        }
+        length
+    }
+
+    /// Switch track 0 to a freshly picked idle animation.
+    fn play_idle(&mut self, config: &Config) {
+        if let (Some(spine), Some((idle_name, idle_loop))) =
+            (self.spine.as_mut(), config.pick_idle())
+        {
+            spine.anim.set_animation_by_name(0, idle_name, idle_loop);
+        }
+    }
+
+    fn handle_animation_events(&mut self, events: Vec<AnimationEvent>, config: &Config) {
+        #[cfg(windows)]
+        let queue_changed = events
+            .iter()
+            .any(|event| matches!(event.kind, EventType::Start | EventType::End));
+
+        for event in events {
+            self.broadcast_animation_event(&event);
+
+            let next = match self.spine.as_ref() {
+                Some(spine) => self.action_tracker.on_event(&event, &spine.anim),
+                None => None,
+            };
+            if let Some(action) = next {
+                self.play_action(&action, config);
+            }
 
-                    let new_indices = mesh.indices().iter().map(|i| i + offset);
-                    scratch_ib.extend(new_indices);
+            match (event.kind, event.track_index) {
+                (EventType::Complete, 0) if config.is_idle(&event.animation_name) => {
+                    self.reroll_idle(&event, config);
                 }
+                (EventType::Event, _) => self.run_event_hooks(&event, config),
                 _ => {}
             }
         }
 
+        // Keep the animation queue in the tray menu current
+        #[cfg(windows)]
+        if queue_changed {
+            self.set_tray_menu();
+        }
+    }
+
+    fn broadcast_animation_event(&self, event: &AnimationEvent) {
+        let (track, animation) = (event.track_index, event.animation_name.clone());
+        let remote_event = match (event.kind, &event.event) {
+            (EventType::Start, _) => RemoteEvent::AnimationStarted { track, animation },
+            (EventType::Complete, _) => RemoteEvent::AnimationCompleted { track, animation },
+            (EventType::Event, Some(spine_event)) => RemoteEvent::SpineEvent {
+                name: spine_event.name.clone(),
+                animation,
+                int: spine_event.int_value,
+                float: spine_event.float_value,
+                string: spine_event.string_value.clone(),
+            },
+            _ => return,
+        };
+        self.remote_events.send(&remote_event);
+    }
+
+    fn run_event_hooks(&mut self, event: &AnimationEvent, config: &Config) {
+        let hooks = match &event.event {
+            Some(spine_event) => config.spine_events.get(&spine_event.name),
+            None => None,
+        };
+
+        for hook in hooks.into_iter().flatten() {
+            match hook {
+                EventHook::Command(command) => self.execute(command, config),
+                EventHook::Sound(sound) => self.play_sound(sound),
+                _ => event_hooks::spawn(hook, event),
+            }
+        }
+    }
+
+    /// Pick the next idle animation after the current one completed.
+    fn reroll_idle(&mut self, completed: &AnimationEvent, config: &Config) {
+        let spine = if let Some(spine) = self.spine.as_mut() {
+            spine
+        } else {
+            return;
+        };
+
+        // Leave the track alone if something else took over or is queued
+        match spine.anim.current(0) {
+            Some(entry)
+                if entry.next().is_none()
+                    && entry.animation().name() == completed.animation_name => {}
+            _ => return,
+        }
+
+        if let Some((idle_name, idle_loop)) = config.pick_idle() {
+            if idle_loop && completed.looping && idle_name == completed.animation_name {
+                return;
+            }
+            spine.anim.set_animation_by_name(0, idle_name, idle_loop);
+        }
+    }
+
+    fn update(&mut self, config: &Config) {
+        self.notifier.update();
+        if let Some(gesture) = self.touch.update() {
+            self.gesture(gesture, config);
+        }
+        if let Some(event) = self.pomodoro.update(&config.pomodoro) {
+            self.on_pomodoro_event(event, config);
+        }
+        if let Some(replay) = self.replay.as_mut() {
+            let due = replay.update();
+            if replay.is_done() {
+                self.replay = None;
+                self.update_tray();
+            }
+            for action in due {
+                self.play_action(&action, config);
+            }
+        }
+        let minutes = self.pomodoro_minutes_left();
+        if minutes != self.pomodoro_minutes_shown {
+            self.pomodoro_minutes_shown = minutes;
+            self.update_tray();
+        }
+        if let (Some(monitor), Some(system_load)) =
+            (self.system_monitor.as_mut(), &config.system_load)
+        {
+            for command in monitor.update(system_load) {
+                self.execute(command, config);
+            }
+        }
+        if let Some(until) = self.bubble_until {
+            if Instant::now() >= until {
+                self.bubble_until = None;
+                self.renderer.set_bubble(None);
+                self.frame_pacer.wake();
+                self.request_redraw();
+            }
+        }
+        self.drag.update(&self.window, config.drag_inertia);
+        let docked = self.follow_window.is_some() && !self.windowed;
+        if let Some(follow_window) = &self.follow_window {
+            if docked && !self.drag.is_active() {
+                self.window_follower.update(follow_window, &self.window);
+            }
+        }
+        match (self.peek.as_mut(), &config.peek, self.spine.as_mut()) {
+            (Some(peek), Some(_), Some(_)) if self.windowed || self.drag.is_active() || docked => {
+                peek.stop()
+            }
+            (Some(peek), Some(peek_config), Some(spine)) => {
+                peek.update(peek_config, &self.window, spine)
+            }
+            _ => {}
+        }
+        // Placed by a behavior, so where the window is now isn't worth keeping or correcting
+        let placed = docked || self.peek.as_ref().map_or(false, Peek::is_away);
+        if !self.drag.is_active() && !placed {
+            self.placement.update(&self.window);
+        }
+        if config.snap_to_taskbar && !self.windowed && !self.drag.is_active() && !placed {
+            self.taskbar_snap.update(&self.window, false);
+        }
+        let walk_ended = match (self.wander.as_mut(), &config.wander, self.spine.as_mut()) {
+            (Some(wander), Some(wander_config), Some(_))
+                if self.windowed || self.drag.is_active() || placed =>
+            {
+                wander.rest(wander_config)
+            }
+            (Some(wander), Some(wander_config), Some(spine)) => {
+                wander.update(wander_config, &self.window, spine)
+            }
+            _ => false,
+        };
+        if walk_ended {
+            self.play_idle(config);
+        }
+        if let (Some(blink), Some(blink_config), Some(spine)) =
+            (self.blink.as_mut(), &config.blink, self.spine.as_mut())
+        {
+            blink.update(blink_config, spine);
+        }
+        if let (Some(breathing), Some(breathing_config)) = (&self.breathing, &config.breathing) {
+            let (scale, offset) = breathing.motion(breathing_config);
+            self.renderer.set_motion(scale, offset);
+        }
+        if let (Some(lean), Some(lean_config)) = (self.lean.as_mut(), &config.lean) {
+            lean.update(lean_config, &self.window);
+        }
+        if let (Some(lip_sync), Some(lip_sync_config), Some(spine)) = (
+            self.lip_sync.as_mut(),
+            &config.lip_sync,
+            self.spine.as_mut(),
+        ) {
+            lip_sync.update(lip_sync_config, spine);
+        }
+        self.renderer.update();
+    }
+
+    fn render(&mut self, config: &Config) -> Result<()> {
+        if self.renderer.is_device_lost() {
+            self.renderer.recover(&self.window, config)?;
+        }
+
+        let cursor = config
+            .follow_cursor
+            .as_ref()
+            .and_then(|_| self.window.cursor_position())
+            .map(|pos| self.renderer.window_to_model(pos));
+
+        let lip_sync = self.lip_sync.as_ref();
+        let lean = self.lean.as_ref();
+        let attachment_overrides = &self.attachment_overrides;
+        let frozen_pose = self.frozen_pose.as_ref();
+        if let Some(spine) = self.spine.as_mut() {
+            spine.prepare_render(|skel| {
+                if let Some(pose) = frozen_pose {
+                    skel.apply_pose(pose);
+                }
+                for (slot, attachment) in attachment_overrides {
+                    match attachment {
+                        Some(attachment) => skel.set_attachment(slot, attachment),
+                        None => skel.clear_attachment(slot),
+                    }
+                }
+                if let (Some(follow), Some(cursor)) = (&config.follow_cursor, cursor) {
+                    behavior::follow_cursor::apply(skel, follow, cursor);
+                }
+                if let (Some(lip_sync), Some(lip_sync_config)) = (lip_sync, &config.lip_sync) {
+                    lip_sync.apply(lip_sync_config, skel);
+                }
+                if let (Some(lean), Some(lean_config)) = (lean, &config.lean) {
+                    lean.apply(lean_config, skel);
+                }
+            });
+            let events = spine.anim.take_events();
+            self.handle_animation_events(events, config);
+        }
+
+        for companion in &mut self.companions {
+            companion.spine.prepare_render(|_| {});
+            // Companions only idle, nothing listens to their events
+            companion.spine.anim.take_events();
+        }
+
+        // Back to front: companions behind, the main model, then the rest
+        let opacity = self.vertex_opacity();
+        let behind = self.companions.iter().filter(|c| c.behind);
+        let in_front = self.companions.iter().filter(|c| !c.behind);
+        for skel in behind
+            .map(|c| &c.spine.skel)
+            .chain(self.spine.as_ref().map(|spine| &spine.skel))
+            .chain(in_front.map(|c| &c.spine.skel))
+        {
+            self.scratch_buffers.fill(
+                skel,
+                self.renderer.as_mut(),
+                &mut self.world_vertices,
+                opacity,
+            );
+        }
+        if let (true, Some(spine)) = (self.debug_overlay, self.spine.as_ref()) {
+            self.scratch_buffers
+                .fill_debug(&spine.skel, &mut self.world_vertices);
+        }
+
         self.renderer.render(&mut self.scratch_buffers)?;
+        #[cfg(windows)]
+        let capture = self.spout.is_some() || self.screenshot.is_some();
+        #[cfg(not(windows))]
+        let capture = self.screenshot.is_some();
+        if capture {
+            let frame = self.renderer.capture(&mut self.scratch_buffers)?;
+            #[cfg(windows)]
+            if let Some(spout) = &self.spout {
+                spout.send(&frame);
+            }
+            if let Some(target) = self.screenshot.take() {
+                if let Err(e) = target.save(&self.window, &frame) {
+                    log::error!("Failed to save screenshot: {}", e);
+                }
+            }
+        }
+        let throttled = self.is_throttled(config);
+        self.frame_pacer.frame_drawn(
+            self.scratch_buffers.fingerprint(),
+            config.fps_limit,
+            throttled,
+        );
         self.scratch_buffers.clear();
 
         Ok(())
     }
 
+    /// Whether to draw at a low frame rate to save power.
+    fn is_throttled(&self, config: &Config) -> bool {
+        // Gliding after a drag moves the window and breathing moves the model in the shader,
+        // both every frame without changing what's drawn
+        config.power_saving
+            && !self.drag.is_active()
+            && (self.window.is_occluded()
+                || (self.frame_pacer.is_static() && self.breathing.is_none()))
+    }
+
+    /// When the next frame is due, `None` if right away.
+    fn next_frame(&self, config: &Config) -> Option<Instant> {
+        self.frame_pacer
+            .next_frame(config.fps_limit, self.is_throttled(config))
+    }
+
+    /// Hide the windows while paused and show them again afterwards.
+    fn update_auto_pause(&mut self, config: &Config) {
+        if !self.auto_pause.update(config) {
+            return;
+        }
+
+        let visible = self.auto_pause.reason().is_none();
+        self.window.set_visible(visible);
+        for extra in self.extra_windows.iter().filter(|extra| extra.visible) {
+            extra.window.set_visible(visible);
+        }
+        if visible {
+            // The swapchain may be stale after the screen was locked or turned off
+            self.resize(self.window.inner_size());
+            for extra in &mut self.extra_windows {
+                extra.refresh_surface();
+            }
+            self.frame_pacer.wake();
+        }
+    }
+
     fn request_redraw(&mut self) {
         self.window.request_redraw();
+        for extra in self.extra_windows.iter().filter(|extra| extra.visible) {
+            extra.window.request_redraw();
+        }
     }
 }
 
-fn create_window<T>(event_loop: &EventLoop<T>, owner: &Window, config: &Config) -> Window {
-    let window = WindowBuilder::new()
+fn create_window<T>(
+    event_loop: &EventLoopWindowTarget<T>,
+    owner: &Window,
+    size: (f64, f64),
+    position: (f64, f64),
+    config: &Config,
+) -> Window {
+    let builder = WindowBuilder::new()
         .with_title("Mon3tr-Widget")
         .with_always_on_top(true)
         .with_decorations(false)
         .with_transparent(true)
-        .with_inner_size(LogicalSize::new(config.window_size.0, config.window_size.1))
+        .with_inner_size(LogicalSize::new(size.0, size.1));
+    #[cfg(windows)]
+    let builder = builder
         .with_owner_window(owner.hwnd() as _)
-        .build(event_loop)
-        .unwrap();
-
-    window.set_outer_position(PhysicalPosition::new(
-        config.window_position.0,
-        config.window_position.1,
-    ));
+        // DirectComposition shows the content, a redirection surface would cover it
+        .with_no_redirection_bitmap(config.gpu.presentation == Presentation::DirectComposition);
+    #[cfg(not(windows))]
+    let _ = config;
+    // Keeps it off the taskbar and out of the window switcher, like an owned window on Windows
+    #[cfg(target_os = "linux")]
+    let builder = {
+        let _ = owner;
+        builder.with_x11_window_type(vec![XWindowType::Utility])
+    };
+    let window = builder.build(event_loop).unwrap();
+
+    window.set_outer_position(PhysicalPosition::new(position.0, position.1));
 
     window
 }
@@ -539,29 +2211,10 @@ fn create_owner_window<Evt>(event_loop: &EventLoop<Evt>) -> Window {
         .unwrap()
 }
 
-fn init_logging() {
-    use fern::colors::ColoredLevelConfig;
-
-    let colors = ColoredLevelConfig::new();
-    fern::Dispatch::new()
-        .format(move |out, message, record| {
-            out.finish(format_args!(
-                "{}[{}][{}] {}",
-                chrono::Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
-                record.target(),
-                colors.color(record.level()),
-                message
-            ))
-        })
-        .level(log::LevelFilter::Info)
-        .chain(std::io::stdout())
-        .apply()
-        .unwrap();
-}
-
 fn main() {
-    // #[cfg(debug_assertions)]
-    init_logging();
+    logging::init();
+    crash::install_hook();
+    spine::set_error_hook(|e| log::error!("Spine: {}", e));
 
     log::info!(
         "Mon3tr-Widget {} {} built {}",
@@ -577,43 +2230,267 @@ fn main() {
         env!("VERGEN_CARGO_TARGET_TRIPLE")
     );
 
-    let config_path = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "config.yml".to_string());
+    let config_path = match cli::parse(std::env::args().skip(1)) {
+        Ok(Invocation::Run { config_path }) => config_path,
+        Ok(Invocation::Send(command)) => {
+            if let Err(e) = remote::pipe::send(&command) {
+                log::error!("Failed to send command: {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Ok(Invocation::Export {
+            config_path,
+            options,
+        }) => {
+            let config = config::load(&config_path).unwrap();
+            if let Err(e) = export::export(&config, &options) {
+                log::error!("Export failed: {:#}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(e) => {
+            log::error!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let instance_guard = match InstanceGuard::acquire() {
+        Some(guard) => guard,
+        None => {
+            log::info!("Mon3tr-Widget is already running");
+            return;
+        }
+    };
+
+    crash::set_config_path(&config_path);
     let mut config = config::load(&config_path).unwrap();
+    logging::configure(&config.log);
 
     let event_loop = EventLoop::<UserEvent>::with_user_event();
     let owner_window = create_owner_window(&event_loop);
-    let window = create_window(&event_loop, &owner_window, &config);
+    let window = create_window(
+        &event_loop,
+        &owner_window,
+        config.window_size,
+        config.window_position,
+        &config,
+    );
+    #[cfg(windows)]
     let keyboard_hook = KeyboardHook::new(event_loop.create_proxy());
+    let session_watcher = SessionWatcher::new(&owner_window, event_loop.create_proxy());
+    let attention_watcher = (!config.notifications.is_empty())
+        .then(|| AttentionWatcher::new(&owner_window, event_loop.create_proxy()));
+    remote::pipe::start(event_loop.create_proxy());
+    if let Some(twitch) = &config.twitch {
+        remote::twitch::start(twitch.clone(), event_loop.create_proxy());
+    }
+    if let Some(mqtt) = &config.mqtt {
+        remote::mqtt::start(mqtt.clone(), event_loop.create_proxy());
+    }
+
+    let remote_status = SharedStatus::default();
+    let remote_events = Broadcaster::default();
+    if let Some(remote) = &config.remote {
+        if let Err(e) = remote::http::start(
+            remote.port,
+            remote_status.clone(),
+            remote.allowed_origins.clone(),
+            event_loop.create_proxy(),
+        ) {
+            log::error!("Failed to start remote control server: {}", e);
+        }
+        if let Some(port) = remote.websocket_port {
+            if let Err(e) = remote::websocket::start(
+                port,
+                remote_status.clone(),
+                remote_events.clone(),
+                remote.allowed_origins.clone(),
+                event_loop.create_proxy(),
+            ) {
+                log::error!("Failed to start WebSocket server: {}", e);
+            }
+        }
+        if let Some(port) = remote.vtube_studio_port {
+            let actions = config
+                .actions
+                .iter()
+                .filter_map(|action| action.name.clone())
+                .collect();
+            if let Err(e) = remote::vtube_studio::start(
+                port,
+                remote_status.clone(),
+                actions,
+                event_loop.create_proxy(),
+            ) {
+                log::error!("Failed to start VTube Studio API server: {}", e);
+            }
+        }
+    }
 
-    let (mut state, tray_receiver) = pollster::block_on(State::new(window, &config));
+    let (mut state, tray_receiver) =
+        pollster::block_on(State::new(window, &config, remote_status, remote_events));
+    state.open_extra_windows(&event_loop, &owner_window, &config);
+    if let Some(action) = &config.on_startup {
+        state.play_action(action, &config);
+    }
+
+    for url in &config.model_urls {
+        match download::pack_name(url) {
+            Some(name) if !download::data_path(&name).exists() => {
+                download::start(url.clone(), false, event_loop.create_proxy());
+            }
+            Some(_) => {}
+            None => log::warn!("Not a URL to a model pack: {}", url),
+        }
+    }
 
     let mut close_requested = false;
+    // Set once the exit action started playing
+    let mut exit_at: Option<Instant> = None;
+    let mut ui_windows = UiWindows::default();
+    let proxy = event_loop.create_proxy();
 
-    event_loop.run(move |event, _, control_flow| {
+    event_loop.run(move |event, target, control_flow| {
         let _ = owner_window;
+        #[cfg(windows)]
         let _ = keyboard_hook;
+        let _ = &session_watcher;
+        let _ = &attention_watcher;
+        let _ = &instance_guard;
 
         if let Ok(tray_event) = tray_receiver.try_recv() {
+            state.frame_pacer.wake();
             match tray_event {
+                TrayEvent::OpenSettings => {
+                    ui_windows.open_settings(target, &config);
+                }
+                TrayEvent::OpenAnimationPreview => {
+                    ui_windows.open_preview(target);
+                }
                 TrayEvent::ToggleWindowed => {
                     state.toggle_windowed();
                 }
                 TrayEvent::ToggleClickPassthrough => {
                     state.toggle_click_passthrough();
                 }
+                TrayEvent::ToggleDebugOverlay => {
+                    state.toggle_debug_overlay();
+                }
+                TrayEvent::ToggleStatsOverlay => {
+                    state.toggle_stats_overlay();
+                }
+                TrayEvent::ToggleFreezePose => {
+                    state.toggle_freeze_pose();
+                }
+                TrayEvent::ToggleRecording => {
+                    state.toggle_recording(&config);
+                }
+                TrayEvent::ToggleReplay => {
+                    state.toggle_replay(&config);
+                }
+                TrayEvent::ToggleChromaKey => {
+                    state.set_chroma_key(!state.chroma_key, &config);
+                }
+                TrayEvent::ToggleAlwaysOnBottom => {
+                    state.set_always_on_bottom(!state.always_on_bottom);
+                }
+                TrayEvent::FollowWindow(i) => {
+                    if let Some(window) = state.followable_windows.get(i).cloned() {
+                        state.follow(window);
+                    }
+                }
+                TrayEvent::StopFollowing => {
+                    state.follow_window = None;
+                    state.update_tray();
+                }
+                TrayEvent::RefreshWindowList => {
+                    state.followable_windows = window_ext::list_windows();
+                    state.update_tray();
+                }
+                TrayEvent::StartWork => {
+                    state.start_pomodoro(Phase::Work, &config);
+                }
+                TrayEvent::StartBreak => {
+                    state.start_pomodoro(Phase::Break, &config);
+                }
+                TrayEvent::StopTimer => {
+                    state.stop_pomodoro();
+                }
+                TrayEvent::ToggleAutostart => {
+                    if let Err(e) = autostart::set_enabled(!autostart::is_enabled(), &config_path) {
+                        log::error!("Failed to change autostart: {}", e);
+                    }
+                    state.update_tray();
+                }
+                TrayEvent::OpenLogFolder => {
+                    let dir = logging::log_dir();
+                    if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| open_folder(&dir)) {
+                        log::error!("Failed to open log folder: {}", e);
+                    }
+                }
                 TrayEvent::SetOpacity(opacity) => {
                     state.set_opacity(opacity);
                 }
+                TrayEvent::SetPlaybackSpeed(index) => {
+                    state.set_playback_speed(PLAYBACK_SPEEDS[index]);
+                }
+                TrayEvent::SetVolume(volume) => {
+                    state.set_volume(volume);
+                }
+                TrayEvent::ToggleMute => {
+                    state.toggle_mute();
+                }
                 TrayEvent::SetModel(index) => {
-                    state.load_data_file_index(index).unwrap();
+                    if let Err(e) = state.load_data_file_index(index, &config) {
+                        log::error!("Failed to load model: {}", e);
+                    }
+                }
+                TrayEvent::ReloadModelList => {
+                    if let Err(e) = state.rescan_data_files() {
+                        log::error!("Failed to rescan data directory: {}", e);
+                    }
+                }
+                TrayEvent::AddModelFromUrl => {
+                    ui_windows.open_add_model(target, proxy.clone());
+                }
+                TrayEvent::ToggleExtraWindow(index) => {
+                    state.toggle_extra_window(index);
+                }
+                TrayEvent::CloseExtraWindow(index) => {
+                    state.close_extra_window(index);
+                }
+                TrayEvent::ExportAnimation => {
+                    state.export_current_animation(&config);
+                }
+                TrayEvent::SaveScreenshot => {
+                    if let Some(path) =
+                        dialog::save_file(&state.window, "screenshot.png", &[("PNG", "*.png")])
+                    {
+                        state.take_screenshot(ScreenshotTarget::File(path));
+                    }
+                }
+                TrayEvent::CopyScreenshot => {
+                    state.take_screenshot(ScreenshotTarget::Clipboard);
                 }
                 TrayEvent::About => {}
                 TrayEvent::Exit => {
                     close_requested = true;
                 }
-                TrayEvent::TriggerAnimation(_) => {}
+                TrayEvent::TriggerAnimation(name) => {
+                    state.play_animation(&name, &config);
+                }
+                TrayEvent::ClearAnimationQueue => {
+                    state.back_to_idle(&config);
+                }
+                TrayEvent::SetAttachment(slot, attachment) => {
+                    state.attachment_overrides.insert(slot, attachment);
+                    state.update_tray();
+                }
+                TrayEvent::ResetAttachment(slot) => {
+                    state.reset_attachment(&slot);
+                }
             }
         }
 
@@ -622,6 +2499,7 @@ fn main() {
                 ref event,
                 window_id,
             } if window_id == state.window.id() => {
+                state.frame_pacer.wake();
                 if !state.input(event, &config) {
                     match event {
                         WindowEvent::CloseRequested => {
@@ -644,16 +2522,44 @@ fn main() {
                     }
                 }
             }
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if state.extra_window_mut(window_id).is_some() => {
+                if let Some(extra) = state.extra_window_mut(window_id) {
+                    extra.on_event(event);
+                }
+                if matches!(event, WindowEvent::CloseRequested) {
+                    state.update_tray();
+                }
+            }
+            Event::RedrawRequested(window_id) if state.extra_window_mut(window_id).is_some() => {
+                let opacity = state.vertex_opacity();
+                if let Some(extra) = state.extra_window_mut(window_id) {
+                    extra.redraw(opacity, &config);
+                }
+            }
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if ui_windows.owns(window_id) => {
+                ui_windows.on_event(window_id, event);
+            }
+            Event::RedrawRequested(window_id) if ui_windows.owns(window_id) => {
+                ui_windows.redraw(window_id, &mut state, &mut config, &config_path);
+            }
             Event::RedrawRequested(window_id) if window_id == state.window.id() => {
-                state.update();
+                state.update(&config);
 
-                match state.render() {
+                match state.render(&config) {
                     Ok(_) => {}
                     Err(e) => {
                         if let Some(surface_error) = e.downcast_ref::<wgpu::SurfaceError>() {
                             match surface_error {
                                 // Reconfigure the surface if lost
-                                wgpu::SurfaceError::Lost => state.resize(state.size),
+                                wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                                    state.resize(state.size)
+                                }
                                 // The system is out of memory, we should probably quit
                                 wgpu::SurfaceError::OutOfMemory => {
                                     *control_flow = ControlFlow::Exit
@@ -666,9 +2572,31 @@ fn main() {
                 }
             }
             Event::MainEventsCleared => {
-                state.request_redraw();
+                state.update_auto_pause(&config);
+                let next_frame = match state.auto_pause.reason() {
+                    // Nothing to draw, only check whether to resume
+                    Some(_) => Some(state.auto_pause.next_check()),
+                    None => state.next_frame(&config),
+                };
+                match next_frame {
+                    Some(at) if at > Instant::now() => {
+                        *control_flow = ControlFlow::WaitUntil(at);
+                    }
+                    _ => {
+                        *control_flow = ControlFlow::Poll;
+                        state.request_redraw();
+                    }
+                }
+                ui_windows.request_redraw();
+
+                if close_requested && exit_at.is_none() {
+                    exit_at = Some(Instant::now() + state.play_exit_action(&config));
+                }
+                if exit_at.map_or(false, |at| Instant::now() >= at) {
+                    if let Some(peek) = state.peek.as_mut() {
+                        peek.show(&state.window);
+                    }
 
-                if close_requested {
                     // Save window parameters
                     let logical_size = state
                         .window
@@ -680,24 +2608,62 @@ fn main() {
                         let pos = pos.cast();
                         config.window_position = (pos.x, pos.y);
                     }
+                    state.placement.save(&state.window, &mut config);
+                    config.follow_window = state.follow_window.clone();
+                    config.attachment_overrides = state.attachment_overrides.clone();
+                    config.frozen_pose = state.frozen_pose.clone();
+
+                    config.opacity = state.opacity;
+                    config.playback_speed = spine_state::playback_speed();
+                    config.volume = state.audio.volume();
+                    config.muted = state.audio.is_muted();
+                    config.chroma_key = state.chroma_key;
+                    config.always_on_bottom = state.always_on_bottom;
+
+                    for extra in &state.extra_windows {
+                        if let Ok(pos) = extra.window.outer_position() {
+                            let pos = pos.cast();
+                            config.extra_windows[extra.index].position = (pos.x, pos.y);
+                        }
+                    }
 
-                    // config.scale = state.scaling_state.model_scaling();
+                    config.scale = state.model_scale;
 
                     let _ = config::save(&config, &config_path);
 
                     *control_flow = ControlFlow::Exit;
                 }
             }
-            Event::UserEvent(e) => match e {
-                UserEvent::GlobalKey {
-                    state: ElementState::Pressed,
-                    vk_code,
-                    ..
-                } => {
-                    // dbg!(vk_code);
+            Event::UserEvent(e) => {
+                state.frame_pacer.wake();
+                match e {
+                    UserEvent::GlobalKey {
+                        state: key_state,
+                        vk_code,
+                        modifiers,
+                    } => {
+                        state.global_key(key_state, vk_code, modifiers, &config);
+                    }
+                    UserEvent::Command(command) => {
+                        state.execute(&command, &config);
+                    }
+                    UserEvent::PackDownloaded {
+                        name,
+                        temp_path,
+                        select,
+                    } => {
+                        if let Err(e) = state.install_pack(&name, &temp_path, select, &config) {
+                            state.report_add_model_error(&e);
+                        }
+                    }
+                    UserEvent::Session(event) => {
+                        state.auto_pause.on_session_event(event);
+                    }
+                    UserEvent::Attention(window) => {
+                        state.on_attention(&window, &config);
+                    }
                 }
-                _ => {}
-            },
+            }
             _ => {}
         }
     });
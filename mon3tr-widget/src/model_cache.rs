@@ -0,0 +1,41 @@
+use std::collections::VecDeque;
+
+use crate::spine_state::SpineState;
+
+/// Recently used models kept loaded, along with their textures on the GPU, so switching back
+/// to them doesn't read and upload everything again.
+pub struct ModelCache {
+    capacity: usize,
+    /// Most recently used first.
+    models: VecDeque<SpineState>,
+}
+
+impl ModelCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            models: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Take the model loaded from `pack` out of the cache.
+    pub fn take(&mut self, pack: &str) -> Option<SpineState> {
+        let index = self.models.iter().position(|spine| spine.pack == pack)?;
+        self.models.remove(index)
+    }
+
+    /// Keep a model that is no longer shown, dropping the least recently used one if full.
+    pub fn put(&mut self, mut spine: SpineState) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        spine.pause();
+        self.models.truncate(self.capacity - 1);
+        self.models.push_front(spine);
+    }
+
+    pub fn clear(&mut self) {
+        self.models.clear();
+    }
+}
@@ -0,0 +1,127 @@
+//! Balloon notifications from the notification area, for problems the user should know about
+//! without having to read the log.
+
+#[cfg(windows)]
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND, PWSTR},
+    UI::{
+        Shell::{
+            Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIIF_WARNING, NIM_ADD, NIM_DELETE, NIM_MODIFY,
+            NOTIFYICONDATAW,
+        },
+        WindowsAndMessaging::{LoadIconW, IDI_INFORMATION},
+    },
+};
+#[cfg(windows)]
+use winit::platform::windows::WindowExtWindows;
+use winit::window::Window;
+
+/// Balloons need an icon of their own, it's removed again once the balloon is gone.
+#[cfg(windows)]
+const ICON_ID: u32 = 0x4d33;
+#[cfg(windows)]
+const SHOWN_FOR: Duration = Duration::from_secs(10);
+
+#[cfg(windows)]
+pub struct Notifier {
+    hwnd: HWND,
+    shown_at: Option<Instant>,
+}
+
+#[cfg(windows)]
+impl Notifier {
+    pub fn new(window: &Window) -> Self {
+        Self {
+            hwnd: HWND(window.hwnd() as isize),
+            shown_at: None,
+        }
+    }
+
+    pub fn show(&mut self, title: &str, message: &str) {
+        let mut data = self.icon_data();
+        data.uFlags = NIF_ICON | NIF_INFO;
+        data.hIcon = unsafe {
+            LoadIconW(
+                HINSTANCE::default(),
+                PWSTR(IDI_INFORMATION as usize as *mut u16),
+            )
+        };
+        copy_wide(&mut data.szInfoTitle, title);
+        copy_wide(&mut data.szInfo, message);
+        data.dwInfoFlags = NIIF_WARNING;
+
+        let command = if self.shown_at.is_some() {
+            NIM_MODIFY
+        } else {
+            NIM_ADD
+        };
+        if unsafe { Shell_NotifyIconW(command, &data) }.as_bool() {
+            self.shown_at = Some(Instant::now());
+        } else {
+            log::warn!("Failed to show notification: {}", message);
+        }
+    }
+
+    /// Remove the icon once the balloon had time to be read.
+    pub fn update(&mut self) {
+        if matches!(self.shown_at, Some(at) if at.elapsed() >= SHOWN_FOR) {
+            self.remove();
+        }
+    }
+
+    fn remove(&mut self) {
+        if self.shown_at.take().is_some() {
+            unsafe { Shell_NotifyIconW(NIM_DELETE, &self.icon_data()) };
+        }
+    }
+
+    fn icon_data(&self) -> NOTIFYICONDATAW {
+        NOTIFYICONDATAW {
+            cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: self.hwnd,
+            uID: ICON_ID,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+/// Copy `s` into a fixed size, null terminated buffer, cutting it short if needed.
+#[cfg(windows)]
+fn copy_wide(buf: &mut [u16], s: &str) {
+    let len = buf.len() - 1;
+    for (dst, src) in buf[..len].iter_mut().zip(s.encode_utf16().chain(Some(0))) {
+        *dst = src;
+    }
+}
+
+/// Desktop notifications through `notify-send` where there are no balloons.
+#[cfg(not(windows))]
+pub struct Notifier;
+
+#[cfg(not(windows))]
+impl Notifier {
+    pub fn new(_window: &Window) -> Self {
+        Self
+    }
+
+    pub fn show(&mut self, title: &str, message: &str) {
+        let result = std::process::Command::new("notify-send")
+            .args(["--app-name=Mon3tr-Widget", title, message])
+            .spawn();
+        if let Err(e) = result {
+            log::warn!("Failed to show notification {:?}: {}", message, e);
+        }
+    }
+
+    pub fn update(&mut self) {}
+}
@@ -0,0 +1,123 @@
+//! Hiding the widget while nobody can see it or the system says it shouldn't be bothering
+//! anyone.
+
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use windows::Win32::{
+    System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS},
+    UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE,
+        QUNS_RUNNING_D3D_FULL_SCREEN,
+    },
+};
+
+use crate::{config::Config, session::SessionEvent};
+
+/// How often the system state is polled.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    /// The screen is locked, turned off or the system is going to sleep.
+    Away,
+    BatterySaver,
+    /// A fullscreen app or presentation is in the foreground.
+    Fullscreen,
+}
+
+pub struct AutoPause {
+    last_check: Option<Instant>,
+    reason: Option<PauseReason>,
+    locked: bool,
+    display_off: bool,
+    suspended: bool,
+}
+
+impl AutoPause {
+    pub fn new() -> Self {
+        Self {
+            last_check: None,
+            reason: None,
+            locked: false,
+            display_off: false,
+            suspended: false,
+        }
+    }
+
+    pub fn on_session_event(&mut self, event: SessionEvent) {
+        match event {
+            SessionEvent::Locked => self.locked = true,
+            SessionEvent::Unlocked => self.locked = false,
+            SessionEvent::DisplayOff => self.display_off = true,
+            SessionEvent::DisplayOn => self.display_off = false,
+            SessionEvent::Suspending => self.suspended = true,
+            SessionEvent::Resumed => self.suspended = false,
+        }
+        // Take effect right away
+        self.last_check = None;
+    }
+
+    pub fn reason(&self) -> Option<PauseReason> {
+        self.reason
+    }
+
+    /// When the state should be checked again.
+    pub fn next_check(&self) -> Instant {
+        self.last_check.unwrap_or_else(Instant::now) + CHECK_INTERVAL
+    }
+
+    /// Poll the system state if it's time to, returns whether the widget should now be
+    /// paused or resumed.
+    pub fn update(&mut self, config: &Config) -> bool {
+        let now = Instant::now();
+        if matches!(self.last_check, Some(last) if now - last < CHECK_INTERVAL) {
+            return false;
+        }
+        self.last_check = Some(now);
+
+        let reason = if self.locked || self.display_off || self.suspended {
+            Some(PauseReason::Away)
+        } else if config.pause_on_battery_saver && is_battery_saver_on() {
+            Some(PauseReason::BatterySaver)
+        } else if config.pause_on_fullscreen && is_fullscreen_app_running() {
+            Some(PauseReason::Fullscreen)
+        } else {
+            None
+        };
+
+        let changed = reason.is_some() != self.reason.is_some();
+        if changed {
+            match reason {
+                Some(reason) => log::info!("Pausing: {:?}", reason),
+                None => log::info!("Resuming"),
+            }
+        }
+        self.reason = reason;
+        changed
+    }
+}
+
+#[cfg(windows)]
+fn is_battery_saver_on() -> bool {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.as_bool() && status.SystemStatusFlag == 1
+}
+
+#[cfg(windows)]
+fn is_fullscreen_app_running() -> bool {
+    matches!(
+        unsafe { SHQueryUserNotificationState() },
+        Ok(QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE)
+    )
+}
+
+#[cfg(not(windows))]
+fn is_battery_saver_on() -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+fn is_fullscreen_app_running() -> bool {
+    false
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    monitor::MonitorHandle,
+    window::Window,
+};
+
+use crate::{
+    config::Config,
+    drag::clamp_to_area,
+    window_ext::{ScreenRect, SpineWidgetWindowExt},
+};
+
+/// How often monitors and the work area are polled for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Remembers where the window was on each monitor and puts it back there, or at least on
+/// screen, when monitors are plugged in or out or the work area changes.
+///
+/// Positions are kept relative to the monitor in logical pixels, so they survive the
+/// monitor moving around in the virtual screen or changing its DPI.
+pub struct Placement {
+    /// Window position by monitor name.
+    positions: HashMap<String, (f64, f64)>,
+    /// Monitor the window was last placed on by the user.
+    monitor: Option<String>,
+    layout: Vec<MonitorLayout>,
+    work_area: Option<ScreenRect>,
+    last_check: Option<Instant>,
+}
+
+#[derive(Debug, PartialEq)]
+struct MonitorLayout {
+    name: Option<String>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale_factor: f64,
+}
+
+impl Placement {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            positions: config.monitor_positions.clone(),
+            monitor: config.monitor.clone(),
+            layout: Vec::new(),
+            work_area: None,
+            last_check: None,
+        }
+    }
+
+    /// Move the window to where it was on its monitor at startup, if that monitor is connected.
+    pub fn restore(&mut self, window: &Window) {
+        self.layout = layout(window);
+        self.work_area = window.work_area();
+        self.move_home(window);
+        keep_on_screen(window);
+    }
+
+    /// Check for monitor or work area changes, at most once per [`POLL_INTERVAL`]. Otherwise
+    /// notes where the window is now. Skip this while the window is being moved.
+    pub fn update(&mut self, window: &Window) {
+        let now = Instant::now();
+        if let Some(last_check) = self.last_check {
+            if now - last_check < POLL_INTERVAL {
+                return;
+            }
+        }
+        self.last_check = Some(now);
+
+        let layout = layout(window);
+        let work_area = window.work_area();
+        if layout != self.layout {
+            log::info!("Monitors changed, re-anchoring the window");
+            self.layout = layout;
+            self.work_area = work_area;
+            self.move_home(window);
+            keep_on_screen(window);
+        } else if work_area != self.work_area {
+            self.work_area = work_area;
+            keep_on_screen(window);
+        } else {
+            self.remember(window);
+        }
+    }
+
+    /// Write the position on the current monitor to `config`.
+    pub fn save(&mut self, window: &Window, config: &mut Config) {
+        self.remember(window);
+        config.monitor_positions = self.positions.clone();
+        config.monitor = self.monitor.clone();
+    }
+
+    fn remember(&mut self, window: &Window) {
+        let (monitor, position) = match (window.current_monitor(), window.outer_position()) {
+            (Some(monitor), Ok(position)) => (monitor, position),
+            _ => return,
+        };
+        let name = match monitor.name() {
+            Some(name) => name,
+            None => return,
+        };
+
+        let origin = monitor.position();
+        let scale_factor = monitor.scale_factor();
+        let relative = (
+            (position.x - origin.x) as f64 / scale_factor,
+            (position.y - origin.y) as f64 / scale_factor,
+        );
+        self.positions.insert(name.clone(), relative);
+        self.monitor = Some(name);
+    }
+
+    fn move_home(&self, window: &Window) {
+        let name = match &self.monitor {
+            Some(name) => name,
+            None => return,
+        };
+        let (monitor, relative) = match (find_monitor(window, name), self.positions.get(name)) {
+            (Some(monitor), Some(relative)) => (monitor, relative),
+            _ => return,
+        };
+
+        let origin = monitor.position();
+        let scale_factor = monitor.scale_factor();
+        window.set_outer_position(PhysicalPosition::new(
+            origin.x as f64 + relative.0 * scale_factor,
+            origin.y as f64 + relative.1 * scale_factor,
+        ));
+    }
+}
+
+fn layout(window: &Window) -> Vec<MonitorLayout> {
+    window
+        .available_monitors()
+        .map(|monitor| MonitorLayout {
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect()
+}
+
+fn find_monitor(window: &Window, name: &str) -> Option<MonitorHandle> {
+    window
+        .available_monitors()
+        .find(|monitor| monitor.name().as_deref() == Some(name))
+}
+
+/// Pull the window into the work area of the nearest monitor.
+fn keep_on_screen(window: &Window) {
+    let (work_area, position) = match (window.work_area(), window.outer_position()) {
+        (Some(work_area), Ok(position)) => (work_area, position),
+        _ => return,
+    };
+    let size = window.outer_size();
+
+    let target = clamp_to_area(position.cast(), size.width, size.height, work_area);
+    if target != position.cast() {
+        window.set_outer_position(target);
+    }
+}
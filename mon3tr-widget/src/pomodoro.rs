@@ -0,0 +1,76 @@
+//! Work and break timer controlled from the tray menu.
+
+use std::time::{Duration, Instant};
+
+use crate::config::PomodoroConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+/// What happened when the timer ran out, see [`Pomodoro::update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroEvent {
+    Started(Phase),
+    /// The break is over and the timer stopped, only without `repeat`.
+    Finished,
+}
+
+pub struct Pomodoro {
+    /// Current phase and when it ends, `None` while stopped.
+    current: Option<(Phase, Instant)>,
+}
+
+impl Pomodoro {
+    pub fn new() -> Self {
+        Self { current: None }
+    }
+
+    pub fn start(&mut self, phase: Phase, config: &PomodoroConfig) {
+        let minutes = match phase {
+            Phase::Work => config.work_minutes,
+            Phase::Break => config.break_minutes,
+        };
+        let ends = Instant::now() + Duration::from_secs_f32(minutes.max(0.0) * 60.0);
+        self.current = Some((phase, ends));
+    }
+
+    pub fn stop(&mut self) {
+        self.current = None;
+    }
+
+    pub fn phase(&self) -> Option<Phase> {
+        self.current.map(|(phase, _)| phase)
+    }
+
+    /// Time left in the current phase.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.current
+            .map(|(_, ends)| ends.saturating_duration_since(Instant::now()))
+    }
+
+    /// Moves on to the next phase once the current one is over.
+    pub fn update(&mut self, config: &PomodoroConfig) -> Option<PomodoroEvent> {
+        let (phase, ends) = self.current?;
+        if Instant::now() < ends {
+            return None;
+        }
+
+        match phase {
+            Phase::Work => {
+                self.start(Phase::Break, config);
+                Some(PomodoroEvent::Started(Phase::Break))
+            }
+            Phase::Break if config.repeat => {
+                self.start(Phase::Work, config);
+                Some(PomodoroEvent::Started(Phase::Work))
+            }
+            Phase::Break => {
+                self.stop();
+                Some(PomodoroEvent::Finished)
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Triggered actions written to a file as they are played, and played back from it, so a
+//! sequence can be choreographed once and captured again and again.
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Action;
+
+/// One line of a recording.
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    /// Seconds since the recording started
+    time: f32,
+    action: Action,
+}
+
+/// Writes every recorded action to the file right away, so nothing is lost on a crash.
+pub struct Recorder {
+    file: BufWriter<File>,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Start recording to `path`, replacing what was recorded there before.
+    pub fn start(path: &str) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+        log::info!("Recording actions to {}", path);
+        Ok(Self {
+            file: BufWriter::new(file),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, action: &Action) {
+        let entry = Entry {
+            time: self.started.elapsed().as_secs_f32(),
+            action: action.clone(),
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        if let Err(e) = writeln!(self.file, "{}", line).and_then(|_| self.file.flush()) {
+            log::error!("Failed to record action: {}", e);
+        }
+    }
+}
+
+/// Plays the actions of a recording at the times they were recorded.
+pub struct Replay {
+    entries: VecDeque<Entry>,
+    started: Instant,
+}
+
+impl Replay {
+    /// Start replaying the recording at `path` from its beginning.
+    pub fn start(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+        let mut entries = VecDeque::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid recording at line {}", i + 1))?;
+            entries.push_back(entry);
+        }
+
+        log::info!("Replaying {} actions from {}", entries.len(), path);
+        Ok(Self {
+            entries,
+            started: Instant::now(),
+        })
+    }
+
+    /// Actions due by now, in the order they were recorded.
+    pub fn update(&mut self) -> Vec<Action> {
+        let now = self.started.elapsed().as_secs_f32();
+        let mut due = vec![];
+        while self
+            .entries
+            .front()
+            .map_or(false, |entry| entry.time <= now)
+        {
+            due.extend(self.entries.pop_front().map(|entry| entry.action));
+        }
+        due
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,91 @@
+use std::{io::Cursor, thread};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+use winit::event_loop::EventLoopProxy;
+
+use super::{is_allowed_origin, parse_command_path, SharedStatus};
+use crate::UserEvent;
+
+/// REST server listening on localhost.
+///
+/// - `GET /status`, `GET /animations`, `GET /models` query the widget state
+/// - `POST /action/{name}`, `POST /animation/{name}`, `POST /model/{index|next}`,
+///   `POST /opacity/{0-100}`, `POST /windowed`, `POST /click_passthrough` control it
+///
+/// Requests from web pages other than `allowed_origins` and local ones are refused.
+pub fn start(
+    port: u16,
+    status: SharedStatus,
+    allowed_origins: Vec<String>,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Result<()> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|e| anyhow!(e))?;
+    log::info!("Remote control listening on http://127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = handle(&request, &status, &allowed_origins, &proxy);
+            if let Err(e) = request.respond(response) {
+                log::warn!("Failed to respond to remote request: {}", e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn handle(
+    request: &Request,
+    status: &SharedStatus,
+    allowed_origins: &[String],
+    proxy: &EventLoopProxy<UserEvent>,
+) -> Response<Cursor<Vec<u8>>> {
+    let origin = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Origin"))
+        .map(|header| header.value.as_str());
+    if !is_allowed_origin(origin, allowed_origins) {
+        log::warn!(
+            "Refused remote request from {:?}",
+            origin.unwrap_or_default()
+        );
+        return error(403, "Origin not allowed");
+    }
+
+    let path = request.url().split('?').next().unwrap_or_default();
+
+    match request.method() {
+        Method::Get => {
+            let status = status.lock().unwrap();
+            match path.trim_end_matches('/') {
+                "/status" => json(&*status),
+                "/animations" => json(&status.animations),
+                "/models" => json(&status.models),
+                _ => error(404, "Not found"),
+            }
+        }
+        Method::Post => match parse_command_path(path) {
+            Some(command) => {
+                log::debug!("Remote command: {:?}", command);
+                if proxy.send_event(UserEvent::Command(command)).is_err() {
+                    return error(503, "Widget is shutting down");
+                }
+                Response::from_string("").with_status_code(204)
+            }
+            None => error(404, "Unknown command"),
+        },
+        _ => error(405, "Method not allowed"),
+    }
+}
+
+fn json<T: Serialize>(value: &T) -> Response<Cursor<Vec<u8>>> {
+    Response::from_data(serde_json::to_vec(value).unwrap())
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+}
+
+fn error(code: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(message).with_status_code(code)
+}
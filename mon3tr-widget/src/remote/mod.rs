@@ -0,0 +1,255 @@
+//! Control of the widget from other programs.
+//!
+//! Servers run on their own threads, they send [`Command`]s to the event loop as
+//! [`crate::UserEvent::Command`] and answer queries from the last published [`Status`].
+//! Changes are pushed to subscribed clients through the [`Broadcaster`].
+
+use std::sync::{mpsc::Sender, Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::config::Command;
+
+pub mod http;
+pub mod mqtt;
+pub mod pipe;
+pub mod twitch;
+pub mod vtube_studio;
+pub mod websocket;
+
+/// Snapshot of the widget state, published by the event loop whenever it changes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Status {
+    pub models: Vec<String>,
+    /// Index into `models` of the loaded model
+    pub model: Option<usize>,
+    pub animations: Vec<String>,
+    /// Opacity from 0 to 100
+    pub opacity: u8,
+    pub windowed: bool,
+    pub click_passthrough: bool,
+}
+
+pub type SharedStatus = Arc<Mutex<Status>>;
+
+/// Something that happened in the widget, pushed to subscribed clients.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteEvent {
+    Status(Status),
+    ModelSwitched {
+        index: usize,
+        name: String,
+    },
+    AnimationStarted {
+        track: usize,
+        animation: String,
+    },
+    AnimationCompleted {
+        track: usize,
+        animation: String,
+    },
+    /// User-defined event keyed in an animation
+    SpineEvent {
+        name: String,
+        animation: String,
+        int: i32,
+        float: f32,
+        string: Option<String>,
+    },
+    /// Answer to [`Command::Snapshot`], send it back as [`Command::ApplySnapshot`] to another
+    /// widget showing the same model to mirror it
+    Snapshot(spine::Snapshot),
+}
+
+/// Sends serialized [`RemoteEvent`]s to every subscribed client.
+#[derive(Clone, Default)]
+pub struct Broadcaster {
+    subscribers: Arc<Mutex<Vec<Sender<String>>>>,
+}
+
+impl Broadcaster {
+    pub fn subscribe(&self, sender: Sender<String>) {
+        self.subscribers.lock().unwrap().push(sender);
+    }
+
+    pub fn send(&self, event: &RemoteEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let message = serde_json::to_string(event).unwrap();
+        // Clients that went away have dropped their receiver
+        subscribers.retain(|sender| sender.send(message.clone()).is_ok());
+    }
+}
+
+/// Whether a client sending the `Origin` header `origin` may send commands. Browsers send the
+/// origin of the page with every cross-site request, other programs don't send any.
+pub fn is_allowed_origin(origin: Option<&str>, allowed: &[String]) -> bool {
+    let origin = match origin {
+        Some(origin) => origin.trim().trim_end_matches('/'),
+        None => return true,
+    };
+    if allowed
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    {
+        return true;
+    }
+
+    let host = match origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    {
+        Some(host) => host,
+        None => return false,
+    };
+    ["localhost", "127.0.0.1", "[::1]"].iter().any(|local| {
+        host.strip_prefix(local).map_or(false, |port| {
+            port.is_empty()
+                || port
+                    .strip_prefix(':')
+                    .map_or(false, |p| p.parse::<u16>().is_ok())
+        })
+    })
+}
+
+/// Decode `%XX` escapes in a URL path segment.
+pub fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parse a REST style path like `animation/Attack` into a command.
+pub fn parse_command_path(path: &str) -> Option<Command> {
+    let mut segments = path.trim_matches('/').splitn(2, '/');
+    let (kind, arg) = (segments.next()?, segments.next().map(percent_decode));
+
+    Some(match (kind, arg) {
+        ("action", Some(name)) => Command::Action(name),
+        ("animation", Some(name)) => Command::Animation(name),
+        ("model", Some(arg)) if arg == "next" => Command::NextModel,
+        ("model", Some(index)) => Command::SetModel(index.parse().ok()?),
+        ("opacity", Some(value)) => Command::SetOpacity(value.parse::<u8>().ok()?.min(100)),
+        ("speed", Some(value)) => Command::SetPlaybackSpeed(value.parse().ok()?),
+        ("windowed", None) => Command::ToggleWindowed,
+        ("click_passthrough", None) => Command::ToggleClickPassthrough,
+        ("snapshot", None) => Command::Snapshot,
+        ("recording", None) => Command::ToggleRecording,
+        ("replay", None) => Command::ToggleReplay,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decodes_escapes() {
+        assert_eq!(percent_decode("Attack%20B"), "Attack B");
+        assert_eq!(percent_decode("%E5%BE%85%E6%9C%BA"), "待机");
+        // Broken escapes are kept as they are
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn allows_local_origins() {
+        for origin in [
+            None,
+            Some("http://localhost"),
+            Some("http://localhost:8080/"),
+            Some("https://127.0.0.1:3000"),
+            Some("http://[::1]:5173"),
+        ] {
+            assert!(is_allowed_origin(origin, &[]), "{:?} rejected", origin);
+        }
+    }
+
+    #[test]
+    fn rejects_foreign_origins() {
+        for origin in [
+            "https://example.com",
+            "http://localhost.example.com",
+            "http://127.0.0.1.example.com:80",
+            "http://localhost:http",
+            "null",
+            "file://",
+        ] {
+            assert!(
+                !is_allowed_origin(Some(origin), &[]),
+                "{:?} allowed",
+                origin
+            );
+        }
+    }
+
+    #[test]
+    fn allows_configured_origins() {
+        let allowed = vec!["https://overlay.example.com/".to_owned()];
+        assert!(is_allowed_origin(
+            Some("https://overlay.example.com"),
+            &allowed
+        ));
+        assert!(!is_allowed_origin(Some("https://example.com"), &allowed));
+    }
+
+    #[test]
+    fn parses_command_paths() {
+        let parse = parse_command_path;
+        assert_eq!(parse("/action/Wave"), Some(Command::Action("Wave".into())));
+        assert_eq!(
+            parse("animation/Attack%20B/"),
+            Some(Command::Animation("Attack B".into()))
+        );
+        assert_eq!(parse("model/next"), Some(Command::NextModel));
+        assert_eq!(parse("model/2"), Some(Command::SetModel(2)));
+        assert_eq!(parse("opacity/150"), Some(Command::SetOpacity(100)));
+        assert_eq!(parse("speed/0.5"), Some(Command::SetPlaybackSpeed(0.5)));
+        assert_eq!(parse("windowed"), Some(Command::ToggleWindowed));
+        assert_eq!(
+            parse("/click_passthrough"),
+            Some(Command::ToggleClickPassthrough)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_or_malformed_paths() {
+        for path in [
+            "",
+            "/",
+            "dance",
+            "action",
+            "model/first",
+            "opacity/-1",
+            "windowed/x",
+        ] {
+            assert_eq!(parse_command_path(path), None, "{:?} parsed", path);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::{thread, time::Duration};
+
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{config::MqttConfig, UserEvent};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Subscribe to the configured topics and trigger commands on matching messages.
+pub fn start(config: MqttConfig, proxy: EventLoopProxy<UserEvent>) {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let (Some(username), Some(password)) = (&config.username, &config.password) {
+        options.set_credentials(username, password);
+    }
+
+    let (mut client, mut connection) = Client::new(options, 16);
+
+    thread::spawn(move || {
+        for notification in connection.iter() {
+            match notification {
+                // Subscriptions do not survive reconnects
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    log::info!("Connected to MQTT broker {}", config.host);
+                    for trigger in &config.triggers {
+                        if let Err(e) = client.subscribe(&trigger.topic, QoS::AtMostOnce) {
+                            log::error!("Failed to subscribe to {}: {}", trigger.topic, e);
+                        }
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    let payload = String::from_utf8_lossy(&publish.payload);
+                    let matching = config.triggers.iter().filter(|trigger| {
+                        topic_matches(&trigger.topic, &publish.topic)
+                            && trigger
+                                .payload
+                                .as_ref()
+                                .map_or(true, |expected| expected == payload.trim())
+                    });
+
+                    for trigger in matching {
+                        log::debug!("MQTT command from {}: {:?}", publish.topic, trigger.command);
+                        if proxy
+                            .send_event(UserEvent::Command(trigger.command.clone()))
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("MQTT connection error: {}", e);
+                    // The next iteration reconnects
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        }
+    });
+}
+
+/// Match a topic against a filter that may contain `+` and `#` wildcards.
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut filter_levels = filter.split('/');
+    let mut topic_levels = topic.split('/');
+
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(f), Some(t)) if f == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(windows)]
+use std::{
+    fs::{File, OpenOptions},
+    os::windows::io::FromRawHandle,
+};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    thread,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{GetLastError, ERROR_PIPE_CONNECTED},
+    Storage::FileSystem::PIPE_ACCESS_INBOUND,
+    System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS,
+        PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    },
+};
+use winit::event_loop::EventLoopProxy;
+
+use crate::{config::Command, UserEvent};
+
+#[cfg(windows)]
+const PIPE_NAME: &str = r"\\.\pipe\mon3tr-widget";
+
+/// Listen for commands from other instances, sent one JSON [`Command`] per line.
+pub fn start(proxy: EventLoopProxy<UserEvent>) {
+    thread::spawn(move || {
+        let listener = match Listener::bind() {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Failed to open command pipe: {}", e);
+                return;
+            }
+        };
+        loop {
+            let pipe = match listener.accept() {
+                Ok(pipe) => pipe,
+                Err(e) => {
+                    log::error!("Command pipe stopped: {}", e);
+                    return;
+                }
+            };
+
+            for line in BufReader::new(pipe).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                match serde_json::from_str::<Command>(&line) {
+                    Ok(command) => {
+                        log::debug!("Command from another instance: {:?}", command);
+                        if proxy.send_event(UserEvent::Command(command)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => log::warn!("Invalid command {:?}: {}", line, e),
+                }
+            }
+        }
+    });
+}
+
+/// Hands out one connection per client sending commands.
+struct Listener {
+    #[cfg(unix)]
+    listener: UnixListener,
+}
+
+#[cfg(windows)]
+impl Listener {
+    fn bind() -> Result<Self> {
+        Ok(Self {})
+    }
+
+    fn accept(&self) -> Result<impl Read> {
+        accept()
+    }
+}
+
+#[cfg(unix)]
+impl Listener {
+    fn bind() -> Result<Self> {
+        let path = socket_path();
+        // Left behind if the last instance did not exit cleanly
+        let _ = std::fs::remove_file(&path);
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+        })
+    }
+
+    fn accept(&self) -> Result<impl Read> {
+        Ok(self.listener.accept()?.0)
+    }
+}
+
+#[cfg(unix)]
+fn socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("mon3tr-widget.sock")
+}
+
+/// Create a pipe instance and wait for a client to connect to it.
+#[cfg(windows)]
+fn accept() -> Result<File> {
+    unsafe {
+        let handle = CreateNamedPipeW(
+            PIPE_NAME,
+            PIPE_ACCESS_INBOUND,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            0,
+            4096,
+            0,
+            std::ptr::null(),
+        )
+        .ok()?;
+
+        // Closing the file also closes the pipe instance
+        let pipe = File::from_raw_handle(handle.0 as _);
+        if !ConnectNamedPipe(handle, std::ptr::null_mut()).as_bool()
+            && GetLastError() != ERROR_PIPE_CONNECTED
+        {
+            return Err(windows::core::Error::from_win32().into());
+        }
+
+        Ok(pipe)
+    }
+}
+
+/// Send a command to the running instance.
+pub fn send(command: &Command) -> Result<()> {
+    #[cfg(windows)]
+    let pipe = OpenOptions::new().write(true).open(PIPE_NAME);
+    #[cfg(unix)]
+    let pipe = UnixStream::connect(socket_path());
+    let mut pipe = pipe.context("Mon3tr-Widget does not seem to be running")?;
+    writeln!(pipe, "{}", serde_json::to_string(command)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use winit::event_loop::EventLoopProxy;
+
+use crate::{
+    config::{Command, TwitchConfig},
+    UserEvent,
+};
+
+const SERVER: &str = "irc.chat.twitch.tv:6667";
+/// Read-only login that does not need a token.
+const ANONYMOUS_NICK: &str = "justinfan31415";
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Join the configured channel and trigger commands from chat messages and reward redemptions.
+pub fn start(config: TwitchConfig, proxy: EventLoopProxy<UserEvent>) {
+    thread::spawn(move || {
+        let mut chat = Chat {
+            config,
+            proxy,
+            last_triggered: HashMap::new(),
+        };
+
+        loop {
+            match chat.run() {
+                Ok(()) => return,
+                Err(e) => log::warn!("Twitch chat disconnected: {}", e),
+            }
+            thread::sleep(RECONNECT_DELAY);
+        }
+    });
+}
+
+struct Chat {
+    config: TwitchConfig,
+    proxy: EventLoopProxy<UserEvent>,
+    /// When each chat command or reward last triggered, for the cooldown.
+    last_triggered: HashMap<String, Instant>,
+}
+
+impl Chat {
+    /// Returns `Ok` once the widget is gone, errors on disconnect.
+    fn run(&mut self) -> Result<()> {
+        let mut stream = TcpStream::connect(SERVER)?;
+        let channel = self.config.channel.trim_start_matches('#').to_lowercase();
+
+        // Tags carry the reward ID of channel point redemptions
+        writeln!(stream, "CAP REQ :twitch.tv/tags\r")?;
+        match (&self.config.username, &self.config.token) {
+            (Some(username), Some(token)) => {
+                writeln!(stream, "PASS {}\r", token)?;
+                writeln!(stream, "NICK {}\r", username.to_lowercase())?;
+            }
+            _ => writeln!(stream, "NICK {}\r", ANONYMOUS_NICK)?,
+        }
+        writeln!(stream, "JOIN #{}\r", channel)?;
+        log::info!("Joined Twitch chat of {}", channel);
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                anyhow::bail!("Connection closed");
+            }
+            let line = line.trim_end();
+
+            if let Some(server) = line.strip_prefix("PING") {
+                writeln!(stream, "PONG{}\r", server)?;
+                continue;
+            }
+
+            if let Some(message) = Message::parse(line) {
+                if !self.handle(&message) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns `false` once the event loop is gone.
+    fn handle(&mut self, message: &Message) -> bool {
+        let (key, command) = if let Some(reward_id) = message.tags.get("custom-reward-id") {
+            match self.config.rewards.get(*reward_id) {
+                Some(command) => (reward_id.to_string(), command),
+                None => {
+                    log::info!("Unmapped channel point reward {} redeemed", reward_id);
+                    return true;
+                }
+            }
+        } else {
+            let word = message.text.split_whitespace().next().unwrap_or_default();
+            match self
+                .config
+                .commands
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(word))
+            {
+                Some((name, command)) => (name.to_lowercase(), command),
+                None => return true,
+            }
+        };
+
+        let now = Instant::now();
+        let cooldown = Duration::from_secs_f32(self.config.cooldown);
+        if let Some(last) = self.last_triggered.get(&key) {
+            if now - *last < cooldown {
+                return true;
+            }
+        }
+        self.last_triggered.insert(key, now);
+
+        log::debug!("Twitch command: {:?}", command);
+        self.proxy
+            .send_event(UserEvent::Command(command.clone()))
+            .is_ok()
+    }
+}
+
+/// A `PRIVMSG` sent to the channel.
+struct Message<'a> {
+    tags: HashMap<&'a str, &'a str>,
+    text: &'a str,
+}
+
+impl<'a> Message<'a> {
+    /// Parse e.g. `@tag=value;... :nick!nick@host PRIVMSG #channel :text`.
+    fn parse(line: &'a str) -> Option<Self> {
+        let (tags, rest) = match line.strip_prefix('@') {
+            Some(tagged) => tagged.split_once(' ')?,
+            None => ("", line),
+        };
+
+        let mut parts = rest.splitn(4, ' ');
+        let (_prefix, kind, _channel, text) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if kind != "PRIVMSG" {
+            return None;
+        }
+
+        Some(Self {
+            tags: tags
+                .split(';')
+                .filter_map(|tag| tag.split_once('='))
+                .collect(),
+            text: text.strip_prefix(':').unwrap_or(text),
+        })
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tungstenite::{Message, WebSocket};
+use winit::event_loop::EventLoopProxy;
+
+use super::{SharedStatus, Status};
+use crate::{config::Command, UserEvent};
+
+const API_NAME: &str = "VTubeStudioPublicAPI";
+const API_VERSION: &str = "1.0";
+
+/// `errorID`s of `APIError` responses, as numbered by VTube Studio.
+const ERROR_INTERNAL: u32 = 0;
+const ERROR_REQUEST_TYPE_UNKNOWN: u32 = 5;
+
+/// Hotkey IDs are the name of what they trigger with one of these prefixes.
+const ACTION_PREFIX: &str = "action:";
+const ANIMATION_PREFIX: &str = "animation:";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    #[serde(rename = "requestID", default)]
+    request_id: String,
+    message_type: String,
+    #[serde(default)]
+    data: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    api_name: &'static str,
+    api_version: &'static str,
+    timestamp: u128,
+    #[serde(rename = "requestID")]
+    request_id: String,
+    message_type: String,
+    data: Value,
+}
+
+/// Server speaking a subset of the VTube Studio public API, so stream tools that already
+/// trigger VTube Studio hotkeys can play actions and animations of the widget.
+///
+/// Models are the model packs and hotkeys are the named `actions` plus every animation of the
+/// loaded model. Only listening on localhost, so any plugin asking for a token gets one.
+pub fn start(
+    port: u16,
+    status: SharedStatus,
+    actions: Vec<String>,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    log::info!("VTube Studio API listening on ws://127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept VTube Studio API connection: {}", e);
+                    continue;
+                }
+            };
+
+            let status = status.clone();
+            let actions = actions.clone();
+            let proxy = proxy.clone();
+            thread::spawn(move || {
+                if let Err(e) = serve(stream, &status, &actions, &proxy) {
+                    log::debug!("VTube Studio API client disconnected: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+fn serve(
+    stream: TcpStream,
+    status: &SharedStatus,
+    actions: &[String],
+    proxy: &EventLoopProxy<UserEvent>,
+) -> Result<()> {
+    let mut socket: WebSocket<TcpStream> =
+        tungstenite::accept(stream).map_err(|e| anyhow::anyhow!("Handshake failed: {}", e))?;
+
+    loop {
+        let text = match socket.read_message()? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Ok(()),
+            _ => continue,
+        };
+
+        let request = match serde_json::from_str::<Request>(&text) {
+            Ok(request) => request,
+            Err(e) => {
+                log::warn!("Invalid VTube Studio API request {:?}: {}", text, e);
+                continue;
+            }
+        };
+
+        let status = status.lock().unwrap().clone();
+        let (message_type, data) = match handle(&request, &status, actions) {
+            Ok((response_type, data, command)) => {
+                if let Some(command) = command {
+                    log::debug!("VTube Studio API command: {:?}", command);
+                    if proxy.send_event(UserEvent::Command(command)).is_err() {
+                        return Ok(());
+                    }
+                }
+                (response_type.to_owned(), data)
+            }
+            Err((error_id, message)) => (
+                "APIError".to_owned(),
+                json!({ "errorID": error_id, "message": message }),
+            ),
+        };
+
+        let response = Response {
+            api_name: API_NAME,
+            api_version: API_VERSION,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis()),
+            request_id: request.request_id,
+            message_type,
+            data,
+        };
+        socket.write_message(Message::Text(serde_json::to_string(&response)?))?;
+    }
+}
+
+/// Answer a request, returns the response type, its data and what the widget should do.
+fn handle(
+    request: &Request,
+    status: &Status,
+    actions: &[String],
+) -> Result<(&'static str, Value, Option<Command>), (u32, String)> {
+    let data = &request.data;
+    let model = status.model.map(|index| model_json(status, index));
+
+    Ok(match request.message_type.as_str() {
+        "APIStateRequest" => (
+            "APIStateResponse",
+            json!({
+                "active": true,
+                "vTubeStudioVersion": env!("CARGO_PKG_VERSION"),
+                "currentSessionAuthenticated": true,
+            }),
+            None,
+        ),
+        "AuthenticationTokenRequest" => (
+            "AuthenticationTokenResponse",
+            json!({ "authenticationToken": "mon3tr-widget" }),
+            None,
+        ),
+        "AuthenticationRequest" => (
+            "AuthenticationResponse",
+            json!({ "authenticated": true, "reason": "Localhost only, no token needed" }),
+            None,
+        ),
+        "CurrentModelRequest" => {
+            let mut response = model.unwrap_or_else(|| json!({ "modelLoaded": false }));
+            response["modelLoaded"] = json!(status.model.is_some());
+            ("CurrentModelResponse", response, None)
+        }
+        "AvailableModelsRequest" => {
+            let models: Vec<Value> = (0..status.models.len())
+                .map(|index| model_json(status, index))
+                .collect();
+            (
+                "AvailableModelsResponse",
+                json!({ "numberOfModels": models.len(), "availableModels": models }),
+                None,
+            )
+        }
+        "ModelLoadRequest" => {
+            let id = data["modelID"].as_str().unwrap_or_default();
+            let index = id
+                .parse::<usize>()
+                .ok()
+                .filter(|&index| index < status.models.len())
+                .ok_or_else(|| (ERROR_INTERNAL, format!("No model with ID {:?}", id)))?;
+            (
+                "ModelLoadResponse",
+                json!({ "modelID": id }),
+                Some(Command::SetModel(index)),
+            )
+        }
+        "HotkeysInCurrentModelRequest" => {
+            let hotkeys: Vec<Value> = actions
+                .iter()
+                .map(|name| hotkey_json(name, ACTION_PREFIX))
+                .chain(
+                    status
+                        .animations
+                        .iter()
+                        .map(|name| hotkey_json(name, ANIMATION_PREFIX)),
+                )
+                .collect();
+            let mut response = model.unwrap_or_else(|| json!({}));
+            response["modelLoaded"] = json!(status.model.is_some());
+            response["availableHotkeys"] = json!(hotkeys);
+            ("HotkeysInCurrentModelResponse", response, None)
+        }
+        "HotkeyTriggerRequest" => {
+            let id = data["hotkeyID"].as_str().unwrap_or_default();
+            let command = if let Some(name) = id.strip_prefix(ACTION_PREFIX) {
+                Command::Action(name.to_owned())
+            } else if let Some(name) = id.strip_prefix(ANIMATION_PREFIX) {
+                Command::Animation(name.to_owned())
+            } else {
+                return Err((ERROR_INTERNAL, format!("No hotkey with ID {:?}", id)));
+            };
+            (
+                "HotkeyTriggerResponse",
+                json!({ "hotkeyID": id }),
+                Some(command),
+            )
+        }
+        other => {
+            return Err((
+                ERROR_REQUEST_TYPE_UNKNOWN,
+                format!("Unsupported request type {:?}", other),
+            ))
+        }
+    })
+}
+
+fn model_json(status: &Status, index: usize) -> Value {
+    let name = &status.models[index];
+    json!({
+        "modelLoaded": status.model == Some(index),
+        "modelName": name,
+        "modelID": index.to_string(),
+        "vtsModelName": name,
+        "vtsModelIconName": "",
+    })
+}
+
+fn hotkey_json(name: &str, prefix: &str) -> Value {
+    json!({
+        "name": name,
+        "type": "TriggerAnimation",
+        "description": name,
+        "file": "",
+        "hotkeyID": format!("{}{}", prefix, name),
+        "keyCombination": [],
+        "onScreenButtonID": -1,
+    })
+}
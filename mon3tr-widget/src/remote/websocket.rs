@@ -0,0 +1,128 @@
+use std::{
+    io::ErrorKind,
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    http::StatusCode,
+    Error, Message, WebSocket,
+};
+use winit::event_loop::EventLoopProxy;
+
+use super::{is_allowed_origin, Broadcaster, RemoteEvent, SharedStatus};
+use crate::{config::Command, UserEvent};
+
+/// How long a client thread waits for incoming messages before sending out queued events.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// WebSocket server listening on localhost.
+///
+/// Clients get the current [`super::Status`] on connect, then every [`RemoteEvent`] as JSON.
+/// They can send [`Command`]s as JSON, e.g. `{"action": "Attack"}` or `"next_model"`.
+/// Connections from web pages other than `allowed_origins` and local ones are refused.
+pub fn start(
+    port: u16,
+    status: SharedStatus,
+    events: Broadcaster,
+    allowed_origins: Vec<String>,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    log::info!("WebSocket listening on ws://127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept WebSocket connection: {}", e);
+                    continue;
+                }
+            };
+
+            let status = status.clone();
+            let events = events.clone();
+            let allowed_origins = allowed_origins.clone();
+            let proxy = proxy.clone();
+            thread::spawn(move || {
+                let socket = match handshake(stream, &allowed_origins) {
+                    Ok(socket) => socket,
+                    Err(e) => {
+                        log::warn!("Refused WebSocket connection: {}", e);
+                        return;
+                    }
+                };
+
+                let (sender, receiver) = mpsc::channel();
+                let hello = RemoteEvent::Status(status.lock().unwrap().clone());
+                let _ = sender.send(serde_json::to_string(&hello).unwrap());
+                events.subscribe(sender);
+
+                if let Err(e) = serve(socket, receiver, proxy) {
+                    log::debug!("WebSocket client disconnected: {}", e);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
+
+/// Accept the connection unless it comes from a web page that isn't allowed.
+fn handshake(stream: TcpStream, allowed_origins: &[String]) -> Result<WebSocket<TcpStream>> {
+    let check_origin = |request: &Request, response: Response| {
+        let origin = request
+            .headers()
+            .get("Origin")
+            .map(|origin| origin.to_str().unwrap_or_default());
+        if is_allowed_origin(origin, allowed_origins) {
+            return Ok(response);
+        }
+
+        let mut refused = ErrorResponse::new(Some(format!(
+            "Origin {} not allowed",
+            origin.unwrap_or_default()
+        )));
+        *refused.status_mut() = StatusCode::FORBIDDEN;
+        Err(refused)
+    };
+
+    tungstenite::accept_hdr(stream, check_origin)
+        .map_err(|e| anyhow::anyhow!("Handshake failed: {}", e))
+}
+
+fn serve(
+    mut socket: WebSocket<TcpStream>,
+    events: Receiver<String>,
+    proxy: EventLoopProxy<UserEvent>,
+) -> Result<()> {
+    socket.get_mut().set_read_timeout(Some(POLL_INTERVAL))?;
+
+    loop {
+        for event in events.try_iter() {
+            socket.write_message(Message::Text(event))?;
+        }
+
+        match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str::<Command>(&text) {
+                Ok(command) => {
+                    log::debug!("Remote command: {:?}", command);
+                    if proxy.send_event(UserEvent::Command(command)).is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => log::warn!("Invalid command {:?}: {}", text, e),
+            },
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
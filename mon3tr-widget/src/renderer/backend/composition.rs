@@ -0,0 +1,267 @@
+//! Presents frames through DirectComposition instead of the swapchain of a transparent window.
+//!
+//! The window is created without a redirection surface and shows a DirectComposition visual
+//! whose content is a flip model swapchain with premultiplied alpha, so DWM composes the model
+//! straight onto the desktop. wgpu can't render into a composition swapchain, so the model is
+//! drawn by a headless [`HardwareRenderer`] and every frame is read back and copied over, which
+//! costs a GPU round trip per frame. The stats overlay isn't drawn in this mode.
+
+use std::ptr;
+
+use anyhow::{anyhow, bail, Result};
+use image::RgbaImage;
+use windows::{
+    core::Interface,
+    Win32::{
+        Foundation::{HINSTANCE, HWND},
+        Graphics::{
+            Direct3D::D3D_DRIVER_TYPE_HARDWARE,
+            Direct3D11::{
+                D3D11CreateDevice, ID3D11DeviceContext, ID3D11Texture2D,
+                D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_SDK_VERSION,
+            },
+            DirectComposition::{
+                DCompositionCreateDevice, IDCompositionDevice, IDCompositionTarget,
+                IDCompositionVisual,
+            },
+            Dxgi::{
+                Common::{
+                    DXGI_ALPHA_MODE_PREMULTIPLIED, DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_UNKNOWN,
+                    DXGI_SAMPLE_DESC,
+                },
+                CreateDXGIFactory2, IDXGIDevice, IDXGIFactory2, IDXGISwapChain1,
+                DXGI_SCALING_STRETCH, DXGI_SWAP_CHAIN_DESC1, DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+                DXGI_USAGE_RENDER_TARGET_OUTPUT,
+            },
+        },
+    },
+};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    platform::windows::WindowExtWindows,
+    window::Window,
+};
+
+use super::hardware::HardwareRenderer;
+use crate::{
+    buffer::ScratchBuffers,
+    config::Config,
+    renderer::{Renderer, Texture},
+};
+
+pub struct CompositionRenderer {
+    inner: HardwareRenderer,
+    presenter: Presenter,
+}
+
+impl CompositionRenderer {
+    pub async fn new(window: &Window, config: &Config) -> Result<Self> {
+        let size = window.inner_size();
+        let mut inner = HardwareRenderer::headless(size, config).await?;
+        inner.resize(size, window.scale_factor());
+        let presenter = Presenter::new(window, size)?;
+
+        Ok(Self { inner, presenter })
+    }
+}
+
+impl Renderer for CompositionRenderer {
+    fn resize(&mut self, size: PhysicalSize<u32>, scale_factor: f64) {
+        self.inner.resize(size, scale_factor);
+        if let Err(e) = self.presenter.resize(size) {
+            log::error!("Failed to resize the composition swapchain: {}", e);
+        }
+    }
+
+    fn update(&mut self) {
+        self.inner.update();
+    }
+
+    fn register_texture(&mut self, texture: &Texture) {
+        self.inner.register_texture(texture);
+    }
+
+    fn render(&mut self, buffers: &mut ScratchBuffers) -> Result<()> {
+        let frame = self.inner.capture(buffers)?;
+        self.presenter.present(&frame)
+    }
+
+    fn capture(&mut self, buffers: &mut ScratchBuffers) -> Result<RgbaImage> {
+        self.inner.capture(buffers)
+    }
+
+    fn set_model_scale(&mut self, scale: f32) {
+        self.inner.set_model_scale(scale);
+    }
+
+    fn set_bottom_offset(&mut self, offset: f32) {
+        self.inner.set_bottom_offset(offset);
+    }
+
+    fn set_motion(&mut self, scale: f32, offset: f32) {
+        self.inner.set_motion(scale, offset);
+    }
+
+    fn set_global_tint(&mut self, tint: [f32; 4]) {
+        self.inner.set_global_tint(tint);
+    }
+
+    fn set_background(&mut self, color: Option<[f32; 3]>) {
+        self.inner.set_background(color);
+    }
+
+    fn set_stats_overlay(&mut self, enabled: bool) {
+        self.inner.set_stats_overlay(enabled);
+    }
+
+    fn set_bubble(&mut self, text: Option<String>) {
+        self.inner.set_bubble(text);
+    }
+
+    fn window_to_model(&self, position: PhysicalPosition<f64>) -> [f32; 2] {
+        self.inner.window_to_model(position)
+    }
+
+    fn is_device_lost(&self) -> bool {
+        self.inner.is_device_lost()
+    }
+
+    fn recover(&mut self, window: &Window, config: &Config) -> Result<()> {
+        // The swapchain lives on a device of its own, only the wgpu side needs recreating
+        self.inner.recover(window, config)
+    }
+}
+
+/// The Direct3D 11 swapchain shown by the window's DirectComposition visual.
+struct Presenter {
+    context: ID3D11DeviceContext,
+    swap_chain: IDXGISwapChain1,
+    width: u32,
+    height: u32,
+    /// The frame converted to BGRA.
+    pixels: Vec<u8>,
+    // The window shows the swapchain for as long as these are alive
+    _composition: IDCompositionDevice,
+    _target: IDCompositionTarget,
+    _visual: IDCompositionVisual,
+}
+
+impl Presenter {
+    fn new(window: &Window, size: PhysicalSize<u32>) -> Result<Self> {
+        let (width, height) = (size.width.max(1), size.height.max(1));
+
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(window.hwnd());
+
+            let mut device = None;
+            let mut context = None;
+            D3D11CreateDevice(
+                None,
+                D3D_DRIVER_TYPE_HARDWARE,
+                HINSTANCE::default(),
+                D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+                ptr::null(),
+                0,
+                D3D11_SDK_VERSION,
+                &mut device,
+                ptr::null_mut(),
+                &mut context,
+            )?;
+            let device = device.ok_or_else(|| anyhow!("No Direct3D 11 device was created"))?;
+            let context = context.ok_or_else(|| anyhow!("No Direct3D 11 context was created"))?;
+
+            let factory: IDXGIFactory2 = CreateDXGIFactory2(0)?;
+            let desc = DXGI_SWAP_CHAIN_DESC1 {
+                Width: width,
+                Height: height,
+                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                Stereo: false.into(),
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0,
+                },
+                BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+                BufferCount: 2,
+                Scaling: DXGI_SCALING_STRETCH,
+                SwapEffect: DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL,
+                AlphaMode: DXGI_ALPHA_MODE_PREMULTIPLIED,
+                Flags: 0,
+            };
+            let swap_chain = factory.CreateSwapChainForComposition(&device, &desc, None)?;
+
+            let dxgi_device: IDXGIDevice = device.cast()?;
+            let mut composition: Option<IDCompositionDevice> = None;
+            DCompositionCreateDevice(
+                &dxgi_device,
+                &IDCompositionDevice::IID,
+                &mut composition as *mut _ as *mut _,
+            )?;
+            let composition =
+                composition.ok_or_else(|| anyhow!("No DirectComposition device was created"))?;
+            let target = composition.CreateTargetForHwnd(hwnd, true)?;
+            let visual = composition.CreateVisual()?;
+            visual.SetContent(&swap_chain)?;
+            target.SetRoot(&visual)?;
+            composition.Commit()?;
+
+            Ok(Self {
+                context,
+                swap_chain,
+                width,
+                height,
+                pixels: Vec::new(),
+                _composition: composition,
+                _target: target,
+                _visual: visual,
+            })
+        }
+    }
+
+    fn resize(&mut self, size: PhysicalSize<u32>) -> Result<()> {
+        let (width, height) = (size.width.max(1), size.height.max(1));
+        if (width, height) == (self.width, self.height) {
+            return Ok(());
+        }
+
+        unsafe {
+            self.swap_chain
+                .ResizeBuffers(0, width, height, DXGI_FORMAT_UNKNOWN, 0)?;
+        }
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+
+    /// Copy `frame` into the back buffer and show it. The capture is already premultiplied, the
+    /// scene is drawn over transparent black.
+    fn present(&mut self, frame: &RgbaImage) -> Result<()> {
+        if frame.dimensions() != (self.width, self.height) {
+            bail!(
+                "Frame is {:?}, the swapchain {}x{}",
+                frame.dimensions(),
+                self.width,
+                self.height
+            );
+        }
+
+        self.pixels.clear();
+        self.pixels.extend_from_slice(frame.as_raw());
+        self.pixels
+            .chunks_exact_mut(4)
+            .for_each(|pixel| pixel.swap(0, 2));
+
+        unsafe {
+            let buffer: ID3D11Texture2D = self.swap_chain.GetBuffer(0)?;
+            self.context.UpdateSubresource(
+                &buffer,
+                0,
+                ptr::null(),
+                self.pixels.as_ptr() as *const _,
+                self.width * 4,
+                0,
+            );
+            self.swap_chain.Present(1, 0)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Result};
+use wgpu::util::{DeviceExt, StagingBelt};
+use wgpu_glyph::{
+    ab_glyph::FontArc, GlyphBrush, GlyphBrushBuilder, GlyphCruncher, Layout, Section, Text,
+};
+
+use crate::{config::BubbleStyle, vertex::LineVertex};
+
+/// Tried in order when no font is configured, the first ones cover Chinese as well.
+const FONT_PATHS: &[&str] = &[
+    r"C:\Windows\Fonts\msyh.ttc",
+    r"C:\Windows\Fonts\segoeui.ttf",
+    r"C:\Windows\Fonts\arial.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+];
+/// Space between the text and the edge of the bubble, in logical pixels.
+const PADDING: f32 = 8.0;
+/// Space between the top of the window and the bubble, in logical pixels.
+const MARGIN: f32 = 4.0;
+/// Height of the tail pointing down at the model, in logical pixels.
+const TAIL_SIZE: f32 = 8.0;
+
+/// Draws a short message in a bubble at the top of the window.
+pub struct BubbleOverlay {
+    brush: GlyphBrush<()>,
+    staging_belt: StagingBelt,
+    pipeline: wgpu::RenderPipeline,
+    style: BubbleStyle,
+}
+
+impl BubbleOverlay {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        style: &BubbleStyle,
+    ) -> Result<Self> {
+        let font = match &style.font {
+            Some(path) => std::fs::read(path)?,
+            None => FONT_PATHS
+                .iter()
+                .find_map(|path| std::fs::read(path).ok())
+                .ok_or_else(|| anyhow!("No font found for speech bubbles"))?,
+        };
+        let font = FontArc::try_from_vec(font)?;
+
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Bubble Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("bubble.wgsl").into()),
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bubble Pipeline Layout"),
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Bubble Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "main_v",
+                buffers: &[LineVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "main_f",
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Ok(Self {
+            brush: GlyphBrushBuilder::using_font(font).build(device, format),
+            staging_belt: StagingBelt::new(1024),
+            pipeline,
+            style: style.clone(),
+        })
+    }
+
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        scale_factor: f32,
+        text: &str,
+    ) -> Result<()> {
+        let style = &self.style;
+        let padding = PADDING * scale_factor;
+        let max_text_width = (style.max_width * scale_factor - 2.0 * padding)
+            .min(width as f32 - 2.0 * padding)
+            .max(1.0);
+
+        let mut section = Section {
+            screen_position: (0.0, 0.0),
+            bounds: (max_text_width, f32::INFINITY),
+            layout: Layout::default_wrap(),
+            text: vec![Text::new(text)
+                .with_color(style.text_color)
+                .with_scale(style.font_size * scale_factor)],
+        };
+        let bounds = match self.brush.glyph_bounds(&section) {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+
+        // Centered at the top, so it reads as coming from the model below
+        let box_width = bounds.width() + 2.0 * padding;
+        let box_height = bounds.height() + 2.0 * padding;
+        let left = (width as f32 - box_width) / 2.0 + style.offset.0 * scale_factor;
+        let top = MARGIN * scale_factor + style.offset.1 * scale_factor;
+        let (right, bottom) = (left + box_width, top + box_height);
+        let center = left + box_width / 2.0;
+        let tail = TAIL_SIZE * scale_factor;
+
+        let to_ndc = |x: f32, y: f32| LineVertex {
+            position: [2.0 * x / width as f32 - 1.0, 1.0 - 2.0 * y / height as f32],
+            color: style.background,
+        };
+        let vertices = [
+            to_ndc(left, top),
+            to_ndc(left, bottom),
+            to_ndc(right, bottom),
+            to_ndc(left, top),
+            to_ndc(right, bottom),
+            to_ndc(right, top),
+            to_ndc(center - tail, bottom),
+            to_ndc(center, bottom + tail),
+            to_ndc(center + tail, bottom),
+        ];
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bubble Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        section.screen_position = (left + padding - bounds.min.x, top + padding - bounds.min.y);
+        self.brush.queue(section);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Bubble Encoder"),
+        });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Bubble Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.draw(0..vertices.len() as u32, 0..1);
+        }
+        self.brush
+            .draw_queued(
+                device,
+                &mut self.staging_belt,
+                &mut encoder,
+                view,
+                width,
+                height,
+            )
+            .map_err(|e| anyhow!(e))?;
+        self.staging_belt.finish();
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let recall = self.staging_belt.recall();
+        device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(recall);
+
+        Ok(())
+    }
+}
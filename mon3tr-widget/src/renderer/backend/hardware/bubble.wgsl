@@ -0,0 +1,26 @@
+// Speech bubble background, positions are already in NDC
+
+struct VertexInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn main_v(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    out.color = model.color;
+    return out;
+}
+
+[[stage(fragment)]]
+fn main_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return in.color;
+}
@@ -0,0 +1,42 @@
+// Debug overlay, same transform as the model in shader.wgsl
+
+struct ScalingUniform {
+    window_width: f32;
+    window_height: f32;
+    scale: f32;
+    bottom_offset: f32;
+    tint: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> scaling: ScalingUniform;
+
+struct VertexInput {
+    [[location(0)]] position: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn main_v(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+
+    var x: f32 = 2.0 * (model.position.x + 0.5) / scaling.window_width;
+    var y: f32 = 2.0 * (model.position.y + 0.5) / scaling.window_height;
+    var bottom_offset: f32 = 2.0 * (scaling.bottom_offset + 0.5) / scaling.window_height;
+
+    out.clip_position = vec4<f32>(x * scaling.scale, y * scaling.scale - 1.0 + bottom_offset, 0.0, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn main_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+   return in.color;
+}
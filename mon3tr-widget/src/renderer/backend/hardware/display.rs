@@ -1,31 +1,125 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::config::{GpuConfig, GraphicsBackend, PowerPreference};
+
 pub struct Display {
-    pub surface: wgpu::Surface,
+    /// `None` when rendering offscreen only.
+    pub surface: Option<wgpu::Surface>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
+    /// Set once the device is lost, everything has to be recreated on a new one then.
+    lost: Arc<AtomicBool>,
 }
 
 impl Display {
     pub async fn new(window: &winit::window::Window) -> Self {
+        Self::with_gpu(window, &GpuConfig::default()).await
+    }
+
+    pub async fn with_gpu(window: &winit::window::Window, gpu: &GpuConfig) -> Self {
         let size = window.inner_size();
 
         // The instance is a handle to our GPU
         // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
-        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let instance = wgpu::Instance::new(backends(gpu));
         let surface = unsafe { instance.create_surface(window) };
-        let adapter = instance
+        let adapter = Self::request_adapter(&instance, gpu, Some(&surface)).await;
+        let (device, queue, lost) = Self::request_device(&adapter).await;
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface.get_preferred_format(&adapter).unwrap(),
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        surface.configure(&device, &surface_config);
+
+        Self {
+            surface: Some(surface),
+            device,
+            queue,
+            config: surface_config,
+            lost,
+        }
+    }
+
+    /// Set up the GPU without a window, for offscreen rendering of the given size.
+    pub async fn headless(width: u32, height: u32, gpu: &GpuConfig) -> Self {
+        let instance = wgpu::Instance::new(backends(gpu));
+        let adapter = Self::request_adapter(&instance, gpu, None).await;
+        let (device, queue, lost) = Self::request_device(&adapter).await;
+
+        // Never used to configure a surface, only describes the offscreen target
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+
+        Self {
+            surface: None,
+            device,
+            queue,
+            config,
+            lost,
+        }
+    }
+
+    async fn request_adapter(
+        instance: &wgpu::Instance,
+        gpu: &GpuConfig,
+        surface: Option<&wgpu::Surface>,
+    ) -> wgpu::Adapter {
+        if let Some(name) = &gpu.adapter {
+            let pattern = name.to_lowercase();
+            let adapter = instance.enumerate_adapters(backends(gpu)).find(|adapter| {
+                adapter.get_info().name.to_lowercase().contains(&pattern)
+                    && surface.map_or(true, |surface| adapter.is_surface_supported(surface))
+            });
+            match adapter {
+                Some(adapter) => return adapter,
+                None => log::warn!("No usable adapter matches {:?}, using the default", name),
+            }
+        }
+
+        instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
+                power_preference: match gpu.power_preference {
+                    Some(PowerPreference::HighPerformance) => {
+                        wgpu::PowerPreference::HighPerformance
+                    }
+                    Some(PowerPreference::LowPower) | None => wgpu::PowerPreference::LowPower,
+                },
+                compatible_surface: surface,
                 force_fallback_adapter: false,
             })
             .await
-            .unwrap();
+            .unwrap()
+    }
+
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+    ) -> (wgpu::Device, wgpu::Queue, Arc<AtomicBool>) {
+        let info = adapter.get_info();
+        log::info!("Using {} ({:?})", info.name, info.backend);
+        crate::crash::add_adapter(format!("{} ({:?})", info.name, info.backend));
+
+        // Compressed textures are used when available, see `CompressedImage`
+        let features = adapter.features()
+            & (wgpu::Features::TEXTURE_COMPRESSION_BC
+                | wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR);
 
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
+                    features,
                     limits: wgpu::Limits::default(),
                     label: None,
                 },
@@ -34,26 +128,57 @@ impl Display {
             .await
             .unwrap();
 
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_preferred_format(&adapter).unwrap(),
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
-        };
-        surface.configure(&device, &surface_config);
+        // wgpu panics on errors without a handler, which would take down the whole widget
+        // when the driver resets
+        let lost = Arc::new(AtomicBool::new(false));
+        let lost_flag = lost.clone();
+        device.on_uncaptured_error(move |error| {
+            log::error!("wgpu error: {}", error);
+            // Operations on a lost device fail with "Parent device is lost"
+            if error.to_string().to_lowercase().contains("lost") {
+                lost_flag.store(true, Ordering::Relaxed);
+            }
+        });
 
-        Self {
-            surface,
-            device,
-            queue,
-            config: surface_config,
+        (device, queue, lost)
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Relaxed)
+    }
+
+    /// Switch the surface between the sRGB and the plain variant of its format, so blending
+    /// happens in linear or in gamma space.
+    pub fn set_srgb(&mut self, srgb: bool) {
+        use wgpu::TextureFormat::*;
+
+        self.config.format = match (self.config.format, srgb) {
+            (Bgra8UnormSrgb, false) => Bgra8Unorm,
+            (Bgra8Unorm, true) => Bgra8UnormSrgb,
+            (Rgba8UnormSrgb, false) => Rgba8Unorm,
+            (Rgba8Unorm, true) => Rgba8UnormSrgb,
+            (format, _) => format,
+        };
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
         }
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
         self.config.width = width;
         self.config.height = height;
-        self.surface.configure(&self.device, &self.config);
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+    }
+}
+
+fn backends(gpu: &GpuConfig) -> wgpu::Backends {
+    match gpu.backend {
+        Some(GraphicsBackend::Vulkan) => wgpu::Backends::VULKAN,
+        Some(GraphicsBackend::Dx12) => wgpu::Backends::DX12,
+        Some(GraphicsBackend::Dx11) => wgpu::Backends::DX11,
+        Some(GraphicsBackend::Gl) => wgpu::Backends::GL,
+        None => wgpu::Backends::all(),
     }
 }
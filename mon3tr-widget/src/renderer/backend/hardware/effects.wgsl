@@ -0,0 +1,44 @@
+// Built-in post processing effects, appended to post.wgsl
+
+// Largest alpha within `radius` pixels, sampled on two rings of 8 directions
+fn max_alpha_around(uv: vec2<f32>, radius: f32) -> f32 {
+    var alpha: f32 = 0.0;
+    for (var i: i32 = 0; i < 8; i = i + 1) {
+        let angle = f32(i) * 0.785398;
+        let dir = vec2<f32>(cos(angle), sin(angle)) * post.texel_size;
+        alpha = max(alpha, scene(uv + dir * radius).a);
+        alpha = max(alpha, scene(uv + dir * radius * 0.5).a);
+    }
+    return alpha;
+}
+
+// Average alpha within `radius` pixels
+fn blurred_alpha(uv: vec2<f32>, radius: f32) -> f32 {
+    var alpha: f32 = 0.0;
+    for (var x: i32 = -2; x <= 2; x = x + 1) {
+        for (var y: i32 = -2; y <= 2; y = y + 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * 0.5 * radius * post.texel_size;
+            alpha = alpha + scene(uv + offset).a;
+        }
+    }
+    return alpha / 25.0;
+}
+
+[[stage(fragment)]]
+fn outline_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let alpha = max_alpha_around(in.uv, post.params.x);
+    return behind(scene(in.uv), vec4<f32>(post.color.rgb, post.color.a * alpha));
+}
+
+[[stage(fragment)]]
+fn drop_shadow_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let uv = in.uv - post.params.yz * post.texel_size;
+    let alpha = blurred_alpha(uv, post.params.w);
+    return behind(scene(in.uv), vec4<f32>(post.color.rgb, post.color.a * alpha));
+}
+
+[[stage(fragment)]]
+fn glow_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let alpha = max(blurred_alpha(in.uv, post.params.x), blurred_alpha(in.uv, post.params.x * 2.0) * 0.5);
+    return behind(scene(in.uv), vec4<f32>(post.color.rgb, post.color.a * alpha));
+}
@@ -1,22 +1,35 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, num::NonZeroU32, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use image::RgbaImage;
 use wgpu::IndexFormat;
-use winit::window::Window;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
+};
 
-mod display;
+mod bubble;
+pub mod display;
+mod post;
 mod scaling;
+mod stats;
 mod texture;
 
 pub use texture::HardwareTexture;
 
 use crate::{
     buffer::ScratchBuffers,
-    config::Config,
-    renderer::{texture::TextureID, Renderer},
-    vertex::Vertex,
+    config::{BubbleStyle, ColorSpace, Config},
+    renderer::{
+        texture::{TextureConfig, TextureData, TextureID},
+        Renderer,
+    },
+    vertex::{LineVertex, Vertex},
 };
 
+/// Size of the vertex buffer for the debug overlay, lines beyond it are dropped.
+const LINE_BUFFER_SIZE: wgpu::BufferAddress = 1024 * 256;
+
 pub struct HardwareRenderer {
     display: display::Display,
     scaling: scaling::ScalingState,
@@ -25,15 +38,152 @@ pub struct HardwareRenderer {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
 
+    /// Draws the debug overlay, see [`ScratchBuffers::fill_debug`].
+    line_pipeline: wgpu::RenderPipeline,
+    line_buffer: wgpu::Buffer,
+
     texture_bind_group_layout: wgpu::BindGroupLayout,
     textures: HashMap<TextureID, HardwareTexture>,
+
+    /// Offscreen target for [`Renderer::capture`], recreated when the size changes.
+    capture_target: Option<CaptureTarget>,
+    post_process: Option<post::PostProcess>,
+    /// What the window is cleared to, transparent unless a background color is set.
+    background: wgpu::Color,
+
+    stats: stats::FrameStats,
+    /// Created the first time the overlay is shown.
+    stats_overlay: Option<stats::StatsOverlay>,
+    show_stats: bool,
+
+    /// Created the first time a bubble is shown.
+    bubble: Option<bubble::BubbleOverlay>,
+    bubble_text: Option<String>,
+    bubble_style: BubbleStyle,
+
+    msaa_samples: u32,
+    /// Multisampled color attachment resolved into the target, recreated when the size changes.
+    msaa_target: Option<MsaaTarget>,
+    generate_mipmaps: bool,
+    /// Format of model textures, sRGB when working in linear color space.
+    texture_format: wgpu::TextureFormat,
+}
+
+struct MsaaTarget {
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl MsaaTarget {
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            width,
+            height,
+        }
+    }
+}
+
+struct CaptureTarget {
+    texture: wgpu::Texture,
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    /// Row stride of `buffer`, padded to the alignment required for copies.
+    padded_bytes_per_row: u32,
+}
+
+impl CaptureTarget {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (width * 4 + align - 1) / align * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            texture,
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+        }
+    }
 }
 
 impl HardwareRenderer {
     pub async fn new(window: &Window, config: &Config) -> Result<Self> {
-        let display = display::Display::new(window).await;
+        let display = display::Display::with_gpu(window, &config.gpu).await;
+        Self::with_display(display, window.scale_factor(), config)
+    }
+
+    /// Create a renderer without a window, which can only [`Renderer::capture`].
+    pub async fn headless(size: PhysicalSize<u32>, config: &Config) -> Result<Self> {
+        let display = display::Display::headless(size.width, size.height, &config.gpu).await;
+        Self::with_display(display, 1.0, config)
+    }
+
+    fn with_display(
+        mut display: display::Display,
+        scale_factor: f64,
+        config: &Config,
+    ) -> Result<Self> {
+        let linear = config.color_space == ColorSpace::Linear;
+        display.set_srgb(linear);
         let device = &display.device;
 
+        let features = device.features();
+        crate::renderer::texture::set_compression_support(
+            features.contains(wgpu::Features::TEXTURE_COMPRESSION_BC),
+            features.contains(wgpu::Features::TEXTURE_COMPRESSION_ASTC_LDR),
+        );
+
+        let msaa_samples = match config.msaa_samples {
+            samples @ (1 | 2 | 4 | 8) => samples,
+            samples => {
+                log::warn!("Unsupported MSAA sample count {}, disabling MSAA", samples);
+                1
+            }
+        };
+
         let texture_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[
@@ -62,8 +212,12 @@ impl HardwareRenderer {
             source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
         });
 
-        let (scaling, scaling_bind_group_layout) =
-            scaling::ScalingState::new(&window, device, config);
+        let (scaling, scaling_bind_group_layout) = scaling::ScalingState::new(
+            PhysicalSize::new(display.config.width, display.config.height),
+            scale_factor,
+            device,
+            config,
+        );
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -82,7 +236,7 @@ impl HardwareRenderer {
             },
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "main_f",
+                entry_point: if linear { "main_f_linear" } else { "main_f" },
                 targets: &[wgpu::ColorTargetState {
                     format: display.config.format,
                     blend: Some(wgpu::BlendState::ALPHA_BLENDING),
@@ -102,13 +256,20 @@ impl HardwareRenderer {
             },
             depth_stencil: None, // No depth/stencil buffer.
             multisample: wgpu::MultisampleState {
-                count: 1,                         // 2.
+                count: msaa_samples,
                 mask: !0,                         // All of them.
-                alpha_to_coverage_enabled: false, // No anti-aliasing for now.
+                alpha_to_coverage_enabled: false, // Blending handles the edges.
             },
             multiview: None,
         });
 
+        let line_pipeline = Self::create_line_pipeline(
+            device,
+            &scaling_bind_group_layout,
+            display.config.format,
+            msaa_samples,
+        );
+
         let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Vertex Buffer"),
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
@@ -123,53 +284,191 @@ impl HardwareRenderer {
             mapped_at_creation: false,
         });
 
-        Ok(Self {
+        let post_process = config.post_effect.as_ref().and_then(|effect| {
+            match post::PostProcess::new(device, display.config.format, effect) {
+                Ok(post_process) => Some(post_process),
+                Err(e) => {
+                    log::error!("Failed to set up post processing: {}", e);
+                    None
+                }
+            }
+        });
+
+        let line_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Line Buffer"),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            size: LINE_BUFFER_SIZE,
+            mapped_at_creation: false,
+        });
+
+        let mut renderer = Self {
             display,
             scaling,
             render_pipeline,
             vertex_buffer,
             index_buffer,
+            line_pipeline,
+            line_buffer,
             texture_bind_group_layout,
             textures: HashMap::new(),
-        })
-    }
-}
+            capture_target: None,
+            post_process,
+            background: wgpu::Color::TRANSPARENT,
+            stats: stats::FrameStats::new(config.log_frame_stats),
+            stats_overlay: None,
+            show_stats: false,
+            bubble: None,
+            bubble_text: None,
+            bubble_style: config.bubble.clone(),
+            msaa_samples,
+            msaa_target: None,
+            generate_mipmaps: config.generate_mipmaps,
+            texture_format: if linear {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            },
+        };
+        renderer.set_stats_overlay(config.stats_overlay);
 
-impl Renderer for HardwareRenderer {
-    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>, scale_factor: f64) {
-        self.display.resize(size.width, size.height);
-        self.scaling.resize(size, scale_factor);
+        Ok(renderer)
     }
 
-    fn update(&mut self) {
-        self.scaling.write_to_gpu(&self.display.queue);
-    }
+    fn create_line_pipeline(
+        device: &wgpu::Device,
+        scaling_bind_group_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Debug Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("debug.wgsl").into()),
+        });
 
-    fn register_texture(&mut self, texture: &crate::renderer::Texture) {
-        let id = texture.id();
-        if self.textures.contains_key(&id) {
-            return;
-        }
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Line Pipeline Layout"),
+            bind_group_layouts: &[scaling_bind_group_layout],
+            push_constant_ranges: &[],
+        });
 
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Line Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "main_v",
+                buffers: &[LineVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "main_f",
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+        })
+    }
+
+    fn upload_texture(&mut self, id: TextureID, data: Arc<TextureData>, config: &TextureConfig) {
         let hw_texture = HardwareTexture::from_image(
             &self.display.device,
             &self.display.queue,
             &self.texture_bind_group_layout,
-            texture.image(),
-            texture.config(),
+            data,
+            config,
+            self.texture_format,
+            self.generate_mipmaps,
             None,
         );
 
+        self.stats.texture_bytes += hw_texture.byte_size;
         self.textures.insert(id, hw_texture);
     }
 
-    fn render(&mut self, buffers: &mut ScratchBuffers) -> Result<()> {
-        let queue = &self.display.queue;
+    /// Draw the buffers onto `view`, clearing it first, through the post processing effect if
+    /// there is one. The speech bubble goes on top.
+    fn draw(&mut self, view: &wgpu::TextureView, buffers: &mut ScratchBuffers) {
+        self.draw_model(view, buffers);
+
+        if let (Some(bubble), Some(text)) = (self.bubble.as_mut(), &self.bubble_text) {
+            let size = (self.display.config.width, self.display.config.height);
+            if let Err(e) = bubble.draw(
+                &self.display.device,
+                &self.display.queue,
+                view,
+                size,
+                self.scaling.scale_factor() as f32,
+                text,
+            ) {
+                log::error!("Failed to draw speech bubble: {}", e);
+            }
+        }
+    }
 
-        let output = self.display.surface.get_current_texture()?;
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    fn draw_model(&mut self, view: &wgpu::TextureView, buffers: &mut ScratchBuffers) {
+        let mut post_process = match self.post_process.take() {
+            Some(post_process) => post_process,
+            None => return self.draw_scene(view, buffers, self.background),
+        };
+
+        let scene = post_process.scene_view(
+            &self.display.device,
+            self.display.config.format,
+            self.display.config.width,
+            self.display.config.height,
+        );
+        self.draw_scene(scene, buffers, wgpu::Color::TRANSPARENT);
+        post_process.apply(
+            &self.display.device,
+            &self.display.queue,
+            view,
+            self.background,
+        );
+
+        self.post_process = Some(post_process);
+    }
+
+    /// Draw the buffers onto `view`, clearing it to `clear_color` first.
+    fn draw_scene(
+        &mut self,
+        view: &wgpu::TextureView,
+        buffers: &mut ScratchBuffers,
+        clear_color: wgpu::Color,
+    ) {
+        if self.msaa_samples > 1 {
+            let (width, height) = (self.display.config.width, self.display.config.height);
+            match &self.msaa_target {
+                Some(target) if target.width == width && target.height == height => {}
+                _ => {
+                    self.msaa_target = Some(MsaaTarget::new(
+                        &self.display.device,
+                        self.display.config.format,
+                        width,
+                        height,
+                        self.msaa_samples,
+                    ))
+                }
+            }
+        }
+        // With MSAA, draw into the multisampled texture and resolve into `view`
+        let (view, resolve_target) = match &self.msaa_target {
+            Some(target) if self.msaa_samples > 1 => (&target.view, Some(view)),
+            _ => (view, None),
+        };
+
+        let queue = &self.display.queue;
 
         let mut cleared = false;
 
@@ -189,6 +488,8 @@ impl Renderer for HardwareRenderer {
                 }
                 len
             };
+            self.stats.draw_calls += 1;
+            self.stats.vertices += vb.len();
 
             queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vb));
             queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(ib));
@@ -203,14 +504,14 @@ impl Renderer for HardwareRenderer {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
+                    view,
+                    resolve_target,
                     ops: wgpu::Operations {
                         load: if cleared {
                             wgpu::LoadOp::Load
                         } else {
                             cleared = true;
-                            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                            wgpu::LoadOp::Clear(clear_color)
                         },
                         store: true,
                     },
@@ -234,8 +535,262 @@ impl Renderer for HardwareRenderer {
             queue.submit(std::iter::once(encoder.finish()));
         }
 
+        let lines = buffers.debug_lines();
+        if !lines.is_empty() {
+            let max_vertices = LINE_BUFFER_SIZE as usize / std::mem::size_of::<LineVertex>();
+            let lines = &lines[..lines.len().min(max_vertices) / 2 * 2];
+            queue.write_buffer(&self.line_buffer, 0, bytemuck::cast_slice(lines));
+            self.stats.draw_calls += 1;
+            self.stats.vertices += lines.len();
+
+            let mut encoder =
+                self.display
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Debug Encoder"),
+                    });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Debug Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: if cleared {
+                            wgpu::LoadOp::Load
+                        } else {
+                            wgpu::LoadOp::Clear(clear_color)
+                        },
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            render_pass.set_pipeline(&self.line_pipeline);
+            render_pass.set_bind_group(0, self.scaling.bind_group(), &[]);
+            render_pass.set_vertex_buffer(0, self.line_buffer.slice(..));
+            render_pass.draw(0..lines.len() as u32, 0..1);
+
+            drop(render_pass);
+            queue.submit(std::iter::once(encoder.finish()));
+        }
+    }
+}
+
+impl Renderer for HardwareRenderer {
+    fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>, scale_factor: f64) {
+        self.display.resize(size.width, size.height);
+        self.scaling.resize(size, scale_factor);
+    }
+
+    fn update(&mut self) {
+        self.scaling.write_to_gpu(&self.display.queue);
+
+        // Free the textures of models that have been unloaded
+        let stats = &mut self.stats;
+        self.textures.retain(|_, texture| {
+            let keep = !texture.should_gc();
+            if !keep {
+                stats.texture_bytes -= texture.byte_size;
+            }
+            keep
+        });
+    }
+
+    fn register_texture(&mut self, texture: &crate::renderer::Texture) {
+        let id = texture.id();
+        if self.textures.contains_key(&id) {
+            return;
+        }
+
+        self.upload_texture(id, texture.data(), texture.config());
+    }
+
+    fn render(&mut self, buffers: &mut ScratchBuffers) -> Result<()> {
+        let surface = if let Some(surface) = &self.display.surface {
+            surface
+        } else {
+            bail!("Headless renderer has no surface to render to");
+        };
+
+        let output = surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.stats.begin_frame();
+        self.draw(&view, buffers);
+
+        if let (true, Some(overlay)) = (self.show_stats, self.stats_overlay.as_mut()) {
+            let size = (self.display.config.width, self.display.config.height);
+            let text = self.stats.summary();
+            if let Err(e) = overlay.draw(
+                &self.display.device,
+                &self.display.queue,
+                &view,
+                size,
+                &text,
+            ) {
+                log::error!("Failed to draw stats overlay: {}", e);
+            }
+        }
+
         output.present();
 
         Ok(())
     }
+
+    fn capture(&mut self, buffers: &mut ScratchBuffers) -> Result<RgbaImage> {
+        let (width, height) = (self.display.config.width, self.display.config.height);
+        let format = self.display.config.format;
+
+        let target = match self.capture_target.take() {
+            Some(target) if target.width == width && target.height == height => target,
+            _ => CaptureTarget::new(&self.display.device, format, width, height),
+        };
+
+        let view = target
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.draw(&view, buffers);
+
+        let mut encoder =
+            self.display
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Capture Encoder"),
+                });
+        encoder.copy_texture_to_buffer(
+            target.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &target.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(target.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.display.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = target.buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        self.display.device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(mapping)?;
+
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(target.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..(width * 4) as usize]);
+            }
+        }
+        target.buffer.unmap();
+        self.capture_target = Some(target);
+
+        if matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            pixels
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        Ok(RgbaImage::from_raw(width, height, pixels).unwrap())
+    }
+
+    fn set_model_scale(&mut self, scale: f32) {
+        *self.scaling.model_scaling_mut() = scale;
+    }
+
+    fn set_bottom_offset(&mut self, offset: f32) {
+        self.scaling.set_bottom_offset(offset);
+    }
+
+    fn set_motion(&mut self, scale: f32, offset: f32) {
+        self.scaling.set_motion(scale, offset);
+    }
+
+    fn set_global_tint(&mut self, tint: [f32; 4]) {
+        self.scaling.set_global_tint(tint);
+    }
+
+    fn set_background(&mut self, color: Option<[f32; 3]>) {
+        self.background = match color {
+            Some([r, g, b]) => wgpu::Color {
+                r: r as f64,
+                g: g as f64,
+                b: b as f64,
+                a: 1.0,
+            },
+            None => wgpu::Color::TRANSPARENT,
+        };
+    }
+
+    fn set_stats_overlay(&mut self, enabled: bool) {
+        if enabled && self.stats_overlay.is_none() {
+            match stats::StatsOverlay::new(&self.display.device, self.display.config.format) {
+                Ok(overlay) => self.stats_overlay = Some(overlay),
+                Err(e) => log::error!("Failed to create stats overlay: {}", e),
+            }
+        }
+        self.show_stats = enabled;
+    }
+
+    fn set_bubble(&mut self, text: Option<String>) {
+        if text.is_some() && self.bubble.is_none() {
+            match bubble::BubbleOverlay::new(
+                &self.display.device,
+                self.display.config.format,
+                &self.bubble_style,
+            ) {
+                Ok(bubble) => self.bubble = Some(bubble),
+                Err(e) => log::error!("Failed to create speech bubble: {}", e),
+            }
+        }
+        self.bubble_text = text;
+    }
+
+    fn window_to_model(&self, position: PhysicalPosition<f64>) -> [f32; 2] {
+        self.scaling.window_to_model(position)
+    }
+
+    fn is_device_lost(&self) -> bool {
+        self.display.is_lost()
+    }
+
+    fn recover(&mut self, window: &Window, config: &Config) -> Result<()> {
+        log::warn!("GPU device lost, recreating the renderer");
+
+        let display = match self.display.surface {
+            Some(_) => pollster::block_on(display::Display::with_gpu(window, &config.gpu)),
+            // An offscreen renderer stays offscreen, at the size it had
+            None => pollster::block_on(display::Display::headless(
+                self.display.config.width,
+                self.display.config.height,
+                &config.gpu,
+            )),
+        };
+        let mut renderer = Self::with_display(display, window.scale_factor(), config)?;
+        renderer.scaling.inherit(&self.scaling);
+        renderer.background = self.background;
+        renderer.set_stats_overlay(self.show_stats);
+        renderer.set_bubble(self.bubble_text.take());
+
+        for (id, texture) in &self.textures {
+            if let Some(data) = texture.image.upgrade() {
+                renderer.upload_texture(*id, data, &texture.config);
+            }
+        }
+
+        *self = renderer;
+        Ok(())
+    }
 }
@@ -0,0 +1,281 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use wgpu::util::DeviceExt;
+
+use crate::config::PostEffect;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniform {
+    color: [f32; 4],
+    params: [f32; 4],
+    texel_size: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+/// Renders the model into an offscreen texture, then draws that onto the target through an
+/// effect shader.
+pub struct PostProcess {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform: PostUniform,
+    uniform_buffer: wgpu::Buffer,
+    started: Instant,
+    /// Offscreen target of the model and its bind group, recreated when the size changes.
+    scene: Option<Scene>,
+}
+
+struct Scene {
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    width: u32,
+    height: u32,
+}
+
+impl PostProcess {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        effect: &PostEffect,
+    ) -> Result<Self> {
+        let prelude = include_str!("post.wgsl");
+        let (source, entry_point) = match effect {
+            PostEffect::Custom(path) => (
+                format!("{}\n{}", prelude, std::fs::read_to_string(path)?),
+                "main_f",
+            ),
+            _ => (
+                format!("{}\n{}", prelude, include_str!("effects.wgsl")),
+                match effect {
+                    PostEffect::Outline { .. } => "outline_f",
+                    PostEffect::DropShadow { .. } => "drop_shadow_f",
+                    _ => "glow_f",
+                },
+            ),
+        };
+
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("Post Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("post_bind_group_layout"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Post Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "main_v",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point,
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    // The scene and so the effect output are premultiplied by the alpha blending
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniform = effect_uniform(effect);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Post Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Ok(Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform,
+            uniform_buffer,
+            started: Instant::now(),
+            scene: None,
+        })
+    }
+
+    /// Offscreen texture the model should be drawn to, matching the size of the final target.
+    pub fn scene_view(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> &wgpu::TextureView {
+        if !matches!(&self.scene, Some(scene) if scene.width == width && scene.height == height) {
+            self.scene = Some(self.create_scene(device, format, width, height));
+        }
+
+        &self.scene.as_ref().unwrap().view
+    }
+
+    fn create_scene(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Scene {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Post Scene Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("post_bind_group"),
+        });
+
+        Scene {
+            view,
+            bind_group,
+            width,
+            height,
+        }
+    }
+
+    /// Draw the scene onto `view` through the effect, after it was drawn to [`Self::scene_view`].
+    pub fn apply(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        clear_color: wgpu::Color,
+    ) {
+        let scene = match &self.scene {
+            Some(scene) => scene,
+            None => return,
+        };
+
+        self.uniform.texel_size = [1.0 / scene.width as f32, 1.0 / scene.height as f32];
+        self.uniform.time = self.started.elapsed().as_secs_f32();
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[self.uniform]),
+        );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Post Encoder"),
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &scene.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
+
+fn effect_uniform(effect: &PostEffect) -> PostUniform {
+    let (color, params) = match effect {
+        PostEffect::Outline { color, width } => (*color, [*width, 0.0, 0.0, 0.0]),
+        PostEffect::DropShadow {
+            color,
+            offset,
+            blur,
+        } => (*color, [0.0, offset.0, offset.1, *blur]),
+        PostEffect::Glow { color, radius } => (*color, [*radius, 0.0, 0.0, 0.0]),
+        PostEffect::Custom(_) => ([1.0; 4], [0.0; 4]),
+    };
+
+    PostUniform {
+        color,
+        params,
+        texel_size: [0.0; 2],
+        time: 0.0,
+        _padding: 0.0,
+    }
+}
@@ -0,0 +1,43 @@
+// Post processing prelude, shared by the built-in effects in effects.wgsl and custom shaders.
+// A fragment entry point gets the rendered model in `t_scene` and the effect parameters in `post`.
+
+struct PostUniform {
+    color: vec4<f32>;
+    // Effect specific: width or radius, offset x, offset y, blur
+    params: vec4<f32>;
+    // Size of one pixel of the scene in UV units
+    texel_size: vec2<f32>;
+    time: f32;
+};
+
+[[group(0), binding(0)]]
+var t_scene: texture_2d<f32>;
+[[group(0), binding(1)]]
+var s_scene: sampler;
+[[group(0), binding(2)]]
+var<uniform> post: PostUniform;
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
+
+// A single triangle covering the whole target
+[[stage(vertex)]]
+fn main_v([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = f32((index << 1u) & 2u);
+    let y = f32(index & 2u);
+    out.uv = vec2<f32>(x, y);
+    out.clip_position = vec4<f32>(x * 2.0 - 1.0, 1.0 - y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+fn scene(uv: vec2<f32>) -> vec4<f32> {
+    return textureSample(t_scene, s_scene, uv);
+}
+
+// Draw `under` behind the premultiplied `over`
+fn behind(over: vec4<f32>, under: vec4<f32>) -> vec4<f32> {
+    return over + vec4<f32>(under.rgb * under.a, under.a) * (1.0 - over.a);
+}
@@ -1,5 +1,5 @@
 use wgpu::util::DeviceExt;
-use winit::{dpi::PhysicalSize, window::Window};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 
 use crate::config::Config;
 
@@ -10,6 +10,8 @@ struct ScalingUniform {
     window_height: f32,
     scale: f32,
     bottom_offset: f32,
+    /// Multiplied with the color of every vertex.
+    tint: [f32; 4],
 }
 
 #[derive(Debug)]
@@ -19,21 +21,26 @@ pub struct ScalingState {
     buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     dpi_scale_factor: f64,
+    /// Scale multiplier and bottom offset added when writing the uniform, see
+    /// [`Self::set_motion`].
+    motion: (f32, f32),
 }
 
 impl ScalingState {
     pub fn new(
-        window: &Window,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
         device: &wgpu::Device,
         config: &Config,
     ) -> (Self, wgpu::BindGroupLayout) {
         let scaling_uniform = {
-            let window_logical_size = window.inner_size().to_logical::<f32>(window.scale_factor());
+            let window_logical_size = size.to_logical::<f32>(scale_factor);
             ScalingUniform {
                 window_width: window_logical_size.width,
                 window_height: window_logical_size.height,
                 scale: config.scale,
                 bottom_offset: config.bottom_offset,
+                tint: [1.0; 4],
             }
         };
 
@@ -73,7 +80,8 @@ impl ScalingState {
                 uniform_dirty: false,
                 buffer: scaling_buffer,
                 bind_group: scaling_bind_group,
-                dpi_scale_factor: window.scale_factor(),
+                dpi_scale_factor: scale_factor,
+                motion: (1.0, 0.0),
             },
             scaling_bind_group_layout,
         )
@@ -84,6 +92,32 @@ impl ScalingState {
         self.uniform.window_width = window_logical_size.width;
         self.uniform.window_height = window_logical_size.height;
         self.uniform_dirty = true;
+        self.dpi_scale_factor = scale_factor;
+    }
+
+    /// Inverse of the transform applied in the vertex shader.
+    pub fn window_to_model(&self, position: PhysicalPosition<f64>) -> [f32; 2] {
+        let position = position.to_logical::<f32>(self.dpi_scale_factor);
+        let ScalingUniform {
+            window_width,
+            window_height,
+            scale,
+            bottom_offset,
+            ..
+        } = self.uniform;
+
+        let x_ndc = 2.0 * position.x / window_width - 1.0;
+        let y_ndc = 1.0 - 2.0 * position.y / window_height;
+        let bottom_offset_ndc = 2.0 * (bottom_offset + 0.5) / window_height;
+
+        [
+            x_ndc * window_width / (2.0 * scale) - 0.5,
+            (y_ndc + 1.0 - bottom_offset_ndc) * window_height / (2.0 * scale) - 0.5,
+        ]
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.dpi_scale_factor
     }
 
     pub fn model_scaling(&self) -> f32 {
@@ -96,10 +130,42 @@ impl ScalingState {
         &mut self.uniform.scale
     }
 
+    /// Take over the model scale, offset and tint of the state this one replaces.
+    pub fn inherit(&mut self, previous: &ScalingState) {
+        self.uniform.scale = previous.uniform.scale;
+        self.uniform.bottom_offset = previous.uniform.bottom_offset;
+        self.uniform.tint = previous.uniform.tint;
+        self.motion = previous.motion;
+        self.uniform_dirty = true;
+    }
+
+    pub fn set_bottom_offset(&mut self, offset: f32) {
+        self.uniform.bottom_offset = offset;
+        self.uniform_dirty = true;
+    }
+
+    /// Move the model without changing what [`Self::window_to_model`] maps to, so clicks don't
+    /// wobble along.
+    pub fn set_motion(&mut self, scale: f32, offset: f32) {
+        self.motion = (scale, offset);
+        self.uniform_dirty = true;
+    }
+
+    pub fn set_global_tint(&mut self, tint: [f32; 4]) {
+        self.uniform.tint = tint;
+        self.uniform_dirty = true;
+    }
+
     /// Write the current uniform data to GPU if needed.
     pub fn write_to_gpu(&self, queue: &wgpu::Queue) {
         if self.uniform_dirty {
-            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+            let (scale, offset) = self.motion;
+            let uniform = ScalingUniform {
+                scale: self.uniform.scale * scale,
+                bottom_offset: self.uniform.bottom_offset + offset,
+                ..self.uniform
+            };
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
         }
     }
 
@@ -5,6 +5,7 @@ struct ScalingUniform {
     window_height: f32;
     scale: f32;
     bottom_offset: f32;
+    tint: vec4<f32>;
 };
 
 [[group(1), binding(0)]]
@@ -28,7 +29,7 @@ fn main_v(
 ) -> VertexOutput {
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.tint = model.tint;
+    out.tint = model.tint * scaling.tint;
 
     // Scale from pixel to NDC
     var x: f32 = 2.0 * (model.position.x + 0.5) / scaling.window_width;
@@ -50,4 +51,17 @@ var s_diffuse: sampler;
 [[stage(fragment)]]
 fn main_f(in: VertexOutput) -> [[location(0)]] vec4<f32> {
    return textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.tint;
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let low = c / 12.92;
+    let high = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, c <= vec3<f32>(0.04045));
+}
+
+// Tint colors are authored in sRGB, while the texture sample is already linear here
+[[stage(fragment)]]
+fn main_f_linear(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+   let tint = vec4<f32>(srgb_to_linear(in.tint.rgb), in.tint.a);
+   return textureSample(t_diffuse, s_diffuse, in.tex_coords) * tint;
 }
\ No newline at end of file
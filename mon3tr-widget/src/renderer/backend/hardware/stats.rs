@@ -0,0 +1,158 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use wgpu::util::StagingBelt;
+use wgpu_glyph::{ab_glyph::FontArc, GlyphBrush, GlyphBrushBuilder, Section, Text};
+
+/// Number of frames the averages are taken over.
+const ROLLING_FRAMES: usize = 120;
+const LOG_INTERVAL: Duration = Duration::from_secs(30);
+/// Monospace first, so the numbers do not jump around.
+const FONT_PATHS: &[&str] = &[
+    r"C:\Windows\Fonts\consola.ttf",
+    r"C:\Windows\Fonts\segoeui.ttf",
+    r"C:\Windows\Fonts\arial.ttf",
+];
+const FONT_SIZE: f32 = 16.0;
+
+/// Statistics of the frames presented to the window.
+pub struct FrameStats {
+    frame_times: VecDeque<Duration>,
+    last_frame: Option<Instant>,
+    /// Log the averages every [`LOG_INTERVAL`], when set.
+    last_logged: Option<Instant>,
+
+    /// Counted over the current frame.
+    pub draw_calls: usize,
+    pub vertices: usize,
+    /// Size of all textures uploaded to the GPU.
+    pub texture_bytes: u64,
+}
+
+impl FrameStats {
+    pub fn new(log: bool) -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(ROLLING_FRAMES),
+            last_frame: None,
+            last_logged: log.then(Instant::now),
+            draw_calls: 0,
+            vertices: 0,
+            texture_bytes: 0,
+        }
+    }
+
+    /// Start counting a new frame.
+    pub fn begin_frame(&mut self) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            if self.frame_times.len() == ROLLING_FRAMES {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(now - last_frame);
+        }
+        self.last_frame = Some(now);
+
+        if let Some(last_logged) = self.last_logged {
+            if now - last_logged >= LOG_INTERVAL {
+                log::info!("{}", self.summary().replace('\n', ", "));
+                self.last_logged = Some(now);
+            }
+        }
+
+        self.draw_calls = 0;
+        self.vertices = 0;
+    }
+
+    fn average_frame_time(&self) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32
+    }
+
+    fn max_frame_time(&self) -> Duration {
+        self.frame_times.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn summary(&self) -> String {
+        let average = self.average_frame_time().as_secs_f64();
+        let fps = if average > 0.0 { 1.0 / average } else { 0.0 };
+
+        format!(
+            "Frame time: {:.2} ms avg, {:.2} ms max ({:.0} FPS)\n\
+             Draw calls: {}, vertices: {}\n\
+             Texture memory: {:.1} MiB",
+            average * 1000.0,
+            self.max_frame_time().as_secs_f64() * 1000.0,
+            fps,
+            self.draw_calls,
+            self.vertices,
+            self.texture_bytes as f64 / (1024.0 * 1024.0),
+        )
+    }
+}
+
+/// Draws [`FrameStats`] in the top left corner of the window.
+pub struct StatsOverlay {
+    brush: GlyphBrush<()>,
+    staging_belt: StagingBelt,
+}
+
+impl StatsOverlay {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Result<Self> {
+        let font = FONT_PATHS
+            .iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .ok_or_else(|| anyhow!("No font found for the stats overlay"))?;
+        let font = FontArc::try_from_vec(font)?;
+
+        Ok(Self {
+            brush: GlyphBrushBuilder::using_font(font).build(device, format),
+            staging_belt: StagingBelt::new(1024),
+        })
+    }
+
+    pub fn draw(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        view: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        text: &str,
+    ) -> Result<()> {
+        // A shadow keeps the text readable on any background
+        for (offset, color) in [(1.0, [0.0, 0.0, 0.0, 1.0]), (0.0, [1.0, 1.0, 1.0, 1.0])] {
+            self.brush.queue(Section {
+                screen_position: (8.0 + offset, 8.0 + offset),
+                bounds: (width as f32, height as f32),
+                text: vec![Text::new(text).with_color(color).with_scale(FONT_SIZE)],
+                ..Section::default()
+            });
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Stats Encoder"),
+        });
+        self.brush
+            .draw_queued(
+                device,
+                &mut self.staging_belt,
+                &mut encoder,
+                view,
+                width,
+                height,
+            )
+            .map_err(|e| anyhow!(e))?;
+        self.staging_belt.finish();
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let recall = self.staging_belt.recall();
+        device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(recall);
+
+        Ok(())
+    }
+}
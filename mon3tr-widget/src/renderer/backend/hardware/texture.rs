@@ -1,18 +1,26 @@
-use std::sync::{Arc, Weak};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Weak},
+};
 
 use anyhow::Result;
-use image::{DynamicImage, GenericImageView};
+use image::imageops::{self, FilterType};
 use spine::atlas::{AtlasFilter, AtlasWrap};
 use wgpu::util::DeviceExt;
 
-use crate::renderer::texture::TextureConfig;
+use crate::renderer::texture::{CompressedFormat, TextureConfig, TextureData};
 
 pub struct HardwareTexture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
     pub bind_group: wgpu::BindGroup,
-    pub image: Weak<DynamicImage>, // TODO: cleanup when image is dropped
+    /// Dropped along with the model, the texture is then freed by [`Self::should_gc`].
+    pub image: Weak<TextureData>,
+    /// Kept to upload the texture again after the device was lost.
+    pub config: TextureConfig,
+    /// Size of the base level, for the frame statistics.
+    pub byte_size: u64,
 }
 
 impl HardwareTexture {
@@ -20,13 +28,62 @@ impl HardwareTexture {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         layout: &wgpu::BindGroupLayout,
-        img: Arc<DynamicImage>,
+        img: Arc<TextureData>,
         config: &TextureConfig,
+        format: wgpu::TextureFormat,
+        generate_mipmaps: bool,
         label: Option<&str>,
     ) -> Self {
-        let rgba = img.as_rgba8().unwrap();
         let dimensions = img.dimensions();
 
+        // (min filter, filter between mip levels), `None` if the atlas doesn't use mipmaps
+        let atlas_mipmap_filters = match config.min_filter {
+            AtlasFilter::MipmapNearestNearest => {
+                Some((wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest))
+            }
+            AtlasFilter::MipmapLinearNearest => {
+                Some((wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest))
+            }
+            AtlasFilter::MipmapNearestLinear => {
+                Some((wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear))
+            }
+            AtlasFilter::Mipmap | AtlasFilter::MipmapLinearLinear => {
+                Some((wgpu::FilterMode::Linear, wgpu::FilterMode::Linear))
+            }
+            _ => None,
+        };
+
+        let (data, mip_level_count, format) = match &*img {
+            TextureData::Image(image) => {
+                let rgba = image.as_rgba8().unwrap();
+                let mut data = Cow::Borrowed(rgba.as_raw().as_slice());
+                let mut mip_level_count = 1;
+                if generate_mipmaps && atlas_mipmap_filters.is_some() {
+                    let mut level = rgba.clone();
+                    while level.width() > 1 || level.height() > 1 {
+                        level = imageops::resize(
+                            &level,
+                            (level.width() / 2).max(1),
+                            (level.height() / 2).max(1),
+                            FilterType::Triangle,
+                        );
+                        // Levels are laid out one after another, as `create_texture_with_data`
+                        // expects
+                        data.to_mut().extend_from_slice(level.as_raw());
+                        mip_level_count += 1;
+                    }
+                }
+                (data, mip_level_count, format)
+            }
+            TextureData::Compressed(image) => (
+                Cow::Owned(image.levels.concat()),
+                image.levels.len() as u32,
+                compressed_format(image.format, format.describe().srgb),
+            ),
+        };
+        // Without a mip chain there is nothing to filter between
+        let mipmap_filters = atlas_mipmap_filters.filter(|_| mip_level_count > 1);
+
         let size = wgpu::Extent3d {
             width: dimensions.0,
             height: dimensions.1,
@@ -37,13 +94,13 @@ impl HardwareTexture {
             &wgpu::TextureDescriptor {
                 label,
                 size,
-                mip_level_count: 1,
+                mip_level_count,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                format,
                 usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             },
-            rgba,
+            &data,
         );
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -64,12 +121,12 @@ impl HardwareTexture {
                 AtlasFilter::Linear => wgpu::FilterMode::Linear,
                 _ => wgpu::FilterMode::Linear,
             },
-            min_filter: match config.min_filter {
-                AtlasFilter::Nearest => wgpu::FilterMode::Nearest,
-                AtlasFilter::Linear => wgpu::FilterMode::Linear,
+            min_filter: match (config.min_filter, mipmap_filters) {
+                (_, Some((min_filter, _))) => min_filter,
+                (AtlasFilter::Nearest, _) => wgpu::FilterMode::Nearest,
                 _ => wgpu::FilterMode::Linear,
             },
-            mipmap_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: mipmap_filters.map_or(wgpu::FilterMode::Linear, |(_, filter)| filter),
             ..Default::default()
         });
 
@@ -94,6 +151,8 @@ impl HardwareTexture {
             sampler,
             bind_group,
             image: Arc::downgrade(&img),
+            config: *config,
+            byte_size: img.byte_size(),
         }
     }
 
@@ -101,3 +160,16 @@ impl HardwareTexture {
         self.image.upgrade().is_none()
     }
 }
+
+fn compressed_format(format: CompressedFormat, srgb: bool) -> wgpu::TextureFormat {
+    match (format, srgb) {
+        (CompressedFormat::Bc1, false) => wgpu::TextureFormat::Bc1RgbaUnorm,
+        (CompressedFormat::Bc1, true) => wgpu::TextureFormat::Bc1RgbaUnormSrgb,
+        (CompressedFormat::Bc3, false) => wgpu::TextureFormat::Bc3RgbaUnorm,
+        (CompressedFormat::Bc3, true) => wgpu::TextureFormat::Bc3RgbaUnormSrgb,
+        (CompressedFormat::Bc7, false) => wgpu::TextureFormat::Bc7RgbaUnorm,
+        (CompressedFormat::Bc7, true) => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+        (CompressedFormat::Astc4x4, false) => wgpu::TextureFormat::Astc4x4RgbaUnorm,
+        (CompressedFormat::Astc4x4, true) => wgpu::TextureFormat::Astc4x4RgbaUnormSrgb,
+    }
+}
@@ -1 +1,24 @@
-pub mod hardware;
\ No newline at end of file
+use anyhow::Result;
+use winit::window::Window;
+
+#[cfg(windows)]
+pub mod composition;
+pub mod hardware;
+
+use super::Renderer;
+use crate::config::Config;
+#[cfg(windows)]
+use crate::config::Presentation;
+
+/// Create the renderer drawing into `window`, presenting the way `config` asks for.
+pub async fn for_window(window: &Window, config: &Config) -> Result<Box<dyn Renderer>> {
+    #[cfg(windows)]
+    if config.gpu.presentation == Presentation::DirectComposition {
+        let renderer = composition::CompositionRenderer::new(window, config).await?;
+        return Ok(Box::new(renderer));
+    }
+
+    Ok(Box::new(
+        hardware::HardwareRenderer::new(window, config).await?,
+    ))
+}
@@ -1,16 +1,44 @@
 use anyhow::Result;
-use winit::dpi::PhysicalSize;
+use image::RgbaImage;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::Window,
+};
 
 pub mod backend;
 
 pub mod texture;
 pub use texture::Texture;
 
-use crate::buffer::ScratchBuffers;
+use crate::{buffer::ScratchBuffers, config::Config};
 
 pub trait Renderer {
     fn resize(&mut self, size: PhysicalSize<u32>, scale_factor: f64);
     fn update(&mut self);
     fn register_texture(&mut self, texture: &Texture);
     fn render(&mut self, buffers: &mut ScratchBuffers) -> Result<()>;
+    /// Render the buffers offscreen at the window size and read the result back, with alpha.
+    fn capture(&mut self, buffers: &mut ScratchBuffers) -> Result<RgbaImage>;
+    /// Scale of the model relative to its original size.
+    fn set_model_scale(&mut self, scale: f32);
+    /// Distance between the bottom of the window and the model origin, in logical pixels.
+    fn set_bottom_offset(&mut self, offset: f32);
+    /// Procedural motion on top of the model scale and bottom offset, a scale multiplier and
+    /// an offset in logical pixels. Hit testing ignores it.
+    fn set_motion(&mut self, scale: f32, offset: f32);
+    /// Color multiplied with the whole model, e.g. `[1.0, 0.5, 0.5, 1.0]` to make it reddish.
+    fn set_global_tint(&mut self, tint: [f32; 4]);
+    /// Draw onto a solid color instead of a transparent background.
+    fn set_background(&mut self, color: Option<[f32; 3]>);
+    /// Show frame statistics on top of what is rendered to the window.
+    fn set_stats_overlay(&mut self, enabled: bool);
+    /// Show a message in a speech bubble above the model, `None` to hide it.
+    fn set_bubble(&mut self, text: Option<String>);
+    /// Convert a position in the window's client area to model space.
+    fn window_to_model(&self, position: PhysicalPosition<f64>) -> [f32; 2];
+    /// Whether the GPU device was lost, e.g. by a driver reset, and [`Renderer::recover`]
+    /// is needed to draw again.
+    fn is_device_lost(&self) -> bool;
+    /// Recreate everything on a new device, uploading the textures still in use again.
+    fn recover(&mut self, window: &Window, config: &Config) -> Result<()>;
 }
\ No newline at end of file
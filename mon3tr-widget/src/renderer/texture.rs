@@ -1,12 +1,36 @@
-use std::sync::{atomic::{AtomicU32, Ordering}, Arc};
+use std::sync::{atomic::{AtomicBool, AtomicU32, Ordering}, Arc};
 
-use image::DynamicImage;
-use spine::atlas::{AtlasFilter, AtlasWrap};
+use anyhow::{bail, Result};
+use image::{DynamicImage, GenericImageView};
+use spine::atlas::{AtlasFilter, AtlasPage, AtlasWrap};
 
 // use super::backend::hardware::HardwareTexture;
 
 static TEX_ID: AtomicU32 = AtomicU32::new(0);
 
+/// Compressed formats the GPU can sample, set by the renderer once it has a device.
+static BC_SUPPORTED: AtomicBool = AtomicBool::new(false);
+static ASTC_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Bits of the `f32` factor textures are downscaled by when loaded.
+static TEXTURE_SCALE: AtomicU32 = AtomicU32::new(0x3f80_0000); // 1.0
+
+pub fn set_compression_support(bc: bool, astc: bool) {
+    BC_SUPPORTED.store(bc, Ordering::Relaxed);
+    ASTC_SUPPORTED.store(astc, Ordering::Relaxed);
+}
+
+/// Set the factor textures loaded from now on are downscaled by, clamped to (0, 1].
+pub fn set_texture_scale(scale: f32) {
+    let scale = if scale > 0.0 { scale.min(1.0) } else { 1.0 };
+    TEXTURE_SCALE.store(scale.to_bits(), Ordering::Relaxed);
+}
+
+pub fn texture_scale() -> f32 {
+    f32::from_bits(TEXTURE_SCALE.load(Ordering::Relaxed))
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct TextureConfig {
     pub mag_filter: AtlasFilter,
     pub min_filter: AtlasFilter,
@@ -14,20 +38,157 @@ pub struct TextureConfig {
     pub v_wrap: AtlasWrap,
 }
 
+impl TextureConfig {
+    pub fn from_atlas(atlas: &AtlasPage) -> Self {
+        Self {
+            mag_filter: atlas.mag_filter(),
+            min_filter: atlas.min_filter(),
+            u_wrap: atlas.u_wrap(),
+            v_wrap: atlas.v_wrap(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TextureID(u32);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Bc1,
+    Bc3,
+    Bc7,
+    Astc4x4,
+}
+
+impl CompressedFormat {
+    pub fn is_supported(self) -> bool {
+        match self {
+            CompressedFormat::Bc1 | CompressedFormat::Bc3 | CompressedFormat::Bc7 => {
+                BC_SUPPORTED.load(Ordering::Relaxed)
+            }
+            CompressedFormat::Astc4x4 => ASTC_SUPPORTED.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Block compressed texture with its mip chain, ready to be uploaded as is. Like PNG pages,
+/// it is sampled according to the `color_space` option, whatever the file says.
+pub struct CompressedImage {
+    pub format: CompressedFormat,
+    pub width: u32,
+    pub height: u32,
+    /// Mip levels from the largest one down.
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Parse a KTX2 container holding one 2D BC1/BC3/BC7 or ASTC 4x4 image.
+    pub fn from_ktx2(data: &[u8]) -> Result<Self> {
+        use ktx2::Format;
+
+        let reader = ktx2::Reader::new(data)?;
+        let header = reader.header();
+
+        if header.supercompression_scheme.is_some() {
+            bail!("Supercompressed KTX2 textures are not supported");
+        }
+        if header.pixel_height == 0
+            || header.pixel_depth > 1
+            || header.layer_count > 1
+            || header.face_count > 1
+        {
+            bail!("Only 2D KTX2 textures are supported");
+        }
+        if header.pixel_width % 4 != 0 || header.pixel_height % 4 != 0 {
+            bail!("KTX2 texture size must be a multiple of the block size");
+        }
+        let max_levels = 32 - header.pixel_width.max(header.pixel_height).leading_zeros();
+        if reader.levels().len() as u32 > max_levels {
+            bail!("KTX2 texture has more mip levels than its size allows");
+        }
+
+        let format = match header.format {
+            Some(Format::BC1_RGBA_UNORM_BLOCK | Format::BC1_RGBA_SRGB_BLOCK) => {
+                CompressedFormat::Bc1
+            }
+            Some(Format::BC3_UNORM_BLOCK | Format::BC3_SRGB_BLOCK) => CompressedFormat::Bc3,
+            Some(Format::BC7_UNORM_BLOCK | Format::BC7_SRGB_BLOCK) => CompressedFormat::Bc7,
+            Some(Format::ASTC_4x4_UNORM_BLOCK | Format::ASTC_4x4_SRGB_BLOCK) => {
+                CompressedFormat::Astc4x4
+            }
+            format => bail!("Unsupported KTX2 texture format {:?}", format),
+        };
+
+        Ok(Self {
+            format,
+            width: header.pixel_width,
+            height: header.pixel_height,
+            levels: reader.levels().map(|level| level.to_vec()).collect(),
+        })
+    }
+
+    /// Drop the largest mip levels while the next one is still at least `scale` times the
+    /// original size. Blocks can't be resampled, so this only downscales by powers of two.
+    pub fn downscale(&mut self, scale: f32) {
+        let min_width = self.width as f32 * scale;
+        while self.levels.len() > 1 {
+            let (width, height) = (self.width / 2, self.height / 2);
+            if (width as f32) < min_width || width % 4 != 0 || height % 4 != 0 {
+                break;
+            }
+            self.levels.remove(0);
+            self.width = width;
+            self.height = height;
+        }
+    }
+}
+
+pub enum TextureData {
+    Image(DynamicImage),
+    Compressed(CompressedImage),
+}
+
+impl TextureData {
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            TextureData::Image(image) => image.dimensions(),
+            TextureData::Compressed(image) => (image.width, image.height),
+        }
+    }
+
+    /// Bytes the texture takes on the GPU, without mip levels.
+    pub fn byte_size(&self) -> u64 {
+        match self {
+            TextureData::Image(image) => {
+                let (width, height) = image.dimensions();
+                width as u64 * height as u64 * 4
+            }
+            TextureData::Compressed(image) => {
+                image.levels.first().map_or(0, |level| level.len() as u64)
+            }
+        }
+    }
+}
+
 pub struct Texture {
     id: TextureID,
-    image: Arc<DynamicImage>,
+    data: Arc<TextureData>,
     config: TextureConfig,
 }
 
 impl Texture {
     pub fn new(image: DynamicImage, config: TextureConfig) -> Self {
+        Self::with_data(TextureData::Image(image), config)
+    }
+
+    pub fn compressed(image: CompressedImage, config: TextureConfig) -> Self {
+        Self::with_data(TextureData::Compressed(image), config)
+    }
+
+    fn with_data(data: TextureData, config: TextureConfig) -> Self {
         Self {
             id: TextureID(TEX_ID.fetch_add(1, Ordering::Relaxed)),
-            image: Arc::new(image),
+            data: Arc::new(data),
             config,
         }
     }
@@ -36,8 +197,16 @@ impl Texture {
         self.id
     }
 
-    pub fn image(&self) -> Arc<DynamicImage> {
-        Arc::clone(&self.image)
+    /// The decoded image, `None` for compressed textures.
+    pub fn image(&self) -> Option<&DynamicImage> {
+        match &*self.data {
+            TextureData::Image(image) => Some(image),
+            TextureData::Compressed(_) => None,
+        }
+    }
+
+    pub fn data(&self) -> Arc<TextureData> {
+        Arc::clone(&self.data)
     }
 
     pub fn config(&self) -> &TextureConfig {
@@ -0,0 +1,128 @@
+#[cfg(windows)]
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+#[cfg(windows)]
+use image::ImageOutputFormat;
+use image::RgbaImage;
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HANDLE, HWND},
+    Graphics::Gdi::{BITMAPV5HEADER, BI_BITFIELDS},
+    System::{
+        DataExchange::{
+            CloseClipboard, EmptyClipboard, OpenClipboard, RegisterClipboardFormatW,
+            SetClipboardData,
+        },
+        Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+        SystemServices::CF_DIBV5,
+    },
+};
+#[cfg(windows)]
+use winit::platform::windows::WindowExtWindows;
+use winit::window::Window;
+
+/// `LCS_sRGB` color space of a `BITMAPV5HEADER`.
+#[cfg(windows)]
+const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Where a screenshot of the next frame goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreenshotTarget {
+    File(PathBuf),
+    Clipboard,
+}
+
+impl ScreenshotTarget {
+    pub fn save(&self, owner: &Window, frame: &RgbaImage) -> Result<()> {
+        match self {
+            ScreenshotTarget::File(path) => frame.save(path)?,
+            ScreenshotTarget::Clipboard => copy_to_clipboard(owner, frame)?,
+        }
+
+        Ok(())
+    }
+}
+
+/// Put an image on the clipboard, both as PNG and as a DIB with alpha, as programs differ in
+/// which of them keeps the transparency.
+#[cfg(windows)]
+fn copy_to_clipboard(owner: &Window, frame: &RgbaImage) -> Result<()> {
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgba8(frame.clone())
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)?;
+
+    let header = BITMAPV5HEADER {
+        bV5Size: std::mem::size_of::<BITMAPV5HEADER>() as u32,
+        bV5Width: frame.width() as i32,
+        // Bottom-up, the one every program understands
+        bV5Height: frame.height() as i32,
+        bV5Planes: 1,
+        bV5BitCount: 32,
+        bV5Compression: BI_BITFIELDS as u32,
+        bV5SizeImage: frame.width() * frame.height() * 4,
+        bV5RedMask: 0x00ff_0000,
+        bV5GreenMask: 0x0000_ff00,
+        bV5BlueMask: 0x0000_00ff,
+        bV5AlphaMask: 0xff00_0000,
+        bV5CSType: LCS_SRGB,
+        ..Default::default()
+    };
+    let mut dib = unsafe {
+        std::slice::from_raw_parts(
+            &header as *const BITMAPV5HEADER as *const u8,
+            std::mem::size_of::<BITMAPV5HEADER>(),
+        )
+    }
+    .to_vec();
+    for row in frame.rows().rev() {
+        for pixel in row {
+            let [r, g, b, a] = pixel.0;
+            dib.extend_from_slice(&[b, g, r, a]);
+        }
+    }
+
+    unsafe {
+        if !OpenClipboard(HWND(owner.hwnd() as isize)).as_bool() {
+            bail!("Failed to open the clipboard");
+        }
+        EmptyClipboard();
+        let png_format = RegisterClipboardFormatW("PNG");
+        let result =
+            set_clipboard_data(png_format, &png).and_then(|_| set_clipboard_data(CF_DIBV5, &dib));
+        CloseClipboard();
+
+        result
+    }
+}
+
+/// Copy `data` into global memory and hand it to the clipboard, which must be open.
+#[cfg(windows)]
+unsafe fn set_clipboard_data(format: u32, data: &[u8]) -> Result<()> {
+    let memory = GlobalAlloc(GMEM_MOVEABLE, data.len());
+    if memory == 0 {
+        bail!("Failed to allocate clipboard memory");
+    }
+
+    let ptr = GlobalLock(memory) as *mut u8;
+    if ptr.is_null() {
+        GlobalFree(memory);
+        bail!("Failed to lock clipboard memory");
+    }
+    std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+    GlobalUnlock(memory);
+
+    // The clipboard owns the memory from now on, unless it refused it
+    if SetClipboardData(format, HANDLE(memory)).is_invalid() {
+        GlobalFree(memory);
+        bail!("Failed to set clipboard data");
+    }
+
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn copy_to_clipboard(_owner: &Window, _frame: &RgbaImage) -> Result<()> {
+    bail!("Copying to the clipboard is only supported on Windows")
+}
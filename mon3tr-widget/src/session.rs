@@ -0,0 +1,139 @@
+//! Notifications about the screen being locked or turned off, which winit doesn't report.
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM},
+    System::{
+        Power::{
+            RegisterPowerSettingNotification, UnregisterPowerSettingNotification,
+            DEVICE_NOTIFY_WINDOW_HANDLE, HPOWERNOTIFY, POWERBROADCAST_SETTING,
+        },
+        RemoteDesktop::{
+            WTSRegisterSessionNotification, WTSUnRegisterSessionNotification,
+            NOTIFY_FOR_THIS_SESSION,
+        },
+        SystemServices::GUID_CONSOLE_DISPLAY_STATE,
+    },
+    UI::{
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::{
+            PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, PBT_POWERSETTINGCHANGE, WM_POWERBROADCAST,
+            WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+        },
+    },
+};
+#[cfg(windows)]
+use winit::platform::windows::WindowExtWindows;
+use winit::{event_loop::EventLoopProxy, window::Window};
+
+use crate::UserEvent;
+
+#[cfg(windows)]
+const SUBCLASS_ID: usize = 0x4d33;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEvent {
+    Locked,
+    Unlocked,
+    DisplayOff,
+    DisplayOn,
+    Suspending,
+    Resumed,
+}
+
+/// Listens to the messages of a window for session and power changes.
+#[cfg(windows)]
+pub struct SessionWatcher {
+    hwnd: HWND,
+    power_notify: HPOWERNOTIFY,
+    proxy: *mut EventLoopProxy<UserEvent>,
+}
+
+#[cfg(windows)]
+impl SessionWatcher {
+    /// `window` must outlive the watcher, the hidden owner window is a good fit.
+    pub fn new(window: &Window, proxy: EventLoopProxy<UserEvent>) -> Self {
+        let hwnd = HWND(window.hwnd() as isize);
+        let proxy = Box::into_raw(Box::new(proxy));
+
+        let power_notify = unsafe {
+            SetWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID, proxy as usize);
+            if !WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION).as_bool() {
+                log::warn!("Failed to register for session notifications");
+            }
+            RegisterPowerSettingNotification(
+                HANDLE(hwnd.0),
+                &GUID_CONSOLE_DISPLAY_STATE,
+                DEVICE_NOTIFY_WINDOW_HANDLE,
+            )
+        };
+
+        Self {
+            hwnd,
+            power_notify,
+            proxy,
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for SessionWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.power_notify.is_invalid() {
+                UnregisterPowerSettingNotification(self.power_notify);
+            }
+            WTSUnRegisterSessionNotification(self.hwnd);
+            RemoveWindowSubclass(self.hwnd, Some(subclass_proc), SUBCLASS_ID);
+            drop(Box::from_raw(self.proxy));
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn subclass_proc(
+    hwnd: HWND,
+    msg: u32,
+    w_param: WPARAM,
+    l_param: LPARAM,
+    _id: usize,
+    ref_data: usize,
+) -> LRESULT {
+    let event = match (msg, w_param.0 as u32) {
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => Some(SessionEvent::Locked),
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => Some(SessionEvent::Unlocked),
+        (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some(SessionEvent::Suspending),
+        (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) => Some(SessionEvent::Resumed),
+        (WM_POWERBROADCAST, PBT_POWERSETTINGCHANGE) => {
+            let setting = &*(l_param.0 as *const POWERBROADCAST_SETTING);
+            if setting.PowerSetting == GUID_CONSOLE_DISPLAY_STATE {
+                // 0 is off, 1 on and 2 dimmed
+                match (setting.Data.as_ptr() as *const u32).read_unaligned() {
+                    0 => Some(SessionEvent::DisplayOff),
+                    _ => Some(SessionEvent::DisplayOn),
+                }
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    if let Some(event) = event {
+        let proxy = &*(ref_data as *const EventLoopProxy<UserEvent>);
+        let _ = proxy.send_event(UserEvent::Session(event));
+    }
+
+    DefSubclassProc(hwnd, msg, w_param, l_param)
+}
+
+/// Nothing to listen to yet, the widget just keeps running while away.
+#[cfg(not(windows))]
+pub struct SessionWatcher;
+
+#[cfg(not(windows))]
+impl SessionWatcher {
+    pub fn new(_window: &Window, _proxy: EventLoopProxy<UserEvent>) -> Self {
+        Self
+    }
+}
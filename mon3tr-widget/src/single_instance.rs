@@ -0,0 +1,52 @@
+#[cfg(windows)]
+use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+#[cfg(windows)]
+use windows::Win32::System::Threading::CreateMutexW;
+
+#[cfg(windows)]
+const MUTEX_NAME: &str = "Local\\mon3tr-widget";
+
+/// Held for the lifetime of the widget, so a second launch can tell it is not alone.
+#[cfg(windows)]
+pub struct InstanceGuard(HANDLE);
+
+#[cfg(windows)]
+impl InstanceGuard {
+    /// Returns `None` if another instance is already running.
+    pub fn acquire() -> Option<Self> {
+        unsafe {
+            let handle = CreateMutexW(std::ptr::null(), false, MUTEX_NAME);
+            if handle.is_invalid() {
+                // Something is off, but that should not stop the widget from running
+                log::warn!("Failed to create single instance mutex");
+                return Some(Self(handle));
+            }
+            if GetLastError() == ERROR_ALREADY_EXISTS {
+                CloseHandle(handle);
+                return None;
+            }
+
+            Some(Self(handle))
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        if !self.0.is_invalid() {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+/// There is no session wide mutex to lean on elsewhere, so every launch runs.
+#[cfg(not(windows))]
+pub struct InstanceGuard;
+
+#[cfg(not(windows))]
+impl InstanceGuard {
+    pub fn acquire() -> Option<Self> {
+        Some(Self)
+    }
+}
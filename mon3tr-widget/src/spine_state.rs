@@ -1,11 +1,38 @@
-use std::time::{Duration, Instant};
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use spine::{AnimationState, AnimationStateData, Atlas, Skeleton, SkeletonData};
 
 use crate::config::Config;
 
+/// Playback speeds offered in the tray and stepped through with hotkeys.
+pub const PLAYBACK_SPEEDS: [f32; 6] = [0.25, 0.5, 0.75, 1.0, 1.5, 2.0];
+
+/// Bits of the `f32` speed all models play at.
+static PLAYBACK_SPEED: AtomicU32 = AtomicU32::new(0x3f80_0000); // 1.0
+
+/// Set the speed models play at from their next frame on, clamped to the range of
+/// [`PLAYBACK_SPEEDS`].
+pub fn set_playback_speed(speed: f32) {
+    let (min, max) = (PLAYBACK_SPEEDS[0], PLAYBACK_SPEEDS[PLAYBACK_SPEEDS.len() - 1]);
+    let speed = if speed.is_finite() {
+        speed.clamp(min, max)
+    } else {
+        1.0
+    };
+    PLAYBACK_SPEED.store(speed.to_bits(), Ordering::Relaxed);
+}
+
+pub fn playback_speed() -> f32 {
+    f32::from_bits(PLAYBACK_SPEED.load(Ordering::Relaxed))
+}
+
 pub struct SpineState {
+    /// Path of the model pack this was loaded from.
+    pub pack: String,
     pub atlas: Atlas,
     pub skel_data: SkeletonData,
     pub anim_state_data: AnimationStateData,
@@ -16,11 +43,24 @@ pub struct SpineState {
     last_render: Option<Instant>,
 }
 
+/// Extra model drawn in the same window as the main one, see [`crate::config::ModelInstance`].
+pub struct Companion {
+    pub spine: SpineState,
+    /// Drawn before the main model instead of after it.
+    pub behind: bool,
+}
+
 impl SpineState {
-    pub fn new(pack: &str) -> Result<Self> {
-        let atlas = Atlas::new(&format!("{}??/char.atlas", pack))?;
-        let skel_data =
-            SkeletonData::new_binary(&atlas, &format!("{}??/char.skel", pack), 1.0)?;
+    /// Load a model pack and start playing `idle` (name and whether it loops).
+    pub fn new(pack: &str, idle: Option<(&str, bool)>) -> Result<Self> {
+        // Shared with other windows showing the same pack
+        let atlas = spine::cache::load_atlas(&format!("{}??/char.atlas", pack))?;
+        let skel_data = spine::cache::load_skeleton_data(&format!("{}??/char.skel", pack), 1.0)?;
+        log::info!(
+            "Loaded {}, exported from Spine {}",
+            pack,
+            skel_data.version().unwrap_or("(unknown)")
+        );
         let anim_data = AnimationStateData::new(&skel_data, 0.0)?;
 
         let mut skel = Skeleton::new(&skel_data)?;
@@ -28,9 +68,11 @@ impl SpineState {
         skel.set_y(0.0);
 
         let mut anim = AnimationState::new(&anim_data)?;
-        anim.set_animation_by_name(0, "Idle", true);
+        let (idle_name, idle_loop) = idle.unwrap_or(("Idle", true));
+        anim.set_animation_by_name(0, idle_name, idle_loop);
 
         Ok(Self {
+            pack: pack.to_owned(),
             atlas,
             skel_data,
             anim_state_data: anim_data,
@@ -42,7 +84,24 @@ impl SpineState {
         })
     }
 
-    pub fn prepare_render(&mut self) {
+    /// Move the skeleton by `offset` and scale it around its root, in model units.
+    pub fn place(&mut self, (x, y): (f32, f32), scale: f32) {
+        self.skel.set_x(x);
+        self.skel.set_y(y);
+        if let Some(root) = self.skel.root_bone() {
+            let (scale_x, scale_y) = root.scale();
+            root.set_scale(scale_x * scale, scale_y * scale);
+        }
+    }
+
+    /// Path of a file inside the model pack, for [`crate::utils::load_file_packed`].
+    pub fn pack_file(&self, name: &str) -> String {
+        format!("{}??/{}", self.pack, name)
+    }
+
+    /// Advance the animation and compute world transforms.
+    /// `adjust` is called in between, to modify bones on top of the animated pose.
+    pub fn prepare_render(&mut self, adjust: impl FnOnce(&mut Skeleton)) {
         let now = Instant::now();
         let delta = if let Some(last_render) = self.last_render {
             now - last_render
@@ -52,8 +111,22 @@ impl SpineState {
         .as_secs_f32();
         self.last_render = Some(now);
 
+        // Exports step at their own pace, so only real time playback is slowed down
+        self.anim.set_time_scale(playback_speed());
+        self.step(delta, adjust);
+    }
+
+    /// Forget when the model was last rendered, so it resumes where it stopped instead of
+    /// jumping ahead by the time it was hidden.
+    pub fn pause(&mut self) {
+        self.last_render = None;
+    }
+
+    /// Advance the animation by a fixed number of seconds, see [`Self::prepare_render`].
+    pub fn step(&mut self, delta: f32, adjust: impl FnOnce(&mut Skeleton)) {
         self.anim.update(delta);
         self.skel.apply_animation(&self.anim);
+        adjust(&mut self.skel);
         self.skel.update_world_transform();
     }
 }
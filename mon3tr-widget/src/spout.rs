@@ -0,0 +1,166 @@
+//! Publishes rendered frames through Spout, so OBS and VJ software can receive the model with
+//! transparency instead of capturing the layered window.
+//!
+//! Uses `SpoutLibrary.dll` from the Spout SDK (2.007 or later), which has to be placed next to
+//! the executable.
+
+use std::{
+    ffi::{c_void, CString},
+    os::raw::c_char,
+};
+
+use anyhow::{anyhow, bail, Result};
+use image::RgbaImage;
+use libloading::Library;
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND},
+    Graphics::{
+        Gdi::{GetDC, ReleaseDC, HDC, PFD_DRAW_TO_WINDOW, PFD_MAIN_PLANE, PFD_SUPPORT_OPENGL},
+        OpenGL::{
+            wglCreateContext, wglDeleteContext, wglMakeCurrent, ChoosePixelFormat, SetPixelFormat,
+            HGLRC, PIXELFORMATDESCRIPTOR,
+        },
+    },
+    UI::WindowsAndMessaging::{CreateWindowExW, DestroyWindow, HMENU},
+};
+
+const GL_RGBA: u32 = 0x1908;
+
+/// `SPOUTLIBRARY` C++ interface, only the leading methods we call are declared.
+#[repr(C)]
+struct SpoutLibrary {
+    vtable: *const SpoutVtable,
+}
+
+#[repr(C)]
+struct SpoutVtable {
+    set_sender_name: unsafe extern "system" fn(*mut SpoutLibrary, *const c_char),
+    _set_sender_format: *const c_void,
+    release_sender: unsafe extern "system" fn(*mut SpoutLibrary, u32),
+    _send_fbo: *const c_void,
+    _send_texture: *const c_void,
+    send_image:
+        unsafe extern "system" fn(*mut SpoutLibrary, *const u8, u32, u32, u32, bool) -> bool,
+}
+
+pub struct SpoutSender {
+    spout: *mut SpoutLibrary,
+    _gl: GlContext,
+    // Dropped last, the interface lives in the library
+    _library: Library,
+}
+
+impl SpoutSender {
+    pub fn new(name: &str) -> Result<Self> {
+        unsafe {
+            let library = Library::new("SpoutLibrary.dll")?;
+            let get_spout =
+                library.get::<unsafe extern "system" fn() -> *mut SpoutLibrary>(b"GetSpout\0")?;
+            let spout = get_spout();
+            if spout.is_null() {
+                bail!("Failed to get the Spout interface");
+            }
+
+            // Spout shares frames through OpenGL, which needs a current context
+            let gl = GlContext::new()?;
+
+            let name = CString::new(name)?;
+            ((*(*spout).vtable).set_sender_name)(spout, name.as_ptr());
+            log::info!("Publishing frames to Spout as {:?}", name);
+
+            Ok(Self {
+                spout,
+                _gl: gl,
+                _library: library,
+            })
+        }
+    }
+
+    pub fn send(&self, frame: &RgbaImage) {
+        unsafe {
+            ((*(*self.spout).vtable).send_image)(
+                self.spout,
+                frame.as_ptr(),
+                frame.width(),
+                frame.height(),
+                GL_RGBA,
+                false,
+            );
+        }
+    }
+}
+
+impl Drop for SpoutSender {
+    fn drop(&mut self) {
+        unsafe { ((*(*self.spout).vtable).release_sender)(self.spout, 0) };
+    }
+}
+
+/// OpenGL context on a hidden window, current on the creating thread while it lives.
+struct GlContext {
+    hwnd: HWND,
+    hdc: HDC,
+    hglrc: HGLRC,
+}
+
+impl GlContext {
+    unsafe fn new() -> Result<Self> {
+        let hwnd = CreateWindowExW(
+            0,
+            "STATIC",
+            "Mon3tr-Widget Spout",
+            0,
+            0,
+            0,
+            1,
+            1,
+            HWND::default(),
+            HMENU::default(),
+            HINSTANCE::default(),
+            std::ptr::null(),
+        );
+        if hwnd.0 == 0 {
+            bail!("Failed to create a window for OpenGL");
+        }
+        let hdc = GetDC(hwnd);
+
+        let pfd = PIXELFORMATDESCRIPTOR {
+            nSize: std::mem::size_of::<PIXELFORMATDESCRIPTOR>() as u16,
+            nVersion: 1,
+            dwFlags: PFD_DRAW_TO_WINDOW | PFD_SUPPORT_OPENGL,
+            cColorBits: 32,
+            iLayerType: PFD_MAIN_PLANE as u8,
+            ..Default::default()
+        };
+        let format = ChoosePixelFormat(hdc, &pfd);
+        let context = if format != 0 && SetPixelFormat(hdc, format, &pfd).as_bool() {
+            wglCreateContext(hdc)
+        } else {
+            HGLRC::default()
+        };
+        let gl = Self {
+            hwnd,
+            hdc,
+            hglrc: context,
+        };
+
+        if gl.hglrc.is_invalid() || !wglMakeCurrent(hdc, gl.hglrc).as_bool() {
+            return Err(anyhow!("Failed to create an OpenGL context"));
+        }
+
+        Ok(gl)
+    }
+}
+
+impl Drop for GlContext {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.hglrc.is_invalid() {
+                wglMakeCurrent(HDC::default(), HGLRC::default());
+                wglDeleteContext(self.hglrc);
+            }
+            ReleaseDC(self.hwnd, self.hdc);
+            DestroyWindow(self.hwnd);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! CPU and memory usage of the whole computer, so the model can react to it being busy.
+
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::FILETIME,
+    System::{
+        SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX},
+        Threading::GetSystemTimes,
+    },
+};
+
+use crate::config::{Command, Resource, SystemLoadConfig};
+
+/// How far usage has to fall below `above` to leave a trigger when `below` isn't set.
+const DEFAULT_HYSTERESIS: f32 = 5.0;
+
+/// Time all CPUs spent since boot, in whatever unit the platform counts in.
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+/// Samples usage at an interval and reports triggers crossing their thresholds.
+pub struct SystemMonitor {
+    last_sample: Option<Instant>,
+    last_cpu: Option<CpuTimes>,
+    /// Whether usage is above the threshold of each trigger, by index.
+    active: Vec<bool>,
+}
+
+impl SystemMonitor {
+    pub fn new(config: &SystemLoadConfig) -> Self {
+        Self {
+            last_sample: None,
+            last_cpu: None,
+            active: vec![false; config.triggers.len()],
+        }
+    }
+
+    /// Returns the commands to run for triggers that were entered or left since the last
+    /// sample. Usage is sampled at most once per `interval`.
+    pub fn update<'a>(&mut self, config: &'a SystemLoadConfig) -> Vec<&'a Command> {
+        let now = Instant::now();
+        if let Some(last_sample) = self.last_sample {
+            if now - last_sample < Duration::from_secs_f32(config.interval) {
+                return Vec::new();
+            }
+        }
+        self.last_sample = Some(now);
+
+        // CPU usage is averaged over the time since the previous sample
+        let cpu = cpu_times();
+        let cpu_usage = match (self.last_cpu, cpu) {
+            (Some(last), Some(cpu)) if cpu.total > last.total => {
+                let idle = cpu.idle.saturating_sub(last.idle) as f32;
+                Some(100.0 * (1.0 - idle / (cpu.total - last.total) as f32))
+            }
+            _ => None,
+        };
+        self.last_cpu = cpu;
+        let memory_usage = memory_usage();
+
+        let mut commands = Vec::new();
+        for (trigger, active) in config.triggers.iter().zip(&mut self.active) {
+            let usage = match trigger.resource {
+                Resource::Cpu => cpu_usage,
+                Resource::Memory => memory_usage,
+            };
+            let usage = match usage {
+                Some(usage) => usage,
+                None => continue,
+            };
+
+            let below = trigger.below.unwrap_or(trigger.above - DEFAULT_HYSTERESIS);
+            if !*active && usage >= trigger.above {
+                log::info!("{:?} usage at {:.0}%", trigger.resource, usage);
+                *active = true;
+                commands.extend(&trigger.enter);
+            } else if *active && usage < below {
+                log::info!("{:?} usage back to {:.0}%", trigger.resource, usage);
+                *active = false;
+                commands.extend(&trigger.exit);
+            }
+        }
+        commands
+    }
+}
+
+#[cfg(windows)]
+fn cpu_times() -> Option<CpuTimes> {
+    let to_u64 = |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+
+    let (mut idle, mut kernel, mut user) = (
+        FILETIME::default(),
+        FILETIME::default(),
+        FILETIME::default(),
+    );
+    if !unsafe { GetSystemTimes(&mut idle, &mut kernel, &mut user) }.as_bool() {
+        return None;
+    }
+    // Kernel time includes the idle time
+    Some(CpuTimes {
+        idle: to_u64(idle),
+        total: to_u64(kernel) + to_u64(user),
+    })
+}
+
+#[cfg(windows)]
+fn memory_usage() -> Option<f32> {
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GlobalMemoryStatusEx(&mut status) }.as_bool() {
+        return None;
+    }
+    Some(status.dwMemoryLoad as f32)
+}
+
+#[cfg(not(windows))]
+fn cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    // cpu  user nice system idle iowait irq softirq steal ...
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|time| time.parse().ok())
+        .collect();
+    if times.len() < 5 {
+        return None;
+    }
+    Some(CpuTimes {
+        idle: times[3] + times[4],
+        total: times.iter().sum(),
+    })
+}
+
+#[cfg(not(windows))]
+fn memory_usage() -> Option<f32> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<f32> {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(name))?
+            .trim_start_matches(':')
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+
+    let total = field("MemTotal")?;
+    let available = field("MemAvailable")?;
+    (total > 0.0).then(|| 100.0 * (1.0 - available / total))
+}
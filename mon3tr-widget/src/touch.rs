@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use winit::{
+    dpi::PhysicalPosition,
+    event::{Touch, TouchPhase},
+};
+
+/// Longest touch that still counts as a tap.
+const TAP_TIME: Duration = Duration::from_millis(300);
+/// Holding a finger still this long is a long press.
+const LONG_PRESS_TIME: Duration = Duration::from_millis(800);
+/// A finger moving further than this is dragging, in logical pixels.
+const TAP_DISTANCE: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Short touch at this position in the client area.
+    Tap(PhysicalPosition<f64>),
+    LongPress,
+    /// Move the window by this much so the finger stays where it grabbed the window.
+    Drag(PhysicalPosition<f64>),
+    /// Fingers moved apart or together, by this factor since the last pinch.
+    Pinch(f64),
+}
+
+/// Turns touch events into taps, long presses, drags and pinches.
+pub struct TouchGestures {
+    /// Position of each finger on the window.
+    touches: HashMap<u64, PhysicalPosition<f64>>,
+    /// The only finger down, if there is just one.
+    press: Option<Press>,
+    /// Distance between the first two fingers at the last pinch event.
+    pinch_distance: Option<f64>,
+}
+
+struct Press {
+    id: u64,
+    /// Where the finger went down, the window follows it from there when dragging.
+    start: PhysicalPosition<f64>,
+    started: Instant,
+    dragging: bool,
+    long_pressed: bool,
+}
+
+impl TouchGestures {
+    pub fn new() -> Self {
+        Self {
+            touches: HashMap::new(),
+            press: None,
+            pinch_distance: None,
+        }
+    }
+
+    pub fn touch(&mut self, touch: &Touch, scale_factor: f64) -> Option<Gesture> {
+        let location = touch.location;
+        match touch.phase {
+            TouchPhase::Started => {
+                self.touches.insert(touch.id, location);
+                if self.touches.len() == 1 {
+                    self.press = Some(Press {
+                        id: touch.id,
+                        start: location,
+                        started: Instant::now(),
+                        dragging: false,
+                        long_pressed: false,
+                    });
+                } else {
+                    // A second finger turns it into a pinch
+                    self.press = None;
+                    self.pinch_distance = self.distance();
+                }
+                None
+            }
+            TouchPhase::Moved => {
+                self.touches.insert(touch.id, location);
+
+                if let Some(press) = self.press.as_mut().filter(|press| press.id == touch.id) {
+                    let offset = PhysicalPosition::new(
+                        location.x - press.start.x,
+                        location.y - press.start.y,
+                    );
+                    if !press.dragging && offset.x.hypot(offset.y) < TAP_DISTANCE * scale_factor {
+                        return None;
+                    }
+                    press.dragging = true;
+                    return Some(Gesture::Drag(offset));
+                }
+
+                let (last, distance) = (self.pinch_distance?, self.distance()?);
+                self.pinch_distance = Some(distance);
+                (last > 0.0).then(|| Gesture::Pinch(distance / last))
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&touch.id);
+                if self.touches.len() < 2 {
+                    self.pinch_distance = None;
+                }
+
+                let press = self.press.take().filter(|press| press.id == touch.id)?;
+                let is_tap = touch.phase == TouchPhase::Ended
+                    && !press.dragging
+                    && !press.long_pressed
+                    && press.started.elapsed() <= TAP_TIME;
+                is_tap.then(|| Gesture::Tap(location))
+            }
+        }
+    }
+
+    /// Detects long presses, which happen without any touch event.
+    pub fn update(&mut self) -> Option<Gesture> {
+        let press = self.press.as_mut()?;
+        if press.dragging || press.long_pressed || press.started.elapsed() < LONG_PRESS_TIME {
+            return None;
+        }
+        press.long_pressed = true;
+        Some(Gesture::LongPress)
+    }
+
+    fn distance(&self) -> Option<f64> {
+        let mut touches = self.touches.values();
+        let (a, b) = (touches.next()?, touches.next()?);
+        Some((a.x - b.x).hypot(a.y - b.y))
+    }
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use winit::{
+    dpi::LogicalSize,
+    event_loop::{EventLoopProxy, EventLoopWindowTarget},
+};
+
+use super::EguiWindow;
+use crate::{download, UserEvent};
+
+/// Window asking for the URL of a model pack to download into `data`.
+pub struct AddModelWindow {
+    pub window: EguiWindow,
+    url: String,
+    /// Feedback shown below the input, such as an invalid URL.
+    message: Option<String>,
+    proxy: EventLoopProxy<UserEvent>,
+}
+
+impl AddModelWindow {
+    pub fn open<T>(
+        target: &EventLoopWindowTarget<T>,
+        proxy: EventLoopProxy<UserEvent>,
+    ) -> Result<Self> {
+        Ok(Self {
+            window: EguiWindow::new(
+                target,
+                "Mon3tr-Widget Add Model",
+                LogicalSize::new(480.0, 120.0),
+            )?,
+            url: String::new(),
+            message: None,
+            proxy,
+        })
+    }
+
+    pub fn redraw(&mut self) {
+        let Self {
+            window,
+            url,
+            message,
+            proxy,
+        } = self;
+
+        let result = window.redraw(|ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label("URL of a model pack (.zip)");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(url);
+                    if ui.button("Download").clicked() {
+                        let url = url.trim();
+                        *message = Some(match download::pack_name(url) {
+                            Some(name) => {
+                                download::start(url.to_string(), true, proxy.clone());
+                                format!("Downloading {}…", name)
+                            }
+                            None => "Not a URL to a model pack".to_string(),
+                        });
+                    }
+                });
+                if let Some(message) = message.as_ref() {
+                    ui.label(message.as_str());
+                }
+            });
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to draw add model window: {}", e);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Secondary windows drawn with egui, for changing settings and inspecting models.
+
+use anyhow::{anyhow, Result};
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use winit::{
+    dpi::LogicalSize,
+    event::WindowEvent,
+    event_loop::{EventLoopProxy, EventLoopWindowTarget},
+    window::{Window, WindowBuilder, WindowId},
+};
+
+use crate::{config::Config, renderer::backend::hardware::display::Display, State, UserEvent};
+
+pub mod add_model;
+pub mod preview;
+pub mod settings;
+pub mod spine_widget;
+
+use add_model::AddModelWindow;
+use preview::AnimationPreview;
+use settings::SettingsPanel;
+
+/// The egui windows that are currently open, at most one of each kind.
+#[derive(Default)]
+pub struct UiWindows {
+    settings: Option<SettingsPanel>,
+    preview: Option<AnimationPreview>,
+    add_model: Option<AddModelWindow>,
+}
+
+impl UiWindows {
+    pub fn open_settings<T>(&mut self, target: &EventLoopWindowTarget<T>, config: &Config) {
+        match &self.settings {
+            Some(panel) => panel.window.window.focus_window(),
+            None => match SettingsPanel::open(target, config) {
+                Ok(panel) => self.settings = Some(panel),
+                Err(e) => log::error!("Failed to open settings: {}", e),
+            },
+        }
+    }
+
+    pub fn open_preview<T>(&mut self, target: &EventLoopWindowTarget<T>) {
+        match &self.preview {
+            Some(preview) => preview.window.window.focus_window(),
+            None => match AnimationPreview::open(target) {
+                Ok(preview) => self.preview = Some(preview),
+                Err(e) => log::error!("Failed to open animation preview: {}", e),
+            },
+        }
+    }
+
+    pub fn open_add_model<T>(
+        &mut self,
+        target: &EventLoopWindowTarget<T>,
+        proxy: EventLoopProxy<UserEvent>,
+    ) {
+        match &self.add_model {
+            Some(add_model) => add_model.window.window.focus_window(),
+            None => match AddModelWindow::open(target, proxy) {
+                Ok(add_model) => self.add_model = Some(add_model),
+                Err(e) => log::error!("Failed to open add model window: {}", e),
+            },
+        }
+    }
+
+    /// Whether the window belongs to one of the egui windows.
+    pub fn owns(&self, id: WindowId) -> bool {
+        self.settings.as_ref().map(|panel| panel.window.id()) == Some(id)
+            || self.preview.as_ref().map(|preview| preview.window.id()) == Some(id)
+            || self
+                .add_model
+                .as_ref()
+                .map(|add_model| add_model.window.id())
+                == Some(id)
+    }
+
+    pub fn on_event(&mut self, id: WindowId, event: &WindowEvent) {
+        let close = matches!(event, WindowEvent::CloseRequested);
+
+        if let Some(panel) = &mut self.settings {
+            if panel.window.id() == id {
+                if close {
+                    self.settings = None;
+                } else {
+                    panel.window.on_event(event);
+                }
+                return;
+            }
+        }
+        if let Some(preview) = &mut self.preview {
+            if preview.window.id() == id {
+                if close {
+                    self.preview = None;
+                } else {
+                    preview.window.on_event(event);
+                }
+                return;
+            }
+        }
+        if let Some(add_model) = &mut self.add_model {
+            if add_model.window.id() == id {
+                if close {
+                    self.add_model = None;
+                } else {
+                    add_model.window.on_event(event);
+                }
+            }
+        }
+    }
+
+    pub fn redraw(
+        &mut self,
+        id: WindowId,
+        state: &mut State,
+        config: &mut Config,
+        config_path: &str,
+    ) {
+        if let Some(panel) = &mut self.settings {
+            if panel.window.id() == id {
+                panel.redraw(state, config, config_path);
+            }
+        }
+        if let Some(preview) = &mut self.preview {
+            if preview.window.id() == id {
+                preview.redraw(state, config);
+            }
+        }
+        if let Some(add_model) = &mut self.add_model {
+            if add_model.window.id() == id {
+                add_model.redraw();
+            }
+        }
+    }
+
+    pub fn request_redraw(&self) {
+        if let Some(panel) = &self.settings {
+            panel.window.window.request_redraw();
+        }
+        if let Some(preview) = &self.preview {
+            preview.window.window.request_redraw();
+        }
+        if let Some(add_model) = &self.add_model {
+            add_model.window.window.request_redraw();
+        }
+    }
+}
+
+/// A normal, decorated window with its own GPU surface that egui draws into.
+pub struct EguiWindow {
+    pub window: Window,
+    display: Display,
+    context: egui::Context,
+    state: egui_winit::State,
+    render_pass: RenderPass,
+}
+
+impl EguiWindow {
+    pub fn new<T>(
+        target: &EventLoopWindowTarget<T>,
+        title: &str,
+        size: LogicalSize<f64>,
+    ) -> Result<Self> {
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .with_inner_size(size)
+            .build(target)?;
+
+        let display = pollster::block_on(Display::new(&window));
+        let render_pass = RenderPass::new(&display.device, display.config.format, 1);
+
+        Ok(Self {
+            state: egui_winit::State::new(&window),
+            context: egui::Context::default(),
+            display,
+            render_pass,
+            window,
+        })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Pass a window event to egui, returns `true` if egui used it.
+    pub fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::Resized(size) => self.display.resize(size.width, size.height),
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => self
+                .display
+                .resize(new_inner_size.width, new_inner_size.height),
+            _ => {}
+        }
+
+        self.state.on_event(&self.context, event)
+    }
+
+    /// Run the UI and draw it to the window.
+    pub fn redraw(&mut self, run_ui: impl FnOnce(&egui::Context)) -> Result<()> {
+        let surface = match &self.display.surface {
+            Some(surface) => surface,
+            None => return Ok(()),
+        };
+        // Minimized
+        if self.display.config.width == 0 || self.display.config.height == 0 {
+            return Ok(());
+        }
+
+        let input = self.state.take_egui_input(&self.window);
+        let output = self.context.run(input, run_ui);
+        self.state
+            .handle_platform_output(&self.window, &self.context, output.platform_output);
+        let paint_jobs = self.context.tessellate(output.shapes);
+
+        let frame = surface.get_current_texture()?;
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let device = &self.display.device;
+        let queue = &self.display.queue;
+        let screen_descriptor = ScreenDescriptor {
+            physical_width: self.display.config.width,
+            physical_height: self.display.config.height,
+            scale_factor: self.window.scale_factor() as f32,
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("egui Encoder"),
+        });
+        self.render_pass
+            .add_textures(device, queue, &output.textures_delta)
+            .map_err(|e| anyhow!("{:?}", e))?;
+        self.render_pass
+            .update_buffers(device, queue, &paint_jobs, &screen_descriptor);
+        self.render_pass
+            .execute(
+                &mut encoder,
+                &view,
+                &paint_jobs,
+                &screen_descriptor,
+                Some(wgpu::Color::BLACK),
+            )
+            .map_err(|e| anyhow!("{:?}", e))?;
+        queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+
+        self.render_pass
+            .remove_textures(output.textures_delta)
+            .map_err(|e| anyhow!("{:?}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Scrub through the animation playing on track 0.
+pub fn track_timeline(ui: &mut egui::Ui, state: &mut State) {
+    let entry = match state
+        .spine
+        .as_mut()
+        .and_then(|spine| spine.anim.current_mut(0))
+    {
+        Some(entry) => entry,
+        None => {
+            ui.label("Nothing playing");
+            return;
+        }
+    };
+
+    let duration = entry.animation().duration();
+    let track_time = entry.track_time();
+    // Looping animations keep counting up, show the time within the current loop
+    let loop_start = if entry.is_loop() && duration > 0.0 {
+        track_time - track_time % duration
+    } else {
+        0.0
+    };
+
+    ui.label(entry.animation().name().to_string());
+    let mut time = (track_time - loop_start).min(duration);
+    if ui
+        .add(egui::Slider::new(&mut time, 0.0..=duration).suffix("s"))
+        .changed()
+    {
+        entry.set_track_time(loop_start + time);
+    }
+}
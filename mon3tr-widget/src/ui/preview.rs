@@ -0,0 +1,168 @@
+use anyhow::Result;
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event_loop::EventLoopWindowTarget,
+};
+
+use super::{spine_widget::SpineEguiWidget, track_timeline, EguiWindow};
+use crate::{config::Config, State};
+
+/// Window to play any animation of the model, for authoring configs for new models.
+pub struct AnimationPreview {
+    pub window: EguiWindow,
+    selected: Option<String>,
+    looping: bool,
+    /// Copy of the model playing the selected animation inside the window.
+    model: Option<SpineEguiWidget>,
+}
+
+impl AnimationPreview {
+    pub fn open<T>(target: &EventLoopWindowTarget<T>) -> Result<Self> {
+        Ok(Self {
+            window: EguiWindow::new(
+                target,
+                "Mon3tr-Widget Animations",
+                LogicalSize::new(560.0, 400.0),
+            )?,
+            selected: None,
+            looping: true,
+            model: None,
+        })
+    }
+
+    pub fn redraw(&mut self, state: &mut State, config: &Config) {
+        let Self {
+            window,
+            selected,
+            looping,
+            model,
+        } = self;
+
+        let pack = state.spine.as_ref().map(|spine| spine.pack.clone());
+        if model.as_ref().map(|model| model.pack()) != pack.as_deref() {
+            *model = pack.and_then(|pack| open_model(&pack, selected.as_deref(), config));
+        }
+
+        let animations: Vec<(String, f32)> = state.spine.as_ref().map_or_else(Vec::new, |spine| {
+            spine
+                .skel_data
+                .animations()
+                .iter()
+                .map(|anim| (anim.name().to_string(), anim.duration()))
+                .collect()
+        });
+
+        let result = window.redraw(|ctx| {
+            egui::SidePanel::left("animation_list").show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (name, duration) in &animations {
+                        let is_selected = selected.as_ref() == Some(name);
+                        if ui
+                            .selectable_label(is_selected, format!("{} ({:.2}s)", name, duration))
+                            .clicked()
+                        {
+                            *selected = Some(name.clone());
+                            play(state, model, name, *looping);
+                        }
+                    }
+                });
+            });
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some(name) = selected.as_ref() {
+                        if ui.button("▶ Play").clicked() {
+                            play(state, model, name, *looping);
+                        }
+                    }
+
+                    let paused = current_time_scale(state) == Some(0.0);
+                    if ui.button(if paused { "Resume" } else { "Pause" }).clicked() {
+                        set_time_scale(state, if paused { 1.0 } else { 0.0 });
+                    }
+
+                    if ui.button("Back to idle").clicked() {
+                        *selected = None;
+                        state.play_idle(config);
+                    }
+
+                    if ui.checkbox(looping, "Loop").changed() {
+                        if let Some(entry) = state
+                            .spine
+                            .as_mut()
+                            .and_then(|spine| spine.anim.current_mut(0))
+                        {
+                            entry.set_loop(*looping);
+                        }
+                    }
+                });
+
+                ui.separator();
+                track_timeline(ui, state);
+
+                if let Some(model) = model {
+                    ui.separator();
+                    egui::ScrollArea::both().show(ui, |ui| model.show(ui));
+                }
+            });
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to draw animation preview: {}", e);
+        }
+    }
+}
+
+/// Play the animation on track 0 without returning to idle afterwards.
+fn play(state: &mut State, model: &mut Option<SpineEguiWidget>, name: &str, looping: bool) {
+    if let Some(spine) = state.spine.as_mut() {
+        spine.anim.set_animation_by_name(0, name, looping);
+    }
+    if let Some(model) = model {
+        model
+            .spine_mut()
+            .anim
+            .set_animation_by_name(0, name, looping);
+    }
+}
+
+/// Load the model of the widget again, to preview animations at the size of the widget.
+fn open_model(pack: &str, animation: Option<&str>, config: &Config) -> Option<SpineEguiWidget> {
+    let size = PhysicalSize::new(
+        config.window_size.0.round() as u32,
+        config.window_size.1.round() as u32,
+    );
+    match SpineEguiWidget::new(pack, size, 1.0, config) {
+        Ok(mut model) => {
+            if let Some(animation) = animation {
+                model
+                    .spine_mut()
+                    .anim
+                    .set_animation_by_name(0, animation, true);
+            }
+            Some(model)
+        }
+        Err(e) => {
+            log::error!("Failed to load preview of {}: {}", pack, e);
+            None
+        }
+    }
+}
+
+fn current_time_scale(state: &State) -> Option<f32> {
+    state
+        .spine
+        .as_ref()
+        .and_then(|spine| spine.anim.current(0))
+        .map(|entry| entry.time_scale())
+}
+
+fn set_time_scale(state: &mut State, time_scale: f32) {
+    if let Some(entry) = state
+        .spine
+        .as_mut()
+        .and_then(|spine| spine.anim.current_mut(0))
+    {
+        entry.set_time_scale(time_scale);
+    }
+}
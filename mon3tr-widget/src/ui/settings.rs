@@ -0,0 +1,148 @@
+use anyhow::Result;
+use winit::{dpi::LogicalSize, event_loop::EventLoopWindowTarget};
+
+use super::{track_timeline, EguiWindow};
+use crate::{
+    config::{self, Command, Config},
+    renderer::Renderer,
+    State,
+};
+
+/// Window to adjust the widget while it runs, instead of editing the YAML by hand.
+pub struct SettingsPanel {
+    pub window: EguiWindow,
+    /// The config being edited as YAML, see [`Self::yaml_editor`].
+    yaml: String,
+    /// Result of the last apply or save.
+    message: Option<String>,
+}
+
+impl SettingsPanel {
+    pub fn open<T>(target: &EventLoopWindowTarget<T>, config: &Config) -> Result<Self> {
+        Ok(Self {
+            window: EguiWindow::new(
+                target,
+                "Mon3tr-Widget Settings",
+                LogicalSize::new(420.0, 640.0),
+            )?,
+            yaml: serde_yaml::to_string(config)?,
+            message: None,
+        })
+    }
+
+    pub fn redraw(&mut self, state: &mut State, config: &mut Config, config_path: &str) {
+        let Self {
+            window,
+            yaml,
+            message,
+        } = self;
+
+        let result = window.redraw(|ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.collapsing("Display", |ui| display_settings(ui, state, config));
+                    ui.collapsing("Animations", |ui| animation_list(ui, state, config));
+                    ui.collapsing("Timeline", |ui| track_timeline(ui, state));
+                    ui.collapsing("Config", |ui| {
+                        yaml_editor(ui, state, config, config_path, yaml, message)
+                    });
+                });
+            });
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to draw settings: {}", e);
+        }
+    }
+}
+
+fn display_settings(ui: &mut egui::Ui, state: &mut State, config: &mut Config) {
+    if ui
+        .add(egui::Slider::new(&mut config.scale, 0.1..=3.0).text("Scale"))
+        .changed()
+    {
+        state.set_model_scale(config.scale);
+    }
+
+    if ui
+        .add(egui::Slider::new(&mut config.bottom_offset, -200.0..=200.0).text("Bottom offset"))
+        .changed()
+    {
+        state.renderer.set_bottom_offset(config.bottom_offset);
+    }
+
+    let mut opacity = state.opacity;
+    if ui
+        .add(egui::Slider::new(&mut opacity, 10..=100).text("Opacity"))
+        .changed()
+    {
+        state.set_opacity(opacity);
+    }
+}
+
+fn animation_list(ui: &mut egui::Ui, state: &mut State, config: &Config) {
+    let animations: Vec<(String, f32)> = match &state.spine {
+        Some(spine) => spine
+            .skel_data
+            .animations()
+            .iter()
+            .map(|anim| (anim.name().to_string(), anim.duration()))
+            .collect(),
+        None => return,
+    };
+
+    egui::Grid::new("animations").striped(true).show(ui, |ui| {
+        for (name, duration) in animations {
+            ui.label(&name);
+            ui.label(format!("{:.2}s", duration));
+            if ui.button("▶").clicked() {
+                state.execute(&Command::Animation(name), config);
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// Settings that are only read on startup, like remote control, still need a restart.
+fn yaml_editor(
+    ui: &mut egui::Ui,
+    state: &mut State,
+    config: &mut Config,
+    config_path: &str,
+    yaml: &mut String,
+    message: &mut Option<String>,
+) {
+    ui.horizontal(|ui| {
+        if ui.button("Reload").clicked() {
+            *yaml = serde_yaml::to_string(config).unwrap_or_default();
+            *message = None;
+        }
+        if ui.button("Apply").clicked() {
+            *message = Some(match serde_yaml::from_str::<Config>(yaml) {
+                Ok(new_config) => {
+                    *config = new_config;
+                    state.set_model_scale(config.scale);
+                    state.renderer.set_bottom_offset(config.bottom_offset);
+                    "Applied".to_string()
+                }
+                Err(e) => e.to_string(),
+            });
+        }
+        if ui.button("Save").clicked() {
+            *message = Some(match config::save(config, config_path) {
+                Ok(()) => format!("Saved to {}", config_path),
+                Err(e) => e.to_string(),
+            });
+        }
+    });
+
+    if let Some(message) = message {
+        ui.label(message.as_str());
+    }
+
+    ui.add(
+        egui::TextEdit::multiline(yaml)
+            .code_editor()
+            .desired_width(f32::INFINITY),
+    );
+}
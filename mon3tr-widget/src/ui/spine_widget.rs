@@ -0,0 +1,88 @@
+use anyhow::Result;
+use winit::dpi::PhysicalSize;
+
+use crate::{
+    buffer::ScratchBuffers,
+    config::Config,
+    renderer::{backend::hardware::HardwareRenderer, Renderer},
+    spine_state::SpineState,
+};
+
+/// A model playing inside an egui UI.
+///
+/// egui 0.17 has no paint callbacks, so the model is rendered offscreen with its own renderer
+/// and shown as an image that is replaced every frame.
+pub struct SpineEguiWidget {
+    renderer: HardwareRenderer,
+    spine: SpineState,
+    scratch_buffers: ScratchBuffers,
+    world_vertices: Vec<[f32; 2]>,
+    size: PhysicalSize<u32>,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl SpineEguiWidget {
+    /// Load the model pack at `pack` and draw it at `size` physical pixels.
+    pub fn new(pack: &str, size: PhysicalSize<u32>, scale: f32, config: &Config) -> Result<Self> {
+        // Created first so textures can be loaded in the formats the GPU supports
+        let mut renderer = pollster::block_on(HardwareRenderer::headless(size, config))?;
+        renderer.set_model_scale(scale);
+        let spine = SpineState::new(pack, None)?;
+
+        Ok(Self {
+            renderer,
+            spine,
+            scratch_buffers: ScratchBuffers::new(),
+            world_vertices: Vec::new(),
+            size,
+            texture: None,
+        })
+    }
+
+    /// Path of the model pack shown.
+    pub fn pack(&self) -> &str {
+        &self.spine.pack
+    }
+
+    pub fn spine_mut(&mut self) -> &mut SpineState {
+        &mut self.spine
+    }
+
+    /// Advance the animation and show the current frame, at its size in points.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        self.spine.prepare_render(|_| {});
+        self.spine.anim.take_events();
+
+        self.renderer.update();
+        self.scratch_buffers.fill(
+            &self.spine.skel,
+            &mut self.renderer,
+            &mut self.world_vertices,
+            1.0,
+        );
+        let frame = self.renderer.capture(&mut self.scratch_buffers);
+        self.scratch_buffers.clear();
+
+        match frame {
+            Ok(frame) => {
+                let size = [frame.width() as usize, frame.height() as usize];
+                let image = egui::ColorImage::from_rgba_unmultiplied(size, &frame);
+                match &mut self.texture {
+                    Some(texture) => texture.set(image),
+                    None => self.texture = Some(ui.ctx().load_texture("spine", image)),
+                }
+            }
+            Err(e) => log::error!("Failed to render {}: {}", self.spine.pack, e),
+        }
+
+        // Keep the animation going
+        ui.ctx().request_repaint();
+
+        let points = egui::vec2(self.size.width as f32, self.size.height as f32)
+            / ui.ctx().pixels_per_point();
+        match &self.texture {
+            Some(texture) => ui.image(texture, points),
+            None => ui.allocate_response(points, egui::Sense::hover()),
+        }
+    }
+}
@@ -1,6 +1,12 @@
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::Result;
+use rand::Rng;
 
 pub fn load_file_packed(path: &str) -> Result<Vec<u8>> {
     let mut buf = vec![];
@@ -23,4 +29,76 @@ pub fn exe_dir_path() -> PathBuf {
         .parent()
         .unwrap()
         .to_path_buf()
-}
\ No newline at end of file
+}
+
+/// Show a folder in the file manager.
+pub fn open_folder(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(not(windows))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program).arg(path).spawn()?;
+    Ok(())
+}
+
+/// Random duration between `min` and `max` seconds.
+pub fn random_duration((min, max): (f32, f32)) -> Duration {
+    let secs = if max > min {
+        rand::thread_rng().gen_range(min..max)
+    } else {
+        min
+    };
+    Duration::from_secs_f32(secs.max(0.0))
+}
+
+/// Pick a random item, with probabilities proportional to `weight`.
+pub fn pick_weighted<T>(items: &[T], weight: impl Fn(&T) -> f32) -> Option<&T> {
+    let total: f32 = items.iter().map(|item| weight(item).max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut roll = rand::thread_rng().gen_range(0.0..total);
+    for item in items {
+        let weight = weight(item).max(0.0);
+        if roll < weight {
+            return Some(item);
+        }
+        roll -= weight;
+    }
+
+    items.last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_weighted_needs_a_positive_weight() {
+        let empty: [f32; 0] = [];
+        assert_eq!(pick_weighted(&empty, |w| *w), None);
+        assert_eq!(pick_weighted(&[0.0, -1.0], |w| *w), None);
+    }
+
+    #[test]
+    fn pick_weighted_skips_items_without_weight() {
+        let items = [("a", 0.0), ("b", -2.0), ("c", 1.0), ("d", 0.0)];
+        for _ in 0..100 {
+            assert_eq!(pick_weighted(&items, |(_, w)| *w).unwrap().0, "c");
+        }
+    }
+
+    #[test]
+    fn pick_weighted_picks_every_weighted_item() {
+        let items = [1.0, 1.0, 1.0];
+        let mut picked = [false; 3];
+        for _ in 0..1000 {
+            let item = pick_weighted(&items, |w| *w).unwrap();
+            let index = items.iter().position(|i| std::ptr::eq(i, item)).unwrap();
+            picked[index] = true;
+        }
+        assert_eq!(picked, [true; 3]);
+    }
+}
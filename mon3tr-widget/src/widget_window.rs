@@ -0,0 +1,147 @@
+use anyhow::Result;
+use winit::{
+    event::{ElementState, MouseButton, WindowEvent},
+    window::{Window, WindowId},
+};
+
+use crate::{
+    buffer::ScratchBuffers,
+    config::{Config, WindowInstance},
+    renderer::{backend, Renderer},
+    spine_state::SpineState,
+    utils::exe_dir_path,
+    window_ext::SpineWidgetWindowExt,
+};
+
+/// Extra top-level window showing a model of its own, driven by the main event loop.
+pub struct WidgetWindow {
+    pub window: Window,
+    renderer: Box<dyn Renderer>,
+    spine: SpineState,
+    scratch_buffers: ScratchBuffers,
+    world_vertices: Vec<[f32; 2]>,
+
+    /// Index into `Config::extra_windows` this window was created from.
+    pub index: usize,
+    /// Shown in the tray menu.
+    pub name: String,
+    pub visible: bool,
+    always_on_bottom: bool,
+}
+
+impl WidgetWindow {
+    pub fn new(
+        window: Window,
+        index: usize,
+        instance: &WindowInstance,
+        config: &Config,
+    ) -> Result<Self> {
+        let mut renderer = pollster::block_on(backend::for_window(&window, config))?;
+        renderer.set_model_scale(instance.scale);
+
+        let mut path = exe_dir_path();
+        path.push("data");
+        path.push(&instance.pack);
+        let idle = instance.idle.as_deref().map(|name| (name, true));
+        let spine = SpineState::new(&path.to_string_lossy(), idle)?;
+
+        Ok(Self {
+            window,
+            renderer,
+            spine,
+            scratch_buffers: ScratchBuffers::new(),
+            world_vertices: Vec::new(),
+            index,
+            name: instance.pack.clone(),
+            visible: true,
+            always_on_bottom: false,
+        })
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.window.set_visible(visible);
+        self.visible = visible;
+    }
+
+    pub fn set_click_passthrough(&self, click_passthrough: bool) {
+        self.window.set_click_passthrough(click_passthrough);
+    }
+
+    pub fn set_always_on_bottom(&mut self, on_bottom: bool) {
+        self.window.set_always_on_bottom(on_bottom);
+        self.always_on_bottom = on_bottom;
+    }
+
+    pub fn on_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => {
+                self.renderer.resize(*size, self.window.scale_factor());
+            }
+            WindowEvent::ScaleFactorChanged {
+                new_inner_size,
+                scale_factor,
+            } => {
+                self.renderer.resize(**new_inner_size, *scale_factor);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                let _ = self.window.drag_window();
+            }
+            WindowEvent::Focused(true) if self.always_on_bottom => {
+                self.window.set_always_on_bottom(true);
+            }
+            WindowEvent::CloseRequested => {
+                self.set_visible(false);
+            }
+            _ => {}
+        }
+    }
+
+    /// Reconfigure the surface for the current window size.
+    pub fn refresh_surface(&mut self) {
+        let size = self.window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            self.renderer.resize(size, self.window.scale_factor());
+        }
+    }
+
+    /// Advance the animation and draw it, `opacity` is from 0 to 1.
+    pub fn redraw(&mut self, opacity: f32, config: &Config) {
+        if self.renderer.is_device_lost() {
+            if let Err(e) = self.renderer.recover(&self.window, config) {
+                log::error!("Failed to recover renderer of {}: {}", self.name, e);
+                return;
+            }
+        }
+
+        self.spine.prepare_render(|_| {});
+        // Nothing reacts to the events of these models
+        self.spine.anim.take_events();
+
+        self.renderer.update();
+        self.scratch_buffers.fill(
+            &self.spine.skel,
+            self.renderer.as_mut(),
+            &mut self.world_vertices,
+            opacity,
+        );
+        let result = self.renderer.render(&mut self.scratch_buffers);
+        self.scratch_buffers.clear();
+
+        if let Err(e) = result {
+            match e.downcast_ref::<wgpu::SurfaceError>() {
+                Some(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    self.refresh_surface();
+                }
+                _ => log::error!("Render error in {}: {:?}", self.name, e),
+            }
+        }
+    }
+}
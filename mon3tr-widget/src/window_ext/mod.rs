@@ -0,0 +1,96 @@
+//! Window features winit doesn't provide, implemented for each platform.
+
+use std::time::Duration;
+
+use winit::dpi::PhysicalPosition;
+
+#[cfg(windows)]
+mod win32;
+#[cfg(windows)]
+use self::win32 as platform;
+
+#[cfg(target_os = "linux")]
+mod wayland;
+#[cfg(target_os = "linux")]
+mod x11;
+#[cfg(target_os = "linux")]
+use self::x11 as platform;
+
+/// Screen rectangle in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+/// Top-level window of another application, see [`list_windows`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignWindow {
+    pub id: u64,
+    pub title: String,
+    pub class: String,
+}
+
+impl ForeignWindow {
+    /// Whether the title contains `title` and the class is exactly `class`, unset ones match
+    /// any window.
+    pub fn matches(&self, title: Option<&str>, class: Option<&str>) -> bool {
+        title.map_or(true, |title| self.title.contains(title))
+            && class.map_or(true, |class| self.class == class)
+    }
+}
+
+pub trait SpineWidgetWindowExt {
+    /// Make this window clickable or not (clicking passthrough)
+    fn set_click_passthrough(&self, passthrough: bool);
+
+    /// Cursor position relative to the client area, even if the cursor is outside of the window.
+    fn cursor_position(&self) -> Option<PhysicalPosition<f64>>;
+
+    /// Work area (screen minus taskbar) of the monitor this window is on.
+    fn work_area(&self) -> Option<ScreenRect>;
+
+    /// Whether nothing of this window can be seen, because it's hidden or minimized.
+    fn is_occluded(&self) -> bool;
+
+    /// Keep this window behind all others instead of on top of them. Activating the window
+    /// brings it forward again, so this has to be repeated when it gets focus.
+    fn set_always_on_bottom(&self, on_bottom: bool);
+
+    /// Fade the whole window as one image, from 0 to 1. Not supported on Wayland.
+    fn set_window_opacity(&self, opacity: f32);
+}
+
+/// Whether the left mouse button is currently held down, regardless of which window has focus.
+pub fn is_left_button_down() -> bool {
+    platform::is_left_button_down()
+}
+
+/// Longest time between two clicks that still makes a double click.
+pub fn double_click_time() -> Duration {
+    platform::double_click_time()
+}
+
+/// Visible windows with a title of other applications, topmost first. Empty on Wayland.
+pub fn list_windows() -> Vec<ForeignWindow> {
+    platform::list_windows()
+}
+
+/// Title and class of a window of another application, from its handle.
+#[cfg(windows)]
+pub fn foreign_window(hwnd: windows::Win32::Foundation::HWND) -> ForeignWindow {
+    platform::describe(hwnd)
+}
+
+/// Whether a window from [`list_windows`] asks for attention, e.g. its taskbar entry flashes.
+#[cfg(target_os = "linux")]
+pub fn demands_attention(id: u64) -> bool {
+    platform::demands_attention(id)
+}
+
+/// Outer rectangle of a window from [`list_windows`], `None` once it is closed or minimized.
+pub fn window_rect(id: u64) -> Option<ScreenRect> {
+    platform::window_rect(id)
+}
@@ -0,0 +1,53 @@
+//! The parts of [`SpineWidgetWindowExt`](super::SpineWidgetWindowExt) Wayland allows.
+//!
+//! Windows are always `xdg_toplevel`s created by winit, which can't be turned into
+//! `wlr-layer-shell` surfaces afterwards, so the widget isn't kept above other windows and
+//! compositors decide where it goes. Only click passthrough works, through the input region.
+
+use std::io;
+
+use wayland_client::{
+    protocol::{wl_compositor::WlCompositor, wl_surface::WlSurface},
+    Display, GlobalManager, Proxy,
+};
+use winit::{platform::unix::WindowExtUnix, window::Window};
+
+/// Whether `window` is a Wayland window, so the X11 requests are pointless.
+pub fn is_wayland(window: &Window) -> bool {
+    window.wayland_surface().is_some()
+}
+
+pub fn set_click_passthrough(window: &Window, passthrough: bool) {
+    if let Err(e) = try_set_click_passthrough(window, passthrough) {
+        log::warn!("Failed to set the input region: {}", e);
+    }
+}
+
+fn try_set_click_passthrough(window: &Window, passthrough: bool) -> io::Result<()> {
+    let (display, surface) = match (window.wayland_display(), window.wayland_surface()) {
+        (Some(display), Some(surface)) => (display, surface),
+        _ => return Ok(()),
+    };
+
+    // Our own queue on winit's connection, just to get at the compositor
+    let display = unsafe { Display::from_external_display(display as *mut _) };
+    let mut queue = display.create_event_queue();
+    let globals = GlobalManager::new(&(*display).clone().attach(queue.token()));
+    queue.sync_roundtrip(&mut (), |_, _, _| {})?;
+    let compositor = globals
+        .instantiate_exact::<WlCompositor>(1)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+    let surface = WlSurface::from(unsafe { Proxy::<WlSurface>::from_c_ptr(surface as *mut _) });
+    if passthrough {
+        // Clicks fall through wherever the input region is empty
+        let region = compositor.create_region();
+        surface.set_input_region(Some(&*region));
+        region.destroy();
+    } else {
+        // No region resets it to the whole surface
+        surface.set_input_region(None);
+    }
+    surface.commit();
+    display.flush()
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use winit::{dpi::PhysicalPosition, platform::windows::WindowExtWindows, window::Window};
+
+use windows::Win32::{
+    Foundation::{BOOL, HWND, LPARAM, POINT, PWSTR, RECT},
+    Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, ScreenToClient, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    },
+    UI::{
+        Input::KeyboardAndMouse::{GetAsyncKeyState, GetDoubleClickTime, VK_LBUTTON},
+        WindowsAndMessaging::{
+            EnumWindows, GetClassNameW, GetCursorPos, GetWindowLongPtrW, GetWindowRect,
+            GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow, IsWindowVisible,
+            SetLayeredWindowAttributes, SetWindowLongPtrW, SetWindowPos, GWL_EXSTYLE, HWND_BOTTOM,
+            HWND_TOPMOST, LWA_ALPHA, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, WINDOW_EX_STYLE,
+            WS_EX_LAYERED, WS_EX_TRANSPARENT,
+        },
+    },
+};
+
+use super::{ForeignWindow, ScreenRect, SpineWidgetWindowExt};
+
+impl SpineWidgetWindowExt for Window {
+    fn set_click_passthrough(&self, passthrough: bool) {
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            let window_styles: WINDOW_EX_STYLE = match GetWindowLongPtrW(hwnd, GWL_EXSTYLE) {
+                0 => panic!("GetWindowLongPtrW failed"),
+                n => n.try_into().unwrap(),
+            };
+
+            let window_styles = if passthrough {
+                window_styles | WS_EX_TRANSPARENT | WS_EX_LAYERED //| WS_EX_TOOLWINDOW
+            } else {
+                window_styles & !WS_EX_TRANSPARENT | WS_EX_LAYERED //| WS_EX_TOOLWINDOW
+            };
+
+            if SetWindowLongPtrW(hwnd, GWL_EXSTYLE, window_styles.try_into().unwrap()) == 0 {
+                panic!("SetWindowLongPtrW failed");
+            }
+        }
+        // Also hides the window from the task switcher while disabled
+        self.set_enable(!passthrough);
+    }
+
+    fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            let mut point = POINT::default();
+            if !GetCursorPos(&mut point).as_bool() || !ScreenToClient(hwnd, &mut point).as_bool() {
+                return None;
+            }
+            Some(PhysicalPosition::new(point.x as f64, point.y as f64))
+        }
+    }
+
+    fn work_area(&self) -> Option<ScreenRect> {
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+                return None;
+            }
+            Some(ScreenRect {
+                left: info.rcWork.left,
+                top: info.rcWork.top,
+                right: info.rcWork.right,
+                bottom: info.rcWork.bottom,
+            })
+        }
+    }
+
+    fn is_occluded(&self) -> bool {
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool()
+        }
+    }
+
+    fn set_always_on_bottom(&self, on_bottom: bool) {
+        // Going to the bottom also drops the topmost style
+        let insert_after = if on_bottom { HWND_BOTTOM } else { HWND_TOPMOST };
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            SetWindowPos(
+                hwnd,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+
+    fn set_window_opacity(&self, opacity: f32) {
+        // Relies on the layered style, which `set_click_passthrough` always adds
+        let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+        unsafe {
+            let hwnd: HWND = std::mem::transmute(self.hwnd());
+            SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+        }
+    }
+}
+
+pub fn is_left_button_down() -> bool {
+    unsafe { GetAsyncKeyState(VK_LBUTTON as i32) < 0 }
+}
+
+pub fn double_click_time() -> Duration {
+    Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
+
+pub fn list_windows() -> Vec<ForeignWindow> {
+    unsafe extern "system" fn push_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<ForeignWindow>);
+
+        let mut process_id = 0;
+        GetWindowThreadProcessId(hwnd, &mut process_id);
+        if !IsWindowVisible(hwnd).as_bool() || process_id == std::process::id() {
+            return true.into();
+        }
+
+        let window = describe(hwnd);
+        if !window.title.is_empty() {
+            windows.push(window);
+        }
+        true.into()
+    }
+
+    // Enumerated in Z order, topmost first
+    let mut windows = Vec::new();
+    unsafe {
+        EnumWindows(Some(push_window), LPARAM(&mut windows as *mut _ as isize));
+    }
+    windows
+}
+
+pub fn describe(hwnd: HWND) -> ForeignWindow {
+    unsafe {
+        ForeignWindow {
+            id: hwnd.0 as u64,
+            title: read_string(|buffer, len| GetWindowTextW(hwnd, buffer, len)),
+            class: read_string(|buffer, len| GetClassNameW(hwnd, buffer, len)),
+        }
+    }
+}
+
+pub fn window_rect(id: u64) -> Option<ScreenRect> {
+    unsafe {
+        let hwnd = HWND(id as isize);
+        if !IsWindow(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+            return None;
+        }
+        let mut rect = RECT::default();
+        if !GetWindowRect(hwnd, &mut rect).as_bool() {
+            return None;
+        }
+        Some(ScreenRect {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        })
+    }
+}
+
+/// Read a string from a Win32 function that fills a buffer and returns the length written.
+fn read_string(read: impl FnOnce(PWSTR, i32) -> i32) -> String {
+    let mut buffer = [0u16; 256];
+    let len = read(PWSTR(buffer.as_mut_ptr()), buffer.len() as i32);
+    String::from_utf16_lossy(&buffer[..len.max(0) as usize])
+}
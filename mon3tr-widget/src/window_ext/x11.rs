@@ -0,0 +1,276 @@
+use std::{error::Error, time::Duration};
+
+use winit::{dpi::PhysicalPosition, platform::unix::WindowExtUnix, window::Window};
+use x11rb::{
+    connection::Connection,
+    protocol::{
+        shape::{ConnectionExt as _, SK, SO},
+        xproto::{
+            AtomEnum, ClientMessageEvent, ClipOrdering, ConnectionExt as _, EventMask, KeyButMask,
+            MapState, PropMode,
+        },
+    },
+    rust_connection::RustConnection,
+    wrapper::ConnectionExt as _,
+};
+
+use super::{wayland, ForeignWindow, ScreenRect, SpineWidgetWindowExt};
+
+/// Not configurable through X11 itself, this is the GTK default.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// `_NET_WM_STATE` client message actions.
+const NET_WM_STATE_REMOVE: u32 = 0;
+const NET_WM_STATE_ADD: u32 = 1;
+
+type X11Result<T> = Result<T, Box<dyn Error>>;
+
+thread_local! {
+    /// Our own connection to the server winit uses, window IDs are the same across clients.
+    static CONNECTION: Option<(RustConnection, usize)> = match x11rb::connect(None) {
+        Ok(connection) => Some(connection),
+        Err(e) => {
+            log::warn!("Failed to connect to the X server: {}", e);
+            None
+        }
+    };
+}
+
+/// Run `f` with the connection and the root window, `None` if not on X11 or on errors.
+fn with_connection<T>(f: impl FnOnce(&RustConnection, u32) -> X11Result<T>) -> Option<T> {
+    CONNECTION.with(|connection| {
+        let (conn, screen) = connection.as_ref()?;
+        let root = conn.setup().roots[*screen].root;
+        match f(conn, root) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log::warn!("X11 request failed: {}", e);
+                None
+            }
+        }
+    })
+}
+
+/// Like [`with_connection`], also passing the X11 ID of `window`, which Wayland windows lack.
+fn with_window<T>(
+    window: &Window,
+    f: impl FnOnce(&RustConnection, u32, u32) -> X11Result<T>,
+) -> Option<T> {
+    let id = window.xlib_window()? as u32;
+    with_connection(|conn, root| f(conn, root, id))
+}
+
+fn atom(conn: &RustConnection, name: &str) -> X11Result<u32> {
+    Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+}
+
+impl SpineWidgetWindowExt for Window {
+    fn set_click_passthrough(&self, passthrough: bool) {
+        if wayland::is_wayland(self) {
+            wayland::set_click_passthrough(self, passthrough);
+            return;
+        }
+        with_window(self, |conn, _, window| {
+            if passthrough {
+                // Clicks fall through wherever the input region is empty
+                conn.shape_rectangles(
+                    SO::SET,
+                    SK::INPUT,
+                    ClipOrdering::UNSORTED,
+                    window,
+                    0,
+                    0,
+                    &[],
+                )?;
+            } else {
+                // No mask resets the input region to the whole window
+                conn.shape_mask(SO::SET, SK::INPUT, window, 0, 0, x11rb::NONE)?;
+            }
+            conn.flush()?;
+            Ok(())
+        });
+    }
+
+    fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        with_window(self, |conn, _, window| {
+            let pointer = conn.query_pointer(window)?.reply()?;
+            Ok(PhysicalPosition::new(
+                pointer.win_x as f64,
+                pointer.win_y as f64,
+            ))
+        })
+    }
+
+    fn work_area(&self) -> Option<ScreenRect> {
+        // Covers all monitors together, X11 has no standard way to get it per monitor
+        with_connection(|conn, root| {
+            let net_workarea = atom(conn, "_NET_WORKAREA")?;
+            let reply = conn
+                .get_property(false, root, net_workarea, AtomEnum::CARDINAL, 0, 4)?
+                .reply()?;
+            let area: Vec<u32> = reply.value32().ok_or("Invalid _NET_WORKAREA")?.collect();
+            match area[..] {
+                [x, y, width, height] => Ok(ScreenRect {
+                    left: x as i32,
+                    top: y as i32,
+                    right: (x + width) as i32,
+                    bottom: (y + height) as i32,
+                }),
+                _ => Err("Invalid _NET_WORKAREA".into()),
+            }
+        })
+    }
+
+    fn is_occluded(&self) -> bool {
+        with_window(self, |conn, _, window| {
+            let attributes = conn.get_window_attributes(window)?.reply()?;
+            Ok(attributes.map_state != MapState::VIEWABLE)
+        })
+        .unwrap_or(false)
+    }
+
+    fn set_always_on_bottom(&self, on_bottom: bool) {
+        with_window(self, |conn, root, window| {
+            let net_wm_state = atom(conn, "_NET_WM_STATE")?;
+            let above = atom(conn, "_NET_WM_STATE_ABOVE")?;
+            let below = atom(conn, "_NET_WM_STATE_BELOW")?;
+
+            // Ask the window manager, like winit does for always on top
+            let (remove, add) = if on_bottom {
+                (above, below)
+            } else {
+                (below, above)
+            };
+            for (action, state) in [(NET_WM_STATE_REMOVE, remove), (NET_WM_STATE_ADD, add)] {
+                let event =
+                    ClientMessageEvent::new(32, window, net_wm_state, [action, state, 0, 1, 0]);
+                conn.send_event(
+                    false,
+                    root,
+                    EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                    event,
+                )?;
+            }
+            conn.flush()?;
+            Ok(())
+        });
+    }
+
+    fn set_window_opacity(&self, opacity: f32) {
+        // Read by compositing window managers, from 0 to `u32::MAX`
+        with_window(self, |conn, _, window| {
+            let net_wm_window_opacity = atom(conn, "_NET_WM_WINDOW_OPACITY")?;
+            let value = (opacity.clamp(0.0, 1.0) as f64 * u32::MAX as f64).round() as u32;
+            conn.change_property32(
+                PropMode::REPLACE,
+                window,
+                net_wm_window_opacity,
+                AtomEnum::CARDINAL,
+                &[value],
+            )?;
+            conn.flush()?;
+            Ok(())
+        });
+    }
+}
+
+pub fn is_left_button_down() -> bool {
+    with_connection(|conn, root| {
+        let pointer = conn.query_pointer(root)?.reply()?;
+        Ok(pointer.mask & u16::from(KeyButMask::BUTTON1) != 0)
+    })
+    .unwrap_or(false)
+}
+
+pub fn double_click_time() -> Duration {
+    DOUBLE_CLICK_TIME
+}
+
+pub fn list_windows() -> Vec<ForeignWindow> {
+    with_connection(|conn, root| {
+        let client_list = atom(conn, "_NET_CLIENT_LIST_STACKING")?;
+        let net_wm_name = atom(conn, "_NET_WM_NAME")?;
+        let net_wm_pid = atom(conn, "_NET_WM_PID")?;
+        let utf8_string = atom(conn, "UTF8_STRING")?;
+
+        let reply = conn
+            .get_property(false, root, client_list, AtomEnum::WINDOW, 0, u32::MAX)?
+            .reply()?;
+        let ids: Vec<u32> = reply
+            .value32()
+            .ok_or("Invalid _NET_CLIENT_LIST_STACKING")?
+            .collect();
+
+        let mut windows = Vec::new();
+        // Stacked bottom to top
+        for id in ids.into_iter().rev() {
+            let pid = conn
+                .get_property(false, id, net_wm_pid, AtomEnum::CARDINAL, 0, 1)?
+                .reply()?
+                .value32()
+                .and_then(|mut pid| pid.next());
+            if pid == Some(std::process::id()) {
+                continue;
+            }
+
+            let title = conn
+                .get_property(false, id, net_wm_name, utf8_string, 0, 1024)?
+                .reply()?;
+            let title = String::from_utf8_lossy(&title.value).into_owned();
+            if title.is_empty() {
+                continue;
+            }
+
+            // Instance and class name, each terminated by a null byte
+            let class = conn
+                .get_property(false, id, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)?
+                .reply()?;
+            let class = class
+                .value
+                .split(|&byte| byte == 0)
+                .nth(1)
+                .map(|class| String::from_utf8_lossy(class).into_owned())
+                .unwrap_or_default();
+
+            windows.push(ForeignWindow {
+                id: id as u64,
+                title,
+                class,
+            });
+        }
+        Ok(windows)
+    })
+    .unwrap_or_default()
+}
+
+pub fn demands_attention(id: u64) -> bool {
+    with_connection(|conn, _| {
+        let net_wm_state = atom(conn, "_NET_WM_STATE")?;
+        let demands_attention = atom(conn, "_NET_WM_STATE_DEMANDS_ATTENTION")?;
+        let reply = conn
+            .get_property(false, id as u32, net_wm_state, AtomEnum::ATOM, 0, 64)?
+            .reply()?;
+        Ok(reply.value32().map_or(false, |mut states| {
+            states.any(|state| state == demands_attention)
+        }))
+    })
+    .unwrap_or(false)
+}
+
+pub fn window_rect(id: u64) -> Option<ScreenRect> {
+    with_connection(|conn, root| {
+        let window = id as u32;
+        if conn.get_window_attributes(window)?.reply()?.map_state != MapState::VIEWABLE {
+            return Ok(None);
+        }
+        let geometry = conn.get_geometry(window)?.reply()?;
+        let origin = conn.translate_coordinates(window, root, 0, 0)?.reply()?;
+        Ok(Some(ScreenRect {
+            left: origin.dst_x as i32,
+            top: origin.dst_y as i32,
+            right: origin.dst_x as i32 + geometry.width as i32,
+            bottom: origin.dst_y as i32 + geometry.height as i32,
+        }))
+    })
+    .flatten()
+}
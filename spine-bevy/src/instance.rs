@@ -0,0 +1,208 @@
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::Result;
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use spine::{
+    AnimationState, AnimationStateData, RenderCommand, RenderCommands, Skeleton, SkeletonData,
+};
+
+use crate::{material::SpineMaterial, SpineSkeleton};
+
+/// Depth between the draw calls of a skeleton, so Bevy sorts them in draw order.
+const BATCH_DEPTH: f32 = 0.001;
+
+/// A skeleton and the animations playing on it.
+pub struct SpineInstance {
+    pub skel: Skeleton,
+    pub anim: AnimationState,
+}
+
+impl SpineInstance {
+    pub fn new(data: &SkeletonData) -> Result<Self> {
+        let anim_data = AnimationStateData::new(data, 0.0)?;
+        Ok(Self {
+            skel: Skeleton::new(data)?,
+            anim: AnimationState::new(&anim_data)?,
+        })
+    }
+
+    /// Advance the animations and pose the skeleton.
+    pub fn step(&mut self, delta: f32) {
+        self.anim.update(delta);
+        self.skel.apply_animation(&self.anim);
+        self.skel.update_world_transform();
+    }
+}
+
+/// Added to a [`SpineBundle`] once its skeleton is loaded, look for `Added<Spine>` to start
+/// animations.
+///
+/// Skeletons aren't `Sync` and components have to be, so the instance is behind a mutex. It's
+/// never locked, [`Self::get_mut`] needs the component borrowed mutably anyway.
+#[derive(Component)]
+pub struct Spine(Mutex<SpineInstance>);
+
+impl Spine {
+    pub fn get_mut(&mut self) -> &mut SpineInstance {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A skeleton to show, drawn once it's loaded.
+#[derive(Bundle, Default)]
+pub struct SpineBundle {
+    pub skeleton: Handle<SpineSkeleton>,
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub computed_visibility: ComputedVisibility,
+}
+
+/// Child entities drawing the [`spine::RenderCommand`]s of a skeleton, one each.
+#[derive(Component, Default)]
+pub(crate) struct SpineBatches {
+    commands: RenderCommands,
+    world_vertices: Vec<[f32; 2]>,
+    batches: Vec<Batch>,
+}
+
+struct Batch {
+    entity: Entity,
+    mesh: Handle<Mesh>,
+    material: Handle<SpineMaterial>,
+}
+
+pub(crate) fn spawn_instances(
+    mut commands: Commands,
+    skeletons: Res<Assets<SpineSkeleton>>,
+    query: Query<(Entity, &Handle<SpineSkeleton>), Without<Spine>>,
+) {
+    for (entity, handle) in query.iter() {
+        let skeleton = match skeletons.get(handle) {
+            Some(skeleton) => skeleton,
+            None => continue,
+        };
+        match SpineInstance::new(&skeleton.data) {
+            Ok(instance) => {
+                commands
+                    .entity(entity)
+                    .insert(Spine(Mutex::new(instance)))
+                    .insert(SpineBatches::default());
+            }
+            Err(e) => log::error!("Failed to create a skeleton: {}", e),
+        }
+    }
+}
+
+pub(crate) fn update_instances(time: Res<Time>, mut query: Query<&mut Spine>) {
+    let delta = time.delta_seconds();
+    for mut spine in query.iter_mut() {
+        spine.get_mut().step(delta);
+    }
+}
+
+/// Turn the posed skeletons into meshes, with [`RenderCommands`] batching them.
+pub(crate) fn build_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<SpineMaterial>>,
+    mut skeletons: Query<(Entity, &mut Spine, &mut SpineBatches, &Visibility)>,
+    mut children: Query<&mut Visibility, Without<Spine>>,
+) {
+    for (entity, mut spine, mut spine_batches, visibility) in skeletons.iter_mut() {
+        let SpineBatches {
+            commands: render_commands,
+            world_vertices,
+            batches,
+        } = &mut *spine_batches;
+
+        let mut used = 0;
+        for command in render_commands.extract(&spine.get_mut().skel, world_vertices) {
+            // Pages are only missing if the atlas failed to load them
+            let texture = match unsafe { command.page.render_object::<Handle<Image>>() } {
+                Some(texture) => texture.clone_weak(),
+                None => continue,
+            };
+
+            if used == batches.len() {
+                let mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+                let material = materials.add(SpineMaterial {
+                    texture: texture.clone_weak(),
+                    blend_mode: command.blend_mode,
+                });
+                let child = commands
+                    .spawn_bundle(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(mesh.clone()),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(0.0, 0.0, used as f32 * BATCH_DEPTH),
+                        ..Default::default()
+                    })
+                    .id();
+                commands.entity(entity).add_child(child);
+                batches.push(Batch {
+                    entity: child,
+                    mesh,
+                    material,
+                });
+            }
+
+            let batch = &batches[used];
+            if let Some(mesh) = meshes.get_mut(&batch.mesh) {
+                fill_mesh(mesh, &command);
+            }
+            // Only touch the material when it changes, that prepares it again
+            let changed = materials.get(&batch.material).map_or(false, |material| {
+                material.texture != texture || material.blend_mode != command.blend_mode
+            });
+            if changed {
+                if let Some(material) = materials.get_mut(&batch.material) {
+                    material.texture = texture;
+                    material.blend_mode = command.blend_mode;
+                }
+            }
+            if let Ok(mut child) = children.get_mut(batch.entity) {
+                child.is_visible = visibility.is_visible;
+            }
+            used += 1;
+        }
+
+        // Left over from frames that took more draw calls
+        for batch in &batches[used..] {
+            if let Ok(mut child) = children.get_mut(batch.entity) {
+                child.is_visible = false;
+            }
+        }
+    }
+}
+
+fn fill_mesh(mesh: &mut Mesh, command: &RenderCommand) {
+    let vertices = command.vertices;
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vertices
+            .iter()
+            .map(|vertex| [vertex.position[0], vertex.position[1], 0.0])
+            .collect::<Vec<_>>(),
+    );
+    // Not used by the shader, but every 2D mesh pipeline asks for them
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 0.0, 1.0]; vertices.len()],
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vertices.iter().map(|vertex| vertex.uv).collect::<Vec<_>>(),
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        vertices
+            .iter()
+            .map(|vertex| vertex.color)
+            .collect::<Vec<_>>(),
+    );
+    mesh.set_indices(Some(Indices::U16(command.indices.to_vec())));
+}
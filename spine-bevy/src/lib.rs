@@ -0,0 +1,94 @@
+//! # spine-bevy
+//!
+//! Shows Spine skeletons in Bevy 2D scenes. Add [`SpinePlugin`], then spawn a [`SpineBundle`]
+//! with a `.skel` loaded by the asset server, whose atlas has to be next to it with the same
+//! name. Once it's loaded, the entity gets a [`Spine`] component to play animations with:
+//!
+//! ```ignore
+//! fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+//!     commands.spawn_bundle(SpineBundle {
+//!         skeleton: asset_server.load("char.skel"),
+//!         ..Default::default()
+//!     });
+//! }
+//!
+//! fn start(mut query: Query<&mut Spine, Added<Spine>>) {
+//!     for mut spine in query.iter_mut() {
+//!         spine.get_mut().anim.set_animation_by_name(0, "Idle", true);
+//!     }
+//! }
+//! ```
+//!
+//! Every draw call of a skeleton is a child entity with a 2D mesh, built each frame from the
+//! [`spine::RenderCommands`] of the skeleton.
+
+mod instance;
+mod loader;
+mod material;
+
+use bevy::{prelude::*, sprite::Material2dPlugin};
+
+pub use instance::{Spine, SpineBundle, SpineInstance};
+pub use loader::{SpineAtlas, SpineAtlasLoader, SpineSkeleton, SpineSkeletonLoader};
+pub use material::SpineMaterial;
+
+/// Every atlas loaded here comes with its own loader, spine-c never needs these.
+#[cfg(feature = "callbacks")]
+mod callbacks {
+    use anyhow::{anyhow, Result};
+
+    pub struct NoCallbacks;
+    impl spine::SpineCallbacks for NoCallbacks {
+        type Texture = ();
+
+        type LoadTextureError = anyhow::Error;
+        type LoadFileError = anyhow::Error;
+
+        fn load_texture(path: &str, _: &spine::AtlasPage) -> Result<((), u32, u32)> {
+            Err(anyhow!("{} has to be loaded by the asset server", path))
+        }
+
+        fn load_file(path: &str) -> Result<Vec<u8>> {
+            Err(anyhow!("{} has to be loaded by the asset server", path))
+        }
+    }
+    spine::spine_init!(NoCallbacks);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub enum SpineSystem {
+    /// Advances the animations of every [`Spine`] by the frame time.
+    Update,
+    /// Builds the meshes of the posed skeletons.
+    BuildMeshes,
+}
+
+pub struct SpinePlugin;
+
+impl Plugin for SpinePlugin {
+    fn build(&self, app: &mut App) {
+        app.world.resource_mut::<Assets<Shader>>().set_untracked(
+            material::SPINE_SHADER_HANDLE,
+            Shader::from_wgsl(include_str!("spine.wgsl")),
+        );
+
+        app.add_plugin(Material2dPlugin::<SpineMaterial>::default())
+            .add_asset::<SpineAtlas>()
+            .add_asset::<SpineSkeleton>()
+            .init_asset_loader::<SpineAtlasLoader>()
+            .init_asset_loader::<SpineSkeletonLoader>()
+            .add_system_to_stage(CoreStage::PreUpdate, instance::spawn_instances)
+            // After the game changed animations in Update
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                instance::update_instances.label(SpineSystem::Update),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                instance::build_meshes
+                    .label(SpineSystem::BuildMeshes)
+                    .after(SpineSystem::Update),
+            );
+    }
+}
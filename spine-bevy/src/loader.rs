@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use bevy::{
+    asset::{AssetLoader, AssetPath, HandleId, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use spine::{Atlas, AtlasPage, SkeletonData};
+
+/// An atlas loaded from a `.atlas` file, its pages are loaded as [`Image`]s next to it.
+#[derive(Debug, TypeUuid)]
+#[uuid = "8b3f4a0e-5d2c-4f61-9a7e-3c1b6e2d90f4"]
+pub struct SpineAtlas {
+    pub atlas: Atlas,
+    /// Keep the pages loaded for as long as the atlas is.
+    pub textures: Vec<Handle<Image>>,
+}
+
+/// Skeleton data loaded from a `.skel` file, with the `.atlas` of the same name next to it.
+#[derive(Debug, TypeUuid)]
+#[uuid = "d0a6c1e2-7b49-4e0f-8f35-62a9c4b1f7d3"]
+pub struct SpineSkeleton {
+    pub data: SkeletonData,
+    /// Pages of the atlas the skeleton was loaded with.
+    pub textures: Vec<Handle<Image>>,
+}
+
+#[derive(Default)]
+pub struct SpineAtlasLoader;
+
+impl AssetLoader for SpineAtlasLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let path = path_str(load_context.path())?.to_owned();
+            let mut files = Files::default();
+            files.insert(&path, bytes.to_vec());
+
+            let (atlas, pages) = files.load_atlas(&path)?;
+            let (textures, dependencies) = page_handles(load_context, pages);
+            load_context.set_default_asset(
+                LoadedAsset::new(SpineAtlas { atlas, textures }).with_dependencies(dependencies),
+            );
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["atlas"]
+    }
+}
+
+/// Loads binary skeletons. Bevy can't hand one loaded asset to the loader of another, so each
+/// skeleton reads its atlas again. The pages are shared, they're only loaded once.
+#[derive(Default)]
+pub struct SpineSkeletonLoader;
+
+impl AssetLoader for SpineSkeletonLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let path = path_str(load_context.path())?.to_owned();
+            let atlas_path = path_str(&load_context.path().with_extension("atlas"))?.to_owned();
+            let mut files = Files::default();
+            files.insert(
+                &atlas_path,
+                load_context.read_asset_bytes(&atlas_path).await?,
+            );
+            files.insert(&path, bytes.to_vec());
+
+            let (atlas, pages) = files.load_atlas(&atlas_path)?;
+            let data = SkeletonData::try_new_binary(&atlas, &path, 1.0)?;
+            let (textures, dependencies) = page_handles(load_context, pages);
+            load_context.set_default_asset(
+                LoadedAsset::new(SpineSkeleton { data, textures }).with_dependencies(dependencies),
+            );
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skel"]
+    }
+}
+
+/// spine-c joins page names to the directory of the atlas as strings.
+fn path_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or_else(|| anyhow!("{} is not valid UTF-8", path.display()))
+}
+
+/// Strong handles of the pages an atlas asked for, which keep them loaded, and their paths to
+/// load them as dependencies.
+fn page_handles(
+    load_context: &LoadContext,
+    pages: Vec<AssetPath<'static>>,
+) -> (Vec<Handle<Image>>, Vec<AssetPath<'static>>) {
+    let textures = pages
+        .iter()
+        .map(|page| load_context.get_handle(page.clone()))
+        .collect();
+    (textures, pages)
+}
+
+/// Serves spine-c the files an asset loader already read, and hands out weak handles of the
+/// pages it asks for as their textures.
+#[derive(Default)]
+struct Files {
+    files: HashMap<String, Vec<u8>>,
+    /// Shared with the asset loader, which turns them into dependencies.
+    pages: Arc<Mutex<Vec<AssetPath<'static>>>>,
+}
+
+impl Files {
+    fn insert(&mut self, path: &str, data: Vec<u8>) {
+        self.files.insert(path.to_owned(), data);
+    }
+
+    /// The atlas at `path`, and the paths of the pages it loaded.
+    fn load_atlas(self, path: &str) -> Result<(Atlas, Vec<AssetPath<'static>>)> {
+        let pages = self.pages.clone();
+        let atlas = Atlas::with_loader(path, self)?;
+        let pages = std::mem::take(&mut *pages.lock().unwrap());
+        Ok((atlas, pages))
+    }
+}
+
+impl spine::AtlasLoader for Files {
+    type Texture = Handle<Image>;
+
+    fn load_texture(&mut self, path: &str, page: &AtlasPage) -> Result<(Handle<Image>, u32, u32)> {
+        let path = AssetPath::from(path).to_owned();
+        let handle = Handle::weak(HandleId::from(path.clone()));
+        self.pages.lock().unwrap().push(path);
+        // The image loads later, go by the size the atlas gives for the page
+        Ok((handle, page.width(), page.height()))
+    }
+
+    fn load_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow!("{} was not read by the asset loader", path))
+    }
+}
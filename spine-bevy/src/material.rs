@@ -0,0 +1,159 @@
+use bevy::{
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_asset::{PrepareAssetError, RenderAsset, RenderAssets},
+        render_resource::*,
+        renderer::RenderDevice,
+    },
+    sprite::{Material2dPipeline, SpecializedMaterial2d},
+};
+use spine::BlendMode;
+
+pub const SPINE_SHADER_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Shader::TYPE_UUID, 0x5d1e_93a7_c2b4_0f68);
+
+/// Draws the triangles of a [`spine::RenderCommand`] with a page of the atlas, tinted by the
+/// vertex colors and blended the way Spine does.
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "3e7c2b91-a4d8-4c5f-b06e-91f2d8a7c413"]
+pub struct SpineMaterial {
+    pub texture: Handle<Image>,
+    pub blend_mode: BlendMode,
+}
+
+pub struct GpuSpineMaterial {
+    bind_group: BindGroup,
+    blend_mode: BlendMode,
+}
+
+impl RenderAsset for SpineMaterial {
+    type ExtractedAsset = SpineMaterial;
+    type PreparedAsset = GpuSpineMaterial;
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<Material2dPipeline<SpineMaterial>>,
+        SRes<RenderAssets<Image>>,
+    );
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, pipeline, gpu_images): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let (texture_view, sampler) = match pipeline
+            .mesh2d_pipeline
+            .get_image_texture(gpu_images, &Some(material.texture.clone_weak()))
+        {
+            Some(texture) => texture,
+            // The page is still loading
+            None => return Err(PrepareAssetError::RetryNextUpdate(material)),
+        };
+
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(texture_view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("spine_material_bind_group"),
+            layout: &pipeline.material2d_layout,
+        });
+
+        Ok(GpuSpineMaterial {
+            bind_group,
+            blend_mode: material.blend_mode,
+        })
+    }
+}
+
+impl SpecializedMaterial2d for SpineMaterial {
+    type Key = BlendMode;
+
+    fn key(_: &RenderDevice, material: &GpuSpineMaterial) -> BlendMode {
+        material.blend_mode
+    }
+
+    fn specialize(
+        blend_mode: BlendMode,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.vertex.buffers = vec![layout.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_UV_0.at_shader_location(1),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(2),
+        ])?];
+        // Bones scaled by a negative amount turn triangles around
+        descriptor.primitive.cull_mode = None;
+        if let Some(fragment) = &mut descriptor.fragment {
+            fragment.targets[0].blend = Some(blend_state(blend_mode));
+        }
+        Ok(())
+    }
+
+    fn bind_group(material: &GpuSpineMaterial) -> &BindGroup {
+        &material.bind_group
+    }
+
+    fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        multisampled: false,
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("spine_material_layout"),
+        })
+    }
+
+    fn vertex_shader(_: &AssetServer) -> Option<Handle<Shader>> {
+        Some(SPINE_SHADER_HANDLE.typed())
+    }
+
+    fn fragment_shader(_: &AssetServer) -> Option<Handle<Shader>> {
+        Some(SPINE_SHADER_HANDLE.typed())
+    }
+}
+
+/// Spine's blend modes for straight alpha textures.
+fn blend_state(mode: BlendMode) -> BlendState {
+    let color = |src_factor, dst_factor| BlendComponent {
+        src_factor,
+        dst_factor,
+        operation: BlendOperation::Add,
+    };
+    use BlendFactor::*;
+    BlendState {
+        color: match mode {
+            BlendMode::Normal => color(SrcAlpha, OneMinusSrcAlpha),
+            BlendMode::Additive => color(SrcAlpha, One),
+            BlendMode::Multiply => color(Dst, OneMinusSrcAlpha),
+            BlendMode::Screen => color(One, OneMinusSrc),
+        },
+        alpha: BlendComponent::OVER,
+    }
+}
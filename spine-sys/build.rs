@@ -1,17 +1,228 @@
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-fn main() {
-    let dst = cmake::build("spine-runtimes/spine-c");
+/// Features picking a spine-c version other than 3.5, with the branch to build.
+const VERSION_FEATURES: [(&str, &str); 3] = [
+    ("SPINE_3_8", "3.8"),
+    ("SPINE_4_1", "4.1"),
+    ("SPINE_4_2", "4.2"),
+];
+
+/// Version selected with the `spine-*` features, 3.5 without any.
+fn selected_version() -> &'static str {
+    let selected: Vec<_> = VERSION_FEATURES
+        .iter()
+        .filter(|(feature, _)| env::var_os(format!("CARGO_FEATURE_{}", feature)).is_some())
+        .map(|(_, version)| *version)
+        .collect();
+    match selected[..] {
+        [] => "3.5",
+        [version] => version,
+        _ => panic!("Only one of the spine-3-8, spine-4-1 and spine-4-2 features can be enabled"),
+    }
+}
+
+/// Guess which branch the spine-c headers come from, by what was added in each version.
+fn checkout_version(include: &Path) -> &'static str {
+    let spine = include.join("spine");
+    if spine.join("PhysicsConstraint.h").exists() {
+        "4.2"
+    } else if spine.join("Sequence.h").exists() {
+        "4.1"
+    } else if fs::read_to_string(spine.join("Skeleton.h")).map_or(false, |h| h.contains("scaleX")) {
+        "3.8"
+    } else {
+        "3.5"
+    }
+}
+
+/// The spine-c directory of spine-runtimes, the one with `CMakeLists.txt`. `SPINE_C_DIR`
+/// points to another checkout than the submodule.
+#[cfg(not(feature = "system-spine-c"))]
+fn spine_c_dir() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=SPINE_C_DIR");
+    match env::var_os("SPINE_C_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => {
+            PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("spine-runtimes/spine-c")
+        }
+    }
+}
+
+/// Compile the sources directly with the C compiler cc finds, no CMake needed.
+#[cfg(all(feature = "cc-build", not(feature = "system-spine-c")))]
+fn build_spine_c(dir: &Path, include: &Path) {
+    let src = dir.join("spine-c/src/spine");
+    println!("cargo:rerun-if-changed={}", src.display());
+    let sources = fs::read_dir(&src)
+        .unwrap_or_else(|e| panic!("Couldn't list spine-c sources in {}: {}", src.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "c"));
+
+    let mut build = cc::Build::new();
+    // There's no libc for wasm32-unknown-unknown, the one of wasi-libc works as long as
+    // nothing touches files
+    if is_wasm_unknown() {
+        let sysroot = wasi_sysroot();
+        build.flag(&format!("--sysroot={}", sysroot.display()));
+        println!(
+            "cargo:rustc-link-search=native={}",
+            sysroot.join("lib/wasm32-wasi").display()
+        );
+        println!("cargo:rustc-link-lib=static=c");
+    }
+    build
+        .include(include)
+        .files(sources)
+        .warnings(false)
+        .compile("spine-c");
+}
+
+#[cfg(not(any(feature = "cc-build", feature = "system-spine-c")))]
+fn build_spine_c(dir: &Path, _include: &Path) {
+    if is_wasm_unknown() {
+        panic!("Building for wasm32-unknown-unknown needs the cc-build feature");
+    }
+    let mut config = cmake::Config::new(dir);
+    // The cmake crate doesn't go through emcmake, so CMake needs the toolchain file of the
+    // Emscripten SDK to build for wasm32-unknown-emscripten
+    if is_emscripten() {
+        config.define(
+            "CMAKE_TOOLCHAIN_FILE",
+            emsdk().join("upstream/emscripten/cmake/Modules/Platform/Emscripten.cmake"),
+        );
+    }
+    let dst = config.build();
     println!("cargo:rustc-link-search=native={}/dist/lib", dst.display());
     println!("cargo:rustc-link-lib=static=spine-c");
+}
+
+/// Link a spine-c library built elsewhere, found in `SPINE_C_LIB_DIR` with the headers in
+/// `SPINE_C_INCLUDE_DIR`, or by pkg-config as `spine-c`. It's linked dynamically unless
+/// `SPINE_C_STATIC` is set. Returns the include directory.
+#[cfg(feature = "system-spine-c")]
+fn link_system_spine_c() -> PathBuf {
+    for var in ["SPINE_C_LIB_DIR", "SPINE_C_INCLUDE_DIR", "SPINE_C_STATIC"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    let statik = env::var_os("SPINE_C_STATIC").is_some();
+
+    if let Some(lib_dir) = env::var_os("SPINE_C_LIB_DIR") {
+        println!(
+            "cargo:rustc-link-search=native={}",
+            Path::new(&lib_dir).display()
+        );
+        let kind = if statik { "static" } else { "dylib" };
+        println!("cargo:rustc-link-lib={}=spine-c", kind);
+        let include = env::var_os("SPINE_C_INCLUDE_DIR")
+            .expect("SPINE_C_INCLUDE_DIR must be set along with SPINE_C_LIB_DIR");
+        return PathBuf::from(include);
+    }
+
+    let library = pkg_config::Config::new()
+        .statik(statik)
+        .probe("spine-c")
+        .unwrap_or_else(|e| {
+            panic!(
+                "spine-c not found, set SPINE_C_LIB_DIR and SPINE_C_INCLUDE_DIR or make it \
+                 available to pkg-config: {}",
+                e
+            )
+        });
+    library
+        .include_paths
+        .into_iter()
+        .find(|path| path.join("spine/spine.h").exists())
+        .expect("pkg-config found spine-c, but not the directory with spine/spine.h")
+}
+
+fn is_emscripten() -> bool {
+    env::var("CARGO_CFG_TARGET_OS").unwrap() == "emscripten"
+}
+
+/// The browser target of wgpu and winit, which can't use Emscripten.
+fn is_wasm_unknown() -> bool {
+    env::var("CARGO_CFG_TARGET_ARCH").unwrap() == "wasm32"
+        && env::var("CARGO_CFG_TARGET_OS").unwrap() == "unknown"
+}
+
+/// Sysroot of wasi-libc, whose headers and libc spine-c is built with for
+/// wasm32-unknown-unknown.
+fn wasi_sysroot() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=WASI_SYSROOT");
+    PathBuf::from(env::var_os("WASI_SYSROOT").expect("WASI_SYSROOT must point to wasi-libc"))
+}
+
+/// Root of the Emscripten SDK, needed to build for wasm32-unknown-emscripten.
+fn emsdk() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=EMSDK");
+    PathBuf::from(env::var_os("EMSDK").expect("EMSDK must point to the Emscripten SDK"))
+}
+
+/// Make sure the headers are from the version the bindings are generated for.
+fn check_version(include: &Path) {
+    let version = selected_version();
+    let checkout = checkout_version(include);
+    if version != checkout {
+        panic!(
+            "spine-c in {} is version {}, but {} was selected. Check out the matching branch, \
+             e.g. with `git -C spine-sys/spine-runtimes checkout {}`",
+            include.display(),
+            checkout,
+            version,
+            version
+        );
+    }
+    println!("cargo:rerun-if-changed={}", include.join("spine").display());
+}
+
+fn main() {
+    #[cfg(feature = "system-spine-c")]
+    let include = {
+        let include = link_system_spine_c();
+        check_version(&include);
+        include
+    };
+    #[cfg(not(feature = "system-spine-c"))]
+    let include = {
+        let dir = spine_c_dir();
+        let include = dir.join("spine-c/include");
+        check_version(&include);
+        build_spine_c(&dir, &include);
+        include
+    };
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs");
+    write_bindings(&include, &out_path);
+}
 
+/// Generate bindings for what the safe wrapper needs.
+fn write_bindings(include: &Path, out_path: &Path) {
     println!("cargo:rerun-if-changed=wrapper.h");
-    let bindings = bindgen::Builder::default()
-        .clang_arg(format!("-I{}/spine-runtimes/spine-c/spine-c/include", env::var("CARGO_MANIFEST_DIR").unwrap()))
+    let mut builder = bindgen::Builder::default();
+    // For wasm32-unknown-emscripten, clang needs the libc headers of the Emscripten SDK
+    if is_emscripten() {
+        let sysroot = emsdk().join("upstream/emscripten/cache/sysroot");
+        builder = builder.clang_arg(format!("--sysroot={}", sysroot.display()));
+    } else if is_wasm_unknown() {
+        builder = builder.clang_arg(format!("--sysroot={}", wasi_sysroot().display()));
+    }
+    let bindings = builder
+        .clang_arg(format!("-I{}", include.display()))
+        // Picks the declarations in wrapper.h that exist in this version
+        .clang_arg(format!("-DSPINE_{}", selected_version().replace('.', "_")))
         // The input header we would like to generate
         // bindings for.
         .header("wrapper.h")
+        // Only spine-c itself and the allocation functions from wrapper.h, not everything the
+        // system headers declare. Those are `_sp` prefixed since 3.6
+        .allowlist_function("_?sp[A-Z].*")
+        .allowlist_function("_malloc|_calloc|_free|_set(Debug)?Malloc|_setFree|_readFile")
+        .allowlist_type("_?sp[A-Z].*")
+        .allowlist_var("_?sp[A-Z].*|SP_.*")
+        .derive_debug(true)
+        .derive_default(true)
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
@@ -20,8 +231,7 @@ fn main() {
         // Unwrap the Result and panic on failure.
         .expect("Unable to generate bindings");
 
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     bindings
-        .write_to_file(out_path.join("bindings.rs"))
+        .write_to_file(out_path)
         .expect("Couldn't write bindings!");
 }
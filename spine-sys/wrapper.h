@@ -5,6 +5,8 @@
  * Internal API available for extension:
  */
 
+/* SPINE_<major>_<minor> is defined by build.rs, the allocation functions got the _sp prefix in 3.6 */
+#ifdef SPINE_3_5
 void* _malloc (size_t size, const char* file, int line);
 void* _calloc (size_t num, size_t size, const char* file, int line);
 void _free (void* ptr);
@@ -13,4 +15,35 @@ void _setMalloc (void* (*_malloc) (size_t size));
 void _setDebugMalloc (void* (*_malloc) (size_t size, const char* file, int line));
 void _setFree (void (*_free) (void* ptr));
 
-char* _readFile (const char* path, int* length);
\ No newline at end of file
+char* _readFile (const char* path, int* length);
+#else
+void* _spMalloc (size_t size, const char* file, int line);
+void* _spCalloc (size_t num, size_t size, const char* file, int line);
+void _spFree (void* ptr);
+
+void _spSetMalloc (void* (*_malloc) (size_t size));
+void _spSetDebugMalloc (void* (*_malloc) (size_t size, const char* file, int line));
+void _spSetFree (void (*_free) (void* ptr));
+
+char* _spReadFile (const char* path, int* length);
+#endif
+
+/* Attachments take the sequence of regions they flip through since 4.1 */
+#if defined(SPINE_4_1) || defined(SPINE_4_2)
+void _spAttachmentLoader_init (spAttachmentLoader* self,
+	void (*dispose) (spAttachmentLoader* self),
+	spAttachment* (*createAttachment) (spAttachmentLoader* self, spSkin* skin, spAttachmentType type, const char* name,
+		const char* path, spSequence* sequence),
+	void (*configureAttachment) (spAttachmentLoader* self, spAttachment*),
+	void (*disposeAttachment) (spAttachmentLoader* self, spAttachment*)
+);
+#else
+void _spAttachmentLoader_init (spAttachmentLoader* self,
+	void (*dispose) (spAttachmentLoader* self),
+	spAttachment* (*createAttachment) (spAttachmentLoader* self, spSkin* skin, spAttachmentType type, const char* name,
+		const char* path),
+	void (*configureAttachment) (spAttachmentLoader* self, spAttachment*),
+	void (*disposeAttachment) (spAttachmentLoader* self, spAttachment*)
+);
+#endif
+void _spAttachmentLoader_setError (spAttachmentLoader* self, const char* error1, const char* error2);
\ No newline at end of file
@@ -0,0 +1,20 @@
+use std::env;
+
+/// Turns the `spine-*` features into cfgs for the API changes the wrapper has to follow, so
+/// code only has to name the version a change happened in.
+fn main() {
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{}", name)).is_some();
+    let v4_2 = feature("SPINE_4_2");
+    let v4_1 = v4_2 || feature("SPINE_4_1");
+    let v3_8 = v4_1 || feature("SPINE_3_8");
+
+    for (cfg, enabled) in [
+        ("since_3_8", v3_8),
+        ("since_4_1", v4_1),
+        ("since_4_2", v4_2),
+    ] {
+        if enabled {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+    }
+}
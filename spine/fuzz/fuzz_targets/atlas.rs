@@ -0,0 +1,11 @@
+#![no_main]
+
+mod common;
+
+use common::{MemoryLoader, ATLAS_PATH};
+use libfuzzer_sys::fuzz_target;
+use spine::Atlas;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Atlas::with_loader(ATLAS_PATH, MemoryLoader::atlas(data));
+});
@@ -0,0 +1,84 @@
+//! Assets served from memory, so spine-c parses whatever the fuzzer comes up with.
+
+// Each target only uses part of this
+#![allow(dead_code)]
+
+use spine::{AtlasLoader, AtlasPage, SpineCallbacks};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Everything is loaded through a [`MemoryLoader`], the global callbacks are never used.
+pub struct NoFiles;
+
+impl SpineCallbacks for NoFiles {
+    type Texture = ();
+    type LoadTextureError = Error;
+    type LoadFileError = Error;
+
+    fn load_texture(path: &str, _: &AtlasPage) -> Result<((), u32, u32), Error> {
+        Err(format!("no texture at {}", path).into())
+    }
+
+    fn load_file(path: &str) -> Result<Vec<u8>, Error> {
+        Err(format!("no file at {}", path).into())
+    }
+}
+
+spine::spine_init!(NoFiles);
+
+pub const ATLAS_PATH: &str = "fuzz.atlas";
+pub const SKELETON_PATH: &str = "fuzz.skel";
+
+/// One page with one region, in the format every runtime version reads.
+const ATLAS: &[u8] = b"
+page.png
+size: 64,64
+format: RGBA8888
+filter: Linear,Linear
+repeat: none
+region
+  rotate: false
+  xy: 0, 0
+  size: 8, 8
+  orig: 8, 8
+  offset: 0, 0
+  index: -1
+";
+
+/// Serves the atlas at [`ATLAS_PATH`], the skeleton at any other path, and blank textures.
+pub struct MemoryLoader {
+    atlas: Vec<u8>,
+    skeleton: Vec<u8>,
+}
+
+impl MemoryLoader {
+    pub fn atlas(atlas: &[u8]) -> Self {
+        Self {
+            atlas: atlas.to_vec(),
+            skeleton: vec![],
+        }
+    }
+
+    /// `skeleton` along with a valid atlas.
+    pub fn skeleton(skeleton: &[u8]) -> Self {
+        Self {
+            atlas: ATLAS.to_vec(),
+            skeleton: skeleton.to_vec(),
+        }
+    }
+}
+
+impl AtlasLoader for MemoryLoader {
+    type Texture = ();
+
+    fn load_texture(&mut self, _: &str, _: &AtlasPage) -> anyhow::Result<((), u32, u32)> {
+        Ok(((), 64, 64))
+    }
+
+    fn load_file(&mut self, path: &str) -> anyhow::Result<Vec<u8>> {
+        Ok(match path {
+            ATLAS_PATH => self.atlas.clone(),
+            _ => self.skeleton.clone(),
+        })
+    }
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+mod common;
+
+use common::{MemoryLoader, ATLAS_PATH, SKELETON_PATH};
+use libfuzzer_sys::fuzz_target;
+use spine::{Atlas, SkeletonData};
+
+fuzz_target!(|data: &[u8]| {
+    let atlas = Atlas::with_loader(ATLAS_PATH, MemoryLoader::skeleton(data)).unwrap();
+    let _ = SkeletonData::try_new_binary(&atlas, SKELETON_PATH, 1.0);
+});
@@ -0,0 +1,27 @@
+//! spine-c's allocator, for memory it frees or that it allocated. The functions got the
+//! `_sp` prefix in 3.6.
+
+use std::{os::raw::c_void, ptr};
+
+#[cfg(not(since_3_8))]
+use spine_sys::{_calloc as sp_calloc, _free as sp_free, _malloc as sp_malloc};
+#[cfg(since_3_8)]
+use spine_sys::{_spCalloc as sp_calloc, _spFree as sp_free, _spMalloc as sp_malloc};
+
+/// # Safety
+/// The memory is uninitialized, and has to be freed with [`free`] or by spine-c.
+pub unsafe fn malloc(size: usize) -> *mut c_void {
+    sp_malloc(size as _, ptr::null(), 0)
+}
+
+/// # Safety
+/// The memory has to be freed with [`free`] or by spine-c.
+pub unsafe fn calloc(num: usize, size: usize) -> *mut c_void {
+    sp_calloc(num as _, size as _, ptr::null(), 0)
+}
+
+/// # Safety
+/// `ptr` has to be null or allocated by spine-c, and not used after this.
+pub unsafe fn free(ptr: *mut c_void) {
+    sp_free(ptr)
+}
@@ -1,8 +1,16 @@
 use std::{
+    collections::HashSet,
     ffi::{CStr, CString},
     fmt::Debug,
+    future::Future,
+    marker::PhantomData,
     os::raw::c_int,
-    sync::Arc,
+    pin::Pin,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context, Poll, Waker},
 };
 
 use anyhow::{bail, Result};
@@ -10,14 +18,25 @@ use spine_sys::{
     spAnimation, spAnimationState, spAnimationStateData, spAnimationStateData_create,
     spAnimationStateData_dispose, spAnimationState_addAnimationByName,
     spAnimationState_addEmptyAnimation, spAnimationState_clearTrack, spAnimationState_clearTracks,
-    spAnimationState_create, spAnimationState_dispose, spAnimationState_setAnimationByName,
-    spAnimationState_setEmptyAnimation, spAnimationState_update,
+    spAnimationState_create, spAnimationState_dispose, spAnimationState_getCurrent,
+    spAnimationState_setAnimationByName, spAnimationState_setEmptyAnimation,
+    spAnimationState_update, spEvent, spEventType, spEventType_SP_ANIMATION_COMPLETE,
+    spEventType_SP_ANIMATION_DISPOSE, spEventType_SP_ANIMATION_END, spEventType_SP_ANIMATION_EVENT,
+    spEventType_SP_ANIMATION_INTERRUPT, spEventType_SP_ANIMATION_START, spTimeline, spTrackEntry,
 };
 
-use crate::SkeletonData;
+use crate::{
+    skel::raw_slice,
+    timeline::{Timeline, TimelineTarget},
+    SkeletonData,
+};
 
 #[derive(Debug)]
 struct AnimStateDataPtr(*mut spAnimationStateData);
+
+// SAFETY: mixes are only set while the data is created, animation states only read it
+unsafe impl Send for AnimStateDataPtr {}
+unsafe impl Sync for AnimStateDataPtr {}
 impl Drop for AnimStateDataPtr {
     fn drop(&mut self) {
         unsafe { spAnimationStateData_dispose(self.0) };
@@ -48,11 +67,192 @@ impl AnimationStateData {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    /// The entry started playing.
+    Start,
+    /// Another entry was set while this one was still playing.
+    Interrupt,
+    /// The entry will never be applied again.
+    End,
+    /// The entry reached the end of its animation (every loop, if looping).
+    Complete,
+    /// The entry is about to be freed.
+    Dispose,
+    /// The animation fired a user-defined event, see [`AnimationEvent::event`].
+    Event,
+}
+
+impl From<spEventType> for EventType {
+    fn from(kind: spEventType) -> Self {
+        #[allow(non_upper_case_globals)]
+        match kind {
+            spEventType_SP_ANIMATION_START => EventType::Start,
+            spEventType_SP_ANIMATION_INTERRUPT => EventType::Interrupt,
+            spEventType_SP_ANIMATION_END => EventType::End,
+            spEventType_SP_ANIMATION_COMPLETE => EventType::Complete,
+            spEventType_SP_ANIMATION_DISPOSE => EventType::Dispose,
+            spEventType_SP_ANIMATION_EVENT => EventType::Event,
+            _ => unreachable!("Unknown event type: {}", kind),
+        }
+    }
+}
+
+/// User-defined event keyed in an animation.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub name: String,
+    pub time: f32,
+    pub int_value: i32,
+    pub float_value: f32,
+    pub string_value: Option<String>,
+}
+
+/// Notification collected by [`AnimationState`], see [`AnimationState::take_events`].
+#[derive(Debug, Clone)]
+pub struct AnimationEvent {
+    pub kind: EventType,
+    pub track_index: usize,
+    pub animation_name: String,
+    pub looping: bool,
+    /// Only set for [`EventType::Event`].
+    pub event: Option<Event>,
+}
+
+/// Kept in `spAnimationState.rendererObject` for the listener.
+#[derive(Default)]
+struct Listener {
+    /// Queued until taken out by `take_events`.
+    events: Vec<AnimationEvent>,
+    fades: Vec<Fade>,
+    /// Receivers handed out by `subscribe`, dropped once they hang up.
+    subscribers: Vec<Sender<AnimationEvent>>,
+    /// Entries someone waits on with `completed`.
+    completions: Vec<(*mut spTrackEntry, Arc<Mutex<CompletionState>>)>,
+}
+
+impl Listener {
+    /// Wake whoever waits on `entry`.
+    fn resolve(&mut self, entry: *mut spTrackEntry, completed: bool) {
+        self.completions.retain(|(waited, state)| {
+            if *waited != entry {
+                return true;
+            }
+            resolve(state, completed);
+            false
+        });
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        for (_, state) in self.completions.drain(..) {
+            resolve(&state, false);
+        }
+    }
+}
+
+/// See [`AnimationState::fade_out_track`].
+struct Fade {
+    track_index: usize,
+    /// The empty entry mixing the track out.
+    entry: *mut spTrackEntry,
+    on_done: Box<dyn FnOnce(&mut AnimationState) + Send>,
+}
+
+unsafe extern "C" fn listener(
+    state: *mut spAnimationState,
+    kind: spEventType,
+    entry: *mut spTrackEntry,
+    event: *mut spEvent,
+) {
+    let listener = ((*state).rendererObject as *mut Listener).as_mut().unwrap();
+    if kind == spEventType_SP_ANIMATION_DISPOSE {
+        // Replaced before it finished, and the pointer may be reused from now on
+        listener.fades.retain(|fade| fade.entry != entry);
+        listener.resolve(entry, false);
+    } else if kind == spEventType_SP_ANIMATION_COMPLETE {
+        listener.resolve(entry, true);
+    }
+    let entry = &*(entry as *const TrackEntry);
+
+    let event = event.as_ref().map(|event| Event {
+        name: CStr::from_ptr((*event.data).name)
+            .to_string_lossy()
+            .into_owned(),
+        time: event.time,
+        int_value: event.intValue,
+        float_value: event.floatValue,
+        string_value: event
+            .stringValue
+            .as_ref()
+            .map(|s| CStr::from_ptr(s).to_string_lossy().into_owned()),
+    });
+
+    let event = AnimationEvent {
+        kind: kind.into(),
+        track_index: entry.track_index(),
+        animation_name: entry.animation().name().to_owned(),
+        looping: entry.is_loop(),
+        event,
+    };
+    if !listener.subscribers.is_empty() {
+        listener
+            .subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+    listener.events.push(event);
+}
+
+#[derive(Debug, Default)]
+struct CompletionState {
+    /// Set once the entry completed or went away.
+    completed: Option<bool>,
+    waker: Option<Waker>,
+}
+
+fn resolve(state: &Mutex<CompletionState>, completed: bool) {
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    state.completed.get_or_insert(completed);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+/// Resolves once a track entry completes, see [`AnimationState::completed`].
+///
+/// The output is `true` if the entry played to its end, or `false` if it was replaced,
+/// cleared or the animation state was dropped before that. It only makes progress while the
+/// animation state is updated, wherever that happens.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    state: Arc<Mutex<CompletionState>>,
+}
+
+impl Future for Completion {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.completed {
+            Some(completed) => Poll::Ready(completed),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
 pub struct AnimationState {
     pub(crate) ptr: *mut spAnimationState,
     _data: AnimationStateData,
 }
 
+// SAFETY: the state and its queued events are owned, and it shares only `Send + Sync` data.
+// It's not `Sync`, like `Skeleton`.
+unsafe impl Send for AnimationState {}
+
 impl AnimationState {
     pub fn new(anim_state_data: &AnimationStateData) -> Result<Self> {
         let inner = unsafe { spAnimationState_create(anim_state_data.ptr.0) };
@@ -60,59 +260,209 @@ impl AnimationState {
             bail!("Failed to create animation state");
         }
 
+        unsafe {
+            (*inner).rendererObject = Box::into_raw(Box::new(Listener::default())) as *mut _;
+            (*inner).listener = Some(listener);
+        }
+
         Ok(AnimationState {
             ptr: inner,
             _data: anim_state_data.clone(),
         })
     }
 
+    /// Take the events fired since the last call, in the order they happened.
+    pub fn take_events(&mut self) -> Vec<AnimationEvent> {
+        unsafe {
+            let listener = ((*self.ptr).rendererObject as *mut Listener)
+                .as_mut()
+                .unwrap();
+            std::mem::take(&mut listener.events)
+        }
+    }
+
+    /// A receiver getting every event from now on, as they happen during [`Self::update`].
+    /// They are still queued for [`Self::take_events`] as well.
+    pub fn subscribe(&mut self) -> Receiver<AnimationEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.listener().subscribers.push(sender);
+        receiver
+    }
+
+    /// Resolves when the last entry queued on the given track completes, `None` if the track
+    /// is empty. Looping entries complete at the end of their first loop.
+    pub fn completed(&mut self, track_index: usize) -> Option<Completion> {
+        let mut entry = unsafe { spAnimationState_getCurrent(self.ptr, track_index as c_int) };
+        if entry.is_null() {
+            return None;
+        }
+        unsafe {
+            while !(*entry).next.is_null() {
+                entry = (*entry).next;
+            }
+        }
+
+        let state = Arc::new(Mutex::new(CompletionState::default()));
+        self.listener().completions.push((entry, state.clone()));
+        Some(Completion { state })
+    }
+
+    fn listener(&mut self) -> &mut Listener {
+        unsafe { &mut *((*self.ptr).rendererObject as *mut Listener) }
+    }
+
+    /// The entry currently playing on the given track.
+    pub fn current(&self, track_index: usize) -> Option<&TrackEntry> {
+        unsafe {
+            (spAnimationState_getCurrent(self.ptr, track_index as c_int) as *const TrackEntry)
+                .as_ref()
+        }
+    }
+
+    /// Number of tracks used so far, some of them may be empty by now.
+    pub fn track_count(&self) -> usize {
+        unsafe { (*self.ptr).tracksCount as usize }
+    }
+
+    pub fn current_mut(&mut self, track_index: usize) -> Option<&mut TrackEntry> {
+        unsafe {
+            (spAnimationState_getCurrent(self.ptr, track_index as c_int) as *mut TrackEntry)
+                .as_mut()
+        }
+    }
+
+    /// Playback speed multiplier of all tracks, on top of their own.
+    pub fn time_scale(&self) -> f32 {
+        unsafe { (*self.ptr).timeScale }
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        unsafe { (*self.ptr).timeScale = time_scale };
+    }
+
     /// Update the animation state by time delta.
     pub fn update(&mut self, delta: f32) {
         unsafe {
             spAnimationState_update(self.ptr, delta);
         }
+        self.finish_fades();
+    }
+
+    /// Call back the fades whose track is mixed out by now.
+    fn finish_fades(&mut self) {
+        let done: Vec<Fade> = unsafe {
+            let listener = &mut *((*self.ptr).rendererObject as *mut Listener);
+            if listener.fades.is_empty() {
+                return;
+            }
+
+            let state = self.ptr;
+            let (done, pending) =
+                std::mem::take(&mut listener.fades)
+                    .into_iter()
+                    .partition(|fade| {
+                        spAnimationState_getCurrent(state, fade.track_index as c_int) == fade.entry
+                            && (*fade.entry).mixingFrom.is_null()
+                    });
+            listener.fades = pending;
+            done
+        };
+
+        for fade in done {
+            (fade.on_done)(self);
+        }
     }
 
-    pub fn set_animation_by_name(&mut self, track_index: usize, name: &str, loop_: bool) {
+    /// Play an animation on the given track immediately, returns `None` if there is no
+    /// animation with the given name.
+    pub fn set_animation_by_name(
+        &mut self,
+        track_index: usize,
+        name: &str,
+        loop_: bool,
+    ) -> Option<&mut TrackEntry> {
         let c_str = CString::new(name).unwrap();
         unsafe {
-            spAnimationState_setAnimationByName(
+            (spAnimationState_setAnimationByName(
                 self.ptr,
                 track_index as c_int,
                 c_str.as_ptr(),
                 if loop_ { 1 } else { 0 },
-            );
+            ) as *mut TrackEntry)
+                .as_mut()
         }
     }
 
+    /// Queue an animation after the last entry of the given track, returns `None` if there is
+    /// no animation with the given name.
     pub fn add_animation_by_name(
         &mut self,
         track_index: usize,
         name: &str,
         loop_: bool,
         delay: f32,
-    ) {
+    ) -> Option<&mut TrackEntry> {
         let c_str = CString::new(name).unwrap();
         unsafe {
-            spAnimationState_addAnimationByName(
+            (spAnimationState_addAnimationByName(
                 self.ptr,
                 track_index as c_int,
                 c_str.as_ptr(),
                 if loop_ { 1 } else { 0 },
                 delay,
-            );
+            ) as *mut TrackEntry)
+                .as_mut()
+        }
+    }
+
+    /// Mix the given track out to the setup pose.
+    pub fn set_empty_animation(
+        &mut self,
+        track_index: usize,
+        mix_duration: f32,
+    ) -> Option<&mut TrackEntry> {
+        unsafe {
+            (spAnimationState_setEmptyAnimation(self.ptr, track_index as c_int, mix_duration)
+                as *mut TrackEntry)
+                .as_mut()
         }
     }
 
-    pub fn set_empty_animation(&self, track_index: usize, mix_duration: f32) {
+    /// Like [`Self::set_empty_animation`], calling `on_done` once the track is mixed out
+    /// completely during [`Self::update`]. It's never called if something else is played on
+    /// the track before that.
+    pub fn fade_out_track(
+        &mut self,
+        track_index: usize,
+        mix_duration: f32,
+        on_done: impl FnOnce(&mut AnimationState) + Send + 'static,
+    ) -> Option<&mut TrackEntry> {
         unsafe {
-            spAnimationState_setEmptyAnimation(self.ptr, track_index as c_int, mix_duration);
+            let entry =
+                spAnimationState_setEmptyAnimation(self.ptr, track_index as c_int, mix_duration);
+            if !entry.is_null() {
+                let listener = &mut *((*self.ptr).rendererObject as *mut Listener);
+                listener.fades.push(Fade {
+                    track_index,
+                    entry,
+                    on_done: Box::new(on_done),
+                });
+            }
+            (entry as *mut TrackEntry).as_mut()
         }
     }
 
-    pub fn add_empty_animation(&self, track_index: usize, mix_duration: f32, delay: f32) {
+    /// Queue mixing the given track out to the setup pose.
+    pub fn add_empty_animation(
+        &mut self,
+        track_index: usize,
+        mix_duration: f32,
+        delay: f32,
+    ) -> Option<&mut TrackEntry> {
         unsafe {
-            spAnimationState_addEmptyAnimation(self.ptr, track_index as c_int, mix_duration, delay);
+            (spAnimationState_addEmptyAnimation(self.ptr, track_index as c_int, mix_duration, delay)
+                as *mut TrackEntry)
+                .as_mut()
         }
     }
 
@@ -127,7 +477,94 @@ impl AnimationState {
 
 impl Drop for AnimationState {
     fn drop(&mut self) {
-        unsafe { spAnimationState_dispose(self.ptr) };
+        unsafe {
+            let listener = (*self.ptr).rendererObject as *mut Listener;
+            spAnimationState_dispose(self.ptr);
+            drop(Box::from_raw(listener));
+        };
+    }
+}
+
+/// An animation queued or playing on a track of an [`AnimationState`].
+#[repr(C)]
+pub struct TrackEntry<'st> {
+    pub(crate) inner: spTrackEntry,
+    _state: PhantomData<&'st AnimationState>,
+}
+
+impl<'st> TrackEntry<'st> {
+    pub fn animation(&self) -> &Animation {
+        unsafe { &*(self.inner.animation as *const Animation) }
+    }
+
+    pub fn track_index(&self) -> usize {
+        self.inner.trackIndex as usize
+    }
+
+    pub fn is_loop(&self) -> bool {
+        self.inner.loop_ != 0
+    }
+
+    pub fn set_loop(&mut self, loop_: bool) {
+        self.inner.loop_ = if loop_ { 1 } else { 0 };
+    }
+
+    /// Seconds this entry has been playing, not wrapped for looping animations.
+    pub fn track_time(&self) -> f32 {
+        self.inner.trackTime
+    }
+
+    /// Jump to the given time, e.g. to scrub through the animation.
+    pub fn set_track_time(&mut self, track_time: f32) {
+        self.inner.trackTime = track_time;
+    }
+
+    /// Seconds to wait after the previous entry started before this one starts.
+    pub fn delay(&self) -> f32 {
+        self.inner.delay
+    }
+
+    /// The entry queued after this one, if any.
+    pub fn next(&self) -> Option<&TrackEntry<'st>> {
+        unsafe { (self.inner.next as *const TrackEntry).as_ref() }
+    }
+
+    /// Seconds to mix from the previous entry to this one.
+    pub fn mix_duration(&self) -> f32 {
+        self.inner.mixDuration
+    }
+
+    pub fn set_mix_duration(&mut self, mix_duration: f32) {
+        self.inner.mixDuration = mix_duration;
+    }
+
+    /// Playback speed multiplier of this entry.
+    pub fn time_scale(&self) -> f32 {
+        self.inner.timeScale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.inner.timeScale = time_scale;
+    }
+
+    /// How much this entry is applied over the lower tracks, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        self.inner.alpha
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.inner.alpha = alpha;
+    }
+}
+
+impl<'st> Debug for TrackEntry<'st> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrackEntry")
+            .field("animation", &self.animation().name())
+            .field("track_index", &self.track_index())
+            .field("loop", &self.is_loop())
+            .field("track_time", &self.track_time())
+            .finish()
     }
 }
 
@@ -144,6 +581,41 @@ impl Animation {
     pub fn duration(&self) -> f32 {
         self.inner.duration
     }
+
+    pub fn timelines(&self) -> impl ExactSizeIterator<Item = Timeline<'_>> {
+        self.raw_timelines()
+            .iter()
+            .map(|&timeline| Timeline::new(timeline))
+    }
+
+    /// Bones, slots and constraints both animations key. Played at the same time on
+    /// different tracks, the higher track overrides the lower one for these.
+    pub fn shared_targets(&self, other: &Animation) -> Vec<TimelineTarget> {
+        let theirs: HashSet<_> = other.timelines().filter_map(|t| t.target()).collect();
+        let mut shared: Vec<_> = self
+            .timelines()
+            .filter_map(|t| t.target())
+            .filter(|target| theirs.contains(target))
+            .collect();
+        shared.sort();
+        shared.dedup();
+        shared
+    }
+
+    #[cfg(not(since_4_1))]
+    fn raw_timelines(&self) -> &[*mut spTimeline] {
+        unsafe { raw_slice(self.inner.timelines, self.inner.timelinesCount) }
+    }
+
+    #[cfg(since_4_1)]
+    fn raw_timelines(&self) -> &[*mut spTimeline] {
+        unsafe {
+            match self.inner.timelines.as_ref() {
+                Some(timelines) => raw_slice(timelines.items, timelines.size),
+                None => &[],
+            }
+        }
+    }
 }
 
 impl Debug for Animation {
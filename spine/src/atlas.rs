@@ -1,36 +1,214 @@
-use std::{ffi::{CString, CStr}, marker::PhantomData, ptr::null_mut, slice, sync::Arc};
+use std::{
+    ffi::{c_void, CStr},
+    marker::PhantomData,
+    os::raw::c_int,
+    ptr::null_mut,
+    slice,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 
 use anyhow::{bail, Result};
-use spine_sys::{spAtlas, spAtlasPage, spAtlasRegion, spAtlas_createFromFile, spAtlas_dispose};
+use spine_sys::{
+    spAtlas, spAtlasPage, spAtlasRegion, spAtlas_create, spAtlas_createFromFile, spAtlas_dispose,
+};
+
+use crate::{
+    error::{collect_errors, report},
+    loader::{c_path, check_atlas, FileBuf},
+    AtlasLoader, SpineRuntimeError,
+};
 
 #[derive(Debug)]
 pub(crate) struct AtlasPtr(pub(crate) *mut spAtlas);
+
+// SAFETY: the atlas isn't changed after it's created, its textures are `Send + Sync` and
+// the loader is behind a mutex
+unsafe impl Send for AtlasPtr {}
+unsafe impl Sync for AtlasPtr {}
 impl Drop for AtlasPtr {
     fn drop(&mut self) {
         log::info!("Atlas@{:x} dropped", self.0 as usize);
-        unsafe { spAtlas_dispose(self.0) };
+        unsafe {
+            // Disposing the pages still needs the loader
+            let context = (*self.0).rendererObject as *mut AtlasContext;
+            spAtlas_dispose(self.0);
+            if !context.is_null() {
+                drop(Box::from_raw(context));
+            }
+        }
     }
 }
 
+/// [`AtlasLoader`] of an atlas, kept in `spAtlas.rendererObject`.
+pub(crate) type AtlasContext = Mutex<Box<dyn ErasedAtlasLoader + Send>>;
+
+/// A loader that panicked is still usable, it only loads files.
+fn lock(context: &AtlasContext) -> MutexGuard<'_, Box<dyn ErasedAtlasLoader + Send>> {
+    context.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// [`AtlasLoader`] without the texture type, textures are boxed like with
+/// [`crate::SpineCallbacks`].
+pub(crate) trait ErasedAtlasLoader {
+    fn load_texture(&mut self, path: &str, page: &AtlasPage) -> Result<(*mut c_void, u32, u32)>;
+    fn load_file(&mut self, path: &str) -> Result<Vec<u8>>;
+    /// # Safety
+    /// `texture` has to come from [`Self::load_texture`] of the same loader.
+    unsafe fn dispose_texture(&self, texture: *mut c_void);
+}
+
+impl<L: AtlasLoader> ErasedAtlasLoader for L {
+    fn load_texture(&mut self, path: &str, page: &AtlasPage) -> Result<(*mut c_void, u32, u32)> {
+        let (texture, width, height) = AtlasLoader::load_texture(self, path, page)?;
+        Ok((
+            Box::into_raw(Box::new(texture)) as *mut c_void,
+            width,
+            height,
+        ))
+    }
+
+    fn load_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        AtlasLoader::load_file(self, path)
+    }
+
+    unsafe fn dispose_texture(&self, texture: *mut c_void) {
+        drop(Box::from_raw(texture as *mut L::Texture));
+    }
+}
+
+/// Used by [`crate::spine_init`] to load a texture with the [`AtlasLoader`] of its atlas,
+/// returns `false` if the atlas has none.
+///
+/// # Safety
+/// `page` has to be a page being created by spine-c.
+#[doc(hidden)]
+pub unsafe fn create_texture_with_loader(page: *mut spAtlasPage, path: &str) -> bool {
+    let context = match ((*(*page).atlas).rendererObject as *const AtlasContext).as_ref() {
+        Some(context) => context,
+        None => return false,
+    };
+
+    let result = lock(context).load_texture(path, &*(page as *const AtlasPage));
+    match result {
+        Ok((texture, width, height)) => {
+            (*page).width = width as c_int;
+            (*page).height = height as c_int;
+            (*page).rendererObject = texture;
+        }
+        Err(e) => report(SpineRuntimeError::LoadTexture {
+            path: path.to_owned(),
+            message: e.to_string(),
+        }),
+    }
+    true
+}
+
+/// Counterpart of [`create_texture_with_loader`], returns `false` if the atlas has no loader.
+///
+/// # Safety
+/// `page` has to be a page being disposed by spine-c.
+#[doc(hidden)]
+pub unsafe fn dispose_texture_with_loader(page: *mut spAtlasPage) -> bool {
+    let context = match ((*(*page).atlas).rendererObject as *const AtlasContext).as_ref() {
+        Some(context) => context,
+        None => return false,
+    };
+
+    if !(*page).rendererObject.is_null() {
+        lock(context).dispose_texture((*page).rendererObject);
+        (*page).rendererObject = null_mut();
+    }
+    true
+}
+
 #[derive(Debug, Clone)]
 pub struct Atlas {
     pub(crate) ptr: Arc<AtlasPtr>,
+    load_errors: Arc<Vec<SpineRuntimeError>>,
 }
 
 impl Atlas {
     pub fn new(path: &str) -> Result<Self> {
-        let c_str = CString::new(path).unwrap();
+        let c_str = c_path(path)?;
 
-        let inner = unsafe { spAtlas_createFromFile(c_str.as_ptr(), null_mut()) };
+        let (inner, load_errors) =
+            collect_errors(|| unsafe { spAtlas_createFromFile(c_str.as_ptr(), null_mut()) });
         if inner.is_null() {
             bail!("Failed to create atlas from file: {}", path);
         }
 
         Ok(Atlas {
             ptr: Arc::new(AtlasPtr(inner)),
+            load_errors: Arc::new(load_errors),
         })
     }
 
+    /// Like [`Self::new`], but makes sure the file is laid out like an atlas before spine-c
+    /// parses it, failing with a [`crate::SpineError`] otherwise.
+    pub fn try_new(path: &str) -> Result<Self> {
+        let file = FileBuf::read(path)?;
+        Self::create(path, file.as_bytes(), dir_of(path), None)
+    }
+
+    /// Like [`Self::try_new`], with `loader` reading the atlas and loading its textures
+    /// instead of the [`crate::SpineCallbacks`] given to [`crate::spine_init`]. Skeletons
+    /// loaded with the atlas are read by it too.
+    pub fn with_loader(path: &str, mut loader: impl AtlasLoader) -> Result<Self> {
+        let data = loader.load_file(path)?;
+        let context: AtlasContext = Mutex::new(Box::new(loader));
+        Self::create(path, &data, dir_of(path), Some(Box::new(context)))
+    }
+
+    /// Parse an atlas from text, loading its textures from `dir`. With an
+    /// [`crate::AttachmentLoader`], this can supply regions that were never in a file.
+    pub fn from_memory(data: &str, dir: &str) -> Result<Self> {
+        Self::create("(memory)", data.as_bytes(), dir, None)
+    }
+
+    fn create(
+        name: &str,
+        data: &[u8],
+        dir: &str,
+        context: Option<Box<AtlasContext>>,
+    ) -> Result<Self> {
+        check_atlas(name, data)?;
+        let dir = c_path(dir)?;
+
+        let context = context.map_or(null_mut(), Box::into_raw);
+        let (inner, load_errors) = collect_errors(|| unsafe {
+            spAtlas_create(
+                data.as_ptr() as *const _,
+                data.len() as c_int,
+                dir.as_ptr(),
+                context as *mut c_void,
+            )
+        });
+        if inner.is_null() {
+            if !context.is_null() {
+                drop(unsafe { Box::from_raw(context) });
+            }
+            bail!("Failed to create atlas from file: {}", name);
+        }
+
+        Ok(Atlas {
+            ptr: Arc::new(AtlasPtr(inner)),
+            load_errors: Arc::new(load_errors),
+        })
+    }
+
+    /// Pages whose texture failed to load while the atlas was created. Their regions don't
+    /// render, but the atlas loads anyway.
+    pub fn load_errors(&self) -> &[SpineRuntimeError] {
+        &self.load_errors
+    }
+
+    /// Read a file with the atlas' [`AtlasLoader`], `None` if it has none.
+    pub(crate) fn load_file(&self, path: &str) -> Option<Result<Vec<u8>>> {
+        let context = unsafe { ((*self.ptr.0).rendererObject as *const AtlasContext).as_ref()? };
+        let result = lock(context).load_file(path);
+        Some(result)
+    }
+
     // pub fn regions(&self) -> &[AtlasRegion] {
     //     unsafe {
     //         let regions = (*self.ptr.0).regions as *mut AtlasRegion;
@@ -58,6 +236,15 @@ impl Atlas {
     }
 }
 
+/// Textures are loaded relative to the atlas, like spAtlas_createFromFile does.
+fn dir_of(path: &str) -> &str {
+    match path.rfind(|c| c == '/' || c == '\\') {
+        Some(0) => "/",
+        Some(index) => &path[..index],
+        None => "",
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct AtlasRegion {
@@ -83,57 +270,81 @@ impl AtlasRegion {
     }
 
     pub fn width(&self) -> u32 {
-        self.inner.width as u32
+        self.texture_region().width as u32
     }
 
     pub fn height(&self) -> u32 {
-        self.inner.height as u32
+        self.texture_region().height as u32
     }
 
     pub fn u(&self) -> f32 {
-        self.inner.u as f32
+        self.texture_region().u as f32
     }
 
     pub fn v(&self) -> f32 {
-        self.inner.v as f32
+        self.texture_region().v as f32
     }
 
     pub fn u2(&self) -> f32 {
-        self.inner.u2 as f32
+        self.texture_region().u2 as f32
     }
 
     pub fn v2(&self) -> f32 {
-        self.inner.v2 as f32
+        self.texture_region().v2 as f32
     }
 
     pub fn offset_x(&self) -> f32 {
-        self.inner.offsetX as f32
+        self.texture_region().offsetX as f32
     }
 
     pub fn offset_y(&self) -> f32 {
-        self.inner.offsetY as f32
+        self.texture_region().offsetY as f32
     }
 
     pub fn original_width(&self) -> u32 {
-        self.inner.originalWidth as u32
+        self.texture_region().originalWidth as u32
     }
 
     pub fn original_height(&self) -> u32 {
-        self.inner.originalHeight as u32
+        self.texture_region().originalHeight as u32
     }
 
     pub fn index(&self) -> u32 {
         self.inner.index as u32
     }
 
+    #[cfg(not(since_4_1))]
     pub fn rotated(&self) -> bool {
         self.inner.rotate != 0
     }
 
+    #[cfg(since_4_1)]
+    pub fn rotated(&self) -> bool {
+        self.inner.super_.degrees == 90
+    }
+
+    #[cfg(not(since_4_1))]
     pub fn flipped(&self) -> bool {
         self.inner.flip != 0
     }
 
+    /// Atlases can't flip regions since 4.0.
+    #[cfg(since_4_1)]
+    pub fn flipped(&self) -> bool {
+        false
+    }
+
+    /// Fields that moved into `spTextureRegion` in 4.1 and were part of the region before.
+    #[cfg(not(since_4_1))]
+    fn texture_region(&self) -> &spAtlasRegion {
+        &self.inner
+    }
+
+    #[cfg(since_4_1)]
+    fn texture_region(&self) -> &spine_sys::spTextureRegion {
+        &self.inner.super_
+    }
+
     pub fn next_region(&self) -> Option<&'static AtlasRegion> {
         unsafe { (self.inner.next as *const AtlasRegion).as_ref() }
     }
@@ -151,7 +362,17 @@ impl AtlasPage {
     /// # Safety
     /// This is unsafe if the type given does not match the type actually put as texture.
     #[inline]
-    pub unsafe fn render_object<T>(&self) -> Option<&mut T> {
+    pub unsafe fn render_object<T>(&self) -> Option<&T> {
+        (self.inner.rendererObject as *const T).as_ref()
+    }
+
+    /// Like [`Self::render_object`], pages are shared between threads so changing the texture
+    /// needs the page borrowed mutably.
+    ///
+    /// # Safety
+    /// This is unsafe if the type given does not match the type actually put as texture.
+    #[inline]
+    pub unsafe fn render_object_mut<T>(&mut self) -> Option<&mut T> {
         (self.inner.rendererObject as *mut T).as_mut()
     }
 
@@ -1,8 +1,12 @@
-use std::{ffi::CStr, marker::PhantomData, slice};
+use std::{ffi::CStr, marker::PhantomData, os::raw::c_int, slice};
 
-use spine_sys::{spAttachment, spAttachmentType_SP_ATTACHMENT_MESH, spAttachmentType_SP_ATTACHMENT_PATH, spAttachmentType_SP_ATTACHMENT_REGION, spMeshAttachment, spMeshAttachment_computeWorldVertices, spRegionAttachment, spRegionAttachment_computeWorldVertices};
+use spine_sys::{
+    spAttachment, spAttachmentType_SP_ATTACHMENT_MESH, spAttachmentType_SP_ATTACHMENT_PATH,
+    spAttachmentType_SP_ATTACHMENT_REGION, spMeshAttachment, spRegionAttachment,
+    spRegionAttachment_computeWorldVertices, spVertexAttachment,
+};
 
-use crate::{atlas::AtlasRegion, Slot};
+use crate::{atlas::AtlasRegion, skel::raw_slice, Slot};
 
 #[derive(Debug)]
 pub enum AttachmentType<'s, 'tex> {
@@ -75,25 +79,48 @@ impl<'a, 'tex> RegionAttachment<'a, 'tex> {
     pub fn atlas_region(&self) -> &'tex AtlasRegion {
         unsafe {
             let this = *self.ptr;
-            &*(this.rendererObject as *const AtlasRegion)
+            #[cfg(not(since_4_1))]
+            let region = this.rendererObject;
+            // A spTextureRegion since 4.1, which is the start of the spAtlasRegion
+            #[cfg(since_4_1)]
+            let region = this.region;
+            &*(region as *const AtlasRegion)
         }
     }
 
-    pub fn compute_world_vertices(&self, positions: &mut Vec<[f32; 2]>) {
+    /// Replace the contents of `positions` with the vertices in world space, returning how
+    /// many there are.
+    pub fn compute_world_vertices(&self, positions: &mut Vec<[f32; 2]>) -> usize {
         let count = self.world_vertices_count();
-
-        if positions.len() < count {
-            positions.reserve(count - positions.len());
-        };
+        // spine-c writes all of them, the buffer just has to be long enough
+        positions.resize(count, [0.0; 2]);
 
         unsafe {
+            #[cfg(not(since_3_8))]
             spRegionAttachment_computeWorldVertices(
                 self.ptr,
                 self.slot.inner.bone,
                 positions.as_mut_ptr() as *mut _,
             );
-            positions.set_len(count);
+            #[cfg(all(since_3_8, not(since_4_1)))]
+            spRegionAttachment_computeWorldVertices(
+                self.ptr,
+                self.slot.inner.bone,
+                positions.as_mut_ptr() as *mut _,
+                0,
+                2,
+            );
+            // Takes the slot since 4.1, for sequences
+            #[cfg(since_4_1)]
+            spRegionAttachment_computeWorldVertices(
+                self.ptr,
+                &self.slot.inner as *const _ as *mut _,
+                positions.as_mut_ptr() as *mut _,
+                0,
+                2,
+            );
         }
+        count
     }
 
     /// Get the uniform UV value of the vertex at the given index.
@@ -127,21 +154,32 @@ impl<'a, 'tex> MeshAttachment<'a, 'tex> {
         unsafe { (*self.ptr).super_.worldVerticesLength as usize / 2 }
     }
 
-    pub fn compute_world_vertices(&self, positions: &mut Vec<[f32; 2]>) {
+    /// Replace the contents of `positions` with the vertices in world space, returning how
+    /// many there are.
+    pub fn compute_world_vertices(&self, positions: &mut Vec<[f32; 2]>) -> usize {
         let count = self.world_vertices_count();
-
-        if positions.len() < count {
-            positions.reserve(count - positions.len());
-        };
+        // spine-c writes all of them, the buffer just has to be long enough
+        positions.resize(count, [0.0; 2]);
 
         unsafe {
-            spMeshAttachment_computeWorldVertices(
+            #[cfg(not(since_3_8))]
+            spine_sys::spMeshAttachment_computeWorldVertices(
                 self.ptr,
                 &self.slot.inner as *const _ as *mut _,
                 positions.as_mut_ptr() as *mut _,
             );
-            positions.set_len(count);
+            #[cfg(since_3_8)]
+            spine_sys::spVertexAttachment_computeWorldVertices(
+                &mut (*self.ptr).super_,
+                &self.slot.inner as *const _ as *mut _,
+                0,
+                (count * 2) as _,
+                positions.as_mut_ptr() as *mut _,
+                0,
+                2,
+            );
         }
+        count
     }
 
     /// Get the uniform UV value of the vertex at the given index.
@@ -159,7 +197,12 @@ impl<'a, 'tex> MeshAttachment<'a, 'tex> {
     pub fn atlas_region(&self) -> &'tex AtlasRegion {
         unsafe {
             let this = *self.ptr;
-            &*(this.rendererObject as *const AtlasRegion)
+            #[cfg(not(since_4_1))]
+            let region = this.rendererObject;
+            // A spTextureRegion since 4.1, which is the start of the spAtlasRegion
+            #[cfg(since_4_1)]
+            let region = this.region;
+            &*(region as *const AtlasRegion)
         }
     }
 
@@ -170,6 +213,94 @@ impl<'a, 'tex> MeshAttachment<'a, 'tex> {
             slice::from_raw_parts(this.triangles, this.trianglesCount as usize)
         }
     }
+
+    /// Setup pose vertices and bone weights, shared by all skeletons.
+    pub fn vertex_attachment(&self) -> VertexAttachment<'a> {
+        VertexAttachment {
+            ptr: unsafe { &mut (*self.ptr).super_ },
+            _slot: PhantomData,
+        }
+    }
+}
+
+/// Vertices of a mesh in the setup pose. Animations don't change these, deform timelines
+/// write to [`Slot::deform`] instead, which replaces them while it's not empty.
+#[derive(Debug)]
+pub struct VertexAttachment<'s> {
+    ptr: *mut spVertexAttachment,
+    _slot: PhantomData<&'s Slot<'s>>,
+}
+
+impl<'s> VertexAttachment<'s> {
+    pub(crate) fn new(ptr: *mut spVertexAttachment) -> Self {
+        Self {
+            ptr,
+            _slot: PhantomData,
+        }
+    }
+
+    /// Whether the vertices are weighted to bones, see [`Self::vertices`].
+    pub fn is_weighted(&self) -> bool {
+        unsafe { !(*self.ptr).bones.is_null() }
+    }
+
+    /// For each vertex the number of bones it's weighted to, followed by their indices.
+    /// Empty for vertices that only follow the slot's bone.
+    pub fn bones(&self) -> &[c_int] {
+        unsafe {
+            let this = *self.ptr;
+            raw_slice(this.bones, this.bonesCount)
+        }
+    }
+
+    /// `x, y` of each vertex relative to the slot's bone, or for weighted vertices `x, y,
+    /// weight` for each bone in [`Self::bones`]. Deforms of unweighted meshes have the same
+    /// layout, those of weighted meshes an `x, y` offset for each bone a vertex is weighted
+    /// to, without the weights.
+    pub fn vertices(&self) -> &[f32] {
+        unsafe {
+            let this = *self.ptr;
+            raw_slice(this.vertices, this.verticesCount)
+        }
+    }
+
+    /// Number of values in a deform of this attachment, see [`Self::vertices`].
+    pub fn deform_len(&self) -> usize {
+        let len = self.vertices().len();
+        if self.is_weighted() {
+            len / 3 * 2
+        } else {
+            len
+        }
+    }
+
+    /// Number of vertices in world space, what a deform of an unweighted mesh needs two
+    /// values each for.
+    pub fn world_vertices_count(&self) -> usize {
+        unsafe { (*self.ptr).worldVerticesLength as usize / 2 }
+    }
+
+    /// Attachment whose deform timelines apply to this one, linked meshes share them with
+    /// their parent.
+    #[cfg(all(since_3_8, not(since_4_1)))]
+    pub fn deform_attachment(&self) -> Option<VertexAttachment<'s>> {
+        let ptr = unsafe { (*self.ptr).deformAttachment };
+        (!ptr.is_null()).then(|| VertexAttachment {
+            ptr,
+            _slot: PhantomData,
+        })
+    }
+
+    /// Attachment whose deform timelines apply to this one, linked meshes share them with
+    /// their parent.
+    #[cfg(since_4_1)]
+    pub fn deform_attachment(&self) -> Option<VertexAttachment<'s>> {
+        let ptr = unsafe { (*self.ptr).timelineAttachment };
+        (!ptr.is_null()).then(|| VertexAttachment {
+            ptr: ptr as *mut spVertexAttachment,
+            _slot: PhantomData,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,188 @@
+use std::{
+    ffi::{CStr, CString},
+    mem,
+    os::raw::c_char,
+    ptr,
+    sync::Arc,
+};
+
+#[cfg(since_4_1)]
+use spine_sys::spSequence;
+use spine_sys::{
+    _spAttachmentLoader_init, _spAttachmentLoader_setError, spAtlasAttachmentLoader,
+    spAtlasAttachmentLoader_create, spAttachment, spAttachmentLoader,
+    spAttachmentLoader_createAttachment, spAttachmentLoader_dispose, spAttachmentType,
+    spAttachmentType_SP_ATTACHMENT_MESH, spAttachmentType_SP_ATTACHMENT_REGION, spSkin,
+};
+
+use crate::{alloc, Atlas};
+
+/// Picks the atlas regions attachments show, for skeletons loaded with
+/// [`crate::SkeletonData::try_new_binary_with_loader`]. Regions can come from any atlas,
+/// including ones made with [`Atlas::from_memory`] whose textures are generated by
+/// [`crate::SpineCallbacks::load_texture`].
+pub trait AttachmentLoader {
+    /// Region for the region or mesh attachment `name` in `skin`. `path` is the image the
+    /// skeleton asks for, usually the same as `name`. `None` looks `path` up in the atlas the
+    /// skeleton is loaded with, as usual.
+    fn resolve(&mut self, skin: &str, name: &str, path: &str) -> Option<RegionSource>;
+}
+
+/// A region of some atlas, see [`AttachmentLoader::resolve`].
+#[derive(Debug, Clone)]
+pub struct RegionSource {
+    pub atlas: Atlas,
+    /// Name of the region in `atlas`, the attachment fails to load if it contains a NUL byte.
+    pub region: String,
+}
+
+/// spAttachmentLoader calling an [`AttachmentLoader`]. spine-c frees it with its own allocator,
+/// so it is allocated with that too.
+#[repr(C)]
+struct Shim {
+    super_: spAttachmentLoader,
+    /// Creates the attachments, pointed at another atlas while a resolved one is created.
+    atlas_loader: *mut spAtlasAttachmentLoader,
+    loader: Box<dyn AttachmentLoader>,
+    /// Atlases regions were taken from, the skeleton data has to keep them alive.
+    atlases: Vec<Atlas>,
+}
+
+/// Owns a [`Shim`] until it's dropped, the skeleton binary reading with it doesn't.
+pub(crate) struct LoaderHandle(*mut Shim);
+
+impl LoaderHandle {
+    pub fn new(atlas: &Atlas, loader: Box<dyn AttachmentLoader>) -> Self {
+        unsafe {
+            let shim = alloc::calloc(1, mem::size_of::<Shim>()) as *mut Shim;
+            ptr::addr_of_mut!((*shim).atlas_loader)
+                .write(spAtlasAttachmentLoader_create(atlas.ptr.0));
+            ptr::addr_of_mut!((*shim).loader).write(loader);
+            ptr::addr_of_mut!((*shim).atlases).write(Vec::new());
+            _spAttachmentLoader_init(
+                shim as *mut spAttachmentLoader,
+                Some(dispose),
+                Some(create_attachment),
+                None,
+                None,
+            );
+            LoaderHandle(shim)
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut spAttachmentLoader {
+        self.0 as *mut spAttachmentLoader
+    }
+
+    /// Atlases used by the attachments created so far.
+    pub fn take_atlases(&mut self) -> Vec<Atlas> {
+        unsafe { mem::take(&mut (*self.0).atlases) }
+    }
+}
+
+impl Drop for LoaderHandle {
+    fn drop(&mut self) {
+        unsafe { spAttachmentLoader_dispose(self.as_ptr()) };
+    }
+}
+
+unsafe extern "C" fn dispose(this: *mut spAttachmentLoader) {
+    let shim = this as *mut Shim;
+    spAttachmentLoader_dispose((*shim).atlas_loader as *mut spAttachmentLoader);
+    ptr::drop_in_place(ptr::addr_of_mut!((*shim).loader));
+    ptr::drop_in_place(ptr::addr_of_mut!((*shim).atlases));
+}
+
+#[cfg(not(since_4_1))]
+unsafe extern "C" fn create_attachment(
+    this: *mut spAttachmentLoader,
+    skin: *mut spSkin,
+    type_: spAttachmentType,
+    name: *const c_char,
+    path: *const c_char,
+) -> *mut spAttachment {
+    create(this, skin, type_, name, path, |loader, path| {
+        spAttachmentLoader_createAttachment(loader, skin, type_, name, path)
+    })
+}
+
+/// Since 4.1 spine-c reads the sequence of regions an attachment flips through first, the
+/// atlas loader looks its regions up by `path`.
+#[cfg(since_4_1)]
+unsafe extern "C" fn create_attachment(
+    this: *mut spAttachmentLoader,
+    skin: *mut spSkin,
+    type_: spAttachmentType,
+    name: *const c_char,
+    path: *const c_char,
+    sequence: *mut spSequence,
+) -> *mut spAttachment {
+    create(this, skin, type_, name, path, |loader, path| {
+        spAttachmentLoader_createAttachment(loader, skin, type_, name, path, sequence)
+    })
+}
+
+/// Create the attachment with the atlas loader through `create_with`, from the region the
+/// [`AttachmentLoader`] resolves to if any.
+unsafe fn create(
+    this: *mut spAttachmentLoader,
+    skin: *mut spSkin,
+    type_: spAttachmentType,
+    name: *const c_char,
+    path: *const c_char,
+    create_with: impl Fn(*mut spAttachmentLoader, *const c_char) -> *mut spAttachment,
+) -> *mut spAttachment {
+    let shim = &mut *(this as *mut Shim);
+    let atlas_loader = shim.atlas_loader as *mut spAttachmentLoader;
+    let path = if path.is_null() { name } else { path };
+
+    #[allow(non_upper_case_globals)]
+    let source = match type_ {
+        spAttachmentType_SP_ATTACHMENT_REGION | spAttachmentType_SP_ATTACHMENT_MESH => {
+            let string = |s: *const c_char| CStr::from_ptr(s).to_string_lossy();
+            let skin = string((*skin).name);
+            shim.loader.resolve(&skin, &string(name), &string(path))
+        }
+        _ => None,
+    };
+
+    let attachment = match source {
+        Some(source) => {
+            let region = match CString::new(source.region) {
+                Ok(region) => region,
+                Err(_) => {
+                    // spine-c would look up the name cut short, fail like a missing region
+                    let error = "Region name with a NUL byte for attachment: \0";
+                    _spAttachmentLoader_setError(this, error.as_ptr() as *const c_char, name);
+                    return ptr::null_mut();
+                }
+            };
+            let own_atlas = (*shim.atlas_loader).atlas;
+            (*shim.atlas_loader).atlas = source.atlas.ptr.0;
+            let attachment = create_with(atlas_loader, region.as_ptr());
+            (*shim.atlas_loader).atlas = own_atlas;
+
+            if !shim
+                .atlases
+                .iter()
+                .any(|atlas| Arc::ptr_eq(&atlas.ptr, &source.atlas.ptr))
+            {
+                shim.atlases.push(source.atlas);
+            }
+            attachment
+        }
+        None => create_with(atlas_loader, path),
+    };
+
+    // Without an error the attachment would be left out silently
+    let (error1, error2) = ((*atlas_loader).error1, (*atlas_loader).error2);
+    if attachment.is_null() && !error1.is_null() {
+        let error2 = if error2.is_null() {
+            "\0".as_ptr() as *const c_char
+        } else {
+            error2
+        };
+        _spAttachmentLoader_setError(this, error1, error2);
+    }
+    attachment
+}
@@ -0,0 +1,163 @@
+use std::{
+    ffi::CStr,
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+};
+
+use spine_sys::{spBone, spBone_localToWorld, spBone_worldToLocal, spIkConstraint};
+
+use crate::Skeleton;
+
+#[repr(C)]
+pub struct Bone<'sk> {
+    pub(crate) inner: spBone,
+    _skel: PhantomData<&'sk Skeleton>,
+}
+
+impl<'sk> Bone<'sk> {
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr((*self.inner.data).name).to_str().unwrap() }
+    }
+
+    pub fn parent(&self) -> Option<&Bone<'sk>> {
+        unsafe { (self.inner.parent as *const Bone).as_ref() }
+    }
+
+    /// Length of the bone in the setup pose, zero for bones that are just points.
+    pub fn length(&self) -> f32 {
+        unsafe { (*self.inner.data).length }
+    }
+
+    /// Local X position, relative to the parent bone.
+    pub fn x(&self) -> f32 {
+        self.inner.x
+    }
+
+    /// Local Y position, relative to the parent bone.
+    pub fn y(&self) -> f32 {
+        self.inner.y
+    }
+
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.inner.x = x;
+        self.inner.y = y;
+    }
+
+    /// Local rotation in degrees.
+    pub fn rotation(&self) -> f32 {
+        self.inner.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.inner.rotation = rotation;
+    }
+
+    pub fn scale(&self) -> (f32, f32) {
+        (self.inner.scaleX, self.inner.scaleY)
+    }
+
+    pub fn set_scale(&mut self, scale_x: f32, scale_y: f32) {
+        self.inner.scaleX = scale_x;
+        self.inner.scaleY = scale_y;
+    }
+
+    /// Local shear in degrees.
+    pub fn shear(&self) -> (f32, f32) {
+        (self.inner.shearX, self.inner.shearY)
+    }
+
+    pub fn set_shear(&mut self, shear_x: f32, shear_y: f32) {
+        self.inner.shearX = shear_x;
+        self.inner.shearY = shear_y;
+    }
+
+    /// World X position, as of the last [`Skeleton::update_world_transform`].
+    pub fn world_x(&self) -> f32 {
+        self.inner.worldX
+    }
+
+    /// World Y position, as of the last [`Skeleton::update_world_transform`].
+    pub fn world_y(&self) -> f32 {
+        self.inner.worldY
+    }
+
+    /// Transform a point from world space into the local space of this bone.
+    pub fn world_to_local(&self, world_x: f32, world_y: f32) -> (f32, f32) {
+        let (mut x, mut y) = (0.0, 0.0);
+        unsafe {
+            spBone_worldToLocal(
+                &self.inner as *const _ as *mut _,
+                world_x,
+                world_y,
+                &mut x,
+                &mut y,
+            );
+        }
+        (x, y)
+    }
+
+    /// Transform a point from the local space of this bone into world space.
+    pub fn local_to_world(&self, local_x: f32, local_y: f32) -> (f32, f32) {
+        let (mut x, mut y) = (0.0, 0.0);
+        unsafe {
+            spBone_localToWorld(
+                &self.inner as *const _ as *mut _,
+                local_x,
+                local_y,
+                &mut x,
+                &mut y,
+            );
+        }
+        (x, y)
+    }
+}
+
+impl<'sk> Debug for Bone<'sk> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Bone")
+            .field("name", &self.name())
+            .field("x", &self.x())
+            .field("y", &self.y())
+            .field("rotation", &self.rotation())
+            .finish()
+    }
+}
+
+#[repr(C)]
+pub struct IkConstraint<'sk> {
+    pub(crate) inner: spIkConstraint,
+    _skel: PhantomData<&'sk Skeleton>,
+}
+
+impl<'sk> IkConstraint<'sk> {
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr((*self.inner.data).name).to_str().unwrap() }
+    }
+
+    /// The bone the constrained bones are pointed towards.
+    pub fn target(&self) -> &Bone<'sk> {
+        unsafe { &*(self.inner.target as *const Bone) }
+    }
+
+    pub fn target_mut(&mut self) -> &mut Bone<'sk> {
+        unsafe { &mut *(self.inner.target as *mut Bone) }
+    }
+
+    /// How much the constraint affects the bones, from 0 to 1.
+    pub fn mix(&self) -> f32 {
+        self.inner.mix
+    }
+
+    pub fn set_mix(&mut self, mix: f32) {
+        self.inner.mix = mix;
+    }
+}
+
+impl<'sk> Debug for IkConstraint<'sk> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("IkConstraint")
+            .field("name", &self.name())
+            .field("mix", &self.mix())
+            .finish()
+    }
+}
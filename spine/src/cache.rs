@@ -0,0 +1,84 @@
+//! Loaded atlases and skeletons shared by path, so a model shown twice is only parsed once.
+//!
+//! Nothing is loaded through here unless asked for, and entries stay until they are
+//! invalidated, even when nothing uses them anymore.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Result;
+
+use crate::{Atlas, SkeletonData};
+
+static ATLASES: Mutex<Option<HashMap<String, Atlas>>> = Mutex::new(None);
+/// Keyed by path and the bits of the scale, along with the path of the atlas they use.
+static SKELETONS: Mutex<Option<HashMap<(String, u32), (SkeletonData, String)>>> = Mutex::new(None);
+
+/// A panic while loading leaves the map as it was.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// [`Atlas::try_new`], or the atlas loaded from `path` before.
+pub fn load_atlas(path: &str) -> Result<Atlas> {
+    if let Some(atlas) = lock(&ATLASES)
+        .as_ref()
+        .and_then(|atlases| atlases.get(path))
+    {
+        return Ok(atlas.clone());
+    }
+
+    // Parsed without the lock so other files load meanwhile, the first one loaded is kept
+    let atlas = Atlas::try_new(path)?;
+    let mut atlases = lock(&ATLASES);
+    let atlases = atlases.get_or_insert_with(HashMap::new);
+    Ok(atlases.entry(path.to_owned()).or_insert(atlas).clone())
+}
+
+/// [`SkeletonData::try_new_binary`], or the skeleton loaded from `path` at the same scale
+/// before. The atlas is the `.atlas` file next to it, loaded with [`load_atlas`].
+pub fn load_skeleton_data(path: &str, scale: f32) -> Result<SkeletonData> {
+    let key = (path.to_owned(), scale.to_bits());
+    if let Some((skel_data, _)) = lock(&SKELETONS)
+        .as_ref()
+        .and_then(|skeletons| skeletons.get(&key))
+    {
+        return Ok(skel_data.clone());
+    }
+
+    let atlas_path = Path::new(path)
+        .with_extension("atlas")
+        .to_string_lossy()
+        .into_owned();
+    let atlas = load_atlas(&atlas_path)?;
+    let skel_data = SkeletonData::try_new_binary(&atlas, path, scale)?;
+    let mut skeletons = lock(&SKELETONS);
+    let skeletons = skeletons.get_or_insert_with(HashMap::new);
+    Ok(skeletons
+        .entry(key)
+        .or_insert((skel_data, atlas_path))
+        .0
+        .clone())
+}
+
+/// Forget the atlas or skeletons loaded from `path`, the next load reads it again. Skeletons
+/// using an atlas loaded from `path` are forgotten with it. Those already handed out stay
+/// usable.
+pub fn invalidate(path: &str) {
+    if let Some(atlases) = lock(&ATLASES).as_mut() {
+        atlases.remove(path);
+    }
+    let mut skeletons = lock(&SKELETONS);
+    if let Some(skeletons) = skeletons.as_mut() {
+        skeletons.retain(|(skel_path, _), (_, atlas_path)| skel_path != path && atlas_path != path);
+    }
+}
+
+/// Forget everything loaded so far.
+pub fn clear() {
+    *lock(&ATLASES) = None;
+    *lock(&SKELETONS) = None;
+}
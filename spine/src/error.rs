@@ -0,0 +1,118 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Display, Formatter},
+    mem,
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Why an asset couldn't be loaded, returned inside [`anyhow::Error`] by the `try_` loaders so
+/// callers can tell a broken file apart from other failures with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpineError {
+    /// The path can't be passed to spine-c, it contains a NUL byte.
+    InvalidPath(String),
+    /// [`crate::SpineCallbacks::load_file`] failed or returned nothing.
+    Read(String),
+    /// The file is not a Spine asset of the expected kind, or is cut short.
+    Corrupted { path: String, reason: String },
+    /// The skeleton was exported from a Spine editor this runtime can't read.
+    UnsupportedVersion {
+        path: String,
+        found: String,
+        supported: &'static str,
+    },
+    /// spine-c rejected the file, with its own error message.
+    Parse { path: String, message: String },
+}
+
+impl Display for SpineError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SpineError::InvalidPath(path) => write!(f, "Invalid path: {:?}", path),
+            SpineError::Read(path) => write!(f, "Failed to read file: {}", path),
+            SpineError::Corrupted { path, reason } => {
+                write!(f, "{} is not a valid Spine file: {}", path, reason)
+            }
+            SpineError::UnsupportedVersion {
+                path,
+                found,
+                supported,
+            } => write!(
+                f,
+                "{} was exported from Spine {}, the runtime supports {}",
+                path, found, supported
+            ),
+            SpineError::Parse { path, message } => {
+                write!(f, "Failed to load {}: {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpineError {}
+
+/// A failure inside a callback spine-c calls, which can't return it to whoever is loading
+/// the asset. These go to the hook set with [`set_error_hook`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpineRuntimeError {
+    /// An atlas page texture couldn't be loaded, its regions won't render.
+    LoadTexture { path: String, message: String },
+    /// A file couldn't be read.
+    LoadFile { path: String, message: String },
+}
+
+impl Display for SpineRuntimeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SpineRuntimeError::LoadTexture { path, message } => {
+                write!(f, "Failed to load texture {}: {}", path, message)
+            }
+            SpineRuntimeError::LoadFile { path, message } => {
+                write!(f, "Failed to load file {}: {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpineRuntimeError {}
+
+/// `fn(&SpineRuntimeError)` set with [`set_error_hook`], null until then.
+static ERROR_HOOK: AtomicPtr<()> = AtomicPtr::new(null_mut());
+
+/// Route runtime errors to `hook` instead of stderr, for apps that have no console.
+pub fn set_error_hook(hook: fn(&SpineRuntimeError)) {
+    ERROR_HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+thread_local! {
+    /// Errors reported during [`collect_errors`] on this thread.
+    static COLLECTED: RefCell<Option<Vec<SpineRuntimeError>>> = RefCell::new(None);
+}
+
+/// Run `f`, returning the errors it reported along with its result. They still go to the
+/// hook too.
+pub(crate) fn collect_errors<T>(f: impl FnOnce() -> T) -> (T, Vec<SpineRuntimeError>) {
+    let outer = COLLECTED.with(|collected| collected.replace(Some(Vec::new())));
+    let value = f();
+    let errors = COLLECTED.with(|collected| collected.replace(outer));
+    (value, errors.unwrap_or_default())
+}
+
+/// Pass `error` to the hook set with [`set_error_hook`].
+#[doc(hidden)]
+pub fn report(error: SpineRuntimeError) {
+    COLLECTED.with(|collected| {
+        if let Some(errors) = collected.borrow_mut().as_mut() {
+            errors.push(error.clone());
+        }
+    });
+
+    let hook = ERROR_HOOK.load(Ordering::Acquire);
+    if hook.is_null() {
+        eprintln!("Spine: {}", error);
+    } else {
+        let hook: fn(&SpineRuntimeError) = unsafe { mem::transmute(hook) };
+        hook(&error);
+    }
+}
@@ -2,6 +2,35 @@
 //!
 //! (Almost) safe binding to the `spine-c` runtime, used for 2D animation.
 //!
+//! ## Threads
+//!
+//! [`Atlas`], [`SkeletonData`] and [`AnimationStateData`] are loaded once and only read after
+//! that, so they are `Send + Sync` and can be loaded on another thread than they are used on.
+//! [`Skeleton`] and [`AnimationState`] are `Send` but not `Sync`, each one belongs to one
+//! thread at a time. Textures have to be `Send + Sync` for this, wrap those that aren't in a
+//! [`SpineThreadGuard`].
+//!
+
+/// Error types of the `try_` loaders and the runtime callbacks
+pub mod error;
+pub use error::{set_error_hook, SpineError, SpineRuntimeError};
+
+mod loader;
+
+/// spine-c's allocator, used by [`spine_init`]
+#[doc(hidden)]
+pub mod alloc;
+
+/// Major and minor version of the spine-c runtime, skeletons have to be exported from a
+/// matching editor version. Picked with the `spine-*` features.
+#[cfg(not(since_3_8))]
+pub const RUNTIME_VERSION: &str = "3.5";
+#[cfg(all(since_3_8, not(since_4_1)))]
+pub const RUNTIME_VERSION: &str = "3.8";
+#[cfg(all(since_4_1, not(since_4_2)))]
+pub const RUNTIME_VERSION: &str = "4.1";
+#[cfg(since_4_2)]
+pub const RUNTIME_VERSION: &str = "4.2";
 
 /// Atlas (texture) types
 pub mod atlas;
@@ -9,22 +38,52 @@ pub use atlas::{Atlas, AtlasPage};
 
 /// Animation types
 pub mod anim;
-pub use anim::{AnimationState, AnimationStateData};
+pub use anim::{
+    AnimationEvent, AnimationState, AnimationStateData, Completion, EventType, TrackEntry,
+};
+
+/// Read-only view of what animations key
+pub mod timeline;
+pub use timeline::{Timeline, TimelineKind, TimelineTarget};
 
 /// Skeleton types
 pub mod skel;
-pub use skel::{BlendMode, Skeleton, SkeletonData, Slot};
+pub use skel::{BlendMode, Bounds, Skeleton, SkeletonData, Slot, SlotDatas, Slots};
+
+/// Batching a posed skeleton into textured triangles for any graphics API
+pub mod render;
+pub use render::{RenderCommand, RenderCommands, RenderVertex};
+
+/// Bone and constraint types
+pub mod bone;
+pub use bone::{Bone, IkConstraint};
 
 /// Skeleton attachment types
 pub mod attachment;
 pub use attachment::{Attachment, AttachmentType};
 
+/// Extension point for where attachments get their images from
+pub mod attachment_loader;
+pub use attachment_loader::{AttachmentLoader, RegionSource};
+
+/// Skeleton snapshot types, with `serde` support behind the feature of the same name
+pub mod pose;
+pub use pose::{BonePose, Pose, SlotPose, Snapshot, TrackSnapshot};
+
+/// Atlases and skeletons shared by path
+pub mod cache;
+
+/// Keeping values on the thread they belong to
+pub mod thread;
+pub use thread::SpineThreadGuard;
+
 /// Re-export of FFI bindings
 pub use spine_sys as sys;
 
 /// Callbacks used by Spine runtime to perform various tasks
 pub trait SpineCallbacks {
-    type Texture;
+    /// Shared with every thread the atlas is used on, see the [crate docs](crate#threads).
+    type Texture: Send + Sync;
     type LoadTextureError: AsRef<dyn std::error::Error + Send + Sync + 'static>;
     type LoadFileError: AsRef<dyn std::error::Error + Send + Sync + 'static>;
 
@@ -37,6 +96,21 @@ pub trait SpineCallbacks {
     fn load_file(path: &str) -> Result<Vec<u8>, Self::LoadFileError>;
 }
 
+/// Callbacks of a single atlas, see [`Atlas::with_loader`]. Atlases without one use the
+/// [`SpineCallbacks`] given to [`spine_init`].
+pub trait AtlasLoader: Send + 'static {
+    type Texture: Send + Sync;
+
+    /// Like [`SpineCallbacks::load_texture`], the texture is dropped with the atlas.
+    fn load_texture(
+        &mut self,
+        path: &str,
+        page: &AtlasPage,
+    ) -> anyhow::Result<(Self::Texture, u32, u32)>;
+    /// Read the atlas file, and skeleton files loaded with the atlas.
+    fn load_file(&mut self, path: &str) -> anyhow::Result<Vec<u8>>;
+}
+
 /// Register callbacks to be used by Spine runtime,
 /// you may encounter linking errors regarding `_spAtlasPage_createTexture`
 /// and `_spAtlasPage_disposeTexture` without this.
@@ -50,6 +124,9 @@ macro_rules! spine_init {
             path: *const std::os::raw::c_char,
         ) {
             let path = std::ffi::CStr::from_ptr(path).to_string_lossy();
+            if $crate::atlas::create_texture_with_loader(this, path.as_ref()) {
+                return;
+            }
 
             let page = (this as *const $crate::atlas::AtlasPage).as_ref().unwrap();
 
@@ -57,7 +134,10 @@ macro_rules! spine_init {
                 match <$t as $crate::SpineCallbacks>::load_texture(path.as_ref(), page) {
                     Ok(v) => v,
                     Err(e) => {
-                        eprintln!("Spine: Failed to load texture: {}", e);
+                        $crate::error::report($crate::SpineRuntimeError::LoadTexture {
+                            path: path.into_owned(),
+                            message: e.as_ref().to_string(),
+                        });
                         return;
                     }
                 };
@@ -72,6 +152,10 @@ macro_rules! spine_init {
         #[allow(clippy::missing_safety_doc)]
         #[no_mangle]
         pub unsafe extern "C" fn _spAtlasPage_disposeTexture(this: *mut $crate::sys::spAtlasPage) {
+            if $crate::atlas::dispose_texture_with_loader(this) {
+                return;
+            }
+
             let this = this.as_mut().unwrap();
 
             if this.rendererObject.is_null() {
@@ -96,13 +180,16 @@ macro_rules! spine_init {
             let buf = match <$t as $crate::SpineCallbacks>::load_file(path.as_ref()) {
                 Ok(v) => v,
                 Err(e) => {
-                    eprintln!("Spine: Failed to load file: {}", e);
+                    $crate::error::report($crate::SpineRuntimeError::LoadFile {
+                        path: path.into_owned(),
+                        message: e.as_ref().to_string(),
+                    });
                     return std::ptr::null_mut();
                 }
             };
 
             // Copy is needed because the allocator can be different and we don't control the free process
-            let native_buf = $crate::sys::_malloc(buf.len() as _, std::ptr::null(), 0) as *mut u8;
+            let native_buf = $crate::alloc::malloc(buf.len()) as *mut u8;
             let native_slice = std::slice::from_raw_parts_mut(native_buf, buf.len());
             native_slice.copy_from_slice(&buf);
 
@@ -0,0 +1,464 @@
+//! Checks done on asset files before spine-c sees them, its readers trust their input and
+//! read out of bounds or crash on files that are cut short or not Spine files at all.
+
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_int},
+    slice,
+};
+
+use crate::{error::report, Atlas, SpineError, SpineRuntimeError, RUNTIME_VERSION};
+
+extern "C" {
+    /// Defined by [`crate::spine_init`], goes through [`crate::SpineCallbacks::load_file`].
+    fn _spUtil_readFile(path: *const c_char, length: *mut c_int) -> *mut c_char;
+}
+
+/// File contents, either allocated by spine-c and freed with it, or read by an
+/// [`crate::AtlasLoader`].
+pub(crate) enum FileBuf {
+    Spine { ptr: *mut c_char, len: usize },
+    Owned(Vec<u8>),
+}
+
+impl FileBuf {
+    pub fn read(path: &str) -> Result<Self, SpineError> {
+        let c_path = c_path(path)?;
+        let mut len = 0;
+        let ptr = unsafe { _spUtil_readFile(c_path.as_ptr(), &mut len) };
+        if ptr.is_null() || len < 0 {
+            return Err(SpineError::Read(path.to_owned()));
+        }
+
+        Ok(FileBuf::Spine {
+            ptr,
+            len: len as usize,
+        })
+    }
+
+    /// Read with the [`crate::AtlasLoader`] of `atlas`, or like [`Self::read`] without one.
+    pub fn read_with(atlas: &Atlas, path: &str) -> Result<Self, SpineError> {
+        match atlas.load_file(path) {
+            Some(Ok(data)) => Ok(FileBuf::Owned(data)),
+            Some(Err(e)) => {
+                report(SpineRuntimeError::LoadFile {
+                    path: path.to_owned(),
+                    message: e.to_string(),
+                });
+                Err(SpineError::Read(path.to_owned()))
+            }
+            None => Self::read(path),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            FileBuf::Spine { ptr, len } => unsafe {
+                slice::from_raw_parts(*ptr as *const u8, *len)
+            },
+            FileBuf::Owned(data) => data,
+        }
+    }
+}
+
+impl Drop for FileBuf {
+    fn drop(&mut self) {
+        if let FileBuf::Spine { ptr, .. } = self {
+            unsafe { crate::alloc::free(*ptr as *mut _) };
+        }
+    }
+}
+
+pub(crate) fn c_path(path: &str) -> Result<CString, SpineError> {
+    CString::new(path).map_err(|_| SpineError::InvalidPath(path.to_owned()))
+}
+
+/// Reads from a binary skeleton the way `spSkeletonBinary` does, but never past the end.
+struct BinaryReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BinaryReader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(byte)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.data = self.data.get(len..)?;
+        Some(())
+    }
+
+    fn varint(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for i in 0..5 {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u32) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// `Some(None)` for a null string.
+    fn string(&mut self) -> Option<Option<&'a str>> {
+        let len = match self.varint()? {
+            0 => return Some(None),
+            len => len as usize - 1,
+        };
+        let bytes = self.data.get(..len)?;
+        self.data = &self.data[len..];
+        Some(Some(std::str::from_utf8(bytes).ok()?))
+    }
+}
+
+/// Editor version a binary skeleton was exported from, like `3.5.51`. Newer exports start
+/// with a 64-bit hash instead of a string, both layouts are tried.
+pub(crate) fn binary_version(data: &[u8]) -> Option<String> {
+    binary_header(data).map(|(version, _)| version.to_owned())
+}
+
+/// Version and what follows it.
+fn binary_header(data: &[u8]) -> Option<(&str, BinaryReader<'_>)> {
+    let string_hash = || {
+        let mut reader = BinaryReader { data };
+        reader.string()?;
+        Some((reader.string()??, reader))
+    };
+    let long_hash = || {
+        let mut reader = BinaryReader { data };
+        reader.skip(8)?;
+        Some((reader.string()??, reader))
+    };
+
+    [string_hash(), long_hash()]
+        .into_iter()
+        .flatten()
+        .find(|(version, _)| is_version(version))
+}
+
+fn is_version(version: &str) -> bool {
+    let mut parts = version.split('.');
+    let major = parts
+        .next()
+        .map_or(false, |part| part.parse::<u32>().is_ok());
+    let minor = parts
+        .next()
+        .map_or(false, |part| part.parse::<u32>().is_ok());
+    major && minor
+}
+
+/// Whether skeletons exported from `version` can be read, only the major and minor version
+/// have to match.
+pub(crate) fn is_supported(version: &str) -> bool {
+    let mut parts = version.split('.');
+    let mut supported = RUNTIME_VERSION.split('.');
+    parts.next() == supported.next() && parts.next() == supported.next()
+}
+
+/// Make sure `data` is a binary skeleton of a supported version.
+pub(crate) fn check_binary(path: &str, data: &[u8]) -> Result<(), SpineError> {
+    let corrupted = |reason: &str| SpineError::Corrupted {
+        path: path.to_owned(),
+        reason: reason.to_owned(),
+    };
+
+    if data.first() == Some(&b'{') {
+        return Err(corrupted("this is a JSON skeleton, export it as binary"));
+    }
+    let (version, mut reader) =
+        binary_header(data).ok_or_else(|| corrupted("no version in the header"))?;
+    if !is_supported(version) {
+        return Err(SpineError::UnsupportedVersion {
+            path: path.to_owned(),
+            found: version.to_owned(),
+            supported: RUNTIME_VERSION,
+        });
+    }
+
+    // At least the size and whether nonessential data follows come right after the version
+    reader
+        .skip(9)
+        .ok_or_else(|| corrupted("the file ends after the header"))
+}
+
+/// Make sure `data` follows the atlas format closely enough for spine-c to parse it.
+#[cfg(not(since_4_1))]
+pub(crate) fn check_atlas(path: &str, data: &[u8]) -> Result<(), SpineError> {
+    let text = std::str::from_utf8(data).map_err(|_| SpineError::Corrupted {
+        path: path.to_owned(),
+        reason: "not a text file".to_owned(),
+    })?;
+    let mut lines = AtlasLines {
+        path,
+        lines: text.lines().map(str::trim).enumerate().peekable(),
+        line: 0,
+    };
+
+    let mut pages = 0;
+    let mut in_page = false;
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            in_page = false;
+            continue;
+        }
+
+        if !in_page {
+            // Page header, size is missing in atlases from old texture packers
+            if lines.next_key() == Some("size") {
+                lines.expect("size", Some(2))?;
+            }
+            lines.expect("format", None)?;
+            lines.expect("filter", None)?;
+            lines.expect("repeat", None)?;
+            in_page = true;
+            pages += 1;
+            continue;
+        }
+
+        lines.expect("rotate", None)?;
+        lines.expect("xy", Some(2))?;
+        lines.expect("size", Some(2))?;
+        if lines.next_key() == Some("split") {
+            lines.expect("split", Some(4))?;
+            if lines.next_key() == Some("pad") {
+                lines.expect("pad", Some(4))?;
+            }
+        }
+        lines.expect("orig", Some(2))?;
+        lines.expect("offset", None)?;
+        lines.expect("index", None)?;
+    }
+
+    if pages == 0 {
+        return Err(lines.corrupted("no pages"));
+    }
+    Ok(())
+}
+
+/// The 4.0 format has optional fields in any order, which spine-c reads without
+/// trusting them, so only check that there is a page at all.
+#[cfg(since_4_1)]
+pub(crate) fn check_atlas(path: &str, data: &[u8]) -> Result<(), SpineError> {
+    let text = std::str::from_utf8(data).map_err(|_| SpineError::Corrupted {
+        path: path.to_owned(),
+        reason: "not a text file".to_owned(),
+    })?;
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty());
+    match (lines.next(), lines.next()) {
+        (Some(page), Some(field)) if !page.contains(':') && field.contains(':') => Ok(()),
+        _ => Err(SpineError::Corrupted {
+            path: path.to_owned(),
+            reason: "no pages".to_owned(),
+        }),
+    }
+}
+
+#[cfg(not(since_4_1))]
+struct AtlasLines<'a, I: Iterator<Item = (usize, &'a str)>> {
+    path: &'a str,
+    lines: std::iter::Peekable<I>,
+    /// Index of the last line read.
+    line: usize,
+}
+
+#[cfg(not(since_4_1))]
+impl<'a, I: Iterator<Item = (usize, &'a str)>> AtlasLines<'a, I> {
+    fn next(&mut self) -> Option<&'a str> {
+        let (index, line) = self.lines.next()?;
+        self.line = index;
+        Some(line)
+    }
+
+    /// Key of the next line if it's a `key: value` line, without consuming it.
+    fn next_key(&mut self) -> Option<&'a str> {
+        let line: &'a str = self.lines.peek()?.1;
+        line.split_once(':').map(|(key, _)| key.trim())
+    }
+
+    /// Read a `key: value` line, where the value is `values` non-negative numbers if given.
+    fn expect(&mut self, key: &str, values: Option<usize>) -> Result<(), SpineError> {
+        let value = self
+            .next()
+            .and_then(|line| line.strip_prefix(key))
+            .and_then(|rest| rest.trim_start().strip_prefix(':'))
+            .ok_or_else(|| self.corrupted(&format!("expected `{}`", key)))?;
+
+        if let Some(count) = values {
+            let numbers: Vec<_> = value.split(',').map(|n| n.trim().parse::<u32>()).collect();
+            if numbers.len() != count || numbers.iter().any(Result::is_err) {
+                return Err(self.corrupted(&format!("`{}` needs {} numbers", key, count)));
+            }
+        }
+        Ok(())
+    }
+
+    fn corrupted(&self, reason: &str) -> SpineError {
+        SpineError::Corrupted {
+            path: self.path.to_owned(),
+            reason: format!("line {}: {}", self.line + 1, reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoFiles;
+
+    impl crate::SpineCallbacks for NoFiles {
+        type Texture = ();
+        type LoadTextureError = anyhow::Error;
+        type LoadFileError = anyhow::Error;
+
+        fn load_texture(path: &str, _: &crate::AtlasPage) -> anyhow::Result<((), u32, u32)> {
+            anyhow::bail!("no texture at {}", path)
+        }
+
+        fn load_file(path: &str) -> anyhow::Result<Vec<u8>> {
+            anyhow::bail!("no file at {}", path)
+        }
+    }
+
+    crate::spine_init!(NoFiles);
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut data = vec![s.len() as u8 + 1];
+        data.extend(s.as_bytes());
+        data
+    }
+
+    /// Header with a string hash, then the size and nonessential flag.
+    fn skeleton(version: &str) -> Vec<u8> {
+        let mut data = string("hash");
+        data.extend(string(version));
+        data.extend([0; 9]);
+        data
+    }
+
+    fn supported_version() -> String {
+        format!("{}.10", RUNTIME_VERSION)
+    }
+
+    fn is_corrupted(result: Result<(), SpineError>) -> bool {
+        matches!(result, Err(SpineError::Corrupted { .. }))
+    }
+
+    #[test]
+    fn reads_string_hash_header() {
+        assert_eq!(
+            binary_version(&skeleton("3.5.51")).as_deref(),
+            Some("3.5.51")
+        );
+    }
+
+    #[test]
+    fn reads_long_hash_header() {
+        let mut data = vec![0xab; 8];
+        data.extend(string("4.1.23"));
+        assert_eq!(binary_version(&data).as_deref(), Some("4.1.23"));
+    }
+
+    #[test]
+    fn only_takes_numbers_as_version() {
+        assert!(is_version("3.8.99"));
+        assert!(is_version("4.2"));
+        assert!(!is_version("4"));
+        assert!(!is_version("v4.2"));
+        assert!(!is_version("hash"));
+        assert_eq!(binary_version(&skeleton("hash")), None);
+    }
+
+    #[test]
+    fn compares_major_and_minor_version() {
+        assert!(is_supported(RUNTIME_VERSION));
+        assert!(is_supported(&supported_version()));
+        assert!(!is_supported("1.0.0"));
+        assert!(!is_supported(&format!("{}0", RUNTIME_VERSION)));
+    }
+
+    #[test]
+    fn accepts_supported_skeleton() {
+        assert!(check_binary("test", &skeleton(&supported_version())).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let result = check_binary("test", &skeleton("1.0.0"));
+        assert!(matches!(
+            result,
+            Err(SpineError::UnsupportedVersion { found, .. }) if found == "1.0.0"
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_varint() {
+        assert!(is_corrupted(check_binary("test", &[])));
+        assert!(is_corrupted(check_binary("test", &[0xff, 0xff])));
+        assert!(is_corrupted(check_binary("test", &[0xff; 5])));
+    }
+
+    #[test]
+    fn rejects_oversized_string_length() {
+        let mut data = vec![0xff, 0xff, 0xff, 0xff, 0x0f];
+        data.extend(string(&supported_version()));
+        assert!(is_corrupted(check_binary("test", &data)));
+
+        let mut data = string("hash");
+        data.extend([0x90, 0x4e]);
+        data.extend(supported_version().as_bytes());
+        assert!(is_corrupted(check_binary("test", &data)));
+    }
+
+    #[test]
+    fn rejects_file_ending_after_header() {
+        let mut data = string("hash");
+        data.extend(string(&supported_version()));
+        assert!(is_corrupted(check_binary("test", &data)));
+    }
+
+    #[test]
+    fn rejects_json_skeleton() {
+        assert!(is_corrupted(check_binary("test", b"{\"skeleton\":{}}")));
+    }
+
+    const ATLAS: &str = "
+page.png
+size: 64,64
+format: RGBA8888
+filter: Linear,Linear
+repeat: none
+region
+  rotate: false
+  xy: 0, 0
+  size: 8, 8
+  orig: 8, 8
+  offset: 0, 0
+  index: -1
+";
+
+    #[test]
+    fn accepts_atlas() {
+        assert!(check_atlas("test", ATLAS.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn rejects_garbage_atlas() {
+        assert!(is_corrupted(check_atlas("test", b"")));
+        assert!(is_corrupted(check_atlas("test", &[0xff, 0xfe, 0x00])));
+        assert!(is_corrupted(check_atlas("test", b"page.png\n")));
+    }
+
+    #[cfg(not(since_4_1))]
+    #[test]
+    fn rejects_truncated_atlas() {
+        let end = ATLAS.find("size: 8").unwrap();
+        assert!(is_corrupted(check_atlas("test", ATLAS[..end].as_bytes())));
+    }
+}
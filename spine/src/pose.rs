@@ -0,0 +1,64 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Snapshot of a skeleton, see [`crate::Skeleton::capture_pose`].
+///
+/// Bones and slots are stored by name, so a pose can be applied to another skeleton of the same
+/// model, and parts that don't exist there are skipped.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pose {
+    pub bones: Vec<BonePose>,
+    pub slots: Vec<SlotPose>,
+}
+
+/// Local transform of a bone, relative to its parent.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BonePose {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    /// Degrees
+    pub rotation: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub shear_x: f32,
+    pub shear_y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlotPose {
+    pub name: String,
+    /// `None` if the slot shows nothing.
+    pub attachment: Option<String>,
+    /// RGBA from 0 to 1
+    pub color: [f32; 4],
+}
+
+/// What a skeleton shows and plays, see [`crate::Skeleton::snapshot`].
+///
+/// Smaller than a [`Pose`], it's meant to be sent often to mirror a model somewhere else, where
+/// the same animations recreate the pose.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    pub tracks: Vec<TrackSnapshot>,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Slot name and the name of its attachment, `None` if it shows nothing.
+    pub attachments: Vec<(String, Option<String>)>,
+}
+
+/// The entry playing on a track, queued entries are left out.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrackSnapshot {
+    pub index: usize,
+    pub animation: String,
+    pub looping: bool,
+    /// Seconds the entry has been playing
+    pub time: f32,
+    pub time_scale: f32,
+}
@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use crate::{AtlasPage, AttachmentType, BlendMode, Skeleton};
+
+/// Corners of a region attachment as two triangles.
+const REGION_INDICES: [u16; 6] = [0, 1, 2, 2, 3, 0];
+
+/// Vertex of a [`RenderCommand`], in skeleton space with the skeleton and slot tint applied.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+/// Triangles of consecutive attachments that are drawn with the same atlas page and blend
+/// mode, so they can go into a single draw call.
+#[derive(Debug)]
+pub struct RenderCommand<'a> {
+    pub page: &'a AtlasPage,
+    pub blend_mode: BlendMode,
+    pub vertices: &'a [RenderVertex],
+    /// Into [`Self::vertices`], three per triangle.
+    pub indices: &'a [u16],
+}
+
+#[derive(Debug)]
+struct Batch {
+    page: *const AtlasPage,
+    blend_mode: BlendMode,
+    vertices: Range<usize>,
+    indices: Range<usize>,
+}
+
+/// Buffers for turning a posed skeleton into [`RenderCommand`]s, kept around to reuse their
+/// allocations from frame to frame. Independent of the graphics API, the texture of a command
+/// is whatever the atlas loader put into its page.
+#[derive(Debug, Default)]
+pub struct RenderCommands {
+    batches: Vec<Batch>,
+    vertices: Vec<RenderVertex>,
+    indices: Vec<u16>,
+}
+
+impl RenderCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Batch the region and mesh attachments of `skel` in draw order, after its world
+    /// transform is updated. `world_vertices` is scratch space, like for
+    /// [`crate::attachment::MeshAttachment::compute_world_vertices`].
+    pub fn extract<'a>(
+        &'a mut self,
+        skel: &'a Skeleton,
+        world_vertices: &mut Vec<[f32; 2]>,
+    ) -> impl Iterator<Item = RenderCommand<'a>> + 'a {
+        self.batches.clear();
+        self.vertices.clear();
+        self.indices.clear();
+
+        let skel_tint = skel.tint_color();
+        for slot in skel.iter_slots() {
+            let attachment = match slot.attachment() {
+                Some(attachment) => attachment,
+                None => continue,
+            };
+
+            let slot_tint = slot.tint_color();
+            let color = [
+                skel_tint[0] * slot_tint[0],
+                skel_tint[1] * slot_tint[1],
+                skel_tint[2] * slot_tint[2],
+                skel_tint[3] * slot_tint[3],
+            ];
+
+            match attachment.as_inner() {
+                AttachmentType::Region(region) => {
+                    region.compute_world_vertices(world_vertices);
+                    let vertices = world_vertices.iter().enumerate().map(|(i, &position)| {
+                        let (u, v) = region.uv(i);
+                        RenderVertex {
+                            position,
+                            uv: [u, v],
+                            color,
+                        }
+                    });
+                    self.push(
+                        region.atlas_region().page(),
+                        slot.blend_mode(),
+                        vertices,
+                        &REGION_INDICES,
+                    );
+                }
+                AttachmentType::Mesh(mesh) => {
+                    mesh.compute_world_vertices(world_vertices);
+                    let vertices = world_vertices.iter().enumerate().map(|(i, &position)| {
+                        let (u, v) = mesh.uv(i);
+                        RenderVertex {
+                            position,
+                            uv: [u, v],
+                            color,
+                        }
+                    });
+                    self.push(
+                        mesh.atlas_region().page(),
+                        slot.blend_mode(),
+                        vertices,
+                        mesh.indices(),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let (vertices, indices) = (&self.vertices, &self.indices);
+        self.batches.iter().map(move |batch| RenderCommand {
+            // SAFETY: pages belong to atlases the skeleton keeps alive, and it's borrowed for
+            // as long as the commands are
+            page: unsafe { &*batch.page },
+            blend_mode: batch.blend_mode,
+            vertices: &vertices[batch.vertices.clone()],
+            indices: &indices[batch.indices.clone()],
+        })
+    }
+
+    /// Append an attachment, to the last batch if it has the same page and blend mode and the
+    /// indices still fit.
+    fn push(
+        &mut self,
+        page: *const AtlasPage,
+        blend_mode: BlendMode,
+        vertices: impl ExactSizeIterator<Item = RenderVertex>,
+        indices: &[u16],
+    ) {
+        let count = vertices.len();
+        let fits = |batch: &Batch| {
+            batch.page == page
+                && batch.blend_mode == blend_mode
+                && batch.vertices.len() + count <= u16::MAX as usize + 1
+        };
+        if !self.batches.last().map_or(false, fits) {
+            self.batches.push(Batch {
+                page,
+                blend_mode,
+                vertices: self.vertices.len()..self.vertices.len(),
+                indices: self.indices.len()..self.indices.len(),
+            });
+        }
+
+        let batch = self.batches.last_mut().unwrap();
+        let offset = batch.vertices.len() as u16;
+        self.vertices.extend(vertices);
+        self.indices.extend(indices.iter().map(|i| i + offset));
+        batch.vertices.end = self.vertices.len();
+        batch.indices.end = self.indices.len();
+    }
+
+    /// Vertices and indices of every batch, only for tests that don't have pages to look at.
+    #[cfg(test)]
+    fn batches(&self) -> Vec<(&[RenderVertex], &[u16])> {
+        self.batches
+            .iter()
+            .map(|batch| {
+                (
+                    &self.vertices[batch.vertices.clone()],
+                    &self.indices[batch.indices.clone()],
+                )
+            })
+            .collect()
+    }
+}
+
+// SAFETY: the page pointers are only dereferenced while the skeleton they came from is
+// borrowed, see `RenderCommands::extract`
+unsafe impl Send for RenderCommands {}
+unsafe impl Sync for RenderCommands {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vertices(count: usize) -> impl ExactSizeIterator<Item = RenderVertex> {
+        (0..count).map(|i| RenderVertex {
+            position: [i as f32, 0.0],
+            ..Default::default()
+        })
+    }
+
+    /// Never dereferenced, only compared.
+    fn page(n: usize) -> *const AtlasPage {
+        n as *const AtlasPage
+    }
+
+    #[test]
+    fn merges_attachments_of_the_same_page() {
+        let mut commands = RenderCommands::new();
+        commands.push(page(8), BlendMode::Normal, vertices(4), &REGION_INDICES);
+        commands.push(page(8), BlendMode::Normal, vertices(3), &[0, 1, 2]);
+
+        let batches = commands.batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].0.len(), 7);
+        assert_eq!(batches[0].1, &[0, 1, 2, 2, 3, 0, 4, 5, 6]);
+    }
+
+    #[test]
+    fn splits_on_page_and_blend_mode() {
+        let mut commands = RenderCommands::new();
+        commands.push(page(8), BlendMode::Normal, vertices(4), &REGION_INDICES);
+        commands.push(page(16), BlendMode::Normal, vertices(4), &REGION_INDICES);
+        commands.push(page(16), BlendMode::Additive, vertices(4), &REGION_INDICES);
+
+        let batches = commands.batches();
+        assert_eq!(batches.len(), 3);
+        // Indices start over in each batch
+        assert!(batches
+            .iter()
+            .all(|(_, indices)| indices == &REGION_INDICES));
+    }
+
+    #[test]
+    fn splits_before_indices_overflow() {
+        let mut commands = RenderCommands::new();
+        commands.push(page(8), BlendMode::Normal, vertices(65534), &[0, 1, 65533]);
+        commands.push(page(8), BlendMode::Normal, vertices(4), &REGION_INDICES);
+
+        let batches = commands.batches();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[1].1, &REGION_INDICES);
+    }
+}
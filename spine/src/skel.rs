@@ -1,25 +1,42 @@
 use std::{
     ffi::{CStr, CString},
     fmt::{Debug, Formatter},
+    iter::FusedIterator,
     marker::PhantomData,
+    os::raw::c_int,
     slice,
     sync::Arc,
 };
 
 use anyhow::{bail, Result};
 use spine_sys::{
-    spAnimationState_apply, spBlendMode, spBlendMode_SP_BLEND_MODE_ADDITIVE,
-    spBlendMode_SP_BLEND_MODE_MULTIPLY, spBlendMode_SP_BLEND_MODE_NORMAL,
-    spBlendMode_SP_BLEND_MODE_SCREEN, spSkeleton, spSkeletonBinary_create,
-    spSkeletonBinary_dispose, spSkeletonBinary_readSkeletonDataFile, spSkeletonData,
-    spSkeletonData_dispose, spSkeleton_create, spSkeleton_dispose, spSkeleton_setAttachment,
-    spSkeleton_updateWorldTransform, spSlot, spSlotData,
+    spAnimationState_apply, spAttachmentType_SP_ATTACHMENT_BOUNDING_BOX,
+    spAttachmentType_SP_ATTACHMENT_MESH, spAttachmentType_SP_ATTACHMENT_PATH, spBlendMode,
+    spBlendMode_SP_BLEND_MODE_ADDITIVE, spBlendMode_SP_BLEND_MODE_MULTIPLY,
+    spBlendMode_SP_BLEND_MODE_NORMAL, spBlendMode_SP_BLEND_MODE_SCREEN, spSkeleton,
+    spSkeletonBinary, spSkeletonBinary_create, spSkeletonBinary_createWithLoader,
+    spSkeletonBinary_dispose, spSkeletonBinary_readSkeletonData, spSkeletonData,
+    spSkeletonData_dispose, spSkeleton_create, spSkeleton_dispose, spSkeleton_findBone,
+    spSkeleton_findIkConstraint, spSkeleton_findSlot, spSkeleton_setAttachment,
+    spSkeleton_updateWorldTransform, spSkin_getAttachmentName, spSlot, spSlotData,
+    spVertexAttachment,
 };
 
-use crate::{AnimationState, Atlas, Attachment, anim::Animation};
+use crate::{
+    anim::Animation,
+    attachment::VertexAttachment,
+    attachment_loader::LoaderHandle,
+    loader::{binary_version, check_binary, is_supported, FileBuf},
+    AnimationState, Atlas, Attachment, AttachmentLoader, AttachmentType, Bone, BonePose,
+    IkConstraint, Pose, SlotPose, Snapshot, SpineError, TrackSnapshot, RUNTIME_VERSION,
+};
 
 #[derive(Debug)]
 pub(crate) struct SkelDataPtr(pub(crate) *mut spSkeletonData);
+
+// SAFETY: skeleton data isn't changed after it's loaded, skeletons only read it
+unsafe impl Send for SkelDataPtr {}
+unsafe impl Sync for SkelDataPtr {}
 impl Drop for SkelDataPtr {
     fn drop(&mut self) {
         log::info!("SkeletonData@{:x} dropped", self.0 as usize);
@@ -31,34 +48,133 @@ impl Drop for SkelDataPtr {
 pub struct SkeletonData {
     pub(crate) ptr: Arc<SkelDataPtr>,
     _atlas: Atlas,
+    /// Other atlases an [`AttachmentLoader`] took regions from.
+    _region_atlases: Vec<Atlas>,
 }
 
 impl SkeletonData {
+    /// An atlas made with [`Atlas::with_loader`] reads the file with its loader.
     pub fn new_binary(atlas: &Atlas, path: &str, scale: f32) -> Result<Self> {
-        let path = CString::new(path).unwrap();
+        let file = FileBuf::read_with(atlas, path)?;
+        let data = file.as_bytes();
 
-        let inner = unsafe {
+        unsafe {
             let binary = spSkeletonBinary_create(atlas.ptr.0);
             (*binary).scale = scale;
 
-            let skel_data = spSkeletonBinary_readSkeletonDataFile(binary, path.as_ptr());
-            if skel_data.is_null() {
-                spSkeletonBinary_dispose(binary);
-                bail!(
-                    "Failed to create skeleton data from file: {:?}",
-                    CStr::from_ptr((*binary).error)
-                );
-            }
-            // Dispose the spSkeletonBinary as we no longer need it after loading.
-            spSkeletonBinary_dispose(binary);
+            let skel_data =
+                spSkeletonBinary_readSkeletonData(binary, data.as_ptr(), data.len() as c_int);
+            Self::from_binary(atlas, binary, skel_data, path)
+        }
+    }
+
+    /// Like [`Self::new_binary`], but makes sure the file is a binary skeleton of a supported
+    /// version before spine-c reads it, which doesn't check for truncated or foreign data and
+    /// may crash on it. Such files fail with a [`SpineError`] instead.
+    ///
+    pub fn try_new_binary(atlas: &Atlas, path: &str, scale: f32) -> Result<Self> {
+        let file = FileBuf::read_with(atlas, path)?;
+        let data = file.as_bytes();
+        check_binary(path, data)?;
+
+        unsafe {
+            let binary = spSkeletonBinary_create(atlas.ptr.0);
+            (*binary).scale = scale;
+
+            let skel_data =
+                spSkeletonBinary_readSkeletonData(binary, data.as_ptr(), data.len() as c_int);
+            Self::from_binary(atlas, binary, skel_data, path)
+        }
+    }
 
-            skel_data
+    /// Like [`Self::try_new_binary`], with `loader` picking the atlas regions of attachments.
+    pub fn try_new_binary_with_loader(
+        atlas: &Atlas,
+        loader: impl AttachmentLoader + 'static,
+        path: &str,
+        scale: f32,
+    ) -> Result<Self> {
+        let file = FileBuf::read_with(atlas, path)?;
+        let data = file.as_bytes();
+        check_binary(path, data)?;
+
+        let mut loader = LoaderHandle::new(atlas, Box::new(loader));
+        let mut skel_data = unsafe {
+            let binary = spSkeletonBinary_createWithLoader(loader.as_ptr());
+            (*binary).scale = scale;
+
+            let skel_data =
+                spSkeletonBinary_readSkeletonData(binary, data.as_ptr(), data.len() as c_int);
+            Self::from_binary(atlas, binary, skel_data, path)?
         };
+        skel_data._region_atlases = loader.take_atlases();
+        Ok(skel_data)
+    }
+
+    /// Takes the result of reading `binary` and disposes it.
+    unsafe fn from_binary(
+        atlas: &Atlas,
+        binary: *mut spSkeletonBinary,
+        skel_data: *mut spSkeletonData,
+        path: &str,
+    ) -> Result<Self> {
+        if skel_data.is_null() {
+            // The error belongs to the spSkeletonBinary, copy it before disposing that
+            let message = if (*binary).error.is_null() {
+                "unknown error".to_owned()
+            } else {
+                CStr::from_ptr((*binary).error)
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            spSkeletonBinary_dispose(binary);
+
+            // Files from another editor version are by far the most common reason, and spine-c
+            // only reports whatever it tripped over because of that
+            let version = FileBuf::read_with(atlas, path)
+                .ok()
+                .and_then(|file| binary_version(file.as_bytes()));
+            if let Some(found) = version.filter(|version| !is_supported(version)) {
+                bail!(SpineError::UnsupportedVersion {
+                    path: path.to_owned(),
+                    found,
+                    supported: RUNTIME_VERSION,
+                });
+            }
+            bail!(SpineError::Parse {
+                path: path.to_owned(),
+                message,
+            });
+        }
+        // Dispose the spSkeletonBinary as we no longer need it after loading.
+        spSkeletonBinary_dispose(binary);
 
-        Ok(SkeletonData {
-            ptr: Arc::new(SkelDataPtr(inner)),
+        let skel_data = SkeletonData {
+            ptr: Arc::new(SkelDataPtr(skel_data)),
             _atlas: atlas.clone(),
-        })
+            _region_atlases: Vec::new(),
+        };
+        if let Some(version) = skel_data.version().filter(|version| !is_supported(version)) {
+            log::warn!(
+                "{} was exported from Spine {}, the runtime supports {}, it may look wrong",
+                path,
+                version,
+                RUNTIME_VERSION
+            );
+        }
+        Ok(skel_data)
+    }
+
+    /// Version of the Spine editor the skeleton was exported from, like `3.5.51`.
+    pub fn version(&self) -> Option<&str> {
+        unsafe {
+            let version = (*self.ptr.0).version;
+            if version.is_null() {
+                None
+            } else {
+                CStr::from_ptr(version).to_str().ok()
+            }
+        }
     }
 
     pub fn width(&self) -> f32 {
@@ -69,6 +185,7 @@ impl SkeletonData {
         unsafe { (*self.ptr.0).height }
     }
 
+    #[deprecated(note = "use `iter_slots` or `slot_at`")]
     pub fn slots(&self) -> &[&SlotData] {
         unsafe {
             let slots = (*self.ptr.0).slots as *mut &SlotData;
@@ -77,6 +194,27 @@ impl SkeletonData {
         }
     }
 
+    /// Data of all slots, in setup order.
+    pub fn iter_slots(&self) -> SlotDatas<'_> {
+        unsafe {
+            let this = *self.ptr.0;
+            SlotDatas {
+                slots: raw_slice(this.slots, this.slotsCount).iter(),
+                _skel_data: PhantomData,
+            }
+        }
+    }
+
+    /// Data of the slot at `index` in setup order, the index used by
+    /// [`Self::skin_attachments`].
+    pub fn slot_at(&self, index: usize) -> Option<&SlotData> {
+        let this = unsafe { *self.ptr.0 };
+        let slots = unsafe { raw_slice(this.slots, this.slotsCount) };
+        slots
+            .get(index)
+            .map(|&slot| unsafe { &*(slot as *const SlotData) })
+    }
+
     pub fn animations(&self) -> &[&Animation] {
         unsafe {
             let animations = (*self.ptr.0).animations as *mut &Animation;
@@ -84,6 +222,33 @@ impl SkeletonData {
             slice::from_raw_parts(animations, len)
         }
     }
+
+    /// Attachments of all skins as slot index and attachment name, sorted by slot. These are
+    /// the names [`Skeleton::set_attachment`] accepts.
+    pub fn skin_attachments(&self) -> Vec<(usize, String)> {
+        let mut attachments = Vec::new();
+        unsafe {
+            let this = *self.ptr.0;
+            for i in 0..this.skinsCount as usize {
+                let skin = *this.skins.add(i);
+                for slot in 0..this.slotsCount {
+                    // Names are listed by index until there are no more for the slot
+                    for index in 0.. {
+                        let name = spSkin_getAttachmentName(skin, slot, index);
+                        if name.is_null() {
+                            break;
+                        }
+                        let name = CStr::from_ptr(name).to_string_lossy();
+                        attachments.push((slot as usize, name.into_owned()));
+                    }
+                }
+            }
+        }
+
+        attachments.sort();
+        attachments.dedup();
+        attachments
+    }
 }
 
 #[repr(C)]
@@ -97,8 +262,13 @@ impl<'d> SlotData<'d> {
         unsafe { CStr::from_ptr(self.inner.name).to_str().unwrap() }
     }
 
-    pub fn attachment_name(&self) -> &str {
-        unsafe { CStr::from_ptr(self.inner.attachmentName).to_str().unwrap() }
+    /// Attachment shown in the setup pose, if any.
+    pub fn attachment_name(&self) -> Option<&str> {
+        if self.inner.attachmentName.is_null() {
+            None
+        } else {
+            unsafe { Some(CStr::from_ptr(self.inner.attachmentName).to_str().unwrap()) }
+        }
     }
 }
 
@@ -111,12 +281,60 @@ impl<'d> Debug for SlotData<'d> {
     }
 }
 
+/// Iterator over slot data, see [`SkeletonData::iter_slots`].
+#[derive(Clone)]
+pub struct SlotDatas<'d> {
+    slots: slice::Iter<'d, *mut spSlotData>,
+    _skel_data: PhantomData<&'d SkeletonData>,
+}
+
+impl<'d> Iterator for SlotDatas<'d> {
+    type Item = &'d SlotData<'d>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .next()
+            .map(|&slot| unsafe { &*(slot as *const SlotData) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.slots.size_hint()
+    }
+}
+
+impl<'d> DoubleEndedIterator for SlotDatas<'d> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots
+            .next_back()
+            .map(|&slot| unsafe { &*(slot as *const SlotData) })
+    }
+}
+
+impl<'d> ExactSizeIterator for SlotDatas<'d> {}
+impl<'d> FusedIterator for SlotDatas<'d> {}
+
+/// Slice of a spine-c array and its count, which may be null when empty.
+///
+/// # Safety
+/// `ptr` has to point to `len` elements that live for `'a`.
+pub(crate) unsafe fn raw_slice<'a, T>(ptr: *const T, len: c_int) -> &'a [T] {
+    if ptr.is_null() || len <= 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
 #[derive(Debug)]
 pub struct Skeleton {
     ptr: *mut spSkeleton,
     _data: SkeletonData,
 }
 
+// SAFETY: the skeleton is owned, and shares only `Send + Sync` data. It's not `Sync`, spine-c
+// doesn't promise that reading a skeleton never writes to it.
+unsafe impl Send for Skeleton {}
+
 impl Skeleton {
     pub fn new(skel_data: &SkeletonData) -> Result<Self> {
         let inner = unsafe { spSkeleton_create(skel_data.ptr.0) };
@@ -148,22 +366,70 @@ impl Skeleton {
     }
 
     /// Calculate world transforms for rendering
+    #[cfg(not(since_4_2))]
     pub fn update_world_transform(&mut self) {
         unsafe { spSkeleton_updateWorldTransform(self.ptr) }
     }
 
+    /// Calculate world transforms for rendering. Physics only move with time passed to
+    /// spSkeleton_update, which isn't wrapped yet.
+    #[cfg(since_4_2)]
+    pub fn update_world_transform(&mut self) {
+        unsafe { spSkeleton_updateWorldTransform(self.ptr, spine_sys::spPhysics_SP_PHYSICS_UPDATE) }
+    }
+
+    #[cfg(not(since_3_8))]
     pub fn set_flip_x(&mut self, flip: bool) {
         unsafe {
             (*self.ptr).flipX = if flip { 1 } else { 0 };
         }
     }
 
+    /// Flipping became a negative scale in 3.7.
+    #[cfg(since_3_8)]
+    pub fn set_flip_x(&mut self, flip: bool) {
+        unsafe {
+            let scale = (*self.ptr).scaleX.abs();
+            (*self.ptr).scaleX = if flip { -scale } else { scale };
+        }
+    }
+
+    #[cfg(not(since_3_8))]
+    pub fn flip_x(&self) -> bool {
+        unsafe { (*self.ptr).flipX != 0 }
+    }
+
+    #[cfg(since_3_8)]
+    pub fn flip_x(&self) -> bool {
+        unsafe { (*self.ptr).scaleX < 0.0 }
+    }
+
+    #[cfg(not(since_3_8))]
     pub fn set_flip_y(&mut self, flip: bool) {
         unsafe {
             (*self.ptr).flipY = if flip { 1 } else { 0 };
         }
     }
 
+    #[cfg(since_3_8)]
+    pub fn set_flip_y(&mut self, flip: bool) {
+        unsafe {
+            let scale = (*self.ptr).scaleY.abs();
+            (*self.ptr).scaleY = if flip { -scale } else { scale };
+        }
+    }
+
+    #[cfg(not(since_3_8))]
+    pub fn flip_y(&self) -> bool {
+        unsafe { (*self.ptr).flipY != 0 }
+    }
+
+    #[cfg(since_3_8)]
+    pub fn flip_y(&self) -> bool {
+        unsafe { (*self.ptr).scaleY < 0.0 }
+    }
+
+    #[cfg(not(since_3_8))]
     pub fn tint_color(&self) -> [f32; 4] {
         unsafe {
             let this = *self.ptr;
@@ -171,6 +437,14 @@ impl Skeleton {
         }
     }
 
+    #[cfg(since_3_8)]
+    pub fn tint_color(&self) -> [f32; 4] {
+        unsafe {
+            let color = (*self.ptr).color;
+            [color.r, color.g, color.b, color.a]
+        }
+    }
+
     pub fn set_attachment(&mut self, slot: &str, attachment: &str) {
         let slot = CString::new(slot).unwrap();
         let attachment = CString::new(attachment).unwrap();
@@ -180,6 +454,43 @@ impl Skeleton {
         }
     }
 
+    /// Show nothing in the slot until an animation or [`Self::set_attachment`] sets one.
+    pub fn clear_attachment(&mut self, slot: &str) {
+        let slot = CString::new(slot).unwrap();
+
+        unsafe {
+            spSkeleton_setAttachment(self.ptr, slot.as_ptr(), std::ptr::null());
+        }
+    }
+
+    /// Root bone of the skeleton.
+    pub fn root_bone(&mut self) -> Option<&mut Bone> {
+        unsafe { ((*self.ptr).root as *mut Bone).as_mut() }
+    }
+
+    pub fn find_bone(&mut self, name: &str) -> Option<&mut Bone> {
+        let name = CString::new(name).unwrap();
+        unsafe { (spSkeleton_findBone(self.ptr, name.as_ptr()) as *mut Bone).as_mut() }
+    }
+
+    pub fn find_ik_constraint(&mut self, name: &str) -> Option<&mut IkConstraint> {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            (spSkeleton_findIkConstraint(self.ptr, name.as_ptr()) as *mut IkConstraint).as_mut()
+        }
+    }
+
+    /// All bones, parents before their children.
+    pub fn bones(&self) -> &[&Bone] {
+        unsafe {
+            let this = *self.ptr;
+            let bones = this.bones as *mut &Bone;
+            let len = this.bonesCount as usize;
+            slice::from_raw_parts(bones, len)
+        }
+    }
+
+    #[deprecated(note = "use `iter_slots` or `slot_at`")]
     pub fn slots(&self) -> &[&Slot] {
         unsafe {
             let this = *self.ptr;
@@ -188,6 +499,228 @@ impl Skeleton {
             slice::from_raw_parts(slots, len)
         }
     }
+
+    /// All slots in draw order, back to front.
+    pub fn iter_slots(&self) -> Slots<'_> {
+        unsafe {
+            let this = *self.ptr;
+            Slots {
+                slots: raw_slice(this.drawOrder, this.slotsCount).iter(),
+                _skel: PhantomData,
+            }
+        }
+    }
+
+    /// The slot at `index` in setup order, like [`SkeletonData::slot_at`].
+    pub fn slot_at(&self, index: usize) -> Option<&Slot> {
+        let this = unsafe { *self.ptr };
+        let slots = unsafe { raw_slice(this.slots, this.slotsCount) };
+        slots
+            .get(index)
+            .map(|&slot| unsafe { &*(slot as *const Slot) })
+    }
+
+    pub fn slot_at_mut(&mut self, index: usize) -> Option<&mut Slot> {
+        let this = unsafe { *self.ptr };
+        let slots = unsafe { raw_slice(this.slots, this.slotsCount) };
+        slots
+            .get(index)
+            .map(|&slot| unsafe { &mut *(slot as *mut Slot) })
+    }
+
+    /// Local transforms of all bones and the attachment and color of all slots, to restore
+    /// later with [`Self::apply_pose`].
+    pub fn capture_pose(&self) -> Pose {
+        let bones = self
+            .bones()
+            .iter()
+            .map(|bone| {
+                let (scale_x, scale_y) = bone.scale();
+                let (shear_x, shear_y) = bone.shear();
+                BonePose {
+                    name: bone.name().to_owned(),
+                    x: bone.x(),
+                    y: bone.y(),
+                    rotation: bone.rotation(),
+                    scale_x,
+                    scale_y,
+                    shear_x,
+                    shear_y,
+                }
+            })
+            .collect();
+        let slots = self
+            .iter_slots()
+            .map(|slot| SlotPose {
+                name: slot.name().to_owned(),
+                attachment: slot
+                    .attachment()
+                    .map(|attachment| attachment.name().to_owned()),
+                color: slot.tint_color(),
+            })
+            .collect();
+
+        Pose { bones, slots }
+    }
+
+    /// Put bones and slots back the way [`Self::capture_pose`] found them. Call it after
+    /// applying animations, which would overwrite the pose otherwise, and update the world
+    /// transform afterwards.
+    pub fn apply_pose(&mut self, pose: &Pose) {
+        for bone_pose in &pose.bones {
+            if let Some(bone) = self.find_bone(&bone_pose.name) {
+                bone.set_position(bone_pose.x, bone_pose.y);
+                bone.set_rotation(bone_pose.rotation);
+                bone.set_scale(bone_pose.scale_x, bone_pose.scale_y);
+                bone.set_shear(bone_pose.shear_x, bone_pose.shear_y);
+            }
+        }
+
+        for slot_pose in &pose.slots {
+            let name = CString::new(slot_pose.name.as_str()).unwrap();
+            let slot = unsafe { spSkeleton_findSlot(self.ptr, name.as_ptr()) as *mut Slot };
+            match unsafe { slot.as_mut() } {
+                Some(slot) => slot.set_tint_color(slot_pose.color),
+                None => continue,
+            }
+
+            match &slot_pose.attachment {
+                Some(attachment) => self.set_attachment(&slot_pose.name, attachment),
+                None => self.clear_attachment(&slot_pose.name),
+            }
+        }
+    }
+
+    /// The animations `anim` plays on this skeleton, its flip and the attachments of all slots,
+    /// to mirror it with [`Self::apply_snapshot`].
+    pub fn snapshot(&self, anim: &AnimationState) -> Snapshot {
+        let tracks = (0..anim.track_count())
+            .filter_map(|index| anim.current(index))
+            .map(|entry| TrackSnapshot {
+                index: entry.track_index(),
+                animation: entry.animation().name().to_owned(),
+                looping: entry.is_loop(),
+                time: entry.track_time(),
+                time_scale: entry.time_scale(),
+            })
+            .collect();
+        let attachments = self
+            .iter_slots()
+            .map(|slot| {
+                let attachment = slot
+                    .attachment()
+                    .map(|attachment| attachment.name().to_owned());
+                (slot.name().to_owned(), attachment)
+            })
+            .collect();
+
+        Snapshot {
+            tracks,
+            flip_x: self.flip_x(),
+            flip_y: self.flip_y(),
+            attachments,
+        }
+    }
+
+    /// Play what [`Self::snapshot`] found on another skeleton of the same model. Animations
+    /// already playing jump to the snapshot's time, others start there without mixing, and
+    /// tracks missing from the snapshot are cleared. Animations that key attachments set them
+    /// again when applied.
+    pub fn apply_snapshot(&mut self, anim: &mut AnimationState, snapshot: &Snapshot) {
+        for index in 0..anim.track_count() {
+            if !snapshot.tracks.iter().any(|track| track.index == index) {
+                anim.clear_track(index);
+            }
+        }
+
+        for track in &snapshot.tracks {
+            let playing = anim.current(track.index).map_or(false, |entry| {
+                entry.animation().name() == track.animation && entry.is_loop() == track.looping
+            });
+            let entry = if playing {
+                anim.current_mut(track.index)
+            } else {
+                anim.set_animation_by_name(track.index, &track.animation, track.looping)
+                    .map(|entry| {
+                        entry.set_mix_duration(0.0);
+                        entry
+                    })
+            };
+            match entry {
+                Some(entry) => {
+                    entry.set_track_time(track.time);
+                    entry.set_time_scale(track.time_scale);
+                }
+                None => log::warn!("No animation named {:?} to mirror", track.animation),
+            }
+        }
+
+        self.set_flip_x(snapshot.flip_x);
+        self.set_flip_y(snapshot.flip_y);
+        for (slot, attachment) in &snapshot.attachments {
+            match attachment {
+                Some(attachment) => self.set_attachment(slot, attachment),
+                None => self.clear_attachment(slot),
+            }
+        }
+    }
+
+    /// Box around the region and mesh attachments in their current pose, so world transforms
+    /// have to be up to date. `None` if nothing is attached.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut bounds: Option<Bounds> = None;
+        let mut positions = Vec::new();
+
+        for slot in self.iter_slots() {
+            let attachment = match slot.attachment() {
+                Some(attachment) => attachment,
+                None => continue,
+            };
+            match attachment.as_inner() {
+                AttachmentType::Region(region) => region.compute_world_vertices(&mut positions),
+                AttachmentType::Mesh(mesh) => mesh.compute_world_vertices(&mut positions),
+                AttachmentType::Path(_) => continue,
+            };
+
+            for &[x, y] in &positions {
+                bounds = Some(match bounds {
+                    Some(bounds) => Bounds {
+                        min_x: bounds.min_x.min(x),
+                        min_y: bounds.min_y.min(y),
+                        max_x: bounds.max_x.max(x),
+                        max_y: bounds.max_y.max(y),
+                    },
+                    None => Bounds {
+                        min_x: x,
+                        min_y: y,
+                        max_x: x,
+                        max_y: y,
+                    },
+                });
+            }
+        }
+
+        bounds
+    }
+}
+
+/// Axis-aligned box in world coordinates, see [`Skeleton::bounds`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl Bounds {
+    pub fn width(&self) -> f32 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max_y - self.min_y
+    }
 }
 
 impl Drop for Skeleton {
@@ -196,7 +729,7 @@ impl Drop for Skeleton {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum BlendMode {
     Normal,
     Additive,
@@ -225,15 +758,49 @@ pub struct Slot<'sk> {
 }
 
 impl<'sk> Slot<'sk> {
+    pub fn name(&self) -> &str {
+        unsafe { CStr::from_ptr((*self.inner.data).name).to_str().unwrap() }
+    }
+
     pub fn blend_mode(&self) -> BlendMode {
         unsafe { BlendMode::from((*self.inner.data).blendMode) }
     }
 
+    #[cfg(not(since_3_8))]
     pub fn tint_color(&self) -> [f32; 4] {
         let this = &self.inner;
         [this.r, this.g, this.b, this.a]
     }
 
+    #[cfg(since_3_8)]
+    pub fn tint_color(&self) -> [f32; 4] {
+        let color = &self.inner.color;
+        [color.r, color.g, color.b, color.a]
+    }
+
+    #[cfg(not(since_3_8))]
+    pub fn set_tint_color(&mut self, [r, g, b, a]: [f32; 4]) {
+        let this = &mut self.inner;
+        this.r = r;
+        this.g = g;
+        this.b = b;
+        this.a = a;
+    }
+
+    #[cfg(since_3_8)]
+    pub fn set_tint_color(&mut self, [r, g, b, a]: [f32; 4]) {
+        let color = &mut self.inner.color;
+        color.r = r;
+        color.g = g;
+        color.b = b;
+        color.a = a;
+    }
+
+    /// Bone the slot is attached to.
+    pub fn bone(&self) -> &Bone<'sk> {
+        unsafe { &*(self.inner.bone as *const Bone) }
+    }
+
     pub fn attachment(&self) -> Option<Attachment<'_, 'sk>> {
         if self.inner.attachment.is_null() {
             None
@@ -241,4 +808,216 @@ impl<'sk> Slot<'sk> {
             Some(Attachment::new(self.inner.attachment, self))
         }
     }
+
+    /// Vertices of the mesh attachment as set by deform timelines, laid out as described in
+    /// [`VertexAttachment::vertices`]. Empty if the mesh is in its setup pose. Cleared when
+    /// the attachment changes.
+    pub fn deform(&self) -> &[f32] {
+        let (deform, count, _) = self.deform_fields();
+        unsafe { raw_slice(deform, count) }
+    }
+
+    /// Change the deform in place, e.g. after the animation is applied and before world
+    /// vertices are computed.
+    pub fn deform_mut(&mut self) -> &mut [f32] {
+        let (deform, count, _) = self.deform_fields();
+        if deform.is_null() || count <= 0 {
+            return &mut [];
+        }
+        unsafe { slice::from_raw_parts_mut(deform, count as usize) }
+    }
+
+    /// Replace the deform, an empty one puts the mesh back in its setup pose. Others need
+    /// [`VertexAttachment::deform_len`] values of the attachment, which spine-c reads.
+    pub fn set_deform(&mut self, values: &[f32]) -> Result<()> {
+        if !values.is_empty() {
+            match self.deform_len() {
+                Some(len) if len == values.len() => {}
+                Some(len) => bail!(
+                    "Deform of slot {} needs {} values, got {}",
+                    self.name(),
+                    len,
+                    values.len()
+                ),
+                None => bail!("Slot {} has no vertices to deform", self.name()),
+            }
+        }
+
+        let (mut deform, _, capacity) = self.deform_fields();
+        if values.len() > capacity as usize {
+            // spine-c grows and frees the array with its own allocator
+            unsafe {
+                crate::alloc::free(deform as *mut _);
+                deform =
+                    crate::alloc::malloc(values.len() * std::mem::size_of::<f32>()) as *mut f32;
+                slice::from_raw_parts_mut(deform, values.len()).copy_from_slice(values);
+            }
+            self.set_deform_fields(deform, values.len() as c_int, values.len() as c_int);
+        } else {
+            if !values.is_empty() {
+                unsafe { slice::from_raw_parts_mut(deform, values.len()) }.copy_from_slice(values);
+            }
+            self.set_deform_fields(deform, values.len() as c_int, capacity);
+        }
+        Ok(())
+    }
+
+    /// Number of values in a deform of the attachment, `None` if it has no vertices.
+    fn deform_len(&self) -> Option<usize> {
+        let attachment = self.inner.attachment;
+        if attachment.is_null() {
+            return None;
+        }
+
+        #[allow(non_upper_case_globals)]
+        match unsafe { (*attachment).type_ } {
+            spAttachmentType_SP_ATTACHMENT_MESH
+            | spAttachmentType_SP_ATTACHMENT_BOUNDING_BOX
+            | spAttachmentType_SP_ATTACHMENT_PATH => {}
+            #[cfg(since_3_8)]
+            spine_sys::spAttachmentType_SP_ATTACHMENT_CLIPPING => {}
+            _ => return None,
+        }
+        Some(VertexAttachment::new(attachment as *mut spVertexAttachment).deform_len())
+    }
+
+    /// Deform array, count and capacity.
+    #[cfg(not(since_3_8))]
+    fn deform_fields(&self) -> (*mut f32, c_int, c_int) {
+        let this = &self.inner;
+        (
+            this.attachmentVertices,
+            this.attachmentVerticesCount,
+            this.attachmentVerticesCapacity,
+        )
+    }
+
+    #[cfg(since_3_8)]
+    fn deform_fields(&self) -> (*mut f32, c_int, c_int) {
+        let this = &self.inner;
+        (this.deform, this.deformCount, this.deformCapacity)
+    }
+
+    #[cfg(not(since_3_8))]
+    fn set_deform_fields(&mut self, deform: *mut f32, count: c_int, capacity: c_int) {
+        let this = &mut self.inner;
+        this.attachmentVertices = deform;
+        this.attachmentVerticesCount = count;
+        this.attachmentVerticesCapacity = capacity;
+    }
+
+    #[cfg(since_3_8)]
+    fn set_deform_fields(&mut self, deform: *mut f32, count: c_int, capacity: c_int) {
+        let this = &mut self.inner;
+        this.deform = deform;
+        this.deformCount = count;
+        this.deformCapacity = capacity;
+    }
+}
+
+/// Iterator over the slots of a skeleton, see [`Skeleton::iter_slots`].
+#[derive(Clone)]
+pub struct Slots<'sk> {
+    slots: slice::Iter<'sk, *mut spSlot>,
+    _skel: PhantomData<&'sk Skeleton>,
+}
+
+impl<'sk> Iterator for Slots<'sk> {
+    type Item = &'sk Slot<'sk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .next()
+            .map(|&slot| unsafe { &*(slot as *const Slot) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.slots.size_hint()
+    }
+}
+
+impl<'sk> DoubleEndedIterator for Slots<'sk> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.slots
+            .next_back()
+            .map(|&slot| unsafe { &*(slot as *const Slot) })
+    }
+}
+
+impl<'sk> ExactSizeIterator for Slots<'sk> {}
+impl<'sk> FusedIterator for Slots<'sk> {}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::raw::c_char, ptr};
+
+    use spine_sys::{
+        spAttachment, spBoneData_create, spBone_create, spMeshAttachment, spMeshAttachment_create,
+        spSlotData_create, spSlot_create,
+    };
+
+    use super::*;
+
+    /// Mesh with `vertices`, weighted if `bones` isn't empty. Leaked like the rest.
+    fn mesh(vertices: Vec<f32>, bones: Vec<c_int>) -> *mut spMeshAttachment {
+        unsafe {
+            let mesh = spMeshAttachment_create("mesh\0".as_ptr() as *const c_char);
+            let this = &mut (*mesh).super_;
+            this.verticesCount = vertices.len() as c_int;
+            this.vertices = Box::leak(vertices.into_boxed_slice()).as_mut_ptr();
+            if !bones.is_empty() {
+                this.bonesCount = bones.len() as c_int;
+                this.bones = Box::leak(bones.into_boxed_slice()).as_mut_ptr();
+            }
+            mesh
+        }
+    }
+
+    /// Slot showing `attachment`, on a bone of its own.
+    fn slot(attachment: *mut spAttachment) -> &'static mut Slot<'static> {
+        unsafe {
+            let name = "test\0".as_ptr() as *const c_char;
+            let bone_data = spBoneData_create(0, name, ptr::null_mut());
+            let bone = spBone_create(bone_data, ptr::null_mut(), ptr::null_mut());
+            let slot = spSlot_create(spSlotData_create(0, name, bone_data), bone);
+            (*slot).attachment = attachment;
+            &mut *(slot as *mut Slot)
+        }
+    }
+
+    #[test]
+    fn counts_deform_values() {
+        let unweighted = mesh(vec![0.0; 6], vec![]);
+        let unweighted = VertexAttachment::new(unsafe { &mut (*unweighted).super_ });
+        assert_eq!(unweighted.deform_len(), 6);
+
+        // Two vertices, on one and two bones
+        let weighted = mesh(vec![0.0; 9], vec![1, 0, 2, 0, 1]);
+        let weighted = VertexAttachment::new(unsafe { &mut (*weighted).super_ });
+        assert_eq!(weighted.deform_len(), 6);
+    }
+
+    #[test]
+    fn sets_deform_of_matching_length() {
+        let slot = slot(mesh(vec![0.0; 6], vec![]) as *mut spAttachment);
+        slot.set_deform(&[1.0; 6]).unwrap();
+        assert_eq!(slot.deform(), &[1.0; 6]);
+        slot.set_deform(&[]).unwrap();
+        assert!(slot.deform().is_empty());
+    }
+
+    #[test]
+    fn rejects_deform_of_wrong_length() {
+        let slot = slot(mesh(vec![0.0; 6], vec![]) as *mut spAttachment);
+        assert!(slot.set_deform(&[1.0; 4]).is_err());
+        assert!(slot.set_deform(&[1.0; 8]).is_err());
+        assert!(slot.deform().is_empty());
+    }
+
+    #[test]
+    fn rejects_deform_without_vertices() {
+        let slot = slot(ptr::null_mut());
+        assert!(slot.set_deform(&[1.0; 6]).is_err());
+        slot.set_deform(&[]).unwrap();
+    }
 }
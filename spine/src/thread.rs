@@ -0,0 +1,96 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    mem::ManuallyDrop,
+    thread::{self, ThreadId},
+};
+
+/// Makes a value that must stay on one thread usable where the runtime needs `Send + Sync`,
+/// like [`crate::SpineCallbacks::Texture`] when the textures are handles of a graphics context
+/// that is bound to the render thread.
+///
+/// Only the thread that created the guard can get at the value, others panic. Dropping the
+/// guard on another thread leaks the value instead of dropping it there.
+pub struct SpineThreadGuard<T> {
+    value: ManuallyDrop<T>,
+    thread: ThreadId,
+}
+
+// SAFETY: the value is only ever touched on the thread that created the guard
+unsafe impl<T> Send for SpineThreadGuard<T> {}
+unsafe impl<T> Sync for SpineThreadGuard<T> {}
+
+impl<T> SpineThreadGuard<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            thread: thread::current().id(),
+        }
+    }
+
+    /// Whether this is the thread the value belongs to.
+    pub fn is_owner(&self) -> bool {
+        thread::current().id() == self.thread
+    }
+
+    /// # Panics
+    /// If called from another thread than the one that created the guard.
+    pub fn get(&self) -> &T {
+        self.check();
+        &self.value
+    }
+
+    /// # Panics
+    /// If called from another thread than the one that created the guard.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.check();
+        &mut self.value
+    }
+
+    /// # Panics
+    /// If called from another thread than the one that created the guard.
+    pub fn into_inner(self) -> T {
+        self.check();
+        let mut this = ManuallyDrop::new(self);
+        unsafe { ManuallyDrop::take(&mut this.value) }
+    }
+
+    fn check(&self) {
+        if !self.is_owner() {
+            panic!("SpineThreadGuard used on another thread than the one that created it");
+        }
+    }
+}
+
+impl<T> Drop for SpineThreadGuard<T> {
+    fn drop(&mut self) {
+        if self.is_owner() {
+            unsafe { ManuallyDrop::drop(&mut self.value) };
+        } else {
+            log::warn!("SpineThreadGuard dropped on another thread, leaking its value");
+        }
+    }
+}
+
+impl<T: Debug> Debug for SpineThreadGuard<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("SpineThreadGuard");
+        if self.is_owner() {
+            debug.field("value", &*self.value);
+        }
+        debug.field("thread", &self.thread).finish()
+    }
+}
+
+/// Keeps the threading model in the crate docs from changing by accident.
+#[allow(dead_code)]
+fn assert_thread_safety() {
+    fn send_sync<T: Send + Sync>() {}
+    fn send<T: Send>() {}
+
+    send_sync::<crate::Atlas>();
+    send_sync::<crate::SkeletonData>();
+    send_sync::<crate::AnimationStateData>();
+    send_sync::<crate::Completion>();
+    send::<crate::Skeleton>();
+    send::<crate::AnimationState>();
+}
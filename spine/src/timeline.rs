@@ -0,0 +1,363 @@
+use std::marker::PhantomData;
+#[cfg(not(since_4_1))]
+use std::os::raw::c_int;
+
+#[cfg(not(since_4_1))]
+use spine_sys::{
+    spAttachmentTimeline, spColorTimeline, spDeformTimeline, spDrawOrderTimeline, spEventTimeline,
+    spIkConstraintTimeline, spPathConstraintMixTimeline, spPathConstraintPositionTimeline,
+    spPathConstraintSpacingTimeline, spRotateTimeline, spScaleTimeline, spShearTimeline,
+    spTransformConstraintTimeline, spTranslateTimeline,
+};
+use spine_sys::{
+    spTimeline, spTimelineType_SP_TIMELINE_ATTACHMENT, spTimelineType_SP_TIMELINE_DEFORM,
+    spTimelineType_SP_TIMELINE_DRAWORDER, spTimelineType_SP_TIMELINE_EVENT,
+    spTimelineType_SP_TIMELINE_IKCONSTRAINT, spTimelineType_SP_TIMELINE_PATHCONSTRAINTMIX,
+    spTimelineType_SP_TIMELINE_PATHCONSTRAINTPOSITION,
+    spTimelineType_SP_TIMELINE_PATHCONSTRAINTSPACING, spTimelineType_SP_TIMELINE_ROTATE,
+    spTimelineType_SP_TIMELINE_SCALE, spTimelineType_SP_TIMELINE_SHEAR,
+    spTimelineType_SP_TIMELINE_TRANSFORMCONSTRAINT, spTimelineType_SP_TIMELINE_TRANSLATE,
+};
+
+use crate::anim::Animation;
+
+/// What a timeline animates. Newer runtimes split some of these up further, like
+/// translation along one axis, those are reported as the combined kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimelineKind {
+    Rotate,
+    Translate,
+    Scale,
+    Shear,
+    Attachment,
+    Color,
+    /// Light and dark color of a slot.
+    TwoColor,
+    /// Mesh vertices, see [`crate::Slot::deform`].
+    Deform,
+    Event,
+    DrawOrder,
+    IkConstraint,
+    TransformConstraint,
+    PathConstraintPosition,
+    PathConstraintSpacing,
+    PathConstraintMix,
+    /// Something this binding doesn't know about, like physics in 4.2.
+    Other,
+}
+
+/// Part of the skeleton a timeline changes, as the index in its skeleton data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimelineTarget {
+    Bone(usize),
+    Slot(usize),
+    IkConstraint(usize),
+    TransformConstraint(usize),
+    PathConstraint(usize),
+}
+
+/// Keys of one property of an [`Animation`], read only.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeline<'a> {
+    ptr: *mut spTimeline,
+    _animation: PhantomData<&'a Animation>,
+}
+
+impl<'a> Timeline<'a> {
+    pub(crate) fn new(ptr: *mut spTimeline) -> Self {
+        Self {
+            ptr,
+            _animation: PhantomData,
+        }
+    }
+
+    pub fn kind(&self) -> TimelineKind {
+        kind_of(unsafe { (*self.ptr).type_ })
+    }
+
+    /// `None` for events and the draw order, which belong to the whole skeleton.
+    #[cfg(not(since_4_1))]
+    pub fn target(&self) -> Option<TimelineTarget> {
+        let ptr = self.ptr;
+        let index = |index: c_int| index as usize;
+        unsafe {
+            Some(match self.kind() {
+                TimelineKind::Rotate => {
+                    TimelineTarget::Bone(index((*(ptr as *const spRotateTimeline)).boneIndex))
+                }
+                TimelineKind::Translate => {
+                    TimelineTarget::Bone(index((*(ptr as *const spTranslateTimeline)).boneIndex))
+                }
+                TimelineKind::Scale => {
+                    TimelineTarget::Bone(index((*(ptr as *const spScaleTimeline)).boneIndex))
+                }
+                TimelineKind::Shear => {
+                    TimelineTarget::Bone(index((*(ptr as *const spShearTimeline)).boneIndex))
+                }
+                TimelineKind::Attachment => {
+                    TimelineTarget::Slot(index((*(ptr as *const spAttachmentTimeline)).slotIndex))
+                }
+                TimelineKind::Color => {
+                    TimelineTarget::Slot(index((*(ptr as *const spColorTimeline)).slotIndex))
+                }
+                #[cfg(since_3_8)]
+                TimelineKind::TwoColor => TimelineTarget::Slot(index(
+                    (*(ptr as *const spine_sys::spTwoColorTimeline)).slotIndex,
+                )),
+                TimelineKind::Deform => {
+                    TimelineTarget::Slot(index((*(ptr as *const spDeformTimeline)).slotIndex))
+                }
+                TimelineKind::IkConstraint => TimelineTarget::IkConstraint(index(
+                    (*(ptr as *const spIkConstraintTimeline)).ikConstraintIndex,
+                )),
+                TimelineKind::TransformConstraint => TimelineTarget::TransformConstraint(index(
+                    (*(ptr as *const spTransformConstraintTimeline)).transformConstraintIndex,
+                )),
+                TimelineKind::PathConstraintPosition => TimelineTarget::PathConstraint(index(
+                    (*(ptr as *const spPathConstraintPositionTimeline)).pathConstraintIndex,
+                )),
+                TimelineKind::PathConstraintSpacing => TimelineTarget::PathConstraint(index(
+                    (*(ptr as *const spPathConstraintSpacingTimeline)).pathConstraintIndex,
+                )),
+                TimelineKind::PathConstraintMix => TimelineTarget::PathConstraint(index(
+                    (*(ptr as *const spPathConstraintMixTimeline)).pathConstraintIndex,
+                )),
+                _ => return None,
+            })
+        }
+    }
+
+    /// `None` for events and the draw order, which belong to the whole skeleton.
+    #[cfg(since_4_1)]
+    pub fn target(&self) -> Option<TimelineTarget> {
+        // Property IDs are 64-bit, the high half is the property and the low half the index,
+        // or the slot and attachment for deforms
+        let property = unsafe {
+            let this = &*self.ptr;
+            if this.propertyIdsCount <= 0 {
+                return None;
+            }
+            (this.propertyIds[0] & 0xffff_ffff) as u32
+        };
+        let index = property as usize;
+        Some(match self.kind() {
+            TimelineKind::Rotate
+            | TimelineKind::Translate
+            | TimelineKind::Scale
+            | TimelineKind::Shear => TimelineTarget::Bone(index),
+            TimelineKind::Attachment | TimelineKind::Color | TimelineKind::TwoColor => {
+                TimelineTarget::Slot(index)
+            }
+            TimelineKind::Deform => TimelineTarget::Slot((property >> 16) as usize),
+            TimelineKind::IkConstraint => TimelineTarget::IkConstraint(index),
+            TimelineKind::TransformConstraint => TimelineTarget::TransformConstraint(index),
+            TimelineKind::PathConstraintPosition
+            | TimelineKind::PathConstraintSpacing
+            | TimelineKind::PathConstraintMix => TimelineTarget::PathConstraint(index),
+            _ => return None,
+        })
+    }
+
+    /// Number of keys.
+    #[cfg(not(since_4_1))]
+    pub fn frame_count(&self) -> usize {
+        let ptr = self.ptr;
+        let entries = match entries_per_key(self.kind()) {
+            Some(entries) => entries,
+            None => return 0,
+        };
+        let values = unsafe {
+            match self.kind() {
+                TimelineKind::Rotate => (*(ptr as *const spRotateTimeline)).framesCount,
+                TimelineKind::Translate => (*(ptr as *const spTranslateTimeline)).framesCount,
+                TimelineKind::Scale => (*(ptr as *const spScaleTimeline)).framesCount,
+                TimelineKind::Shear => (*(ptr as *const spShearTimeline)).framesCount,
+                TimelineKind::Attachment => (*(ptr as *const spAttachmentTimeline)).framesCount,
+                TimelineKind::Color => (*(ptr as *const spColorTimeline)).framesCount,
+                #[cfg(since_3_8)]
+                TimelineKind::TwoColor => {
+                    (*(ptr as *const spine_sys::spTwoColorTimeline)).framesCount
+                }
+                TimelineKind::Deform => (*(ptr as *const spDeformTimeline)).framesCount,
+                TimelineKind::Event => (*(ptr as *const spEventTimeline)).framesCount,
+                TimelineKind::DrawOrder => (*(ptr as *const spDrawOrderTimeline)).framesCount,
+                TimelineKind::IkConstraint => (*(ptr as *const spIkConstraintTimeline)).framesCount,
+                TimelineKind::TransformConstraint => {
+                    (*(ptr as *const spTransformConstraintTimeline)).framesCount
+                }
+                TimelineKind::PathConstraintPosition => {
+                    (*(ptr as *const spPathConstraintPositionTimeline)).framesCount
+                }
+                TimelineKind::PathConstraintSpacing => {
+                    (*(ptr as *const spPathConstraintSpacingTimeline)).framesCount
+                }
+                TimelineKind::PathConstraintMix => {
+                    (*(ptr as *const spPathConstraintMixTimeline)).framesCount
+                }
+                _ => return 0,
+            }
+        };
+        values.max(0) as usize / entries
+    }
+
+    /// Number of keys.
+    #[cfg(since_4_1)]
+    pub fn frame_count(&self) -> usize {
+        unsafe { (*self.ptr).frameCount.max(0) as usize }
+    }
+}
+
+/// spine-c counts the values of all keys of a timeline, which take this many each. `None`
+/// for kinds it has no timeline for.
+#[cfg(not(since_4_1))]
+fn entries_per_key(kind: TimelineKind) -> Option<usize> {
+    Some(match kind {
+        TimelineKind::Rotate => 2,
+        TimelineKind::Translate | TimelineKind::Scale | TimelineKind::Shear => 3,
+        TimelineKind::Attachment
+        | TimelineKind::Deform
+        | TimelineKind::Event
+        | TimelineKind::DrawOrder => 1,
+        TimelineKind::Color => 5,
+        #[cfg(since_3_8)]
+        TimelineKind::TwoColor => 8,
+        TimelineKind::IkConstraint => IK_CONSTRAINT_ENTRIES,
+        TimelineKind::TransformConstraint => 5,
+        TimelineKind::PathConstraintPosition | TimelineKind::PathConstraintSpacing => 2,
+        TimelineKind::PathConstraintMix => 3,
+        _ => return None,
+    })
+}
+
+/// Values per key of IK constraint timelines, softness and more were added in 3.8.
+#[cfg(all(not(since_3_8), not(since_4_1)))]
+const IK_CONSTRAINT_ENTRIES: usize = 3;
+#[cfg(all(since_3_8, not(since_4_1)))]
+const IK_CONSTRAINT_ENTRIES: usize = 6;
+
+#[cfg(not(since_4_1))]
+fn kind_of(type_: spine_sys::spTimelineType) -> TimelineKind {
+    #[allow(non_upper_case_globals)]
+    match type_ {
+        spTimelineType_SP_TIMELINE_ROTATE => TimelineKind::Rotate,
+        spTimelineType_SP_TIMELINE_TRANSLATE => TimelineKind::Translate,
+        spTimelineType_SP_TIMELINE_SCALE => TimelineKind::Scale,
+        spTimelineType_SP_TIMELINE_SHEAR => TimelineKind::Shear,
+        spTimelineType_SP_TIMELINE_ATTACHMENT => TimelineKind::Attachment,
+        spine_sys::spTimelineType_SP_TIMELINE_COLOR => TimelineKind::Color,
+        #[cfg(since_3_8)]
+        spine_sys::spTimelineType_SP_TIMELINE_TWOCOLOR => TimelineKind::TwoColor,
+        spTimelineType_SP_TIMELINE_DEFORM => TimelineKind::Deform,
+        spTimelineType_SP_TIMELINE_EVENT => TimelineKind::Event,
+        spTimelineType_SP_TIMELINE_DRAWORDER => TimelineKind::DrawOrder,
+        spTimelineType_SP_TIMELINE_IKCONSTRAINT => TimelineKind::IkConstraint,
+        spTimelineType_SP_TIMELINE_TRANSFORMCONSTRAINT => TimelineKind::TransformConstraint,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTPOSITION => TimelineKind::PathConstraintPosition,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTSPACING => TimelineKind::PathConstraintSpacing,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTMIX => TimelineKind::PathConstraintMix,
+        _ => TimelineKind::Other,
+    }
+}
+
+#[cfg(since_4_1)]
+fn kind_of(type_: spine_sys::spTimelineType) -> TimelineKind {
+    use spine_sys::{
+        spTimelineType_SP_TIMELINE_ALPHA, spTimelineType_SP_TIMELINE_RGB,
+        spTimelineType_SP_TIMELINE_RGB2, spTimelineType_SP_TIMELINE_RGBA,
+        spTimelineType_SP_TIMELINE_RGBA2, spTimelineType_SP_TIMELINE_SCALEX,
+        spTimelineType_SP_TIMELINE_SCALEY, spTimelineType_SP_TIMELINE_SHEARX,
+        spTimelineType_SP_TIMELINE_SHEARY, spTimelineType_SP_TIMELINE_TRANSLATEX,
+        spTimelineType_SP_TIMELINE_TRANSLATEY,
+    };
+
+    #[allow(non_upper_case_globals)]
+    match type_ {
+        spTimelineType_SP_TIMELINE_ROTATE => TimelineKind::Rotate,
+        spTimelineType_SP_TIMELINE_TRANSLATE
+        | spTimelineType_SP_TIMELINE_TRANSLATEX
+        | spTimelineType_SP_TIMELINE_TRANSLATEY => TimelineKind::Translate,
+        spTimelineType_SP_TIMELINE_SCALE
+        | spTimelineType_SP_TIMELINE_SCALEX
+        | spTimelineType_SP_TIMELINE_SCALEY => TimelineKind::Scale,
+        spTimelineType_SP_TIMELINE_SHEAR
+        | spTimelineType_SP_TIMELINE_SHEARX
+        | spTimelineType_SP_TIMELINE_SHEARY => TimelineKind::Shear,
+        spTimelineType_SP_TIMELINE_ATTACHMENT => TimelineKind::Attachment,
+        spTimelineType_SP_TIMELINE_RGBA
+        | spTimelineType_SP_TIMELINE_RGB
+        | spTimelineType_SP_TIMELINE_ALPHA => TimelineKind::Color,
+        spTimelineType_SP_TIMELINE_RGBA2 | spTimelineType_SP_TIMELINE_RGB2 => {
+            TimelineKind::TwoColor
+        }
+        spTimelineType_SP_TIMELINE_DEFORM => TimelineKind::Deform,
+        spTimelineType_SP_TIMELINE_EVENT => TimelineKind::Event,
+        spTimelineType_SP_TIMELINE_DRAWORDER => TimelineKind::DrawOrder,
+        spTimelineType_SP_TIMELINE_IKCONSTRAINT => TimelineKind::IkConstraint,
+        spTimelineType_SP_TIMELINE_TRANSFORMCONSTRAINT => TimelineKind::TransformConstraint,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTPOSITION => TimelineKind::PathConstraintPosition,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTSPACING => TimelineKind::PathConstraintSpacing,
+        spTimelineType_SP_TIMELINE_PATHCONSTRAINTMIX => TimelineKind::PathConstraintMix,
+        _ => TimelineKind::Other,
+    }
+}
+
+#[cfg(all(test, not(since_4_1)))]
+mod tests {
+    use spine_sys::{spTimelineType_SP_TIMELINE_COLOR, spTranslateTimeline};
+
+    use super::*;
+
+    #[test]
+    fn counts_values_per_key() {
+        assert_eq!(entries_per_key(TimelineKind::Rotate), Some(2));
+        assert_eq!(entries_per_key(TimelineKind::Translate), Some(3));
+        assert_eq!(entries_per_key(TimelineKind::Color), Some(5));
+        assert_eq!(entries_per_key(TimelineKind::DrawOrder), Some(1));
+        assert_eq!(entries_per_key(TimelineKind::TransformConstraint), Some(5));
+        assert_eq!(entries_per_key(TimelineKind::PathConstraintMix), Some(3));
+        assert_eq!(entries_per_key(TimelineKind::Other), None);
+    }
+
+    #[cfg(not(since_3_8))]
+    #[test]
+    fn counts_values_per_key_of_3_5() {
+        assert_eq!(entries_per_key(TimelineKind::IkConstraint), Some(3));
+        assert_eq!(entries_per_key(TimelineKind::TwoColor), None);
+    }
+
+    #[cfg(since_3_8)]
+    #[test]
+    fn counts_values_per_key_of_3_8() {
+        assert_eq!(entries_per_key(TimelineKind::IkConstraint), Some(6));
+        assert_eq!(entries_per_key(TimelineKind::TwoColor), Some(8));
+    }
+
+    #[test]
+    fn reads_kind_from_type() {
+        assert_eq!(
+            kind_of(spTimelineType_SP_TIMELINE_ROTATE),
+            TimelineKind::Rotate
+        );
+        assert_eq!(
+            kind_of(spTimelineType_SP_TIMELINE_COLOR),
+            TimelineKind::Color
+        );
+        assert_eq!(
+            kind_of(spTimelineType_SP_TIMELINE_PATHCONSTRAINTMIX),
+            TimelineKind::PathConstraintMix
+        );
+    }
+
+    #[test]
+    fn divides_values_into_keys() {
+        let mut timeline = spTranslateTimeline::default();
+        timeline.super_.super_.type_ = spTimelineType_SP_TIMELINE_TRANSLATE;
+        timeline.framesCount = 9;
+        let ptr = &mut timeline as *mut spTranslateTimeline as *mut spTimeline;
+        assert_eq!(Timeline::new(ptr).kind(), TimelineKind::Translate);
+        assert_eq!(Timeline::new(ptr).frame_count(), 3);
+
+        timeline.framesCount = -1;
+        assert_eq!(Timeline::new(ptr).frame_count(), 0);
+    }
+}